@@ -0,0 +1,281 @@
+//! Storage and chain backends for the NFT facade
+//!
+//! [`NftBlockchainInteractive`](crate::NftBlockchainInteractive) never talks to a network
+//! directly: metadata goes through a [`StorageBackend`] and mints/deployments go through a
+//! [`ChainBackend`]. Filecoin/IPFS and NEAR implementations live in their own modules; the
+//! in-memory backends here are deterministic and intended for unit tests.
+
+use cid::multihash::MultihashGeneric;
+use cid::Cid;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+/// Multicodec code for raw binary content
+const RAW_CODEC: u64 = 0x55;
+
+/// Multihash code for SHA2-256
+const SHA2_256: u64 = 0x12;
+
+/// Object written to a storage backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredObject {
+    pub cid: String,
+    pub size: u64,
+}
+
+/// Request handed to a chain backend when minting
+#[derive(Debug, Clone)]
+pub struct MintRequest<'a> {
+    pub collection: &'a str,
+    pub token_id: u64,
+    pub owner: &'a str,
+    pub metadata: &'a str,
+    pub metadata_uri: Option<String>,
+}
+
+/// Result of a successful on-chain mint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MintReceipt {
+    pub token_id: String,
+    pub transaction_hash: String,
+}
+
+/// Result of a successful contract deployment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeployReceipt {
+    pub contract_id: String,
+    pub transaction_hash: String,
+    pub code_hash: String,
+}
+
+/// Content-addressed storage (IPFS, Filecoin, ...)
+pub trait StorageBackend {
+    /// Store bytes and return their content identifier
+    fn put(&mut self, data: &[u8]) -> Result<StoredObject, Box<dyn Error>>;
+
+    /// Retrieve bytes previously stored under `cid`
+    fn get(&self, cid: &str) -> Result<Vec<u8>, Box<dyn Error>>;
+
+    /// URI recorded in token metadata for a stored object
+    fn uri(&self, cid: &str) -> String {
+        format!("ipfs://{}", cid)
+    }
+}
+
+/// Chain that NFTs are minted on and contracts are deployed to
+pub trait ChainBackend {
+    /// Account that signs transactions, if one is configured
+    fn account_id(&self) -> Option<&str>;
+
+    /// Mint a token and return the on-chain token id and transaction hash
+    fn mint(&mut self, request: &MintRequest) -> Result<MintReceipt, Box<dyn Error>>;
+
+    /// Deploy contract code to the backend's account
+    fn deploy_contract(&mut self, code: &[u8]) -> Result<DeployReceipt, Box<dyn Error>>;
+}
+
+/// Compute the CIDv1 (raw codec, SHA2-256) of `data`
+///
+/// Matches `IpfsPersistenceLayer::generate_cid` and what an IPFS node returns for
+/// single-block content added with `raw-leaves`.
+pub fn content_cid(data: &[u8]) -> String {
+    let digest = Sha256::digest(data);
+    let hash = MultihashGeneric::<64>::wrap(SHA2_256, &digest)
+        .expect("SHA2-256 digest fits in a 64-byte multihash");
+    Cid::new_v1(RAW_CODEC, hash).to_string()
+}
+
+/// In-memory storage backend for tests
+///
+/// Clones share the same object store, so a test can keep a handle after
+/// passing a boxed copy to the facade.
+#[derive(Clone, Default)]
+pub struct InMemoryStorage {
+    objects: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+}
+
+impl InMemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored objects
+    pub fn len(&self) -> usize {
+        self.objects.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check whether an object is stored under `cid`
+    pub fn contains(&self, cid: &str) -> bool {
+        self.objects.lock().unwrap().contains_key(cid)
+    }
+}
+
+impl StorageBackend for InMemoryStorage {
+    fn put(&mut self, data: &[u8]) -> Result<StoredObject, Box<dyn Error>> {
+        let cid = content_cid(data);
+        self.objects.lock().unwrap().insert(cid.clone(), data.to_vec());
+
+        Ok(StoredObject {
+            cid,
+            size: data.len() as u64,
+        })
+    }
+
+    fn get(&self, cid: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(cid)
+            .cloned()
+            .ok_or_else(|| format!("CID not found: {}", cid).into())
+    }
+}
+
+/// State recorded by [`InMemoryChain`]
+#[derive(Default)]
+struct InMemoryChainState {
+    minted: Vec<MintReceipt>,
+    deployments: Vec<DeployReceipt>,
+    nonce: u64,
+    fail_next_mint: bool,
+}
+
+/// In-memory chain backend for tests
+///
+/// Transaction hashes are derived from a nonce and the request contents, so
+/// the same sequence of calls always produces the same receipts.
+#[derive(Clone)]
+pub struct InMemoryChain {
+    account_id: String,
+    state: Arc<Mutex<InMemoryChainState>>,
+}
+
+impl Default for InMemoryChain {
+    fn default() -> Self {
+        Self::new("test.near")
+    }
+}
+
+impl InMemoryChain {
+    pub fn new(account_id: &str) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            state: Arc::new(Mutex::new(InMemoryChainState::default())),
+        }
+    }
+
+    /// Receipts of every token minted so far
+    pub fn minted(&self) -> Vec<MintReceipt> {
+        self.state.lock().unwrap().minted.clone()
+    }
+
+    /// Receipts of every contract deployed so far
+    pub fn deployments(&self) -> Vec<DeployReceipt> {
+        self.state.lock().unwrap().deployments.clone()
+    }
+
+    /// Reject the next mint, as a node rejecting the transaction would
+    pub fn fail_next_mint(&self) {
+        self.state.lock().unwrap().fail_next_mint = true;
+    }
+
+    fn transaction_hash(nonce: u64, payload: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(nonce.to_le_bytes());
+        hasher.update(payload);
+        hex::encode(hasher.finalize())
+    }
+}
+
+impl ChainBackend for InMemoryChain {
+    fn account_id(&self) -> Option<&str> {
+        Some(&self.account_id)
+    }
+
+    fn mint(&mut self, request: &MintRequest) -> Result<MintReceipt, Box<dyn Error>> {
+        let token_id = format!("{}:{}", request.collection, request.token_id);
+        let mut state = self.state.lock().unwrap();
+
+        if state.minted.iter().any(|r| r.token_id == token_id) {
+            return Err(format!("Token already minted: {}", token_id).into());
+        }
+        if std::mem::take(&mut state.fail_next_mint) {
+            return Err(format!("Mint rejected: {}", token_id).into());
+        }
+
+        state.nonce += 1;
+        let payload = [token_id.as_bytes(), request.owner.as_bytes(), request.metadata.as_bytes()].concat();
+        let receipt = MintReceipt {
+            token_id,
+            transaction_hash: Self::transaction_hash(state.nonce, &payload),
+        };
+        state.minted.push(receipt.clone());
+
+        Ok(receipt)
+    }
+
+    fn deploy_contract(&mut self, code: &[u8]) -> Result<DeployReceipt, Box<dyn Error>> {
+        let mut state = self.state.lock().unwrap();
+        state.nonce += 1;
+
+        let receipt = DeployReceipt {
+            contract_id: self.account_id.clone(),
+            transaction_hash: Self::transaction_hash(state.nonce, code),
+            code_hash: hex::encode(Sha256::digest(code)),
+        };
+        state.deployments.push(receipt.clone());
+
+        Ok(receipt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_cid_is_deterministic() {
+        let cid = content_cid(b"Hello, IPFS!");
+        assert_eq!(cid, content_cid(b"Hello, IPFS!"));
+        assert_ne!(cid, content_cid(b"Hello, IPFS?"));
+        // CIDv1 in base32 with the raw codec
+        assert!(cid.starts_with("bafkrei"));
+    }
+
+    #[test]
+    fn test_in_memory_storage_round_trip() {
+        let mut storage = InMemoryStorage::new();
+        let handle = storage.clone();
+
+        let stored = storage.put(b"{\"name\": \"Test NFT\"}").unwrap();
+        assert_eq!(stored.size, 20);
+        assert!(handle.contains(&stored.cid));
+        assert_eq!(storage.get(&stored.cid).unwrap(), b"{\"name\": \"Test NFT\"}");
+        assert!(storage.get("bafkmissing").is_err());
+    }
+
+    #[test]
+    fn test_in_memory_chain_rejects_duplicate_mint() {
+        let mut chain = InMemoryChain::default();
+        let request = MintRequest {
+            collection: "Test",
+            token_id: 1,
+            owner: "alice.near",
+            metadata: "{}",
+            metadata_uri: None,
+        };
+
+        let receipt = chain.mint(&request).unwrap();
+        assert_eq!(receipt.token_id, "Test:1");
+        assert_eq!(receipt.transaction_hash.len(), 64);
+        assert!(chain.mint(&request).is_err());
+        assert_eq!(chain.minted().len(), 1);
+    }
+}
@@ -0,0 +1,327 @@
+//! # nftbi
+//!
+//! Command-line interface to [`NftBlockchainInteractive`] and the sub-crates.
+//!
+//! Every command prints one JSON document to stdout. Failures print
+//! `{"error": "..."}` and exit with status 1. Collections and client settings
+//! are kept in a state file between runs. Secrets are never written to it: the
+//! Filecoin token is read from `NFTBI_FILECOIN_TOKEN`, and NEAR transactions
+//! are signed with the `NFTBI_NEAR_KEY` key (default `near`) of the keystore
+//! at `NFTBI_KEYSTORE`, unlocked with `NFTBI_KEYSTORE_PASSWORD`.
+
+use clap::{Args, Parser, Subcommand};
+use nft_blockchain_interactive::{content_cid, NftBlockchainInteractive};
+use nft_ipfs_integration::{create_creative_asset, IpfsPersistenceLayer};
+use nft_rust_client::emotion_core::TaxonomyModel;
+use nft_rust_client::{ChainType, CreativeSession, Keystore, KeystoreSigner};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+#[derive(Parser)]
+#[command(name = "nftbi", version, about = "NFT Blockchain Interactive command-line tool")]
+struct Cli {
+    /// State file holding collections and client settings
+    #[arg(long, global = true, env = "NFTBI_STATE", default_value = "nftbi-state.json")]
+    state: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Set the Filecoin and NEAR clients used by later commands
+    Configure(ConfigureArgs),
+    /// Manage collections
+    #[command(subcommand)]
+    Collection(CollectionCommand),
+    /// Mint a token with metadata read from a JSON file
+    Mint(MintArgs),
+    /// Upload a file to IPFS as a creative asset and pin it
+    Upload(UploadArgs),
+    /// Compute the CIDv1 of files without uploading them
+    Cid {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Inspect exported creative sessions
+    #[command(subcommand)]
+    Session(SessionCommand),
+    /// Plan contract deployments
+    #[command(subcommand)]
+    Deploy(DeployCommand),
+}
+
+#[derive(Args)]
+struct ConfigureArgs {
+    /// Filecoin/IPFS HTTP API endpoint
+    #[arg(long)]
+    filecoin_endpoint: Option<String>,
+    /// NEAR network id (testnet, mainnet, ...)
+    #[arg(long)]
+    near_network: Option<String>,
+    /// NEAR account that signs transactions
+    #[arg(long, requires = "near_network")]
+    near_account: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum CollectionCommand {
+    /// Create a collection
+    Create {
+        name: String,
+        symbol: String,
+        #[arg(long, default_value = "ipfs://")]
+        base_uri: String,
+        #[arg(long)]
+        max_supply: Option<u64>,
+    },
+    /// List collection names
+    List,
+    /// Show a collection and its tokens
+    Show { name: String },
+}
+
+#[derive(Args)]
+struct MintArgs {
+    collection: String,
+    token_id: u64,
+    /// JSON metadata file
+    metadata: PathBuf,
+    /// Receiving account; defaults to the NEAR signer
+    #[arg(long)]
+    owner: Option<String>,
+}
+
+#[derive(Args)]
+struct UploadArgs {
+    file: PathBuf,
+    /// Asset name; defaults to the file name
+    #[arg(long)]
+    name: Option<String>,
+    #[arg(long, default_value = "")]
+    description: String,
+    #[arg(long, default_value = "application/octet-stream")]
+    content_type: String,
+    #[arg(long, default_value = "127.0.0.1")]
+    ipfs_host: String,
+    #[arg(long, default_value_t = 5001)]
+    ipfs_port: u16,
+}
+
+#[derive(Subcommand)]
+enum SessionCommand {
+    /// Summarise a session exported with `CreativeSession::export_for_storage`
+    Inspect {
+        file: PathBuf,
+        /// Emotion taxonomy for the category: pad, circumplex or plutchik
+        #[arg(long, default_value = "pad")]
+        taxonomy: TaxonomyModel,
+    },
+}
+
+#[derive(Subcommand)]
+enum DeployCommand {
+    /// Discover artifacts and show what would be deployed
+    Plan {
+        /// Directory searched for contract artifacts
+        #[arg(long)]
+        artifact_dir: Option<String>,
+        /// Deployment manifest to diff against
+        #[arg(long)]
+        manifest: Option<String>,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli) {
+        Ok(output) => {
+            println!("{}", serde_json::to_string_pretty(&output).unwrap_or_default());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            println!("{}", json!({ "error": e.to_string() }));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<Value, Box<dyn Error>> {
+    match cli.command {
+        Command::Configure(args) => {
+            let mut client = open_state(&cli.state)?;
+            if let Some(endpoint) = &args.filecoin_endpoint {
+                client.initialize_filecoin(endpoint, None)?;
+            }
+            if let Some(network) = &args.near_network {
+                client.initialize_near(network, args.near_account.as_deref(), None)?;
+            }
+            client.save_state(&cli.state)?;
+            Ok(serde_json::to_value(client.client_configs())?)
+        }
+
+        Command::Collection(CollectionCommand::Create { name, symbol, base_uri, max_supply }) => {
+            let mut client = open_state(&cli.state)?;
+            if client.get_collection_info(&name).is_some() {
+                return Err(format!("Collection already exists: {}", name).into());
+            }
+            client.create_collection(&name, &symbol, &base_uri, max_supply)?;
+            client.save_state(&cli.state)?;
+            Ok(serde_json::to_value(client.get_collection_info(&name))?)
+        }
+
+        Command::Collection(CollectionCommand::List) => {
+            let client = open_state(&cli.state)?;
+            let mut names = client.list_collections();
+            names.sort();
+            Ok(json!(names))
+        }
+
+        Command::Collection(CollectionCommand::Show { name }) => {
+            let client = open_state(&cli.state)?;
+            let collection = client.get_collection_info(&name).ok_or("Collection not found")?;
+            Ok(serde_json::to_value(collection)?)
+        }
+
+        Command::Mint(args) => {
+            let mut client = open_state(&cli.state)?;
+            let metadata = fs::read_to_string(&args.metadata)?;
+            // Reject malformed files before anything is stored or signed
+            serde_json::from_str::<Value>(&metadata)?;
+
+            let minted = match &args.owner {
+                Some(owner) => client.mint_nft_to(&args.collection, args.token_id, owner, &metadata)?,
+                None => client.mint_nft(&args.collection, args.token_id, &metadata)?,
+            };
+            client.save_state(&cli.state)?;
+            Ok(serde_json::to_value(minted)?)
+        }
+
+        Command::Upload(args) => {
+            let data = fs::read(&args.file)?;
+            let name = match args.name {
+                Some(name) => name,
+                None => file_name(&args.file),
+            };
+
+            let layer = IpfsPersistenceLayer::new(&args.ipfs_host, args.ipfs_port);
+            let asset = create_creative_asset(&name, &args.description, data, &args.content_type, json!({}));
+            let (cid, pin) = tokio::runtime::Runtime::new()?.block_on(layer.upload_creative_asset(asset))?;
+
+            Ok(json!({
+                "name": name,
+                "cid": cid,
+                "pin": pin,
+            }))
+        }
+
+        Command::Cid { files } => {
+            let mut results = Vec::new();
+            for path in files {
+                let data = fs::read(&path)?;
+                results.push(json!({
+                    "path": path.display().to_string(),
+                    "cid": content_cid(&data),
+                    "size": data.len(),
+                }));
+            }
+            Ok(Value::Array(results))
+        }
+
+        Command::Session(SessionCommand::Inspect { file, taxonomy }) => {
+            let session = CreativeSession::import_from_storage(&fs::read(&file)?)?;
+            let mut summary = session.generate_metadata()?;
+            summary["emotional_category"] = json!(session.get_emotional_category_with(&taxonomy));
+            summary["emotion_taxonomy"] = json!(taxonomy.to_string());
+            Ok(summary)
+        }
+
+        Command::Deploy(DeployCommand::Plan { artifact_dir, manifest }) => {
+            let mut client = open_state(&cli.state)?;
+            let mut config = client.deployment_config().clone();
+            if let Some(dir) = &artifact_dir {
+                config = config.with_artifact_dir(dir);
+            }
+            if let Some(path) = &manifest {
+                config = config.with_manifest_path(path);
+            }
+            client.set_deployment_config(config);
+            Ok(serde_json::to_value(client.plan_deployment()?)?)
+        }
+    }
+}
+
+/// Load the state file, or start empty if it does not exist yet, then supply
+/// secrets from the environment
+fn open_state(path: &Path) -> Result<NftBlockchainInteractive, Box<dyn Error>> {
+    let mut client = if path.exists() {
+        NftBlockchainInteractive::load_state(path)?
+    } else {
+        NftBlockchainInteractive::new()
+    };
+
+    let configs = client.client_configs().clone();
+    if let (Some(filecoin), Ok(token)) = (configs.filecoin, std::env::var("NFTBI_FILECOIN_TOKEN")) {
+        client.initialize_filecoin(&filecoin.api_endpoint, Some(&token))?;
+    }
+    if let (Some(near), Ok(keystore)) = (configs.near, std::env::var("NFTBI_KEYSTORE")) {
+        let password = std::env::var("NFTBI_KEYSTORE_PASSWORD").map_err(|_| "NFTBI_KEYSTORE_PASSWORD is not set")?;
+        let key = std::env::var("NFTBI_NEAR_KEY").unwrap_or_else(|_| "near".to_string());
+        let mut signer = KeystoreSigner::new(Arc::new(Keystore::open(keystore, &password)?), &key, ChainType::Near)?;
+        if let Some(account_id) = &near.account_id {
+            signer = signer.with_account_id(account_id);
+        }
+        client.initialize_near(&near.network_id, near.account_id.as_deref(), Some(Arc::new(signer)))?;
+    }
+
+    Ok(client)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_mint() {
+        let cli = Cli::try_parse_from(["nftbi", "--state", "s.json", "mint", "Genesis", "7", "meta.json", "--owner", "alice.near"]).unwrap();
+        assert_eq!(cli.state, PathBuf::from("s.json"));
+        match cli.command {
+            Command::Mint(args) => {
+                assert_eq!(args.collection, "Genesis");
+                assert_eq!(args.token_id, 7);
+                assert_eq!(args.owner.as_deref(), Some("alice.near"));
+            }
+            _ => panic!("expected mint"),
+        }
+    }
+
+    #[test]
+    fn test_parse_session_taxonomy() {
+        let cli = Cli::try_parse_from(["nftbi", "session", "inspect", "s.json", "--taxonomy", "plutchik"]).unwrap();
+        match cli.command {
+            Command::Session(SessionCommand::Inspect { taxonomy, .. }) => {
+                assert_eq!(taxonomy, TaxonomyModel::Plutchik);
+            }
+            _ => panic!("expected session inspect"),
+        }
+        assert!(Cli::try_parse_from(["nftbi", "session", "inspect", "s.json", "--taxonomy", "wheel"]).is_err());
+    }
+}
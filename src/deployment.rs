@@ -0,0 +1,346 @@
+//! Contract deployment planning
+//!
+//! Finds contract artifacts in the workspace, hashes them and compares them
+//! with a manifest of earlier deployments so unchanged contracts are skipped.
+//!
+//! Recognised artifacts:
+//! - NEAR: `*.wasm` (wasm-bindgen `*_bg.wasm` output is ignored)
+//! - Polkadot: ink! `*.contract` bundles (the sibling `.wasm` is not listed separately)
+//! - Solana: Anchor `*.so` programs under a `deploy` directory
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories never searched for artifacts
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "dist", "pkg", "deps", "incremental", "build", ".fingerprint"];
+
+/// Chain a contract artifact is built for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractChain {
+    Near,
+    Polkadot,
+    Solana,
+}
+
+/// Contract artifact found on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractArtifact {
+    pub chain: ContractChain,
+    /// Path relative to the search root, used as the manifest key
+    pub id: String,
+    pub path: PathBuf,
+    /// Hex SHA-256 of the artifact bytes
+    pub sha256: String,
+    pub size: u64,
+}
+
+impl ContractArtifact {
+    /// Read and hash a single artifact
+    pub fn from_file(chain: ContractChain, path: &Path, id: &str) -> Result<Self, Box<dyn Error>> {
+        let bytes = fs::read(path)?;
+
+        Ok(Self {
+            chain,
+            id: id.to_string(),
+            path: path.to_path_buf(),
+            sha256: hex::encode(Sha256::digest(&bytes)),
+            size: bytes.len() as u64,
+        })
+    }
+
+    /// Check whether this artifact is the file at `path`
+    pub fn is_file(&self, path: &Path) -> bool {
+        match (fs::canonicalize(&self.path), fs::canonicalize(path)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Recursively find contract artifacts under `root`, ordered by chain then id
+pub fn discover_artifacts<P: AsRef<Path>>(root: P) -> Result<Vec<ContractArtifact>, Box<dyn Error>> {
+    let root = root.as_ref();
+    let mut artifacts = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+
+            if path.is_dir() {
+                if !SKIPPED_DIRS.contains(&file_name) {
+                    pending.push(path);
+                }
+                continue;
+            }
+
+            if let Some(chain) = classify(&path) {
+                let id = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                artifacts.push(ContractArtifact::from_file(chain, &path, &id)?);
+            }
+        }
+    }
+
+    artifacts.sort_by(|a, b| (a.chain, &a.id).cmp(&(b.chain, &b.id)));
+    Ok(artifacts)
+}
+
+fn classify(path: &Path) -> Option<ContractChain> {
+    let stem = path.file_stem()?.to_str()?;
+
+    match path.extension()?.to_str()? {
+        "contract" => Some(ContractChain::Polkadot),
+        "so" if path.parent()?.file_name()? == "deploy" => Some(ContractChain::Solana),
+        "wasm" if stem.ends_with("_bg") => None,
+        "wasm" if path.with_extension("contract").exists() => None,
+        "wasm" => Some(ContractChain::Near),
+        _ => None,
+    }
+}
+
+/// Result of one deployment attempt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum DeployOutcome {
+    Deployed { transaction_hash: String },
+    Failed { error: String },
+}
+
+/// One deployment attempt recorded in the manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub chain: ContractChain,
+    pub artifact_id: String,
+    pub sha256: String,
+    /// Account or program id the artifact was deployed to
+    pub target: Option<String>,
+    /// CID of the pinned artifact, if it was pinned
+    pub cid: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+    pub outcome: DeployOutcome,
+}
+
+/// History of deployment attempts, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeploymentManifest {
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl DeploymentManifest {
+    /// Load a manifest, or an empty one if `path` does not exist
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn record(&mut self, entry: ManifestEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Most recent successful deployment of an artifact
+    pub fn last_deployed(&self, chain: ContractChain, artifact_id: &str) -> Option<&ManifestEntry> {
+        self.entries.iter().rev().find(|e| {
+            e.chain == chain && e.artifact_id == artifact_id && matches!(e.outcome, DeployOutcome::Deployed { .. })
+        })
+    }
+
+    /// Check whether `artifact` is already deployed to `target` with the same bytes
+    pub fn is_current(&self, artifact: &ContractArtifact, target: Option<&str>) -> bool {
+        match (self.last_deployed(artifact.chain, &artifact.id), target) {
+            (Some(entry), Some(target)) => entry.sha256 == artifact.sha256 && entry.target.as_deref() == Some(target),
+            _ => false,
+        }
+    }
+}
+
+/// Why a planned deployment will not run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Same bytes already deployed to the same target
+    Unchanged,
+    /// Deployment to this chain is disabled in the config
+    ChainDisabled,
+    /// Not the configured contract for its chain
+    NotSelected,
+    /// The facade has no backend for this chain
+    NoBackend,
+}
+
+/// What the planner decided for an artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "reason", rename_all = "snake_case")]
+pub enum PlanAction {
+    Deploy,
+    Skip(SkipReason),
+}
+
+/// Planned deployment of one artifact
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedDeployment {
+    pub artifact: ContractArtifact,
+    pub target: Option<String>,
+    pub action: PlanAction,
+    /// Hash from the last successful deployment, if any
+    pub previous_sha256: Option<String>,
+}
+
+/// Per-chain deployment plan
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeploymentPlan {
+    pub steps: Vec<PlannedDeployment>,
+}
+
+impl DeploymentPlan {
+    /// Add a step, downgrading `Deploy` to `Skip(Unchanged)` when the manifest
+    /// shows the artifact is already live
+    pub fn push(&mut self, artifact: ContractArtifact, target: Option<String>, action: PlanAction, manifest: &DeploymentManifest) {
+        let action = match action {
+            PlanAction::Deploy if manifest.is_current(&artifact, target.as_deref()) => PlanAction::Skip(SkipReason::Unchanged),
+            action => action,
+        };
+        let previous_sha256 = manifest.last_deployed(artifact.chain, &artifact.id).map(|e| e.sha256.clone());
+
+        self.steps.push(PlannedDeployment {
+            artifact,
+            target,
+            action,
+            previous_sha256,
+        });
+    }
+
+    /// Steps that will be deployed
+    pub fn pending(&self) -> impl Iterator<Item = &PlannedDeployment> {
+        self.steps.iter().filter(|s| s.action == PlanAction::Deploy)
+    }
+
+    /// Steps for one chain
+    pub fn for_chain(&self, chain: ContractChain) -> impl Iterator<Item = &PlannedDeployment> {
+        self.steps.iter().filter(move |s| s.artifact.chain == chain)
+    }
+}
+
+impl PlannedDeployment {
+    /// Manifest entry for an attempt at this step
+    pub fn record(&self, target: Option<String>, cid: Option<String>, outcome: DeployOutcome) -> ManifestEntry {
+        ManifestEntry {
+            chain: self.artifact.chain,
+            artifact_id: self.artifact.id.clone(),
+            sha256: self.artifact.sha256.clone(),
+            target,
+            cid,
+            recorded_at: Utc::now(),
+            outcome,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_discover_artifacts() {
+        let root = temp_dir("discover");
+        for dir in ["near", "target/ink", "target/deploy", "target/release/deps", "dist"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("near/nft.wasm"), b"near").unwrap();
+        fs::write(root.join("target/ink/soulbound.contract"), b"{}").unwrap();
+        fs::write(root.join("target/ink/soulbound.wasm"), b"ink").unwrap();
+        fs::write(root.join("target/deploy/marketplace.so"), b"elf").unwrap();
+        fs::write(root.join("target/release/deps/libfoo.so"), b"elf").unwrap();
+        fs::write(root.join("dist/app_bg.wasm"), b"web").unwrap();
+
+        let artifacts = discover_artifacts(&root).unwrap();
+        let found: Vec<(ContractChain, &str)> = artifacts.iter().map(|a| (a.chain, a.id.as_str())).collect();
+        assert_eq!(found, vec![
+            (ContractChain::Near, "near/nft.wasm"),
+            (ContractChain::Polkadot, "target/ink/soulbound.contract"),
+            (ContractChain::Solana, "target/deploy/marketplace.so"),
+        ]);
+        assert_eq!(artifacts[0].sha256, hex::encode(Sha256::digest(b"near")));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_plan_skips_unchanged_artifacts() {
+        let root = temp_dir("plan");
+        fs::write(root.join("nft.wasm"), b"v1").unwrap();
+        let artifact = discover_artifacts(&root).unwrap().remove(0);
+
+        let mut manifest = DeploymentManifest::default();
+        let mut plan = DeploymentPlan::default();
+        plan.push(artifact.clone(), Some("studio.testnet".to_string()), PlanAction::Deploy, &manifest);
+        assert_eq!(plan.pending().count(), 1);
+
+        manifest.record(plan.steps[0].record(Some("studio.testnet".to_string()), None, DeployOutcome::Deployed {
+            transaction_hash: "tx".to_string(),
+        }));
+
+        // Same bytes and target: skipped
+        let mut plan = DeploymentPlan::default();
+        plan.push(artifact.clone(), Some("studio.testnet".to_string()), PlanAction::Deploy, &manifest);
+        assert_eq!(plan.steps[0].action, PlanAction::Skip(SkipReason::Unchanged));
+
+        // New target or new bytes: deployed again
+        let mut plan = DeploymentPlan::default();
+        plan.push(artifact, Some("other.testnet".to_string()), PlanAction::Deploy, &manifest);
+        fs::write(root.join("nft.wasm"), b"v2").unwrap();
+        let changed = discover_artifacts(&root).unwrap().remove(0);
+        plan.push(changed, Some("studio.testnet".to_string()), PlanAction::Deploy, &manifest);
+        assert_eq!(plan.pending().count(), 2);
+        assert_eq!(plan.steps[1].previous_sha256, Some(hex::encode(Sha256::digest(b"v1"))));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let root = temp_dir("manifest");
+        let path = root.join("deployments.json");
+        assert!(DeploymentManifest::load(&path).unwrap().entries.is_empty());
+
+        let mut manifest = DeploymentManifest::default();
+        manifest.record(ManifestEntry {
+            chain: ContractChain::Near,
+            artifact_id: "nft.wasm".to_string(),
+            sha256: "00".to_string(),
+            target: None,
+            cid: None,
+            recorded_at: Utc::now(),
+            outcome: DeployOutcome::Failed {
+                error: "NEAR backend not initialized".to_string(),
+            },
+        });
+        manifest.save(&path).unwrap();
+
+        let loaded = DeploymentManifest::load(&path).unwrap();
+        assert_eq!(loaded, manifest);
+        assert!(loaded.last_deployed(ContractChain::Near, "nft.wasm").is_none());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
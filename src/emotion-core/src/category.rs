@@ -0,0 +1,112 @@
+//! Category labels for VAD points
+
+use crate::Vad;
+use core::fmt;
+
+/// Octant of the VAD space a point falls in
+///
+/// Each axis is split at its neutral value: valence at 0.0, arousal and
+/// dominance at 0.5. Points exactly on a split count as the low side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+#[cfg_attr(feature = "scale", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+pub enum EmotionCategory {
+    /// Positive, aroused, in control
+    Excited,
+    /// Positive, aroused, controlled
+    Happy,
+    /// Positive, calm, in control
+    Proud,
+    /// Positive, calm, controlled
+    Calm,
+    /// Negative, aroused, in control
+    Angry,
+    /// Negative, aroused, controlled
+    Anxious,
+    /// Negative, calm, in control
+    Bored,
+    /// Negative, calm, controlled
+    Sad,
+}
+
+impl EmotionCategory {
+    /// All categories, in octant order
+    pub const ALL: [EmotionCategory; 8] = [
+        Self::Excited,
+        Self::Happy,
+        Self::Proud,
+        Self::Calm,
+        Self::Angry,
+        Self::Anxious,
+        Self::Bored,
+        Self::Sad,
+    ];
+
+    pub fn from_vad(vad: &Vad) -> Self {
+        let positive = vad.valence > Vad::NEUTRAL.valence;
+        let aroused = vad.arousal > Vad::NEUTRAL.arousal;
+        let dominant = vad.dominance > Vad::NEUTRAL.dominance;
+
+        match (positive, aroused, dominant) {
+            (true, true, true) => Self::Excited,
+            (true, true, false) => Self::Happy,
+            (true, false, true) => Self::Proud,
+            (true, false, false) => Self::Calm,
+            (false, true, true) => Self::Angry,
+            (false, true, false) => Self::Anxious,
+            (false, false, true) => Self::Bored,
+            (false, false, false) => Self::Sad,
+        }
+    }
+
+    /// Label used in metadata and by the chain contracts
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Excited => "Excited",
+            Self::Happy => "Happy",
+            Self::Proud => "Proud",
+            Self::Calm => "Calm",
+            Self::Angry => "Angry",
+            Self::Anxious => "Anxious",
+            Self::Bored => "Bored",
+            Self::Sad => "Sad",
+        }
+    }
+}
+
+impl fmt::Display for EmotionCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_octants() {
+        let cases = [
+            ((0.8, 0.9, 0.7), EmotionCategory::Excited),
+            ((0.8, 0.9, 0.3), EmotionCategory::Happy),
+            ((0.8, 0.3, 0.7), EmotionCategory::Proud),
+            ((0.8, 0.3, 0.3), EmotionCategory::Calm),
+            ((-0.3, 0.8, 0.7), EmotionCategory::Angry),
+            ((-0.3, 0.8, 0.3), EmotionCategory::Anxious),
+            ((-0.3, 0.3, 0.7), EmotionCategory::Bored),
+            ((-0.3, 0.3, 0.3), EmotionCategory::Sad),
+        ];
+
+        for ((v, a, d), expected) in cases {
+            assert_eq!(Vad::new(v, a, d).unwrap().category(), expected);
+        }
+    }
+
+    #[test]
+    fn test_neutral_is_low_side() {
+        assert_eq!(Vad::NEUTRAL.category(), EmotionCategory::Sad);
+        // Mildly positive valence is positive, not neutral
+        assert_eq!(Vad::new(0.2, 0.5, 0.5).unwrap().category(), EmotionCategory::Calm);
+    }
+}
@@ -0,0 +1,60 @@
+//! Complexity of an emotional history
+
+use crate::{Vad, AROUSAL_RANGE, DOMINANCE_RANGE, VALENCE_RANGE};
+
+/// Largest possible variance of a value confined to `range`
+fn max_variance((min, max): (f32, f32)) -> f32 {
+    let half_width = (max - min) / 2.0;
+    half_width * half_width
+}
+
+/// Spread of a sequence of VAD points, scaled to 0.0-1.0
+///
+/// The root mean of each dimension's variance relative to the largest
+/// variance its range allows. A constant history scores 0.0; a history split
+/// evenly between opposite corners of the space scores 1.0. Fewer than two
+/// points score 0.0.
+pub fn complexity<I: IntoIterator<Item = Vad>>(history: I) -> f32 {
+    // Welford's online variance, so the history is only walked once
+    let mut count = 0.0f32;
+    let mut mean = [0.0f32; 3];
+    let mut m2 = [0.0f32; 3];
+
+    for vad in history {
+        count += 1.0;
+        for (i, value) in [vad.valence, vad.arousal, vad.dominance].into_iter().enumerate() {
+            let delta = value - mean[i];
+            mean[i] += delta / count;
+            m2[i] += delta * (value - mean[i]);
+        }
+    }
+
+    if count < 2.0 {
+        return 0.0;
+    }
+
+    let normalized = m2[0] / count / max_variance(VALENCE_RANGE)
+        + m2[1] / count / max_variance(AROUSAL_RANGE)
+        + m2[2] / count / max_variance(DOMINANCE_RANGE);
+
+    libm::sqrtf(normalized / 3.0).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complexity_bounds() {
+        let calm = Vad::new(0.2, 0.3, 0.4).unwrap();
+        assert_eq!(complexity([calm]), 0.0);
+        assert_eq!(complexity([calm, calm, calm]), 0.0);
+
+        let low = Vad::new(-1.0, 0.0, 0.0).unwrap();
+        let high = Vad::new(1.0, 1.0, 1.0).unwrap();
+        assert!((complexity([low, high]) - 1.0).abs() < 1e-6);
+
+        let mixed = complexity([low, calm, high]);
+        assert!(mixed > 0.0 && mixed < 1.0);
+    }
+}
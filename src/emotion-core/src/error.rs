@@ -0,0 +1,44 @@
+//! Conversion and validation errors
+
+use core::fmt;
+
+/// Reason a value could not be converted without losing information
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmotionError {
+    /// A component is NaN or infinite
+    NotFinite { field: &'static str },
+    /// A component is outside its VAD range
+    OutOfRange { field: &'static str, value: f32 },
+    /// The target type requires a field the reading does not have
+    Missing { field: &'static str },
+    /// The target type cannot hold a field the reading has
+    Unrepresentable { field: &'static str },
+    /// A timestamp does not fit the target representation
+    TimestampOutOfRange,
+    /// A timestamp has more precision than the target representation
+    PrecisionLoss { field: &'static str },
+    /// No built-in taxonomy has the requested name
+    UnknownTaxonomy,
+    /// A series is too short for the requested model or split
+    InsufficientData { needed: usize, available: usize },
+}
+
+impl fmt::Display for EmotionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFinite { field } => write!(f, "{} is not a finite number", field),
+            Self::OutOfRange { field, value } => write!(f, "{} {} is out of range", field, value),
+            Self::Missing { field } => write!(f, "{} is required by the target type", field),
+            Self::Unrepresentable { field } => write!(f, "{} cannot be stored in the target type", field),
+            Self::TimestampOutOfRange => write!(f, "timestamp is out of range"),
+            Self::PrecisionLoss { field } => write!(f, "{} would lose precision", field),
+            Self::UnknownTaxonomy => write!(f, "unknown emotion taxonomy (expected pad, circumplex or plutchik)"),
+            Self::InsufficientData { needed, available } => {
+                write!(f, "need at least {} observations, got {}", needed, available)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EmotionError {}
@@ -0,0 +1,605 @@
+//! Forecasting VAD time series
+//!
+//! Three [`Forecaster`]s are provided, each modelling valence, arousal and
+//! dominance as independent series:
+//!
+//! - [`Kalman`]: constant-velocity Kalman filter
+//! - [`Holt`]: Holt's linear exponential smoothing (level and trend)
+//! - [`AutoRegressive`]: AR(p) fitted with Yule-Walker over a sliding window
+//!
+//! Each predicts any number of steps ahead with a per-dimension standard
+//! deviation, from which [`Prediction::interval`] builds confidence
+//! intervals. [`backtest`] scores a model on the tail of a trajectory.
+
+use crate::{EmotionError, Vad, AROUSAL_RANGE, DOMINANCE_RANGE, VALENCE_RANGE};
+
+/// z-score of a two-sided 80% normal interval
+pub const Z_80: f32 = 1.281_551_6;
+
+/// z-score of a two-sided 95% normal interval
+pub const Z_95: f32 = 1.959_964;
+
+/// Largest order [`AutoRegressive`] will fit
+pub const MAX_AR_ORDER: usize = 8;
+
+/// Number of recent observations [`AutoRegressive`] fits on
+pub const AR_WINDOW: usize = 64;
+
+const RANGES: [(f32, f32); 3] = [VALENCE_RANGE, AROUSAL_RANGE, DOMINANCE_RANGE];
+
+fn components(vad: &Vad) -> [f32; 3] {
+    [vad.valence, vad.arousal, vad.dominance]
+}
+
+fn clamped([valence, arousal, dominance]: [f32; 3]) -> Vad {
+    Vad::clamped(valence, arousal, dominance)
+}
+
+/// Forecast for a single horizon
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prediction {
+    pub steps_ahead: usize,
+    /// Point forecast, clamped into range
+    pub mean: Vad,
+    /// Standard deviation of the forecast error for valence, arousal and
+    /// dominance
+    pub std_dev: [f32; 3],
+}
+
+impl Prediction {
+    /// Lower and upper bounds of `mean ± z·std_dev`, clamped into range
+    pub fn interval(&self, z: f32) -> (Vad, Vad) {
+        let mean = components(&self.mean);
+        let lower = core::array::from_fn(|i| mean[i] - z * self.std_dev[i]);
+        let upper = core::array::from_fn(|i| mean[i] + z * self.std_dev[i]);
+        (clamped(lower), clamped(upper))
+    }
+
+    /// Whether each component of `actual` lies inside the `z` interval
+    pub fn contains(&self, actual: &Vad, z: f32) -> [bool; 3] {
+        let (lower, upper) = self.interval(z);
+        let (lower, upper, actual) = (components(&lower), components(&upper), components(actual));
+        core::array::from_fn(|i| actual[i] >= lower[i] && actual[i] <= upper[i])
+    }
+
+    /// 1.0 minus the mean width of the 95% interval relative to each
+    /// dimension's range; 0.0 when the interval covers the whole range
+    pub fn confidence(&self) -> f32 {
+        let spread: f32 = (0..3)
+            .map(|i| 2.0 * Z_95 * self.std_dev[i] / (RANGES[i].1 - RANGES[i].0))
+            .map(|share| share.min(1.0))
+            .sum();
+        (1.0 - spread / 3.0).clamp(0.0, 1.0)
+    }
+}
+
+/// Online model of a VAD time series
+pub trait Forecaster {
+    /// Feed the next observation
+    fn observe(&mut self, vad: Vad);
+
+    /// Forecast `steps_ahead` (at least 1) observations past the last one.
+    /// Returns `None` until [`Forecaster::min_observations`] have been seen.
+    fn predict(&self, steps_ahead: usize) -> Option<Prediction>;
+
+    /// Forget every observation
+    fn reset(&mut self);
+
+    /// Observations needed before `predict` returns a forecast
+    fn min_observations(&self) -> usize;
+
+    fn observe_all<I: IntoIterator<Item = Vad>>(&mut self, history: I)
+    where
+        Self: Sized,
+    {
+        for vad in history {
+            self.observe(vad);
+        }
+    }
+}
+
+/// Position-velocity state of one dimension
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct KalmanState {
+    x: [f32; 2],
+    p: [[f32; 2]; 2],
+}
+
+impl KalmanState {
+    /// Advance one step under the constant-velocity model
+    fn advance(&mut self, q: f32) {
+        let [x, v] = self.x;
+        let [[p00, p01], [p10, p11]] = self.p;
+        self.x = [x + v, v];
+        // F P Fᵀ + Q, with Q from white-noise acceleration over one step
+        self.p = [
+            [p00 + p01 + p10 + p11 + q * 0.25, p01 + p11 + q * 0.5],
+            [p10 + p11 + q * 0.5, p11 + q],
+        ];
+    }
+
+    fn update(&mut self, z: f32, r: f32) {
+        let [[p00, p01], [p10, p11]] = self.p;
+        let s = p00 + r;
+        let k = [p00 / s, p10 / s];
+        let innovation = z - self.x[0];
+        self.x = [self.x[0] + k[0] * innovation, self.x[1] + k[1] * innovation];
+        self.p = [
+            [p00 - k[0] * p00, p01 - k[0] * p01],
+            [p10 - k[1] * p00, p11 - k[1] * p01],
+        ];
+    }
+}
+
+/// Constant-velocity Kalman filter on each dimension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kalman {
+    /// Variance of the per-step change in velocity
+    pub process_noise: f32,
+    /// Variance of the observation noise
+    pub measurement_noise: f32,
+    states: [KalmanState; 3],
+    observations: usize,
+}
+
+impl Default for Kalman {
+    fn default() -> Self {
+        Self::new(1e-3, 1e-2)
+    }
+}
+
+impl Kalman {
+    pub fn new(process_noise: f32, measurement_noise: f32) -> Self {
+        Self {
+            process_noise,
+            measurement_noise,
+            states: [KalmanState::default(); 3],
+            observations: 0,
+        }
+    }
+}
+
+impl Forecaster for Kalman {
+    fn observe(&mut self, vad: Vad) {
+        let z = components(&vad);
+        for (state, z) in self.states.iter_mut().zip(z) {
+            if self.observations == 0 {
+                // Start at the first reading with unknown velocity
+                *state = KalmanState {
+                    x: [z, 0.0],
+                    p: [[self.measurement_noise, 0.0], [0.0, 1.0]],
+                };
+            } else {
+                state.advance(self.process_noise);
+                state.update(z, self.measurement_noise);
+            }
+        }
+        self.observations += 1;
+    }
+
+    fn predict(&self, steps_ahead: usize) -> Option<Prediction> {
+        if steps_ahead == 0 || self.observations < self.min_observations() {
+            return None;
+        }
+
+        let mut mean = [0.0; 3];
+        let mut std_dev = [0.0; 3];
+        for (i, state) in self.states.iter().enumerate() {
+            let mut state = *state;
+            for _ in 0..steps_ahead {
+                state.advance(self.process_noise);
+            }
+            mean[i] = state.x[0];
+            std_dev[i] = libm::sqrtf((state.p[0][0] + self.measurement_noise).max(0.0));
+        }
+
+        Some(Prediction {
+            steps_ahead,
+            mean: clamped(mean),
+            std_dev,
+        })
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.process_noise, self.measurement_noise);
+    }
+
+    fn min_observations(&self) -> usize {
+        2
+    }
+}
+
+/// Level, trend and one-step error of one dimension
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct HoltState {
+    level: f32,
+    trend: f32,
+    squared_error: f32,
+}
+
+/// Holt's linear exponential smoothing on each dimension
+///
+/// Intervals use the variance of the one-step errors seen so far, widened
+/// for longer horizons by Holt's error-propagation formula.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Holt {
+    /// Level smoothing, 0.0-1.0
+    pub alpha: f32,
+    /// Trend smoothing, 0.0-1.0
+    pub beta: f32,
+    states: [HoltState; 3],
+    observations: usize,
+}
+
+impl Default for Holt {
+    fn default() -> Self {
+        Self::new(0.5, 0.3)
+    }
+}
+
+impl Holt {
+    pub fn new(alpha: f32, beta: f32) -> Self {
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            beta: beta.clamp(0.0, 1.0),
+            states: [HoltState::default(); 3],
+            observations: 0,
+        }
+    }
+}
+
+impl Forecaster for Holt {
+    fn observe(&mut self, vad: Vad) {
+        let z = components(&vad);
+        for (state, z) in self.states.iter_mut().zip(z) {
+            match self.observations {
+                0 => state.level = z,
+                1 => {
+                    state.trend = z - state.level;
+                    state.level = z;
+                }
+                _ => {
+                    let forecast = state.level + state.trend;
+                    state.squared_error += (z - forecast) * (z - forecast);
+                    let level = self.alpha * z + (1.0 - self.alpha) * forecast;
+                    state.trend = self.beta * (level - state.level) + (1.0 - self.beta) * state.trend;
+                    state.level = level;
+                }
+            }
+        }
+        self.observations += 1;
+    }
+
+    fn predict(&self, steps_ahead: usize) -> Option<Prediction> {
+        if steps_ahead == 0 || self.observations < self.min_observations() {
+            return None;
+        }
+
+        // Σ_{j=1}^{h-1} α²(1 + jβ)²
+        let spread: f32 = (1..steps_ahead)
+            .map(|j| {
+                let weight = self.alpha * (1.0 + j as f32 * self.beta);
+                weight * weight
+            })
+            .sum();
+        let errors = (self.observations - 2) as f32;
+
+        let mut mean = [0.0; 3];
+        let mut std_dev = [0.0; 3];
+        for (i, state) in self.states.iter().enumerate() {
+            mean[i] = state.level + steps_ahead as f32 * state.trend;
+            std_dev[i] = libm::sqrtf(state.squared_error / errors * (1.0 + spread));
+        }
+
+        Some(Prediction {
+            steps_ahead,
+            mean: clamped(mean),
+            std_dev,
+        })
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.alpha, self.beta);
+    }
+
+    fn min_observations(&self) -> usize {
+        // Two to initialise level and trend, one more for an error estimate
+        3
+    }
+}
+
+/// AR(p) model on each dimension, refitted over the last [`AR_WINDOW`]
+/// observations at every prediction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoRegressive {
+    order: usize,
+    window: [[f32; 3]; AR_WINDOW],
+    len: usize,
+    next: usize,
+}
+
+impl Default for AutoRegressive {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+impl AutoRegressive {
+    /// Model of the given order, clamped to 1..=[`MAX_AR_ORDER`]
+    pub fn new(order: usize) -> Self {
+        Self {
+            order: order.clamp(1, MAX_AR_ORDER),
+            window: [[0.0; 3]; AR_WINDOW],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// One dimension of the window, oldest first
+    fn series(&self, dimension: usize) -> ([f32; AR_WINDOW], usize) {
+        let start = (self.next + AR_WINDOW - self.len) % AR_WINDOW;
+        let mut series = [0.0; AR_WINDOW];
+        for (i, value) in series.iter_mut().take(self.len).enumerate() {
+            *value = self.window[(start + i) % AR_WINDOW][dimension];
+        }
+        (series, self.len)
+    }
+
+    /// Yule-Walker fit by Levinson-Durbin: coefficients φ₁..φₚ (index 0
+    /// unused), the series mean and the innovation variance
+    fn fit(&self, series: &[f32]) -> ([f32; MAX_AR_ORDER + 1], f32, f32) {
+        let n = series.len() as f32;
+        let mean = series.iter().sum::<f32>() / n;
+
+        let mut autocovariance = [0.0f32; MAX_AR_ORDER + 1];
+        for (lag, value) in autocovariance.iter_mut().take(self.order + 1).enumerate() {
+            *value = series
+                .iter()
+                .zip(&series[lag..])
+                .map(|(a, b)| (a - mean) * (b - mean))
+                .sum::<f32>()
+                / n;
+        }
+
+        let mut phi = [0.0f32; MAX_AR_ORDER + 1];
+        let mut error = autocovariance[0];
+        if error <= f32::EPSILON {
+            // Constant series: the mean is the forecast
+            return (phi, mean, 0.0);
+        }
+        for k in 1..=self.order {
+            let acc = autocovariance[k] - (1..k).map(|j| phi[j] * autocovariance[k - j]).sum::<f32>();
+            let reflection = (acc / error).clamp(-1.0, 1.0);
+            let previous = phi;
+            phi[k] = reflection;
+            for j in 1..k {
+                phi[j] = previous[j] - reflection * previous[k - j];
+            }
+            error *= 1.0 - reflection * reflection;
+        }
+        (phi, mean, error.max(0.0))
+    }
+}
+
+impl Forecaster for AutoRegressive {
+    fn observe(&mut self, vad: Vad) {
+        self.window[self.next] = components(&vad);
+        self.next = (self.next + 1) % AR_WINDOW;
+        self.len = (self.len + 1).min(AR_WINDOW);
+    }
+
+    fn predict(&self, steps_ahead: usize) -> Option<Prediction> {
+        if steps_ahead == 0 || self.len < self.min_observations() {
+            return None;
+        }
+
+        let p = self.order;
+        let mut mean = [0.0; 3];
+        let mut std_dev = [0.0; 3];
+        for dimension in 0..3 {
+            let (series, len) = self.series(dimension);
+            let series = &series[..len];
+            let (phi, series_mean, variance) = self.fit(series);
+
+            // Most recent centred values and ψ weights, newest first
+            let mut recent = [0.0f32; MAX_AR_ORDER];
+            for (i, value) in recent.iter_mut().take(p).enumerate() {
+                *value = series[len - 1 - i] - series_mean;
+            }
+            let mut psi = [0.0f32; MAX_AR_ORDER];
+            psi[0] = 1.0;
+            let mut psi_squared = 1.0;
+
+            let mut forecast = 0.0;
+            for step in 0..steps_ahead {
+                forecast = (1..=p).map(|i| phi[i] * recent[i - 1]).sum();
+                recent.copy_within(0..p - 1, 1);
+                recent[0] = forecast;
+
+                if step + 1 < steps_ahead {
+                    let next_psi: f32 = (1..=p).map(|i| phi[i] * psi[i - 1]).sum();
+                    psi.copy_within(0..p - 1, 1);
+                    psi[0] = next_psi;
+                    psi_squared += next_psi * next_psi;
+                }
+            }
+
+            mean[dimension] = series_mean + forecast;
+            std_dev[dimension] = libm::sqrtf(variance * psi_squared);
+        }
+
+        Some(Prediction {
+            steps_ahead,
+            mean: clamped(mean),
+            std_dev,
+        })
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.order);
+    }
+
+    fn min_observations(&self) -> usize {
+        2 * self.order + 1
+    }
+}
+
+/// Accuracy of a model's forecasts over a held-out tail
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestReport {
+    /// Number of held-out observations
+    pub samples: usize,
+    /// Mean absolute error for valence, arousal and dominance
+    pub mae: [f32; 3],
+    /// Root mean squared error for valence, arousal and dominance
+    pub rmse: [f32; 3],
+    /// Share of held-out components inside their 95% interval
+    pub coverage: f32,
+}
+
+impl BacktestReport {
+    pub fn mean_mae(&self) -> f32 {
+        self.mae.iter().sum::<f32>() / 3.0
+    }
+
+    pub fn mean_rmse(&self) -> f32 {
+        self.rmse.iter().sum::<f32>() / 3.0
+    }
+}
+
+/// Fit `model` on all but the last `holdout` points of `trajectory`, then
+/// forecast 1..=`holdout` steps ahead and compare against the held-out points
+///
+/// The model is reset first and left fitted on the training part.
+pub fn backtest<F: Forecaster + ?Sized>(
+    model: &mut F,
+    trajectory: &[Vad],
+    holdout: usize,
+) -> Result<BacktestReport, EmotionError> {
+    let needed = model.min_observations() + holdout.max(1);
+    if holdout == 0 || trajectory.len() < needed {
+        return Err(EmotionError::InsufficientData {
+            needed,
+            available: trajectory.len(),
+        });
+    }
+
+    let split = trajectory.len() - holdout;
+    model.reset();
+    for vad in &trajectory[..split] {
+        model.observe(*vad);
+    }
+
+    let mut absolute = [0.0f32; 3];
+    let mut squared = [0.0f32; 3];
+    let mut covered = 0usize;
+    for (step, actual) in trajectory[split..].iter().enumerate() {
+        let prediction = model.predict(step + 1).ok_or(EmotionError::InsufficientData {
+            needed,
+            available: trajectory.len(),
+        })?;
+        let (forecast, actual_components) = (components(&prediction.mean), components(actual));
+        for i in 0..3 {
+            let error = actual_components[i] - forecast[i];
+            absolute[i] += error.abs();
+            squared[i] += error * error;
+        }
+        covered += prediction.contains(actual, Z_95).iter().filter(|inside| **inside).count();
+    }
+
+    let n = holdout as f32;
+    Ok(BacktestReport {
+        samples: holdout,
+        mae: absolute.map(|sum| sum / n),
+        rmse: squared.map(|sum| libm::sqrtf(sum / n)),
+        coverage: covered as f32 / (3 * holdout) as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Valence rising steadily, arousal oscillating, dominance constant
+    fn trajectory(len: usize) -> impl Iterator<Item = Vad> {
+        (0..len).map(|t| {
+            let t = t as f32;
+            Vad::clamped(-0.8 + 0.02 * t, 0.5 + 0.3 * libm::sinf(t * 0.6), 0.4)
+        })
+    }
+
+    #[test]
+    fn test_models_follow_linear_trend() {
+        let mut kalman = Kalman::default();
+        let mut holt = Holt::default();
+        kalman.observe_all(trajectory(30));
+        holt.observe_all(trajectory(30));
+
+        let expected = -0.8 + 0.02 * 32.0;
+        for prediction in [kalman.predict(3).unwrap(), holt.predict(3).unwrap()] {
+            assert!((prediction.mean.valence - expected).abs() < 0.05, "{:?}", prediction);
+            assert!((prediction.mean.dominance - 0.4).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_intervals_widen_with_horizon() {
+        let models: [&mut dyn Forecaster; 3] = [&mut Kalman::default(), &mut Holt::default(), &mut AutoRegressive::new(2)];
+        for model in models {
+            for vad in trajectory(40) {
+                model.observe(vad);
+            }
+            let near = model.predict(1).unwrap();
+            let far = model.predict(8).unwrap();
+            assert!(far.std_dev[1] >= near.std_dev[1]);
+            assert!(far.confidence() <= near.confidence());
+
+            let (lower, upper) = far.interval(Z_95);
+            assert!(lower.arousal <= far.mean.arousal && far.mean.arousal <= upper.arousal);
+            assert!(model.predict(0).is_none());
+        }
+    }
+
+    #[test]
+    fn test_min_observations() {
+        let mut ar = AutoRegressive::new(3);
+        for vad in trajectory(ar.min_observations() - 1) {
+            ar.observe(vad);
+        }
+        assert!(ar.predict(1).is_none());
+        ar.observe(Vad::NEUTRAL);
+        assert!(ar.predict(1).is_some());
+        assert_eq!(AutoRegressive::new(50).order(), MAX_AR_ORDER);
+    }
+
+    #[test]
+    fn test_autoregressive_fits_oscillation() {
+        // A sinusoid is AR(2), so arousal should be tracked far better than
+        // a trend model can
+        let history: [Vad; 60] = core::array::from_fn(|t| trajectory(t + 1).last().unwrap());
+        let ar = backtest(&mut AutoRegressive::new(2), &history, 10).unwrap();
+        let holt = backtest(&mut Holt::default(), &history, 10).unwrap();
+        assert_eq!(ar.samples, 10);
+        assert!(ar.mae[1] < 0.1, "{:?}", ar);
+        assert!(ar.mae[1] < holt.mae[1] / 2.0, "{:?} vs {:?}", ar, holt);
+        assert!(ar.rmse[1] >= ar.mae[1]);
+    }
+
+    #[test]
+    fn test_backtest() {
+        let history: [Vad; 40] = core::array::from_fn(|t| trajectory(t + 1).last().unwrap());
+        let report = backtest(&mut Holt::default(), &history, 5).unwrap();
+        assert!(report.mean_mae() <= report.mean_rmse());
+        assert!((0.0..=1.0).contains(&report.coverage));
+
+        assert_eq!(
+            backtest(&mut Holt::default(), &history[..4], 2),
+            Err(EmotionError::InsufficientData { needed: 5, available: 4 })
+        );
+        assert!(backtest(&mut Kalman::default(), &history, 0).is_err());
+    }
+}
@@ -0,0 +1,31 @@
+//! # Emotion Core
+//!
+//! Canonical valence-arousal-dominance (VAD) types shared by the Rust client,
+//! IPFS storage, and the NEAR, Solana and Polkadot contracts.
+//!
+//! Every crate converts its own emotion type to and from [`EmotionReading`]
+//! and takes category labels and complexity scores from here, so the same
+//! reading gets the same label wherever it is handled. The default label is
+//! the PAD octant; [`EmotionTaxonomy`] lets callers pick another model.
+//! [`Forecaster`]s predict where a series of readings is heading.
+//!
+//! The crate is `no_std` with the default `std` feature turned off. Codec
+//! derives are behind the `serde`, `borsh` and `scale` features.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod category;
+mod complexity;
+mod error;
+mod forecast;
+mod reading;
+mod taxonomy;
+mod vad;
+
+pub use category::*;
+pub use complexity::*;
+pub use error::*;
+pub use forecast::*;
+pub use reading::*;
+pub use taxonomy::*;
+pub use vad::*;
@@ -0,0 +1,226 @@
+//! Timestamped emotion readings
+//!
+//! [`EmotionReading`] is the exchange type between crates. Conversions into
+//! chain-specific types fail rather than drop or invent data: a missing
+//! confidence is not defaulted, and a nanosecond timestamp is not truncated to
+//! seconds. Use [`EmotionReading::truncate_to_seconds`] or build the target
+//! from [`EmotionReading::vad`] when that loss is intended.
+
+use crate::{EmotionCategory, EmotionError, Vad};
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const NANOS_PER_MILLI: i64 = 1_000_000;
+
+/// VAD point with optional confidence and capture time
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub struct EmotionReading {
+    pub vad: Vad,
+    /// Certainty of the reading, 0.0-1.0
+    pub confidence: Option<f32>,
+    /// Capture time in nanoseconds since the Unix epoch
+    pub timestamp_ns: Option<i64>,
+}
+
+impl From<Vad> for EmotionReading {
+    fn from(vad: Vad) -> Self {
+        Self::new(vad)
+    }
+}
+
+impl EmotionReading {
+    pub fn new(vad: Vad) -> Self {
+        Self {
+            vad,
+            confidence: None,
+            timestamp_ns: None,
+        }
+    }
+
+    pub fn with_confidence(mut self, confidence: f32) -> Result<Self, EmotionError> {
+        if !confidence.is_finite() {
+            return Err(EmotionError::NotFinite { field: "confidence" });
+        }
+        if !(0.0..=1.0).contains(&confidence) {
+            return Err(EmotionError::OutOfRange {
+                field: "confidence",
+                value: confidence,
+            });
+        }
+        self.confidence = Some(confidence);
+        Ok(self)
+    }
+
+    pub fn with_timestamp_ns(mut self, timestamp_ns: i64) -> Self {
+        self.timestamp_ns = Some(timestamp_ns);
+        self
+    }
+
+    /// Set the timestamp from whole seconds
+    pub fn with_timestamp_secs(self, timestamp_secs: i64) -> Result<Self, EmotionError> {
+        let nanos = timestamp_secs
+            .checked_mul(NANOS_PER_SECOND)
+            .ok_or(EmotionError::TimestampOutOfRange)?;
+        Ok(self.with_timestamp_ns(nanos))
+    }
+
+    /// Set the timestamp from whole milliseconds (JavaScript `Date.now()`)
+    pub fn with_timestamp_millis(self, timestamp_millis: i64) -> Result<Self, EmotionError> {
+        let nanos = timestamp_millis
+            .checked_mul(NANOS_PER_MILLI)
+            .ok_or(EmotionError::TimestampOutOfRange)?;
+        Ok(self.with_timestamp_ns(nanos))
+    }
+
+    /// Set the timestamp from unsigned nanoseconds (NEAR block time)
+    pub fn with_timestamp_ns_u64(self, timestamp_ns: u64) -> Result<Self, EmotionError> {
+        let nanos = i64::try_from(timestamp_ns).map_err(|_| EmotionError::TimestampOutOfRange)?;
+        Ok(self.with_timestamp_ns(nanos))
+    }
+
+    /// Check the VAD point and confidence
+    pub fn validate(&self) -> Result<(), EmotionError> {
+        self.vad.validate()?;
+        if let Some(confidence) = self.confidence {
+            Self::new(self.vad).with_confidence(confidence)?;
+        }
+        Ok(())
+    }
+
+    pub fn category(&self) -> EmotionCategory {
+        self.vad.category()
+    }
+
+    /// Confidence, for targets that always store one
+    pub fn require_confidence(&self) -> Result<f32, EmotionError> {
+        self.confidence.ok_or(EmotionError::Missing { field: "confidence" })
+    }
+
+    /// Timestamp in nanoseconds, for targets that always store one
+    pub fn require_timestamp_ns(&self) -> Result<i64, EmotionError> {
+        self.timestamp_ns.ok_or(EmotionError::Missing { field: "timestamp" })
+    }
+
+    /// Timestamp in whole seconds; fails if it has a sub-second part
+    pub fn require_timestamp_secs(&self) -> Result<i64, EmotionError> {
+        self.require_timestamp_in(NANOS_PER_SECOND)
+    }
+
+    /// Timestamp in whole milliseconds; fails if it has a sub-millisecond part
+    pub fn require_timestamp_millis(&self) -> Result<i64, EmotionError> {
+        self.require_timestamp_in(NANOS_PER_MILLI)
+    }
+
+    fn require_timestamp_in(&self, nanos_per_unit: i64) -> Result<i64, EmotionError> {
+        let nanos = self.require_timestamp_ns()?;
+        if nanos % nanos_per_unit != 0 {
+            return Err(EmotionError::PrecisionLoss { field: "timestamp" });
+        }
+        Ok(nanos / nanos_per_unit)
+    }
+
+    /// Timestamp in unsigned nanoseconds (NEAR block time)
+    pub fn require_timestamp_ns_u64(&self) -> Result<u64, EmotionError> {
+        u64::try_from(self.require_timestamp_ns()?).map_err(|_| EmotionError::TimestampOutOfRange)
+    }
+
+    /// Drop the sub-second part of the timestamp, rounding towards the past
+    pub fn truncate_to_seconds(mut self) -> Self {
+        self.timestamp_ns = self
+            .timestamp_ns
+            .map(|nanos| nanos.div_euclid(NANOS_PER_SECOND) * NANOS_PER_SECOND);
+        self
+    }
+
+    /// Fail if the reading carries a confidence or timestamp, for targets
+    /// that only store the VAD point
+    pub fn into_vad_only(self) -> Result<Vad, EmotionError> {
+        if self.confidence.is_some() {
+            return Err(EmotionError::Unrepresentable { field: "confidence" });
+        }
+        if self.timestamp_ns.is_some() {
+            return Err(EmotionError::Unrepresentable { field: "timestamp" });
+        }
+        Ok(self.vad)
+    }
+}
+
+#[cfg(feature = "scale")]
+impl parity_scale_codec::Encode for EmotionReading {
+    fn encode_to<T: parity_scale_codec::Output + ?Sized>(&self, dest: &mut T) {
+        self.vad.encode_to(dest);
+        self.confidence.map(f32::to_bits).encode_to(dest);
+        self.timestamp_ns.encode_to(dest);
+    }
+}
+
+#[cfg(feature = "scale")]
+impl parity_scale_codec::Decode for EmotionReading {
+    fn decode<I: parity_scale_codec::Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        Ok(Self {
+            vad: Vad::decode(input)?,
+            confidence: Option::<u32>::decode(input)?.map(f32::from_bits),
+            timestamp_ns: Option::<i64>::decode(input)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading() -> EmotionReading {
+        EmotionReading::new(Vad::new(0.6, 0.7, 0.4).unwrap())
+    }
+
+    #[test]
+    fn test_timestamp_precision() {
+        let whole = reading().with_timestamp_secs(1_700_000_000).unwrap();
+        assert_eq!(whole.require_timestamp_secs(), Ok(1_700_000_000));
+
+        let fine = reading().with_timestamp_ns(1_700_000_000_123_456_789);
+        assert_eq!(fine.require_timestamp_secs(), Err(EmotionError::PrecisionLoss { field: "timestamp" }));
+        assert_eq!(fine.truncate_to_seconds().require_timestamp_secs(), Ok(1_700_000_000));
+
+        assert_eq!(reading().with_timestamp_secs(i64::MAX).err(), Some(EmotionError::TimestampOutOfRange));
+        assert_eq!(reading().with_timestamp_ns_u64(u64::MAX).err(), Some(EmotionError::TimestampOutOfRange));
+    }
+
+    #[test]
+    fn test_vad_only_targets() {
+        assert!(reading().into_vad_only().is_ok());
+        let with_confidence = reading().with_confidence(0.9).unwrap();
+        assert_eq!(
+            with_confidence.into_vad_only(),
+            Err(EmotionError::Unrepresentable { field: "confidence" })
+        );
+        assert!(reading().with_confidence(1.5).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let reading = reading().with_confidence(0.9).unwrap().with_timestamp_ns(42);
+        let json = serde_json::to_string(&reading).unwrap();
+        assert_eq!(serde_json::from_str::<EmotionReading>(&json).unwrap(), reading);
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_round_trip() {
+        let reading = reading().with_confidence(0.9).unwrap().with_timestamp_ns(42);
+        let bytes = borsh::to_vec(&reading).unwrap();
+        assert_eq!(borsh::from_slice::<EmotionReading>(&bytes).unwrap(), reading);
+    }
+
+    #[cfg(feature = "scale")]
+    #[test]
+    fn test_scale_round_trip() {
+        use parity_scale_codec::{Decode, Encode};
+
+        let reading = reading().with_confidence(0.9).unwrap().with_timestamp_ns(-42);
+        let bytes = reading.encode();
+        assert_eq!(EmotionReading::decode(&mut &bytes[..]).unwrap(), reading);
+    }
+}
@@ -0,0 +1,360 @@
+//! Pluggable emotion taxonomies
+//!
+//! A taxonomy turns a [`Vad`] point into a named emotion. Three models are
+//! provided:
+//!
+//! - [`PadOctants`]: the eight octants of the VAD space ([`EmotionCategory`])
+//! - [`Circumplex`]: Russell's circumplex, eight sectors of the
+//!   valence-arousal plane by angle
+//! - [`Plutchik`]: the nearest of Plutchik's eight primary emotions
+//!
+//! [`TaxonomyModel`] selects one of them by name, for call sites that take
+//! the model from configuration.
+
+use crate::{EmotionCategory, EmotionError, Vad};
+use core::f32::consts::PI;
+use core::fmt;
+use core::str::FromStr;
+
+/// Result of classifying a VAD point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Classification {
+    /// Emotion name, one of the taxonomy's [`EmotionTaxonomy::labels`]
+    pub label: &'static str,
+    /// Strength of the emotion, 0.0-1.0
+    pub intensity: f32,
+    /// How clearly the point belongs to `label` rather than a neighbour,
+    /// 0.0-1.0. Points on a boundary between two labels score 0.0.
+    pub confidence: f32,
+}
+
+/// Model that names the emotion at a VAD point
+pub trait EmotionTaxonomy {
+    /// Short identifier, as accepted by [`TaxonomyModel::from_str`]
+    fn name(&self) -> &'static str;
+
+    /// Every label `classify` can return
+    fn labels(&self) -> &'static [&'static str];
+
+    fn classify(&self, vad: &Vad) -> Classification;
+}
+
+/// The eight octants of the VAD space, split at neutral
+///
+/// Labels match [`EmotionCategory`]. Intensity is [`Vad::intensity`];
+/// confidence is the distance to the nearest split, relative to the half
+/// width of that axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PadOctants;
+
+impl PadOctants {
+    const LABELS: [&'static str; 8] = [
+        "Excited", "Happy", "Proud", "Calm", "Angry", "Anxious", "Bored", "Sad",
+    ];
+}
+
+impl EmotionTaxonomy for PadOctants {
+    fn name(&self) -> &'static str {
+        "pad"
+    }
+
+    fn labels(&self) -> &'static [&'static str] {
+        &Self::LABELS
+    }
+
+    fn classify(&self, vad: &Vad) -> Classification {
+        let margin = (vad.valence - Vad::NEUTRAL.valence)
+            .abs()
+            .min((vad.arousal - Vad::NEUTRAL.arousal).abs() * 2.0)
+            .min((vad.dominance - Vad::NEUTRAL.dominance).abs() * 2.0);
+
+        Classification {
+            label: EmotionCategory::from_vad(vad).as_str(),
+            intensity: vad.intensity(),
+            confidence: margin.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Russell's circumplex model of affect
+///
+/// Valence and arousal are both rescaled to -1.0-1.0 and read as a point on
+/// a circle. The angle picks one of eight 45° sectors, starting with
+/// "Pleased" centred on 0° (positive valence, neutral arousal) and going
+/// anticlockwise. Dominance is ignored.
+///
+/// Intensity is the distance from the centre, with the corners of the plane
+/// at 1.0. Confidence falls to 0.0 at a sector edge, and is scaled down for
+/// points within [`Circumplex::AMBIGUOUS_RADIUS`] of the centre, where the
+/// angle means little.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Circumplex;
+
+impl Circumplex {
+    const LABELS: [&'static str; 8] = [
+        "Pleased", "Excited", "Alert", "Tense", "Miserable", "Depressed", "Tired", "Relaxed",
+    ];
+
+    const SECTOR_DEGREES: f32 = 360.0 / 8.0;
+
+    /// Intensity below which a point is too close to neutral for its angle
+    /// to be trusted
+    pub const AMBIGUOUS_RADIUS: f32 = 0.25;
+
+    /// Angle of the point on the valence-arousal plane, in degrees 0-360,
+    /// anticlockwise from positive valence
+    pub fn angle(vad: &Vad) -> f32 {
+        let (x, y) = Self::plane(vad);
+        let degrees = libm::atan2f(y, x) * 180.0 / PI;
+        if degrees < 0.0 {
+            degrees + 360.0
+        } else {
+            degrees
+        }
+    }
+
+    /// Distance from the centre of the plane, scaled to 0.0-1.0
+    pub fn radius(vad: &Vad) -> f32 {
+        let (x, y) = Self::plane(vad);
+        (libm::sqrtf(x * x + y * y) / core::f32::consts::SQRT_2).min(1.0)
+    }
+
+    fn plane(vad: &Vad) -> (f32, f32) {
+        (vad.valence, (vad.arousal - Vad::NEUTRAL.arousal) * 2.0)
+    }
+}
+
+impl EmotionTaxonomy for Circumplex {
+    fn name(&self) -> &'static str {
+        "circumplex"
+    }
+
+    fn labels(&self) -> &'static [&'static str] {
+        &Self::LABELS
+    }
+
+    fn classify(&self, vad: &Vad) -> Classification {
+        let angle = Self::angle(vad);
+        let radius = Self::radius(vad);
+
+        // Shift by half a sector so sector 0 spans -22.5° to 22.5°
+        let shifted = (angle + Self::SECTOR_DEGREES / 2.0) % 360.0;
+        let sector = (shifted / Self::SECTOR_DEGREES) as usize % 8;
+        let offset = (shifted % Self::SECTOR_DEGREES - Self::SECTOR_DEGREES / 2.0).abs();
+        let centrality = 1.0 - offset / (Self::SECTOR_DEGREES / 2.0);
+
+        Classification {
+            label: Self::LABELS[sector],
+            intensity: radius,
+            confidence: (centrality * (radius / Self::AMBIGUOUS_RADIUS).min(1.0)).clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Plutchik's wheel of eight primary emotions
+///
+/// Each emotion has a prototype VAD point; a reading takes the label of the
+/// nearest prototype, measured with valence halved so that every axis spans
+/// 1.0. Intensity is [`Vad::intensity`], the wheel's radial dimension.
+/// Confidence compares the nearest and second-nearest prototypes, so a point
+/// equidistant from two emotions scores 0.0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Plutchik;
+
+impl Plutchik {
+    const LABELS: [&'static str; 8] = [
+        "Joy",
+        "Trust",
+        "Fear",
+        "Surprise",
+        "Sadness",
+        "Disgust",
+        "Anger",
+        "Anticipation",
+    ];
+
+    /// Prototype (valence, arousal, dominance) of each label, in wheel order
+    const PROTOTYPES: [(f32, f32, f32); 8] = [
+        (0.8, 0.7, 0.7),
+        (0.6, 0.35, 0.5),
+        (-0.65, 0.8, 0.2),
+        (0.2, 0.85, 0.4),
+        (-0.65, 0.3, 0.25),
+        (-0.6, 0.55, 0.6),
+        (-0.5, 0.8, 0.75),
+        (0.4, 0.65, 0.6),
+    ];
+
+    fn distance(vad: &Vad, (v, a, d): (f32, f32, f32)) -> f32 {
+        let dv = (vad.valence - v) / 2.0;
+        let da = vad.arousal - a;
+        let dd = vad.dominance - d;
+        libm::sqrtf(dv * dv + da * da + dd * dd)
+    }
+}
+
+impl EmotionTaxonomy for Plutchik {
+    fn name(&self) -> &'static str {
+        "plutchik"
+    }
+
+    fn labels(&self) -> &'static [&'static str] {
+        &Self::LABELS
+    }
+
+    fn classify(&self, vad: &Vad) -> Classification {
+        let mut nearest = (0, f32::INFINITY);
+        let mut second = f32::INFINITY;
+        for (i, prototype) in Self::PROTOTYPES.iter().enumerate() {
+            let distance = Self::distance(vad, *prototype);
+            if distance < nearest.1 {
+                second = nearest.1;
+                nearest = (i, distance);
+            } else if distance < second {
+                second = distance;
+            }
+        }
+
+        let total = nearest.1 + second;
+        let confidence = if total > 0.0 { (second - nearest.1) / total } else { 0.0 };
+
+        Classification {
+            label: Self::LABELS[nearest.0],
+            intensity: vad.intensity(),
+            confidence: confidence.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Built-in taxonomy chosen at runtime
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+#[cfg_attr(feature = "scale", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+pub enum TaxonomyModel {
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "pad"))]
+    PadOctant,
+    Circumplex,
+    Plutchik,
+}
+
+impl TaxonomyModel {
+    pub const ALL: [TaxonomyModel; 3] = [Self::PadOctant, Self::Circumplex, Self::Plutchik];
+
+    fn taxonomy(&self) -> &'static dyn EmotionTaxonomy {
+        match self {
+            Self::PadOctant => &PadOctants,
+            Self::Circumplex => &Circumplex,
+            Self::Plutchik => &Plutchik,
+        }
+    }
+}
+
+impl EmotionTaxonomy for TaxonomyModel {
+    fn name(&self) -> &'static str {
+        self.taxonomy().name()
+    }
+
+    fn labels(&self) -> &'static [&'static str] {
+        self.taxonomy().labels()
+    }
+
+    fn classify(&self, vad: &Vad) -> Classification {
+        self.taxonomy().classify(vad)
+    }
+}
+
+impl FromStr for TaxonomyModel {
+    type Err = EmotionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|model| model.name().eq_ignore_ascii_case(s))
+            .ok_or(EmotionError::UnknownTaxonomy)
+    }
+}
+
+impl fmt::Display for TaxonomyModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vad(v: f32, a: f32, d: f32) -> Vad {
+        Vad::new(v, a, d).unwrap()
+    }
+
+    #[test]
+    fn test_pad_octants_match_category() {
+        for v in [-0.9, -0.2, 0.0, 0.3, 1.0] {
+            for a in [0.0, 0.4, 0.5, 0.9] {
+                let point = vad(v, a, 0.8);
+                assert_eq!(PadOctants.classify(&point).label, point.category().as_str());
+            }
+        }
+        assert_eq!(PadOctants.classify(&Vad::NEUTRAL).confidence, 0.0);
+        assert_eq!(PadOctants.classify(&vad(1.0, 1.0, 0.0)).confidence, 1.0);
+    }
+
+    #[test]
+    fn test_circumplex_sectors() {
+        let cases = [
+            ((1.0, 0.5), "Pleased", 0.0),
+            ((1.0, 1.0), "Excited", 45.0),
+            ((0.0, 1.0), "Alert", 90.0),
+            ((-1.0, 1.0), "Tense", 135.0),
+            ((-1.0, 0.5), "Miserable", 180.0),
+            ((-1.0, 0.0), "Depressed", 225.0),
+            ((0.0, 0.0), "Tired", 270.0),
+            ((1.0, 0.0), "Relaxed", 315.0),
+        ];
+        for ((v, a), label, angle) in cases {
+            let point = vad(v, a, 0.5);
+            let classification = Circumplex.classify(&point);
+            assert_eq!(classification.label, label);
+            assert!((Circumplex::angle(&point) - angle).abs() < 1e-3);
+            assert!(classification.confidence > 0.99);
+        }
+
+        // On the edge between Pleased and Excited
+        let edge = vad(1.0, 0.5 + libm::tanf(PI / 8.0) / 2.0, 0.5);
+        assert!(Circumplex.classify(&edge).confidence < 1e-3);
+        // Too close to neutral to trust the angle
+        let faint = Circumplex.classify(&vad(0.05, 0.5, 0.5));
+        assert_eq!(faint.label, "Pleased");
+        assert!(faint.confidence < 0.2);
+    }
+
+    #[test]
+    fn test_plutchik_prototypes() {
+        for (label, (v, a, d)) in Plutchik::LABELS.iter().zip(Plutchik::PROTOTYPES) {
+            let classification = Plutchik.classify(&vad(v, a, d));
+            assert_eq!(classification.label, *label);
+            assert_eq!(classification.confidence, 1.0);
+        }
+        // Fear and anger differ by dominance
+        assert_eq!(Plutchik.classify(&vad(-0.6, 0.9, 0.1)).label, "Fear");
+        assert_eq!(Plutchik.classify(&vad(-0.6, 0.9, 0.9)).label, "Anger");
+    }
+
+    #[test]
+    fn test_model_selection() {
+        assert_eq!("Circumplex".parse::<TaxonomyModel>(), Ok(TaxonomyModel::Circumplex));
+        assert_eq!("pad".parse::<TaxonomyModel>(), Ok(TaxonomyModel::PadOctant));
+        assert_eq!("wheel".parse::<TaxonomyModel>(), Err(EmotionError::UnknownTaxonomy));
+
+        let point = vad(0.7, 0.8, 0.3);
+        for model in TaxonomyModel::ALL {
+            let classification = model.classify(&point);
+            assert!(model.labels().contains(&classification.label));
+            assert_eq!(point.classify(&model), classification);
+        }
+    }
+}
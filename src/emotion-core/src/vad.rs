@@ -0,0 +1,160 @@
+//! Valence-arousal-dominance point
+
+use crate::{Classification, EmotionCategory, EmotionError, EmotionTaxonomy};
+
+/// Valence range: displeasure (-1.0) to pleasure (1.0)
+pub const VALENCE_RANGE: (f32, f32) = (-1.0, 1.0);
+
+/// Arousal range: calm (0.0) to excited (1.0)
+pub const AROUSAL_RANGE: (f32, f32) = (0.0, 1.0);
+
+/// Dominance range: controlled (0.0) to in control (1.0)
+pub const DOMINANCE_RANGE: (f32, f32) = (0.0, 1.0);
+
+/// Distance from [`Vad::NEUTRAL`] to the furthest corner of the VAD space
+const MAX_NEUTRAL_DISTANCE: f32 = 1.224_744_9; // sqrt(1.0 + 0.25 + 0.25)
+
+/// Canonical emotional state in the valence-arousal-dominance model
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub struct Vad {
+    pub valence: f32,
+    pub arousal: f32,
+    pub dominance: f32,
+}
+
+impl Default for Vad {
+    fn default() -> Self {
+        Self::NEUTRAL
+    }
+}
+
+impl Vad {
+    /// Centre of the VAD space
+    pub const NEUTRAL: Vad = Vad {
+        valence: 0.0,
+        arousal: 0.5,
+        dominance: 0.5,
+    };
+
+    /// Create a point, rejecting NaN and out-of-range components
+    pub fn new(valence: f32, arousal: f32, dominance: f32) -> Result<Self, EmotionError> {
+        let vad = Self {
+            valence,
+            arousal,
+            dominance,
+        };
+        vad.validate()?;
+        Ok(vad)
+    }
+
+    /// Create a point, clamping components into range; NaN becomes neutral
+    pub fn clamped(valence: f32, arousal: f32, dominance: f32) -> Self {
+        fn clamp(value: f32, (min, max): (f32, f32), neutral: f32) -> f32 {
+            if value.is_nan() {
+                neutral
+            } else {
+                value.clamp(min, max)
+            }
+        }
+
+        Self {
+            valence: clamp(valence, VALENCE_RANGE, Self::NEUTRAL.valence),
+            arousal: clamp(arousal, AROUSAL_RANGE, Self::NEUTRAL.arousal),
+            dominance: clamp(dominance, DOMINANCE_RANGE, Self::NEUTRAL.dominance),
+        }
+    }
+
+    /// Check that every component is finite and in range
+    pub fn validate(&self) -> Result<(), EmotionError> {
+        check("valence", self.valence, VALENCE_RANGE)?;
+        check("arousal", self.arousal, AROUSAL_RANGE)?;
+        check("dominance", self.dominance, DOMINANCE_RANGE)
+    }
+
+    /// Category label for this point
+    pub fn category(&self) -> EmotionCategory {
+        EmotionCategory::from_vad(self)
+    }
+
+    /// Label, intensity and confidence for this point under `taxonomy`
+    pub fn classify<T: EmotionTaxonomy + ?Sized>(&self, taxonomy: &T) -> Classification {
+        taxonomy.classify(self)
+    }
+
+    /// Euclidean distance to another point
+    pub fn distance(&self, other: &Vad) -> f32 {
+        let dv = self.valence - other.valence;
+        let da = self.arousal - other.arousal;
+        let dd = self.dominance - other.dominance;
+        libm::sqrtf(dv * dv + da * da + dd * dd)
+    }
+
+    /// Distance from neutral, scaled to 0.0-1.0
+    pub fn intensity(&self) -> f32 {
+        (self.distance(&Self::NEUTRAL) / MAX_NEUTRAL_DISTANCE).min(1.0)
+    }
+}
+
+fn check(field: &'static str, value: f32, (min, max): (f32, f32)) -> Result<(), EmotionError> {
+    if !value.is_finite() {
+        return Err(EmotionError::NotFinite { field });
+    }
+    if value < min || value > max {
+        return Err(EmotionError::OutOfRange { field, value });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "scale")]
+impl parity_scale_codec::Encode for Vad {
+    // SCALE has no float encoding, so components are written as IEEE-754 bits
+    fn encode_to<T: parity_scale_codec::Output + ?Sized>(&self, dest: &mut T) {
+        self.valence.to_bits().encode_to(dest);
+        self.arousal.to_bits().encode_to(dest);
+        self.dominance.to_bits().encode_to(dest);
+    }
+
+    fn size_hint(&self) -> usize {
+        12
+    }
+}
+
+#[cfg(feature = "scale")]
+impl parity_scale_codec::Decode for Vad {
+    fn decode<I: parity_scale_codec::Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        Ok(Self {
+            valence: f32::from_bits(u32::decode(input)?),
+            arousal: f32::from_bits(u32::decode(input)?),
+            dominance: f32::from_bits(u32::decode(input)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_validates_ranges() {
+        assert!(Vad::new(0.5, 0.5, 0.5).is_ok());
+        assert_eq!(
+            Vad::new(0.5, 1.5, 0.5),
+            Err(EmotionError::OutOfRange { field: "arousal", value: 1.5 })
+        );
+        assert_eq!(Vad::new(f32::NAN, 0.5, 0.5), Err(EmotionError::NotFinite { field: "valence" }));
+    }
+
+    #[test]
+    fn test_clamped() {
+        let vad = Vad::clamped(-2.0, f32::NAN, 1.2);
+        assert_eq!(vad, Vad { valence: -1.0, arousal: 0.5, dominance: 1.0 });
+    }
+
+    #[test]
+    fn test_intensity() {
+        assert_eq!(Vad::NEUTRAL.intensity(), 0.0);
+        assert!((Vad::clamped(1.0, 1.0, 1.0).intensity() - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,88 @@
+//! Filecoin/IPFS storage backend
+//!
+//! Talks to the Kubo-compatible HTTP API exposed by IPFS nodes and Filecoin
+//! pinning gateways (`/api/v0/add`, `/api/v0/cat`).
+
+use crate::backend::{StorageBackend, StoredObject};
+use reqwest::blocking::multipart::{Form, Part};
+use serde::Deserialize;
+use std::error::Error;
+
+/// Filecoin client for IPFS and storage operations
+pub struct FilecoinClient {
+    api_endpoint: String,
+    auth_token: Option<String>,
+    http: reqwest::blocking::Client,
+}
+
+/// Response body of `/api/v0/add`
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AddResponse {
+    hash: String,
+    size: String,
+}
+
+impl FilecoinClient {
+    pub fn new(api_endpoint: &str, auth_token: Option<&str>) -> Self {
+        Self {
+            api_endpoint: api_endpoint.trim_end_matches('/').to_string(),
+            auth_token: auth_token.map(|s| s.to_string()),
+            http: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// API endpoint this client talks to
+    pub fn api_endpoint(&self) -> &str {
+        &self.api_endpoint
+    }
+
+    fn post(&self, path: &str) -> reqwest::blocking::RequestBuilder {
+        let request = self.http.post(format!("{}/api/v0/{}", self.api_endpoint, path));
+        match &self.auth_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+impl StorageBackend for FilecoinClient {
+    fn put(&mut self, data: &[u8]) -> Result<StoredObject, Box<dyn Error>> {
+        let form = Form::new().part("file", Part::bytes(data.to_vec()).file_name("data"));
+
+        let response: AddResponse = self
+            .post("add")
+            .query(&[("cid-version", "1"), ("raw-leaves", "true"), ("pin", "true")])
+            .multipart(form)
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        Ok(StoredObject {
+            cid: response.hash,
+            size: response.size.parse()?,
+        })
+    }
+
+    fn get(&self, cid: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let bytes = self
+            .post("cat")
+            .query(&[("arg", cid)])
+            .send()?
+            .error_for_status()?
+            .bytes()?;
+
+        Ok(bytes.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_normalization() {
+        let client = FilecoinClient::new("http://localhost:5001/", Some("token"));
+        assert_eq!(client.api_endpoint(), "http://localhost:5001");
+    }
+}
@@ -4,6 +4,7 @@
 //! Enhanced with advanced emotional computing capabilities
 
 use crate::ipfs_client::IpfsClient;
+use emotion_core::{EmotionError, EmotionReading, EmotionTaxonomy, Forecaster, Holt, PadOctants, Prediction, Vad};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
@@ -108,40 +109,40 @@ impl EmotionalVector {
     
     /// Get emotional category based on VAD values
     pub fn get_emotional_category(valence: f32, arousal: f32, dominance: f32) -> String {
-        match (valence, arousal, dominance) {
-            (v, a, d) if v > 0.5 && a > 0.5 && d > 0.5 => "Excited".to_string(),
-            (v, a, d) if v > 0.5 && a > 0.5 && d <= 0.5 => "Happy".to_string(),
-            (v, a, d) if v > 0.5 && a <= 0.5 && d > 0.5 => "Proud".to_string(),
-            (v, a, d) if v > 0.5 && a <= 0.5 && d <= 0.5 => "Calm".to_string(),
-            (v, a, d) if v <= 0.5 && a > 0.5 && d > 0.5 => "Angry".to_string(),
-            (v, a, d) if v <= 0.5 && a > 0.5 && d <= 0.5 => "Anxious".to_string(),
-            (v, a, d) if v <= 0.5 && a <= 0.5 && d > 0.5 => "Bored".to_string(),
-            _ => "Sad".to_string(),
-        }
+        Self::get_emotional_category_with(valence, arousal, dominance, &PadOctants)
+    }
+    
+    /// Get emotional category under a chosen taxonomy
+    pub fn get_emotional_category_with(
+        valence: f32,
+        arousal: f32,
+        dominance: f32,
+        taxonomy: &dyn EmotionTaxonomy,
+    ) -> String {
+        Vad::clamped(valence, arousal, dominance).classify(taxonomy).label.to_string()
     }
     
     /// Calculate emotional complexity based on VAD values
+    ///
+    /// A single state has no spread, so this is its intensity: the distance
+    /// from neutral, scaled to 0.0-1.0.
     pub fn calculate_emotional_complexity(valence: f32, arousal: f32, dominance: f32) -> f32 {
-        // Calculate the distance from the emotional center (0.0, 0.5, 0.5)
-        let center_distance = ((valence - 0.0).powi(2) + (arousal - 0.5).powi(2) + (dominance - 0.5).powi(2)).sqrt();
-        
-        // Normalize to 0.0-1.0 range
-        (center_distance / 1.5).min(1.0)
+        Vad::clamped(valence, arousal, dominance).intensity()
     }
     
     /// Calculate emotional distance between two states
     pub fn distance(&self, other: &EmotionalVector) -> f32 {
-        let dv = self.valence - other.valence;
-        let da = self.arousal - other.arousal;
-        let dd = self.dominance - other.dominance;
-        (dv * dv + da * da + dd * dd).sqrt()
+        self.vad().distance(&other.vad())
     }
     
     /// Check if emotional state is valid
     pub fn is_valid(&self) -> bool {
-        self.valence >= -1.0 && self.valence <= 1.0
-            && self.arousal >= 0.0 && self.arousal <= 1.0
-            && self.dominance >= 0.0 && self.dominance <= 1.0
+        Vad::new(self.valence, self.arousal, self.dominance).is_ok()
+    }
+
+    /// VAD point of this state, clamped into range
+    pub fn vad(&self) -> Vad {
+        Vad::clamped(self.valence, self.arousal, self.dominance)
     }
     
     /// Compress to efficient storage format
@@ -155,6 +156,39 @@ impl EmotionalVector {
     }
 }
 
+impl From<Vad> for EmotionalVector {
+    fn from(vad: Vad) -> Self {
+        Self::new(vad.valence, vad.arousal, vad.dominance)
+    }
+}
+
+impl TryFrom<&EmotionalVector> for Vad {
+    type Error = EmotionError;
+
+    fn try_from(v: &EmotionalVector) -> Result<Self, Self::Error> {
+        Vad::new(v.valence, v.arousal, v.dominance)
+    }
+}
+
+impl TryFrom<&EmotionalVector> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(v: &EmotionalVector) -> Result<Self, Self::Error> {
+        Ok(EmotionReading::new(Vad::try_from(v)?))
+    }
+}
+
+/// Fails if the reading has a confidence or timestamp, which this type cannot hold
+impl TryFrom<EmotionReading> for EmotionalVector {
+    type Error = EmotionError;
+
+    fn try_from(reading: EmotionReading) -> Result<Self, Self::Error> {
+        let vad = reading.into_vad_only()?;
+        vad.validate()?;
+        Ok(vad.into())
+    }
+}
+
 impl CompressedEmotionalState {
     /// Decompress to full emotional state
     pub fn decompress(&self, base_timestamp: u64) -> (u64, EmotionalVector) {
@@ -166,6 +200,11 @@ impl CompressedEmotionalState {
         );
         (timestamp, emotional_vector)
     }
+
+    /// VAD point of this state, clamped into range
+    pub fn vad(&self) -> Vad {
+        Vad::clamped((self.v as f32) / 100.0, (self.a as f32) / 100.0, (self.d as f32) / 100.0)
+    }
 }
 
 impl EmotionalTrajectory {
@@ -218,18 +257,7 @@ impl EmotionalTrajectory {
         // Identify dominant emotions
         let mut emotion_counts = HashMap::new();
         for state in &self.compressed_states {
-            // Decompress to get emotional category
-            let valence = (state.v as f32) / 100.0;
-            let arousal = (state.a as f32) / 100.0;
-            let dominance = (state.d as f32) / 100.0;
-            
-            let category = match (valence, arousal, dominance) {
-                (v, a, _d) if v > 0.5 && a > 0.5 => "Excited".to_string(),
-                (v, a, _d) if v > 0.5 && a <= 0.5 => "Happy".to_string(),
-                (v, a, _d) if v <= 0.5 && a > 0.5 => "Anxious".to_string(),
-                _ => "Calm".to_string(),
-            };
-            
+            let category = state.vad().classify(&PadOctants).label.to_string();
             *emotion_counts.entry(category).or_insert(0) += 1;
         }
         
@@ -288,43 +316,24 @@ impl EmotionalTrajectory {
         (avg_distance / 100.0 + change_ratio).min(1.0)
     }
     
+    /// Forecast the trajectory `steps_ahead` states past its end
+    pub fn forecast(&self, forecaster: &mut dyn Forecaster, steps_ahead: usize) -> Option<Prediction> {
+        forecaster.reset();
+        for state in &self.compressed_states {
+            forecaster.observe(Vad::clamped(
+                state.v as f32 / 100.0,
+                state.a as f32 / 100.0,
+                state.d as f32 / 100.0,
+            ));
+        }
+        forecaster.predict(steps_ahead)
+    }
+    
     /// Predict next emotional state in the trajectory
     pub fn predict_next_state(&mut self) -> Option<EmotionalVector> {
-        if self.compressed_states.len() < 3 {
-            return None;
-        }
-        
-        // Simple prediction based on last few states
-        let len = self.compressed_states.len();
-        let last_state = &self.compressed_states[len-1];
-        let prev_state = &self.compressed_states[len-2];
-        
-        let delta_v = last_state.v as i16 - prev_state.v as i16;
-        let delta_a = last_state.a as i16 - prev_state.a as i16;
-        let delta_d = last_state.d as i16 - prev_state.d as i16;
-        
-        let predicted_v = last_state.v as i16 + delta_v;
-        let predicted_a = last_state.a as i16 + delta_a;
-        let predicted_d = last_state.d as i16 + delta_d;
-        
-        // Confidence decreases with prediction distance
-        self.prediction_confidence = 0.8 - (len as f32 * 0.05).min(0.7);
-        
-        Some(EmotionalVector {
-            valence: (predicted_v as f32) / 100.0,
-            arousal: (predicted_a as f32) / 100.0,
-            dominance: (predicted_d as f32) / 100.0,
-            emotional_category: EmotionalVector::get_emotional_category(
-                (predicted_v as f32) / 100.0,
-                (predicted_a as f32) / 100.0,
-                (predicted_d as f32) / 100.0,
-            ),
-            emotional_complexity: EmotionalVector::calculate_emotional_complexity(
-                (predicted_v as f32) / 100.0,
-                (predicted_a as f32) / 100.0,
-                (predicted_d as f32) / 100.0,
-            ),
-        })
+        let prediction = self.forecast(&mut Holt::default(), 1)?;
+        self.prediction_confidence = prediction.confidence();
+        Some(prediction.mean.into())
     }
 }
 
@@ -425,31 +434,9 @@ impl NeuroemotiveSession {
         variance_sum / self.emotional_states.len() as f32
     }
     
-    /// Calculate emotional complexity based on variance and trajectory
+    /// Calculate emotional complexity as the normalised spread of the session
     pub fn calculate_emotional_complexity(&self) -> f32 {
-        if self.emotional_states.len() < 2 {
-            return 0.0;
-        }
-        
-        // Use variance as the base complexity measure
-        let variance = self.emotional_variance();
-        
-        // Add trajectory complexity (direction changes)
-        let mut direction_changes = 0;
-        for i in 1..self.emotional_states.len() {
-            let prev = &self.emotional_states[i-1];
-            let curr = &self.emotional_states[i];
-            
-            // Check for significant direction changes in valence
-            if (curr.valence - prev.valence).abs() > 0.3 {
-                direction_changes += 1;
-            }
-        }
-        
-        let trajectory_complexity = direction_changes as f32 / self.emotional_states.len() as f32;
-        
-        // Combine variance and trajectory complexity (0-1 scale)
-        (variance + trajectory_complexity).min(1.0)
+        emotion_core::complexity(self.emotional_states.iter().map(EmotionalVector::vad))
     }
     
     /// Calculate creativity index based on emotional dynamics
@@ -481,4 +468,53 @@ impl NeuroemotiveSession {
         let json = serde_json::to_string_pretty(self)?;
         client.add_json(&json).await
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emotion_core_conversion() {
+        let vector = EmotionalVector::new(0.7, 0.8, 0.3);
+        assert_eq!(vector.emotional_category, "Happy");
+
+        let reading = EmotionReading::try_from(&vector).unwrap();
+        assert_eq!(reading.category().as_str(), vector.emotional_category);
+        assert_eq!(EmotionalVector::try_from(reading).unwrap().valence, 0.7);
+
+        let timed = reading.with_timestamp_ns(1);
+        assert_eq!(
+            EmotionalVector::try_from(timed).err(),
+            Some(EmotionError::Unrepresentable { field: "timestamp" })
+        );
+    }
+
+    #[test]
+    fn test_trajectory_labels_match_the_vector() {
+        for (v, a, d) in [(0.7, 0.8, 0.3), (0.6, 0.9, 0.8), (-0.4, 0.2, 0.7), (0.3, 0.4, 0.2)] {
+            let vector = EmotionalVector::new(v, a, d);
+            let mut trajectory = EmotionalTrajectory::new("t".to_string(), "creator".to_string());
+            trajectory.add_state(vector.compress(0));
+
+            assert_eq!(vector.emotional_category, Vad::clamped(v, a, d).category().as_str());
+            assert_eq!(trajectory.metadata.dominant_emotions, vec![vector.emotional_category]);
+        }
+    }
+
+    #[test]
+    fn test_trajectory_forecast() {
+        let mut trajectory = EmotionalTrajectory::new("t".to_string(), "creator".to_string());
+        assert!(trajectory.predict_next_state().is_none());
+
+        for i in 0..6 {
+            let vector = EmotionalVector::new(-0.5 + 0.1 * i as f32, 0.3, 0.6);
+            trajectory.add_state(vector.compress(i * 100));
+        }
+        let next = trajectory.predict_next_state().unwrap();
+        assert!((next.valence - 0.1).abs() < 0.05);
+        assert!(trajectory.prediction_confidence > 0.5);
+
+        let far = trajectory.forecast(&mut emotion_core::Kalman::default(), 4).unwrap();
+        assert!(far.std_dev[0] > 0.0);
+    }
+}
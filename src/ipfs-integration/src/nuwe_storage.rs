@@ -6,6 +6,8 @@ use crate::ipfs_client::IpfsClient;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 
+pub use session_core::{FrameReference, FrameTimePercentiles, PerformanceMetrics};
+
 /// NUWE creative session for IPFS storage
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NuweSession {
@@ -42,16 +44,6 @@ pub struct FractalSnapshot {
     pub color_palette: Vec<u32>,
 }
 
-/// Performance metrics for session
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct PerformanceMetrics {
-    pub avg_fps: f32,
-    pub peak_fps: f32,
-    pub total_frames: u64,
-    pub dropped_frames: u32,
-    pub avg_render_time_ms: f32,
-}
-
 /// Emotional state snapshot
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct EmotionalSnapshot {
@@ -70,16 +62,6 @@ pub struct NuweAssetBundle {
     pub audio_track: Option<AudioReference>,
 }
 
-/// Reference to a rendered frame
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct FrameReference {
-    pub frame_number: u64,
-    pub timestamp: u64,
-    pub cid: String,
-    pub format: String, // "png", "jpg", "webp"
-    pub resolution: (u32, u32),
-}
-
 /// Shader compilation output
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ShaderOutput {
@@ -118,6 +100,7 @@ impl NuweSession {
                 total_frames: 0,
                 dropped_frames: 0,
                 avg_render_time_ms: 0.0,
+                frame_times: None,
             },
             emotional_data: Vec::new(),
         }
@@ -171,6 +154,19 @@ impl NuweAssetBundle {
         self.rendered_frames.push(frame);
     }
 
+    /// Store a captured frame's encoded bytes and add its reference with the
+    /// resulting CID
+    pub async fn upload_frame(
+        &mut self,
+        client: &IpfsClient,
+        mut frame: FrameReference,
+        bytes: &[u8],
+    ) -> Result<&FrameReference, Box<dyn Error>> {
+        frame.cid = client.add_bytes(bytes).await?;
+        self.rendered_frames.push(frame);
+        Ok(self.rendered_frames.last().expect("frame just added"))
+    }
+
     /// Add shader output
     pub fn add_shader_output(&mut self, output: ShaderOutput) {
         self.shader_outputs.push(output);
@@ -247,6 +243,19 @@ mod tests {
         assert_eq!(session.fractal_params.len(), 1);
     }
 
+    #[test]
+    fn test_performance_metrics_frame_times() {
+        let legacy =
+            r#"{"avg_fps":58.0,"peak_fps":61.0,"total_frames":900,"dropped_frames":4,"avg_render_time_ms":17.2}"#;
+        let metrics: PerformanceMetrics = serde_json::from_str(legacy).unwrap();
+        assert!(metrics.frame_times.is_none());
+        assert!(!serde_json::to_string(&metrics).unwrap().contains("frame_times"));
+
+        let with_frame_times = legacy.replace('}', r#","frame_times":{"p50_ms":16.6,"p95_ms":24.0,"p99_ms":33.3}}"#);
+        let metrics: PerformanceMetrics = serde_json::from_str(&with_frame_times).unwrap();
+        assert_eq!(metrics.frame_times.unwrap().p95_ms, 24.0);
+    }
+
     #[test]
     fn test_asset_bundle_size_estimation() {
         let session = NuweSession::new(
@@ -271,4 +280,26 @@ mod tests {
         let size = bundle.estimated_size_bytes();
         assert!(size > 0);
     }
+
+    #[tokio::test]
+    async fn test_upload_captured_frame() {
+        let session = NuweSession::new(
+            "test".to_string(),
+            SessionType::VJPerformance,
+            "creator".to_string(),
+        );
+        let mut bundle = NuweAssetBundle::new(session);
+        let client = IpfsClient::new("localhost".to_string(), 5001);
+
+        // As emitted by the renderer's frame capture, before upload
+        let frame: FrameReference = serde_json::from_str(
+            r#"{"frame_number": 42, "timestamp": 1700000000420, "cid": "", "format": "webp", "resolution": [640, 360]}"#,
+        )
+        .unwrap();
+        let uploaded = bundle.upload_frame(&client, frame, &[0u8; 300]).await.unwrap().clone();
+
+        assert!(!uploaded.cid.is_empty());
+        assert_eq!(uploaded.resolution, (640, 360));
+        assert_eq!(bundle.rendered_frames, [uploaded]);
+    }
 }
@@ -0,0 +1,169 @@
+//! Per-collection token ownership ledger
+//!
+//! Off-chain mirror of what a collection has minted: who owns each token,
+//! its metadata and when it was minted.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+
+/// Single token tracked by a [`TokenLedger`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenRecord {
+    pub token_id: u64,
+    pub owner: String,
+    pub metadata: String,
+    pub minted_at: DateTime<Utc>,
+}
+
+/// Token id → record mapping for one collection
+///
+/// Ids are never reused: minting an id that is live or was burned fails.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenLedger {
+    tokens: BTreeMap<u64, TokenRecord>,
+    burned: BTreeSet<u64>,
+}
+
+impl TokenLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether `token_id` has ever been minted
+    pub fn is_taken(&self, token_id: u64) -> bool {
+        self.tokens.contains_key(&token_id) || self.burned.contains(&token_id)
+    }
+
+    /// Record a newly minted token
+    pub fn mint(&mut self, token_id: u64, owner: &str, metadata: &str) -> Result<&TokenRecord, Box<dyn Error>> {
+        self.insert(TokenRecord {
+            token_id,
+            owner: owner.to_string(),
+            metadata: metadata.to_string(),
+            minted_at: Utc::now(),
+        })
+    }
+
+    /// Record an existing token as-is, e.g. when restoring a mirror
+    pub fn insert(&mut self, record: TokenRecord) -> Result<&TokenRecord, Box<dyn Error>> {
+        if self.is_taken(record.token_id) {
+            return Err(format!("Token {} already minted", record.token_id).into());
+        }
+
+        let token_id = record.token_id;
+        Ok(self.tokens.entry(token_id).or_insert(record))
+    }
+
+    /// Move a token from `from` to `to`
+    pub fn transfer(&mut self, token_id: u64, from: &str, to: &str) -> Result<(), Box<dyn Error>> {
+        let record = self.tokens.get_mut(&token_id).ok_or("Token not found")?;
+        if record.owner != from {
+            return Err(format!("Token {} is not owned by {}", token_id, from).into());
+        }
+
+        record.owner = to.to_string();
+        Ok(())
+    }
+
+    /// Destroy a token; its id stays reserved
+    pub fn burn(&mut self, token_id: u64, owner: &str) -> Result<TokenRecord, Box<dyn Error>> {
+        match self.tokens.get(&token_id) {
+            None => return Err("Token not found".into()),
+            Some(record) if record.owner != owner => {
+                return Err(format!("Token {} is not owned by {}", token_id, owner).into());
+            }
+            Some(_) => {}
+        }
+
+        self.burned.insert(token_id);
+        Ok(self.tokens.remove(&token_id).expect("token checked above"))
+    }
+
+    pub fn get(&self, token_id: u64) -> Option<&TokenRecord> {
+        self.tokens.get(&token_id)
+    }
+
+    pub fn owner_of(&self, token_id: u64) -> Option<&str> {
+        self.tokens.get(&token_id).map(|r| r.owner.as_str())
+    }
+
+    /// Live tokens owned by `owner`, ordered by token id
+    pub fn tokens_of(&self, owner: &str) -> Vec<&TokenRecord> {
+        self.tokens.values().filter(|r| r.owner == owner).collect()
+    }
+
+    /// Up to `limit` live tokens with ids >= `from_token_id`, ordered by token id
+    pub fn range(&self, from_token_id: u64, limit: usize) -> Vec<&TokenRecord> {
+        self.tokens.range(from_token_id..).map(|(_, r)| r).take(limit).collect()
+    }
+
+    /// Iterate over all live tokens, ordered by token id
+    pub fn iter(&self) -> impl Iterator<Item = &TokenRecord> {
+        self.tokens.values()
+    }
+
+    /// Number of live tokens
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Number of burned tokens
+    pub fn burned_count(&self) -> usize {
+        self.burned.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mint_rejects_duplicates() {
+        let mut ledger = TokenLedger::new();
+        ledger.mint(1, "alice.near", "{}").unwrap();
+
+        assert!(ledger.mint(1, "bob.near", "{}").is_err());
+        assert_eq!(ledger.owner_of(1), Some("alice.near"));
+    }
+
+    #[test]
+    fn test_transfer_and_burn() {
+        let mut ledger = TokenLedger::new();
+        ledger.mint(1, "alice.near", "{}").unwrap();
+
+        assert!(ledger.transfer(1, "bob.near", "carol.near").is_err());
+        ledger.transfer(1, "alice.near", "bob.near").unwrap();
+        assert_eq!(ledger.owner_of(1), Some("bob.near"));
+
+        assert!(ledger.burn(1, "alice.near").is_err());
+        let burned = ledger.burn(1, "bob.near").unwrap();
+        assert_eq!(burned.token_id, 1);
+        assert!(ledger.get(1).is_none());
+
+        // Burned ids cannot be minted again
+        assert!(ledger.mint(1, "alice.near", "{}").is_err());
+        assert_eq!(ledger.burned_count(), 1);
+    }
+
+    #[test]
+    fn test_enumeration() {
+        let mut ledger = TokenLedger::new();
+        for id in [5, 1, 3, 2, 4] {
+            let owner = if id % 2 == 0 { "even.near" } else { "odd.near" };
+            ledger.mint(id, owner, "{}").unwrap();
+        }
+
+        let odd: Vec<u64> = ledger.tokens_of("odd.near").iter().map(|r| r.token_id).collect();
+        assert_eq!(odd, vec![1, 3, 5]);
+
+        let page: Vec<u64> = ledger.range(2, 2).iter().map(|r| r.token_id).collect();
+        assert_eq!(page, vec![2, 3]);
+        assert_eq!(ledger.len(), 5);
+    }
+}
@@ -3,63 +3,112 @@
 //! Interactive NFT system with Filecoin and NEAR blockchain integration.
 //! Smart contracts for connecting Nuwe system to Filecoin and NEAR blockchains.
 
+use nft_rust_client::KeystoreSigner;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+mod backend;
+mod deployment;
+mod filecoin;
+mod ledger;
+mod near;
+mod persistence;
+#[cfg(test)]
+mod test_util;
+
+pub use backend::*;
+pub use deployment::*;
+pub use filecoin::*;
+pub use ledger::*;
+pub use near::*;
+pub use persistence::*;
+
+/// Owner recorded for tokens minted without a chain account
+pub const LOCAL_OWNER: &str = "local";
 
 /// Main NFT blockchain interface
 pub struct NftBlockchainInteractive {
-    // Filecoin integration
-    filecoin_client: Option<FilecoinClient>,
+    // Content-addressed storage for metadata (Filecoin/IPFS)
+    storage_backend: Option<Box<dyn StorageBackend>>,
 
-    // NEAR integration
-    near_client: Option<NearClient>,
+    // Chain that tokens are minted on (NEAR)
+    chain_backend: Option<Box<dyn ChainBackend>>,
 
     // NFT collections
     collections: HashMap<String, NftCollection>,
 
     // Deployment configuration
     deployment_config: DeploymentConfig,
-}
 
-/// Filecoin client for IPFS and storage operations
-#[allow(dead_code)]
-pub struct FilecoinClient {
-    api_endpoint: String,
-    auth_token: Option<String>,
-}
-
-/// NEAR blockchain client
-#[allow(dead_code)]
-pub struct NearClient {
-    network_id: String,
-    account_id: Option<String>,
-    private_key: Option<String>,
+    // Non-secret settings of the initialized clients, kept for persistence
+    client_configs: ClientConfigs,
 }
 
 /// NFT collection metadata
 #[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct NftCollection {
     name: String,
     symbol: String,
     base_uri: String,
     max_supply: Option<u64>,
     minted_count: u64,
+    ledger: TokenLedger,
+}
+
+impl NftCollection {
+    /// Ownership ledger for tokens minted in this collection
+    pub fn ledger(&self) -> &TokenLedger {
+        &self.ledger
+    }
 }
 
 /// Deployment configuration for testnets
+///
+/// With `auto_deploy` off, `deploy_to_testnets` is a dry run that only plans.
 #[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DeploymentConfig {
     filecoin_testnet: bool,
     near_testnet: bool,
     auto_deploy: bool,
+    contract_wasm_path: String,
+    // Directory searched for contract artifacts
+    artifact_dir: String,
+    // Record of earlier deployments, used to skip unchanged contracts
+    manifest_path: String,
+}
+
+/// Outcome of minting through the facade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintedNft {
+    pub token_id: u64,
+    pub owner: String,
+    pub metadata_cid: Option<String>,
+    pub metadata_uri: Option<String>,
+    pub chain_receipt: Option<MintReceipt>,
+}
+
+/// Outcome of deploying to testnets
+#[derive(Debug, Clone, Default)]
+pub struct DeploymentReport {
+    pub dry_run: bool,
+    pub plan: DeploymentPlan,
+    pub filecoin: Option<StoredObject>,
+    pub near: Option<DeployReceipt>,
 }
 
 impl Default for NftBlockchainInteractive {
     fn default() -> Self {
         Self {
-            filecoin_client: None,
-            near_client: None,
+            storage_backend: None,
+            chain_backend: None,
             collections: HashMap::new(),
             deployment_config: DeploymentConfig::default(),
+            client_configs: ClientConfigs::default(),
         }
     }
 }
@@ -70,32 +119,86 @@ impl Default for DeploymentConfig {
             filecoin_testnet: true,
             near_testnet: true,
             auto_deploy: false,
+            contract_wasm_path: "src/wasm-contracts/nft_near_wasm.wasm".to_string(),
+            artifact_dir: ".".to_string(),
+            manifest_path: "deployment-manifest.json".to_string(),
         }
     }
 }
 
+impl DeploymentConfig {
+    /// Search `dir` for contract artifacts
+    pub fn with_artifact_dir(mut self, dir: &str) -> Self {
+        self.artifact_dir = dir.to_string();
+        self
+    }
+
+    /// Record deployments in the manifest at `path`
+    pub fn with_manifest_path(mut self, path: &str) -> Self {
+        self.manifest_path = path.to_string();
+        self
+    }
+
+    /// Deploy for real instead of only planning
+    pub fn with_auto_deploy(mut self, auto_deploy: bool) -> Self {
+        self.auto_deploy = auto_deploy;
+        self
+    }
+}
+
 impl NftBlockchainInteractive {
     pub fn new() -> Self {
         Self::default()
     }
 
     pub fn initialize_filecoin(&mut self, endpoint: &str, auth_token: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
-        self.filecoin_client = Some(FilecoinClient {
+        self.storage_backend = Some(Box::new(FilecoinClient::new(endpoint, auth_token)));
+        self.client_configs.filecoin = Some(FilecoinConfig {
             api_endpoint: endpoint.to_string(),
-            auth_token: auth_token.map(|s| s.to_string()),
         });
         Ok(())
     }
 
-    pub fn initialize_near(&mut self, network_id: &str, account_id: Option<&str>, private_key: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
-        self.near_client = Some(NearClient {
+    /// Set up NEAR for `account_id`; with a `signer` the client can send
+    /// transactions and acts for the signer's account
+    pub fn initialize_near(&mut self, network_id: &str, account_id: Option<&str>, signer: Option<Arc<KeystoreSigner>>) -> Result<(), Box<dyn std::error::Error>> {
+        let mut near = NearClient::new(network_id, account_id);
+        if let Some(signer) = signer {
+            near = near.with_signer(signer)?;
+        }
+        self.client_configs.near = Some(NearConfig {
             network_id: network_id.to_string(),
-            account_id: account_id.map(|s| s.to_string()),
-            private_key: private_key.map(|s| s.to_string()),
+            account_id: near.account_id().map(|s| s.to_string()),
         });
+        self.chain_backend = Some(Box::new(near));
         Ok(())
     }
 
+    /// Non-secret settings of the clients set up by `initialize_*`
+    pub fn client_configs(&self) -> &ClientConfigs {
+        &self.client_configs
+    }
+
+    pub fn deployment_config(&self) -> &DeploymentConfig {
+        &self.deployment_config
+    }
+
+    pub fn set_deployment_config(&mut self, config: DeploymentConfig) {
+        self.deployment_config = config;
+    }
+
+    /// Use a custom storage backend (e.g. [`InMemoryStorage`] in tests)
+    pub fn set_storage_backend(&mut self, backend: Box<dyn StorageBackend>) {
+        self.storage_backend = Some(backend);
+        self.client_configs.filecoin = None;
+    }
+
+    /// Use a custom chain backend (e.g. [`InMemoryChain`] in tests)
+    pub fn set_chain_backend(&mut self, backend: Box<dyn ChainBackend>) {
+        self.chain_backend = Some(backend);
+        self.client_configs.near = None;
+    }
+
     pub fn create_collection(&mut self, name: &str, symbol: &str, base_uri: &str, max_supply: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
         let collection = NftCollection {
             name: name.to_string(),
@@ -103,48 +206,180 @@ impl NftBlockchainInteractive {
             base_uri: base_uri.to_string(),
             max_supply,
             minted_count: 0,
+            ledger: TokenLedger::new(),
         };
 
         self.collections.insert(name.to_string(), collection);
         Ok(())
     }
 
-    pub fn mint_nft(&mut self, collection_name: &str, token_id: u64, metadata: &str) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(collection) = self.collections.get_mut(collection_name) {
-            if let Some(max_supply) = collection.max_supply {
-                if collection.minted_count >= max_supply {
-                    return Err("Max supply reached".into());
-                }
+    /// Mint to the chain backend's account, or [`LOCAL_OWNER`] without one
+    pub fn mint_nft(&mut self, collection_name: &str, token_id: u64, metadata: &str) -> Result<MintedNft, Box<dyn std::error::Error>> {
+        let owner = self
+            .chain_backend
+            .as_ref()
+            .and_then(|chain| chain.account_id())
+            .unwrap_or(LOCAL_OWNER)
+            .to_string();
+
+        self.mint_nft_to(collection_name, token_id, &owner, metadata)
+    }
+
+    pub fn mint_nft_to(&mut self, collection_name: &str, token_id: u64, owner: &str, metadata: &str) -> Result<MintedNft, Box<dyn std::error::Error>> {
+        let collection = self.collections.get_mut(collection_name).ok_or("Collection not found")?;
+
+        if let Some(max_supply) = collection.max_supply {
+            if collection.minted_count >= max_supply {
+                return Err("Max supply reached".into());
             }
+        }
 
-            collection.minted_count += 1;
+        if collection.ledger.is_taken(token_id) {
+            return Err(format!("Token {} already minted in {}", token_id, collection_name).into());
+        }
 
-            // Store metadata on Filecoin/IPFS if client is available
-            if let Some(ref _filecoin) = self.filecoin_client {
-                self.store_metadata_on_filecoin(metadata)?;
+        // Store metadata first so the chain can reference it
+        let (metadata_cid, metadata_uri) = match self.storage_backend.as_mut() {
+            Some(storage) => {
+                let stored = storage.put(metadata.as_bytes())?;
+                let uri = storage.uri(&stored.cid);
+                (Some(stored.cid), Some(uri))
             }
+            None => (None, None),
+        };
+
+        let chain_receipt = match self.chain_backend.as_mut() {
+            Some(chain) => Some(chain.mint(&MintRequest {
+                collection: collection_name,
+                token_id,
+                owner,
+                metadata,
+                metadata_uri: metadata_uri.clone(),
+            })?),
+            None => None,
+        };
 
-            // Mint on NEAR if client is available
-            if let Some(ref _near) = self.near_client {
-                self.mint_on_near(collection_name, token_id, metadata)?;
+        collection.ledger.mint(token_id, owner, metadata)?;
+        collection.minted_count += 1;
+
+        Ok(MintedNft {
+            token_id,
+            owner: owner.to_string(),
+            metadata_cid,
+            metadata_uri,
+            chain_receipt,
+        })
+    }
+
+    /// Record a transfer in the collection's ledger
+    pub fn transfer_nft(&mut self, collection_name: &str, token_id: u64, from: &str, to: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let collection = self.collections.get_mut(collection_name).ok_or("Collection not found")?;
+        collection.ledger.transfer(token_id, from, to)
+    }
+
+    /// Record a burn in the collection's ledger
+    pub fn burn_nft(&mut self, collection_name: &str, token_id: u64, owner: &str) -> Result<TokenRecord, Box<dyn std::error::Error>> {
+        let collection = self.collections.get_mut(collection_name).ok_or("Collection not found")?;
+        collection.ledger.burn(token_id, owner)
+    }
+
+    /// Tokens owned by `owner` in a collection
+    pub fn tokens_of_owner(&self, collection_name: &str, owner: &str) -> Vec<&TokenRecord> {
+        self.collections
+            .get(collection_name)
+            .map(|c| c.ledger.tokens_of(owner))
+            .unwrap_or_default()
+    }
+
+    /// Plan deployments of every discovered contract artifact
+    ///
+    /// Only the NEAR contract at `contract_wasm_path` is deployable through the
+    /// facade; other artifacts are listed with the reason they are skipped.
+    pub fn plan_deployment(&self) -> Result<DeploymentPlan, Box<dyn std::error::Error>> {
+        let config = &self.deployment_config;
+        let mut artifacts = discover_artifacts(&config.artifact_dir)?;
+
+        let contract_path = Path::new(&config.contract_wasm_path);
+        let contract_id = match artifacts.iter().find(|a| a.is_file(contract_path)) {
+            Some(artifact) => Some(artifact.id.clone()),
+            None if contract_path.is_file() => {
+                artifacts.push(ContractArtifact::from_file(ContractChain::Near, contract_path, &config.contract_wasm_path)?);
+                Some(config.contract_wasm_path.clone())
             }
+            None => None,
+        };
 
-            Ok(())
-        } else {
-            Err("Collection not found".into())
+        let manifest = DeploymentManifest::load(&config.manifest_path)?;
+        let near_account = self.chain_backend.as_ref().and_then(|chain| chain.account_id()).map(|s| s.to_string());
+
+        let mut plan = DeploymentPlan::default();
+        for artifact in artifacts {
+            let (target, action) = match artifact.chain {
+                ContractChain::Near if !config.near_testnet => (None, PlanAction::Skip(SkipReason::ChainDisabled)),
+                ContractChain::Near if contract_id.as_ref() != Some(&artifact.id) => (None, PlanAction::Skip(SkipReason::NotSelected)),
+                ContractChain::Near => (near_account.clone(), PlanAction::Deploy),
+                ContractChain::Polkadot | ContractChain::Solana => (None, PlanAction::Skip(SkipReason::NoBackend)),
+            };
+            plan.push(artifact, target, action, &manifest);
         }
+
+        Ok(plan)
     }
 
-    pub fn deploy_to_testnets(&self) -> Result<(), Box<dyn std::error::Error>> {
-        if self.deployment_config.filecoin_testnet {
-            self.deploy_filecoin_contracts()?;
+    /// Plan, and with `auto_deploy` set, run the deployment
+    ///
+    /// Every attempt is recorded in the manifest at `manifest_path`.
+    pub fn deploy_to_testnets(&mut self) -> Result<DeploymentReport, Box<dyn std::error::Error>> {
+        let mut report = DeploymentReport {
+            dry_run: !self.deployment_config.auto_deploy,
+            plan: self.plan_deployment()?,
+            ..Default::default()
+        };
+
+        if report.dry_run || report.plan.pending().next().is_none() {
+            return Ok(report);
         }
 
-        if self.deployment_config.near_testnet {
-            self.deploy_near_contracts()?;
+        let mut manifest = DeploymentManifest::load(&self.deployment_config.manifest_path)?;
+        let steps: Vec<PlannedDeployment> = report.plan.pending().cloned().collect();
+
+        for step in steps {
+            let code = std::fs::read(&step.artifact.path)?;
+            match self.deploy_near_contract(&code) {
+                Ok((pinned, receipt)) => {
+                    manifest.record(step.record(
+                        Some(receipt.contract_id.clone()),
+                        pinned.as_ref().map(|p| p.cid.clone()),
+                        DeployOutcome::Deployed {
+                            transaction_hash: receipt.transaction_hash.clone(),
+                        },
+                    ));
+                    report.filecoin = pinned;
+                    report.near = Some(receipt);
+                }
+                Err(e) => {
+                    manifest.record(step.record(step.target.clone(), None, DeployOutcome::Failed { error: e.to_string() }));
+                    manifest.save(&self.deployment_config.manifest_path)?;
+                    return Err(e);
+                }
+            }
         }
 
-        Ok(())
+        manifest.save(&self.deployment_config.manifest_path)?;
+        Ok(report)
+    }
+
+    fn deploy_near_contract(&mut self, code: &[u8]) -> Result<(Option<StoredObject>, DeployReceipt), Box<dyn std::error::Error>> {
+        // Pin the contract code so the deployed bytes stay content-addressed
+        let pinned = if self.deployment_config.filecoin_testnet {
+            let storage = self.storage_backend.as_mut().ok_or("Filecoin backend not initialized")?;
+            Some(storage.put(code)?)
+        } else {
+            None
+        };
+
+        let chain = self.chain_backend.as_mut().ok_or("NEAR backend not initialized")?;
+        Ok((pinned, chain.deploy_contract(code)?))
     }
 
     pub fn get_collection_info(&self, name: &str) -> Option<&NftCollection> {
@@ -154,31 +389,6 @@ impl NftBlockchainInteractive {
     pub fn list_collections(&self) -> Vec<String> {
         self.collections.keys().cloned().collect()
     }
-
-    // Private helper methods
-    fn store_metadata_on_filecoin(&self, metadata: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Placeholder for Filecoin/IPFS storage
-        println!("Storing metadata on Filecoin: {}", metadata);
-        Ok(())
-    }
-
-    fn mint_on_near(&self, _collection_name: &str, _token_id: u64, _metadata: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Placeholder for NEAR minting
-        println!("Minting NFT on NEAR: {} #{}", _collection_name, _token_id);
-        Ok(())
-    }
-
-    fn deploy_filecoin_contracts(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Placeholder for Filecoin contract deployment
-        println!("Deploying contracts to Filecoin testnet");
-        Ok(())
-    }
-
-    fn deploy_near_contracts(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Placeholder for NEAR contract deployment
-        println!("Deploying contracts to NEAR testnet");
-        Ok(())
-    }
 }
 
 /// Simple test function to verify the library compiles
@@ -198,8 +408,8 @@ mod tests {
     #[test]
     fn test_initialization() {
         let client = NftBlockchainInteractive::new();
-        assert!(client.filecoin_client.is_none());
-        assert!(client.near_client.is_none());
+        assert!(client.storage_backend.is_none());
+        assert!(client.chain_backend.is_none());
         assert!(client.collections.is_empty());
     }
 
@@ -208,15 +418,15 @@ mod tests {
         let mut client = NftBlockchainInteractive::new();
         let result = client.initialize_filecoin("https://api.filecoin.com", Some("token"));
         assert!(result.is_ok());
-        assert!(client.filecoin_client.is_some());
+        assert!(client.storage_backend.is_some());
     }
 
     #[test]
     fn test_near_initialization() {
         let mut client = NftBlockchainInteractive::new();
-        let result = client.initialize_near("testnet", Some("account.near"), Some("private_key"));
+        let result = client.initialize_near("testnet", Some("account.near"), None);
         assert!(result.is_ok());
-        assert!(client.near_client.is_some());
+        assert!(client.chain_backend.is_some());
     }
 
     #[test]
@@ -239,6 +449,147 @@ mod tests {
         assert_eq!(collection.minted_count, 1);
     }
 
+    #[test]
+    fn test_mint_nft_with_backends() {
+        let storage = InMemoryStorage::new();
+        let chain = InMemoryChain::new("studio.testnet");
+
+        let mut client = NftBlockchainInteractive::new();
+        client.set_storage_backend(Box::new(storage.clone()));
+        client.set_chain_backend(Box::new(chain.clone()));
+        client.create_collection("Test Collection", "TEST", "ipfs://", Some(1000)).unwrap();
+
+        let minted = client.mint_nft("Test Collection", 7, "{\"name\": \"Test NFT\"}").unwrap();
+        let cid = minted.metadata_cid.unwrap();
+        assert!(storage.contains(&cid));
+        assert_eq!(minted.metadata_uri.unwrap(), format!("ipfs://{}", cid));
+
+        let receipt = minted.chain_receipt.unwrap();
+        assert_eq!(receipt.token_id, "Test Collection:7");
+        assert_eq!(chain.minted(), vec![receipt]);
+    }
+
+    #[test]
+    fn test_failed_chain_mint_does_not_count() {
+        let chain = InMemoryChain::default();
+        let mut client = NftBlockchainInteractive::new();
+        client.set_chain_backend(Box::new(chain.clone()));
+        client.create_collection("Test Collection", "TEST", "ipfs://", None).unwrap();
+
+        chain.fail_next_mint();
+        assert!(client.mint_nft("Test Collection", 1, "{}").is_err());
+        assert_eq!(client.get_collection_info("Test Collection").unwrap().minted_count, 0);
+
+        // The token is still free, so a retry goes through
+        client.mint_nft("Test Collection", 1, "{}").unwrap();
+        assert_eq!(client.get_collection_info("Test Collection").unwrap().minted_count, 1);
+        assert_eq!(chain.minted().len(), 1);
+    }
+
+    /// Client whose deployment config points at a fresh temp directory holding `nft.wasm`
+    fn deployment_client(name: &str) -> (NftBlockchainInteractive, std::path::PathBuf) {
+        let dir = test_util::temp_dir(name);
+        std::fs::write(dir.join("nft.wasm"), b"\0asm").unwrap();
+
+        let mut client = NftBlockchainInteractive::new();
+        let config = &mut client.deployment_config;
+        config.artifact_dir = dir.display().to_string();
+        config.contract_wasm_path = dir.join("nft.wasm").display().to_string();
+        config.manifest_path = dir.join("deployments.json").display().to_string();
+        config.auto_deploy = true;
+        (client, dir)
+    }
+
+    #[test]
+    fn test_deploy_requires_backends() {
+        let (mut client, dir) = deployment_client("deploy");
+        assert!(client.deploy_to_testnets().is_err());
+
+        let chain = InMemoryChain::default();
+        client.set_storage_backend(Box::new(InMemoryStorage::new()));
+        client.set_chain_backend(Box::new(chain.clone()));
+
+        let report = client.deploy_to_testnets().unwrap();
+        assert!(report.filecoin.is_some());
+        assert_eq!(report.near.unwrap().contract_id, "test.near");
+        assert_eq!(chain.deployments().len(), 1);
+
+        // Both attempts are in the manifest
+        let manifest = DeploymentManifest::load(dir.join("deployments.json")).unwrap();
+        assert_eq!(manifest.entries.len(), 2);
+        assert!(matches!(manifest.entries[0].outcome, DeployOutcome::Failed { .. }));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_deploy_skips_unchanged_contract() {
+        let (mut client, dir) = deployment_client("redeploy");
+        let chain = InMemoryChain::default();
+        client.set_storage_backend(Box::new(InMemoryStorage::new()));
+        client.set_chain_backend(Box::new(chain.clone()));
+
+        client.deploy_to_testnets().unwrap();
+        let report = client.deploy_to_testnets().unwrap();
+        assert!(report.near.is_none());
+        assert_eq!(report.plan.steps[0].action, PlanAction::Skip(SkipReason::Unchanged));
+        assert_eq!(chain.deployments().len(), 1);
+
+        std::fs::write(dir.join("nft.wasm"), b"\0asm\x01").unwrap();
+        assert!(client.deploy_to_testnets().unwrap().near.is_some());
+        assert_eq!(chain.deployments().len(), 2);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_deploy_dry_run() {
+        let (mut client, dir) = deployment_client("dry-run");
+        client.deployment_config.auto_deploy = false;
+        let chain = InMemoryChain::default();
+        client.set_chain_backend(Box::new(chain.clone()));
+
+        let report = client.deploy_to_testnets().unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.plan.pending().count(), 1);
+        assert_eq!(report.plan.steps[0].target.as_deref(), Some("test.near"));
+        assert!(chain.deployments().is_empty());
+        assert!(!dir.join("deployments.json").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_mint_nft_duplicate_token_id() {
+        let mut client = NftBlockchainInteractive::new();
+        client.create_collection("Test Collection", "TEST", "ipfs://", None).unwrap();
+
+        client.mint_nft("Test Collection", 1, "{}").unwrap();
+        assert!(client.mint_nft("Test Collection", 1, "{}").is_err());
+
+        let collection = client.get_collection_info("Test Collection").unwrap();
+        assert_eq!(collection.minted_count, 1);
+        assert_eq!(collection.ledger().owner_of(1), Some(LOCAL_OWNER));
+    }
+
+    #[test]
+    fn test_transfer_and_burn_nft() {
+        let mut client = NftBlockchainInteractive::new();
+        client.create_collection("Test Collection", "TEST", "ipfs://", Some(2)).unwrap();
+        client.mint_nft_to("Test Collection", 1, "alice.near", "{}").unwrap();
+        client.mint_nft_to("Test Collection", 2, "alice.near", "{}").unwrap();
+
+        client.transfer_nft("Test Collection", 1, "alice.near", "bob.near").unwrap();
+        assert_eq!(client.tokens_of_owner("Test Collection", "alice.near").len(), 1);
+        assert_eq!(client.tokens_of_owner("Test Collection", "bob.near")[0].token_id, 1);
+
+        client.burn_nft("Test Collection", 2, "alice.near").unwrap();
+        assert!(client.tokens_of_owner("Test Collection", "alice.near").is_empty());
+
+        // Burning does not free up supply
+        assert!(client.mint_nft_to("Test Collection", 3, "alice.near", "{}").is_err());
+    }
+
     #[test]
     fn test_mint_nft_collection_not_found() {
         let mut client = NftBlockchainInteractive::new();
@@ -11,6 +11,7 @@ use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, near, AccountId, Promise, Timestamp, NearToken};
 use near_contract_standards::non_fungible_token::TokenId;
+use reputation_core::{AuditEntry, Reputation, ReputationConfig, ReputationEvent, Source};
 
 mod nuwe_marketplace;
 mod modurust_marketplace;
@@ -18,6 +19,10 @@ mod modurust_marketplace;
 pub use nuwe_marketplace::*;
 pub use modurust_marketplace::*;
 
+/// Audit entries kept per token; older ones are folded into the token's
+/// starting standing so each history takes bounded storage
+pub const MAX_AUDIT_ENTRIES: usize = 32;
+
 /// Marketplace contract
 #[near(contract_state)]
 pub struct CreativeMarketplace {
@@ -45,6 +50,12 @@ pub struct CreativeMarketplace {
     // Token reputation tracking
     pub token_reputations: LookupMap<TokenId, f32>,
     
+    // Evidence and the latest audit entries behind each token reputation
+    pub reputation_histories: LookupMap<TokenId, Reputation>,
+    
+    // Scoring rules for token reputation
+    pub reputation_config: ReputationConfig,
+    
     // Emotional data tracking for NFTs
     pub emotional_data: LookupMap<TokenId, EmotionalMetadata>,
     
@@ -52,6 +63,21 @@ pub struct CreativeMarketplace {
     pub marketplace_stats: MarketplaceStats,
 }
 
+/// Marketplace state as deployed before reputation histories were kept
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CreativeMarketplaceV1 {
+    pub owner_id: AccountId,
+    pub listings: UnorderedMap<ListingId, NFTListing>,
+    pub user_balances: LookupMap<AccountId, NearToken>,
+    pub dao: DAO,
+    pub soulbound_tokens: LookupMap<TokenId, bool>,
+    pub cross_chain_tokens: LookupMap<TokenId, ChainInfo>,
+    pub next_listing_id: u64,
+    pub token_reputations: LookupMap<TokenId, f32>,
+    pub emotional_data: LookupMap<TokenId, EmotionalMetadata>,
+    pub marketplace_stats: MarketplaceStats,
+}
+
 // Marketplace statistics
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -225,6 +251,8 @@ impl CreativeMarketplace {
             cross_chain_tokens: LookupMap::new(b"c".to_vec()),
             next_listing_id: 1,
             token_reputations: LookupMap::new(b"r".to_vec()),
+            reputation_histories: LookupMap::new(b"h".to_vec()),
+            reputation_config: ReputationConfig::default(),
             emotional_data: LookupMap::new(b"e".to_vec()),
             marketplace_stats: MarketplaceStats {
                 total_sales: 0,
@@ -235,6 +263,29 @@ impl CreativeMarketplace {
         }
     }
 
+    /// Upgrade state written by a marketplace deployed before reputation
+    /// histories were kept. Stored scores seed a history on the token's next event.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: CreativeMarketplaceV1 =
+            env::state_read().unwrap_or_else(|| env::panic_str("No marketplace state to migrate"));
+        Self {
+            owner_id: old.owner_id,
+            listings: old.listings,
+            user_balances: old.user_balances,
+            dao: old.dao,
+            soulbound_tokens: old.soulbound_tokens,
+            cross_chain_tokens: old.cross_chain_tokens,
+            next_listing_id: old.next_listing_id,
+            token_reputations: old.token_reputations,
+            reputation_histories: LookupMap::new(b"h".to_vec()),
+            reputation_config: ReputationConfig::default(),
+            emotional_data: old.emotional_data,
+            marketplace_stats: old.marketplace_stats,
+        }
+    }
+
     /// List an NFT for sale with emotional and reputation data
     #[payable]
     pub fn list_nft_with_emotion(
@@ -256,7 +307,7 @@ impl CreativeMarketplace {
         self.next_listing_id += 1;
         
         // Get reputation score if available
-        let reputation_score = self.token_reputations.get(&token_id);
+        let reputation_score = self.get_token_reputation(token_id.clone());
         
         let listing = NFTListing {
             listing_id,
@@ -291,9 +342,12 @@ impl CreativeMarketplace {
         self.list_nft_with_emotion(token_id, price, chain_info, metadata, None)
     }
 
-    /// Buy an NFT with emotional pricing consideration
+    /// Buy an NFT with emotional pricing consideration. The deposit beyond
+    /// the price pays for the reputation storage the sale adds; the rest is
+    /// refunded.
     #[payable]
     pub fn buy_nft(&mut self, listing_id: ListingId) -> Promise {
+        let initial_storage = env::storage_usage();
         let mut listing = self.listings.get(&listing_id).expect("Listing not found");
         
         if !listing.is_active {
@@ -307,6 +361,9 @@ impl CreativeMarketplace {
         listing.is_active = false;
         self.listings.insert(&listing_id, &listing);
         
+        // Rate the sale against earlier sales before it counts towards them
+        let quality = self.sale_quality(listing.price);
+        
         // Update marketplace stats
         self.marketplace_stats.total_sales += 1;
         self.marketplace_stats.total_volume = self.marketplace_stats.total_volume
@@ -314,6 +371,10 @@ impl CreativeMarketplace {
             .expect("Overflow in total volume calculation");
         self.marketplace_stats.active_listings -= 1;
         
+        // A completed sale counts towards the token's reputation
+        self.record_reputation(&listing.token_id, Source::Sale, quality);
+        Self::charge_storage(initial_storage, listing.price);
+        
         // Transfer funds to seller
         Promise::new(listing.seller)
             .transfer(listing.price)
@@ -356,14 +417,118 @@ impl CreativeMarketplace {
         self.emotional_data.get(&token_id)
     }
     
-    /// Set reputation score for a token
+    /// Seed the reputation score of a token that has no recorded events yet
+    /// (owner only). The deposit pays for the history's storage.
+    #[payable]
     pub fn set_token_reputation(&mut self, token_id: TokenId, reputation: f32) {
-        self.token_reputations.insert(&token_id, &reputation);
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can set token reputation");
+        if self.reputation_histories.get(&token_id).is_some_and(|h| h.interactions() > 0) {
+            env::panic_str("Token reputation already has recorded events");
+        }
+        let initial_storage = env::storage_usage();
+        let seeded = Reputation::with_score(&self.reputation_config, reputation, Self::now_secs());
+        self.token_reputations.insert(&token_id, &seeded.score());
+        self.reputation_histories.insert(&token_id, &seeded);
+        Self::charge_storage(initial_storage, NearToken::from_yoctonear(0));
     }
     
-    /// Get reputation score for a token
+    /// Record a rated event against a token's reputation (owner only). The
+    /// deposit pays for the storage the event adds.
+    #[payable]
+    pub fn record_token_reputation(&mut self, token_id: TokenId, source: String, quality: f32) -> f32 {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can record reputation events");
+        let source: Source = source.parse().unwrap_or_else(|e: reputation_core::ReputationError| env::panic_str(&e.to_string()));
+        let initial_storage = env::storage_usage();
+        let score = self.record_reputation(&token_id, source, quality);
+        Self::charge_storage(initial_storage, NearToken::from_yoctonear(0));
+        score
+    }
+    
+    /// Replace the reputation decay, weights and model parameters (owner
+    /// only). The model itself cannot change, since existing histories were
+    /// scored under it.
+    pub fn set_reputation_config(&mut self, config: ReputationConfig) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can update reputation config");
+        config.validate().unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        if std::mem::discriminant(&config.model) != std::mem::discriminant(&self.reputation_config.model) {
+            env::panic_str("Cannot change the reputation model");
+        }
+        self.reputation_config = config;
+    }
+    
+    /// Get the rules token reputations are scored with
+    pub fn get_reputation_config(&self) -> ReputationConfig {
+        self.reputation_config
+    }
+    
+    /// Get reputation score for a token, decayed to the current block
     pub fn get_token_reputation(&self, token_id: TokenId) -> Option<f32> {
-        self.token_reputations.get(&token_id)
+        match self.reputation_histories.get(&token_id) {
+            Some(history) => Some(history.score_at(&self.reputation_config, Self::now_secs())),
+            None => self.token_reputations.get(&token_id),
+        }
+    }
+    
+    /// Get the latest recorded changes to a token's reputation, oldest first;
+    /// at most [`MAX_AUDIT_ENTRIES`] are kept
+    pub fn get_token_reputation_audit_log(&self, token_id: TokenId) -> Vec<AuditEntry> {
+        self.reputation_histories
+            .get(&token_id)
+            .map(|history| history.audit_log().to_vec())
+            .unwrap_or_default()
+    }
+    
+    fn now_secs() -> u64 {
+        env::block_timestamp() / 1_000_000_000
+    }
+    
+    /// Quality of a sale at `price`: 0.5 at the average price of earlier
+    /// sales, approaching 1.0 above it and 0.0 below it
+    fn sale_quality(&self, price: NearToken) -> f32 {
+        let stats = &self.marketplace_stats;
+        if stats.total_sales == 0 {
+            return 0.5;
+        }
+        let average = stats.total_volume.as_yoctonear() as f64 / stats.total_sales as f64;
+        let price = price.as_yoctonear() as f64;
+        if price + average <= 0.0 {
+            return 0.5;
+        }
+        (price / (price + average)) as f32
+    }
+    
+    /// Require the attached deposit, less `reserved`, to pay for storage
+    /// added since `initial_storage`, and refund what is left over
+    fn charge_storage(initial_storage: u64, reserved: NearToken) {
+        let added = env::storage_usage().saturating_sub(initial_storage);
+        let required = env::storage_byte_cost().saturating_mul(added.into()).saturating_add(reserved);
+        let attached = env::attached_deposit();
+        if attached < required {
+            env::panic_str(&format!("Attach {} yoctoNEAR to cover the price and storage", required.as_yoctonear()));
+        }
+        let refund = attached.saturating_sub(required);
+        if refund.as_yoctonear() > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+    
+    /// Apply an event to a token's reputation and cache the new score
+    fn record_reputation(&mut self, token_id: &TokenId, source: Source, quality: f32) -> f32 {
+        let now = Self::now_secs();
+        let mut history = self.reputation_histories.get(token_id).unwrap_or_else(|| {
+            // Carry over a score set before histories were kept
+            let seed = self.token_reputations.get(token_id).unwrap_or(0.5);
+            Reputation::with_score(&self.reputation_config, seed, now)
+        });
+        let entry = history
+            .record(&self.reputation_config, ReputationEvent::new(source, quality, now))
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        history
+            .compact(&self.reputation_config, MAX_AUDIT_ENTRIES)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        self.token_reputations.insert(token_id, &entry.score_after);
+        self.reputation_histories.insert(token_id, &history);
+        entry.score_after
     }
     
     /// Get listing by ID with emotional and reputation data
@@ -559,4 +724,118 @@ mod tests {
         assert_eq!(stats.active_listings, 0);
         assert_eq!(stats.total_users, 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_migrate_from_v1_state() {
+        let mut context = get_context();
+        context.predecessor_account_id("marketplace.testnet".parse().unwrap());
+        testing_env!(context.build());
+        
+        let mut old = CreativeMarketplaceV1 {
+            owner_id: "owner.testnet".parse().unwrap(),
+            listings: UnorderedMap::new(b"l".to_vec()),
+            user_balances: LookupMap::new(b"b".to_vec()),
+            dao: DAO {
+                proposals: UnorderedMap::new(b"p".to_vec()),
+                members: UnorderedSet::new(b"m".to_vec()),
+                next_proposal_id: 1,
+                quorum_percentage: 51,
+            },
+            soulbound_tokens: LookupMap::new(b"s".to_vec()),
+            cross_chain_tokens: LookupMap::new(b"c".to_vec()),
+            next_listing_id: 7,
+            token_reputations: LookupMap::new(b"r".to_vec()),
+            emotional_data: LookupMap::new(b"e".to_vec()),
+            marketplace_stats: MarketplaceStats {
+                total_sales: 3,
+                total_volume: NearToken::from_yoctonear(0),
+                active_listings: 0,
+                total_users: 2,
+            },
+        };
+        old.token_reputations.insert(&"token1".to_string(), &0.7);
+        env::state_write(&old);
+        
+        let mut marketplace = CreativeMarketplace::migrate();
+        assert_eq!(marketplace.next_listing_id, 7);
+        assert_eq!(marketplace.marketplace_stats.total_sales, 3);
+        assert_eq!(marketplace.get_token_reputation("token1".to_string()), Some(0.7));
+        
+        // The stored score seeds the history on the next event
+        context.predecessor_account_id("owner.testnet".parse().unwrap());
+        context.attached_deposit(NearToken::from_millinear(100));
+        testing_env!(context.build());
+        marketplace.record_token_reputation("token1".to_string(), "sale".to_string(), 1.0);
+        let audit_log = marketplace.get_token_reputation_audit_log("token1".to_string());
+        assert_eq!(audit_log[0].score_before, 0.7);
+    }
+
+    #[test]
+    fn test_token_reputation_history() {
+        let mut context = get_context();
+        context.predecessor_account_id("owner.testnet".parse().unwrap());
+        context.attached_deposit(NearToken::from_millinear(100));
+        testing_env!(context.build());
+        
+        let mut marketplace = CreativeMarketplace::new("owner.testnet".parse().unwrap());
+        marketplace.set_token_reputation("token1".to_string(), 0.6);
+        assert_eq!(marketplace.get_token_reputation("token1".to_string()), Some(0.6));
+        
+        let score = marketplace.record_token_reputation("token1".to_string(), "sale".to_string(), 1.0);
+        assert!(score > 0.6);
+        
+        let audit_log = marketplace.get_token_reputation_audit_log("token1".to_string());
+        assert_eq!(audit_log.len(), 1);
+        assert_eq!(audit_log[0].score_before, 0.6);
+        assert_eq!(audit_log[0].score_after, score);
+    }
+    
+    #[test]
+    fn test_audit_log_is_capped() {
+        let mut context = get_context();
+        context.predecessor_account_id("owner.testnet".parse().unwrap());
+        context.attached_deposit(NearToken::from_millinear(100));
+        testing_env!(context.build());
+        
+        let mut marketplace = CreativeMarketplace::new("owner.testnet".parse().unwrap());
+        let mut score = 0.0;
+        for i in 0..MAX_AUDIT_ENTRIES + 8 {
+            let quality = if i % 2 == 0 { 0.9 } else { 0.4 };
+            score = marketplace.record_token_reputation("token1".to_string(), "interaction".to_string(), quality);
+        }
+        
+        let audit_log = marketplace.get_token_reputation_audit_log("token1".to_string());
+        assert_eq!(audit_log.len(), MAX_AUDIT_ENTRIES);
+        assert_eq!(audit_log[0].sequence, 8);
+        assert_eq!(audit_log[MAX_AUDIT_ENTRIES - 1].score_after, score);
+        
+        let history = marketplace.reputation_histories.get(&"token1".to_string()).unwrap();
+        assert_eq!(history.interactions(), (MAX_AUDIT_ENTRIES + 8) as u64);
+        assert_eq!(history.verify(&marketplace.reputation_config), Ok(()));
+    }
+    
+    #[test]
+    #[should_panic(expected = "Only owner can set token reputation")]
+    fn test_only_owner_sets_token_reputation() {
+        let context = get_context().build();
+        testing_env!(context);
+        
+        let mut marketplace = CreativeMarketplace::new("owner.testnet".parse().unwrap());
+        marketplace.set_token_reputation("token1".to_string(), 1.0);
+    }
+    
+    #[test]
+    fn test_sale_quality_follows_average_price() {
+        let context = get_context().build();
+        testing_env!(context);
+        
+        let mut marketplace = CreativeMarketplace::new("owner.testnet".parse().unwrap());
+        assert_eq!(marketplace.sale_quality(NearToken::from_near(5)), 0.5);
+        
+        marketplace.marketplace_stats.total_sales = 2;
+        marketplace.marketplace_stats.total_volume = NearToken::from_near(4);
+        assert_eq!(marketplace.sale_quality(NearToken::from_near(2)), 0.5);
+        assert!(marketplace.sale_quality(NearToken::from_near(6)) > 0.7);
+        assert!(marketplace.sale_quality(NearToken::from_millinear(500)) < 0.3);
+    }
+}
@@ -11,6 +11,7 @@ use near_sdk::{
     env, near_bindgen, AccountId, Balance, CryptoHash, PanicOnDefault, Promise, PromiseOrValue,
 };
 use std::collections::HashMap;
+use emotion_core::{EmotionError, EmotionReading, Vad};
 
 /// NEP-177 Token Metadata
 /// https://nomicon.io/Standards/Tokens/NonFungibleToken/Metadata
@@ -53,6 +54,31 @@ pub struct EmotionalState {
     pub timestamp: u64,
 }
 
+impl TryFrom<&EmotionalState> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(state: &EmotionalState) -> Result<Self, Self::Error> {
+        EmotionReading::new(Vad::new(state.valence, state.arousal, state.dominance)?)
+            .with_confidence(state.confidence)?
+            .with_timestamp_ns_u64(state.timestamp)
+    }
+}
+
+impl TryFrom<EmotionReading> for EmotionalState {
+    type Error = EmotionError;
+
+    fn try_from(reading: EmotionReading) -> Result<Self, Self::Error> {
+        reading.validate()?;
+        Ok(Self {
+            valence: reading.vad.valence,
+            arousal: reading.vad.arousal,
+            dominance: reading.vad.dominance,
+            confidence: reading.require_confidence()?,
+            timestamp: reading.require_timestamp_ns_u64()?,
+        })
+    }
+}
+
 /// Token struct following NEP-171
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct Token {
@@ -5,6 +5,7 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::Timestamp;
+use emotion_core::{EmotionError, EmotionReading, EmotionTaxonomy, PadOctants, Vad};
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
@@ -117,44 +118,83 @@ impl EmotionalData {
     
     /// Update emotional complexity based on trajectory variance
     fn update_emotional_complexity(&mut self) {
-        if self.emotional_trajectory.len() < 3 {
-            self.emotional_complexity = 0.0;
-            return;
-        }
-        
-        let mut valence_variance = 0.0;
-        let mut arousal_variance = 0.0;
-        let mut dominance_variance = 0.0;
-        
-        // Calculate mean
-        let len = self.emotional_trajectory.len() as f32;
-        let mean_valence: f32 = self.emotional_trajectory.iter().map(|e| e.valence).sum::<f32>() / len;
-        let mean_arousal: f32 = self.emotional_trajectory.iter().map(|e| e.arousal).sum::<f32>() / len;
-        let mean_dominance: f32 = self.emotional_trajectory.iter().map(|e| e.dominance).sum::<f32>() / len;
-        
-        // Calculate variance
-        for emotion in &self.emotional_trajectory {
-            valence_variance += (emotion.valence - mean_valence).powi(2);
-            arousal_variance += (emotion.arousal - mean_arousal).powi(2);
-            dominance_variance += (emotion.dominance - mean_dominance).powi(2);
-        }
-        
-        valence_variance /= len;
-        arousal_variance /= len;
-        dominance_variance /= len;
-        
-        // Combine variances to get complexity score (0.0 to 1.0)
-        self.emotional_complexity = ((valence_variance + arousal_variance + dominance_variance) / 3.0).min(1.0);
+        self.emotional_complexity = emotion_core::complexity(
+            self.emotional_trajectory
+                .iter()
+                .map(|e| Vad::clamped(e.valence, e.arousal, e.dominance)),
+        );
     }
     
     /// Get emotional category based on VAD values
     pub fn get_emotional_category(&self) -> String {
-        match (self.valence, self.arousal) {
-            (v, a) if v > 0.5 && a > 0.5 => "Excited".to_string(),
-            (v, a) if v > 0.5 && a <= 0.5 => "Happy".to_string(),
-            (v, a) if v <= 0.5 && a > 0.5 => "Anxious".to_string(),
-            _ => "Calm".to_string(),
+        self.get_emotional_category_with(&PadOctants)
+    }
+    
+    /// Get emotional category under a chosen taxonomy
+    pub fn get_emotional_category_with(&self, taxonomy: &dyn EmotionTaxonomy) -> String {
+        Vad::clamped(self.valence, self.arousal, self.dominance).classify(taxonomy).label.to_string()
+    }
+}
+
+impl TryFrom<&EmotionalVector> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(v: &EmotionalVector) -> Result<Self, Self::Error> {
+        EmotionReading::new(Vad::new(v.valence, v.arousal, v.dominance)?).with_timestamp_ns_u64(v.timestamp)
+    }
+}
+
+/// Fails if the reading has a confidence, which this type cannot hold
+impl TryFrom<EmotionReading> for EmotionalVector {
+    type Error = EmotionError;
+
+    fn try_from(reading: EmotionReading) -> Result<Self, Self::Error> {
+        reading.validate()?;
+        if reading.confidence.is_some() {
+            return Err(EmotionError::Unrepresentable { field: "confidence" });
         }
+        Ok(Self {
+            valence: reading.vad.valence,
+            arousal: reading.vad.arousal,
+            dominance: reading.vad.dominance,
+            timestamp: reading.require_timestamp_ns_u64()?,
+        })
+    }
+}
+
+impl TryFrom<&EmotionalData> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(data: &EmotionalData) -> Result<Self, Self::Error> {
+        EmotionReading::new(Vad::new(data.valence, data.arousal, data.dominance)?)
+            .with_confidence(data.confidence)?
+            .with_timestamp_ns_u64(data.timestamp)
+    }
+}
+
+impl TryFrom<EmotionReading> for EmotionalData {
+    type Error = EmotionError;
+
+    fn try_from(reading: EmotionReading) -> Result<Self, Self::Error> {
+        reading.validate()?;
+        let timestamp = reading.require_timestamp_ns_u64()?;
+        Ok(Self {
+            timestamp,
+            valence: reading.vad.valence,
+            arousal: reading.vad.arousal,
+            dominance: reading.vad.dominance,
+            confidence: reading.require_confidence()?,
+            raw_vector: vec![],
+            emotional_vector: EmotionalVector {
+                valence: reading.vad.valence,
+                arousal: reading.vad.arousal,
+                dominance: reading.vad.dominance,
+                timestamp,
+            },
+            emotional_trajectory: vec![],
+            predicted_emotion: None,
+            emotional_complexity: 0.0,
+        })
     }
 }
 
@@ -249,11 +289,15 @@ mod tests {
         let mut emotion = EmotionalData::new();
         emotion.valence = 0.8;
         emotion.arousal = 0.9;
+        emotion.dominance = 0.7;
         assert_eq!(emotion.get_emotional_category(), "Excited");
         
+        emotion.dominance = 0.5;
+        assert_eq!(emotion.get_emotional_category(), "Happy");
+        
         emotion.valence = 0.8;
         emotion.arousal = 0.3;
-        assert_eq!(emotion.get_emotional_category(), "Happy");
+        assert_eq!(emotion.get_emotional_category(), "Calm");
         
         emotion.valence = -0.3;
         emotion.arousal = 0.8;
@@ -261,6 +305,24 @@ mod tests {
         
         emotion.valence = -0.3;
         emotion.arousal = 0.3;
-        assert_eq!(emotion.get_emotional_category(), "Calm");
+        assert_eq!(emotion.get_emotional_category(), "Sad");
+        assert_eq!(emotion.get_emotional_category_with(&emotion_core::Circumplex), "Depressed");
+    }
+    
+    #[test]
+    fn test_emotion_core_round_trip() {
+        let emotion = EmotionalData::from_vector(vec![0.7, 0.8, 0.3]);
+        let reading = EmotionReading::try_from(&emotion).unwrap();
+        assert_eq!(reading.category().as_str(), emotion.get_emotional_category());
+        
+        let restored = EmotionalData::try_from(reading).unwrap();
+        assert_eq!(restored.timestamp, emotion.timestamp);
+        assert_eq!(restored.confidence, emotion.confidence);
+        
+        // EmotionalVector has nowhere to keep the confidence
+        assert_eq!(
+            EmotionalVector::try_from(reading).err(),
+            Some(EmotionError::Unrepresentable { field: "confidence" })
+        );
     }
 }
\ No newline at end of file
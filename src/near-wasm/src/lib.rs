@@ -14,6 +14,7 @@ use near_contract_standards::non_fungible_token::core::NonFungibleTokenCore;
 use near_contract_standards::non_fungible_token::enumeration::NonFungibleTokenEnumeration;
 use near_contract_standards::non_fungible_token::approval::NonFungibleTokenApproval;
 use near_sdk::PromiseOrValue;
+use reputation_core::{AuditEntry, Reputation, ReputationConfig, ReputationEvent, Source};
 
 pub use crate::emotional::*;
 pub use crate::interactive::*;
@@ -30,6 +31,10 @@ mod fractal_studio;
 mod wgsl_studio;
 mod enhanced_soulbound;
 
+/// Audit entries kept per token; older ones are folded into the token's
+/// starting standing so each history takes bounded storage
+pub const MAX_AUDIT_ENTRIES: usize = 32;
+
 /// Main interactive NFT contract
 #[near(contract_state)]
 pub struct InteractiveNftContract {
@@ -63,10 +68,32 @@ pub struct InteractiveNftContract {
     // Token reputation scores
     token_reputations: LookupMap<TokenId, f32>,
     
+    // Evidence and the latest audit entries behind each reputation score
+    reputation_histories: LookupMap<TokenId, Reputation>,
+    
+    // Scoring rules for token reputation
+    reputation_config: ReputationConfig,
+    
     // Advanced token analytics
     token_analytics: LookupMap<TokenId, TokenAnalytics>,
 }
 
+/// Contract state as deployed before reputation histories were kept
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct InteractiveNftContractV1 {
+    tokens: NonFungibleToken,
+    owner_id: AccountId,
+    token_metadata: UnorderedMap<TokenId, TokenMetadata>,
+    interaction_history: LookupMap<TokenId, Vec<InteractionEvent>>,
+    emotional_states: LookupMap<TokenId, EmotionalData>,
+    interactive_states: LookupMap<TokenId, InteractiveState>,
+    soulbound_tokens: LookupMap<TokenId, SoulboundToken>,
+    mintbase_integration: MintbaseIntegration,
+    cross_chain_tokens: LookupMap<TokenId, CrossChainInfo>,
+    token_reputations: LookupMap<TokenId, f32>,
+    token_analytics: LookupMap<TokenId, TokenAnalytics>,
+}
+
 // Cross-chain information structure
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -112,10 +139,36 @@ impl InteractiveNftContract {
             mintbase_integration: MintbaseIntegration::new(),
             cross_chain_tokens: LookupMap::new(b"c".to_vec()),
             token_reputations: LookupMap::new(b"r".to_vec()),
+            reputation_histories: LookupMap::new(b"u".to_vec()),
+            reputation_config: ReputationConfig::default(),
             token_analytics: LookupMap::new(b"a".to_vec()),
         }
     }
 
+    /// Upgrade state written by a contract deployed before reputation
+    /// histories were kept. Stored scores seed a history on the token's next interaction.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: InteractiveNftContractV1 =
+            env::state_read().unwrap_or_else(|| env::panic_str("No contract state to migrate"));
+        Self {
+            tokens: old.tokens,
+            owner_id: old.owner_id,
+            token_metadata: old.token_metadata,
+            interaction_history: old.interaction_history,
+            emotional_states: old.emotional_states,
+            interactive_states: old.interactive_states,
+            soulbound_tokens: old.soulbound_tokens,
+            mintbase_integration: old.mintbase_integration,
+            cross_chain_tokens: old.cross_chain_tokens,
+            token_reputations: old.token_reputations,
+            reputation_histories: LookupMap::new(b"u".to_vec()),
+            reputation_config: ReputationConfig::default(),
+            token_analytics: old.token_analytics,
+        }
+    }
+
     /// Mint a new interactive NFT
     #[payable]
     pub fn mint_interactive_nft(
@@ -155,7 +208,9 @@ impl InteractiveNftContract {
         token
     }
 
-    /// Record a user interaction with an NFT
+    /// Record a user interaction with an NFT. The deposit pays for the
+    /// storage the interaction adds; the rest is refunded.
+    #[payable]
     pub fn record_interaction(
         &mut self,
         token_id: TokenId,
@@ -163,6 +218,8 @@ impl InteractiveNftContract {
         data: near_sdk::serde_json::Value,
         intensity: f32,
     ) {
+        let initial_storage = env::storage_usage();
+        
         // Create interaction event
         let interaction = InteractionEvent {
             event_type,
@@ -217,15 +274,21 @@ impl InteractiveNftContract {
         
         self.emotional_states.insert(&token_id, &emotion);
         
-        // Update reputation based on interaction quality
-        let mut reputation = self.token_reputations.get(&token_id).unwrap_or(0.5);
-        // Simple reputation update - positive interactions increase reputation
-        if intensity > 0.5 {
-            reputation = (reputation + 0.1).min(1.0);
-        } else {
-            reputation = (reputation - 0.05).max(0.0);
-        }
-        self.token_reputations.insert(&token_id, &reputation);
+        // Update reputation, treating intensity as interaction quality
+        let quality = if intensity.is_finite() { intensity.clamp(0.0, 1.0) } else { 0.0 };
+        let now = env::block_timestamp() / 1_000_000_000;
+        let mut reputation = self.reputation_histories.get(&token_id).unwrap_or_else(|| {
+            let seed = self.token_reputations.get(&token_id).unwrap_or(0.5);
+            Reputation::with_score(&self.reputation_config, seed, now)
+        });
+        let entry = reputation
+            .record(&self.reputation_config, ReputationEvent::new(Source::Interaction, quality, now))
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        reputation
+            .compact(&self.reputation_config, MAX_AUDIT_ENTRIES)
+            .unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        self.token_reputations.insert(&token_id, &entry.score_after);
+        self.reputation_histories.insert(&token_id, &reputation);
         
         // Update token analytics
         if let Some(mut analytics) = self.token_analytics.get(&token_id) {
@@ -241,6 +304,8 @@ impl InteractiveNftContract {
             
             self.token_analytics.insert(&token_id, &analytics);
         }
+        
+        Self::charge_storage(initial_storage);
     }
 
     /// Get the current emotional state of an NFT
@@ -336,9 +401,33 @@ impl InteractiveNftContract {
         self.cross_chain_tokens.get(&token_id)
     }
     
-    /// Get token reputation score
+    /// Get token reputation score, decayed to the current block
     pub fn get_token_reputation(&self, token_id: TokenId) -> Option<f32> {
-        self.token_reputations.get(&token_id)
+        match self.reputation_histories.get(&token_id) {
+            Some(history) => Some(history.score_at(&self.reputation_config, env::block_timestamp() / 1_000_000_000)),
+            None => self.token_reputations.get(&token_id),
+        }
+    }
+    
+    /// Get the latest recorded changes to a token's reputation, oldest first;
+    /// at most [`MAX_AUDIT_ENTRIES`] are kept
+    pub fn get_token_reputation_audit_log(&self, token_id: TokenId) -> Vec<AuditEntry> {
+        self.reputation_histories
+            .get(&token_id)
+            .map(|history| history.audit_log().to_vec())
+            .unwrap_or_default()
+    }
+    
+    /// Replace the reputation decay, weights and model parameters (owner
+    /// only). The model itself cannot change, since existing histories were
+    /// scored under it.
+    pub fn set_reputation_config(&mut self, config: ReputationConfig) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only owner can update reputation config");
+        config.validate().unwrap_or_else(|e| env::panic_str(&e.to_string()));
+        if std::mem::discriminant(&config.model) != std::mem::discriminant(&self.reputation_config.model) {
+            env::panic_str("Cannot change the reputation model");
+        }
+        self.reputation_config = config;
     }
     
     /// Get token analytics
@@ -399,6 +488,21 @@ impl InteractiveNftContract {
             None
         }
     }
+    
+    /// Require the attached deposit to pay for storage added since
+    /// `initial_storage`, and refund what is left over
+    fn charge_storage(initial_storage: u64) {
+        let added = env::storage_usage().saturating_sub(initial_storage);
+        let required = env::storage_byte_cost().saturating_mul(added.into());
+        let attached = env::attached_deposit();
+        if attached < required {
+            env::panic_str(&format!("Attach {} yoctoNEAR to cover storage", required.as_yoctonear()));
+        }
+        let refund = attached.saturating_sub(required);
+        if refund.as_yoctonear() > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
 }
 
 // Implement NEAR NFT standard methods
@@ -528,6 +632,41 @@ mod tests {
         assert_eq!(contract.nft_total_supply(), U128(0));
     }
 
+    #[test]
+    fn test_migrate_from_v1_state() {
+        let mut context = get_context();
+        context.predecessor_account_id("contract.testnet".parse().unwrap());
+        testing_env!(context.build());
+        
+        let owner_id: AccountId = "owner.testnet".parse().unwrap();
+        let mut old = InteractiveNftContractV1 {
+            tokens: NonFungibleToken::new(
+                b"t".to_vec(),
+                owner_id.clone(),
+                Some(b"o".to_vec()),
+                Some(b"e".to_vec()),
+                Some(b"s".to_vec()),
+            ),
+            owner_id,
+            token_metadata: UnorderedMap::new(b"m".to_vec()),
+            interaction_history: LookupMap::new(b"h".to_vec()),
+            emotional_states: LookupMap::new(b"e".to_vec()),
+            interactive_states: LookupMap::new(b"s".to_vec()),
+            soulbound_tokens: LookupMap::new(b"b".to_vec()),
+            mintbase_integration: MintbaseIntegration::new(),
+            cross_chain_tokens: LookupMap::new(b"c".to_vec()),
+            token_reputations: LookupMap::new(b"r".to_vec()),
+            token_analytics: LookupMap::new(b"a".to_vec()),
+        };
+        old.token_reputations.insert(&"token1".to_string(), &0.7);
+        env::state_write(&old);
+        
+        let contract = InteractiveNftContract::migrate();
+        assert_eq!(contract.nft_total_supply(), U128(0));
+        assert_eq!(contract.get_token_reputation("token1".to_string()), Some(0.7));
+        assert!(contract.get_token_reputation_audit_log("token1".to_string()).is_empty());
+    }
+
     #[test]
     fn test_mint_interactive_nft() {
         let mut context = get_context();
@@ -586,6 +725,7 @@ mod tests {
     fn test_record_interaction() {
         let mut context = get_context();
         context.predecessor_account_id("user.testnet".parse().unwrap());
+        context.attached_deposit(near_sdk::NearToken::from_millinear(100));
         testing_env!(context.build());
         
         let mut contract = InteractiveNftContract::new("owner.testnet".parse().unwrap());
@@ -623,5 +763,37 @@ mod tests {
         let interaction_history = contract.get_interaction_history("token1".to_string());
         assert!(interaction_history.is_some());
         assert_eq!(interaction_history.unwrap().len(), 1);
+        
+        let audit_log = contract.get_token_reputation_audit_log("token1".to_string());
+        assert_eq!(audit_log.len(), 1);
+        assert_eq!(audit_log[0].quality, 0.8);
+        assert_eq!(contract.get_token_reputation("token1".to_string()), Some(audit_log[0].score_after));
+        
+        for _ in 0..MAX_AUDIT_ENTRIES {
+            contract.record_interaction(
+                "token1".to_string(),
+                "view".to_string(),
+                near_sdk::serde_json::json!({}),
+                0.4,
+            );
+        }
+        let audit_log = contract.get_token_reputation_audit_log("token1".to_string());
+        assert_eq!(audit_log.len(), MAX_AUDIT_ENTRIES);
+        assert_eq!(audit_log[0].sequence, 1);
+    }
+    
+    #[test]
+    #[should_panic(expected = "to cover storage")]
+    fn test_record_interaction_charges_storage() {
+        let context = get_context().build();
+        testing_env!(context);
+        
+        let mut contract = InteractiveNftContract::new("owner.testnet".parse().unwrap());
+        contract.record_interaction(
+            "token1".to_string(),
+            "view".to_string(),
+            near_sdk::serde_json::json!({}),
+            0.8,
+        );
     }
 }
\ No newline at end of file
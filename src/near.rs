@@ -0,0 +1,283 @@
+//! NEAR chain backend
+//!
+//! Builds, signs and broadcasts transactions over NEAR JSON-RPC. Transactions
+//! are signed by an ed25519 key held in an encrypted [`Keystore`], which only
+//! decrypts the secret for the signature being made.
+//!
+//! [`Keystore`]: nft_rust_client::Keystore
+
+use crate::backend::{ChainBackend, DeployReceipt, MintReceipt, MintRequest};
+use base64::Engine;
+use borsh::BorshSerialize;
+use nft_rust_client::{ChainType, KeystoreSigner, Signer as _};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::sync::Arc;
+
+/// Gas attached to `nft_mint` calls (300 TGas)
+const MINT_GAS: u64 = 300_000_000_000_000;
+
+/// Deposit attached to `nft_mint` calls to cover storage (0.1 NEAR)
+const MINT_DEPOSIT: u128 = 100_000_000_000_000_000_000_000;
+
+/// NEAR blockchain client
+pub struct NearClient {
+    network_id: String,
+    account_id: Option<String>,
+    signer: Option<Arc<KeystoreSigner>>,
+    rpc_url: String,
+    http: reqwest::blocking::Client,
+}
+
+#[derive(BorshSerialize)]
+enum PublicKey {
+    Ed25519([u8; 32]),
+}
+
+#[derive(BorshSerialize)]
+enum Signature {
+    Ed25519([u8; 64]),
+}
+
+/// Transaction actions, in NEAR's wire order
+#[derive(BorshSerialize)]
+enum Action {
+    #[allow(dead_code)]
+    CreateAccount,
+    DeployContract {
+        code: Vec<u8>,
+    },
+    FunctionCall {
+        method_name: String,
+        args: Vec<u8>,
+        gas: u64,
+        deposit: u128,
+    },
+}
+
+#[derive(BorshSerialize)]
+struct Transaction {
+    signer_id: String,
+    public_key: PublicKey,
+    nonce: u64,
+    receiver_id: String,
+    block_hash: [u8; 32],
+    actions: Vec<Action>,
+}
+
+#[derive(BorshSerialize)]
+struct SignedTransaction {
+    transaction: Transaction,
+    signature: Signature,
+}
+
+impl NearClient {
+    pub fn new(network_id: &str, account_id: Option<&str>) -> Self {
+        Self {
+            network_id: network_id.to_string(),
+            account_id: account_id.map(|s| s.to_string()),
+            signer: None,
+            rpc_url: format!("https://rpc.{}.near.org", network_id),
+            http: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Sign transactions with a keystore key, acting for the signer's account
+    pub fn with_signer(mut self, signer: Arc<KeystoreSigner>) -> Result<Self, Box<dyn Error>> {
+        if signer.chain() != ChainType::Near {
+            return Err("NEAR transactions need a NEAR keystore signer".into());
+        }
+        self.account_id = Some(signer.account_id());
+        self.signer = Some(signer);
+        Ok(self)
+    }
+
+    /// Use a custom RPC endpoint instead of the public one for the network
+    pub fn with_rpc_url(mut self, rpc_url: &str) -> Self {
+        self.rpc_url = rpc_url.to_string();
+        self
+    }
+
+    pub fn network_id(&self) -> &str {
+        &self.network_id
+    }
+
+    /// The signing account and its keystore signer
+    fn signer(&self) -> Result<(String, &KeystoreSigner), Box<dyn Error>> {
+        let signer = self.signer.as_deref().ok_or("NEAR signer not configured")?;
+        let account_id = self.account_id.clone().ok_or("NEAR account id not configured")?;
+        Ok((account_id, signer))
+    }
+
+    fn rpc(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, Box<dyn Error>> {
+        let response: serde_json::Value = self
+            .http
+            .post(&self.rpc_url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": "nft-blockchain-interactive",
+                "method": method,
+                "params": params,
+            }))
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        if let Some(error) = response.get("error") {
+            return Err(format!("NEAR RPC error: {}", error).into());
+        }
+
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| "NEAR RPC response has no result".into())
+    }
+
+    /// Sign and broadcast a transaction, returning its base58 hash
+    fn send_transaction(&self, receiver_id: &str, actions: Vec<Action>) -> Result<String, Box<dyn Error>> {
+        let (signer_id, signer) = self.signer()?;
+        let public_key: [u8; 32] = signer
+            .public_key_bytes()
+            .try_into()
+            .map_err(|_| "NEAR public keys are 32 bytes")?;
+
+        let access_key = self.rpc("query", json!({
+            "request_type": "view_access_key",
+            "finality": "final",
+            "account_id": signer_id,
+            "public_key": format!("ed25519:{}", bs58::encode(public_key).into_string()),
+        }))?;
+
+        let nonce = access_key["nonce"].as_u64().ok_or("Access key has no nonce")? + 1;
+        let block_hash: [u8; 32] = bs58::decode(access_key["block_hash"].as_str().ok_or("Access key has no block hash")?)
+            .into_vec()?
+            .try_into()
+            .map_err(|_| "Invalid block hash length")?;
+
+        let transaction = Transaction {
+            signer_id,
+            public_key: PublicKey::Ed25519(public_key),
+            nonce,
+            receiver_id: receiver_id.to_string(),
+            block_hash,
+            actions,
+        };
+
+        let hash = Sha256::digest(borsh::to_vec(&transaction)?);
+        let signature: [u8; 64] = signer
+            .sign(&hash)?
+            .try_into()
+            .map_err(|_| "NEAR signatures are 64 bytes")?;
+        let signed = SignedTransaction {
+            transaction,
+            signature: Signature::Ed25519(signature),
+        };
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(borsh::to_vec(&signed)?);
+        let outcome = self.rpc("broadcast_tx_commit", json!([encoded]))?;
+
+        if let Some(failure) = outcome["status"].get("Failure") {
+            return Err(format!("NEAR transaction failed: {}", failure).into());
+        }
+
+        Ok(bs58::encode(hash).into_string())
+    }
+}
+
+impl ChainBackend for NearClient {
+    fn account_id(&self) -> Option<&str> {
+        self.account_id.as_deref()
+    }
+
+    fn mint(&mut self, request: &MintRequest) -> Result<MintReceipt, Box<dyn Error>> {
+        let (account_id, _) = self.signer()?;
+        let token_id = format!("{}:{}", request.collection, request.token_id);
+
+        let args = json!({
+            "token_id": token_id,
+            "receiver_id": request.owner,
+            "token_metadata": {
+                "title": format!("{} #{}", request.collection, request.token_id),
+                "reference": request.metadata_uri,
+                "extra": request.metadata,
+            },
+        });
+
+        let transaction_hash = self.send_transaction(&account_id, vec![Action::FunctionCall {
+            method_name: "nft_mint".to_string(),
+            args: serde_json::to_vec(&args)?,
+            gas: MINT_GAS,
+            deposit: MINT_DEPOSIT,
+        }])?;
+
+        Ok(MintReceipt {
+            token_id,
+            transaction_hash,
+        })
+    }
+
+    fn deploy_contract(&mut self, code: &[u8]) -> Result<DeployReceipt, Box<dyn Error>> {
+        let (account_id, _) = self.signer()?;
+        let transaction_hash = self.send_transaction(&account_id, vec![Action::DeployContract {
+            code: code.to_vec(),
+        }])?;
+
+        Ok(DeployReceipt {
+            contract_id: account_id,
+            transaction_hash,
+            code_hash: bs58::encode(Sha256::digest(code)).into_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nft_rust_client::{Kdf, KeyType, Keystore};
+
+    fn keystore() -> (Arc<Keystore>, Vec<u8>) {
+        let mut keystore = Keystore::create("pw", Kdf::Scrypt { log_n: 4, r: 8, p: 1 }).unwrap();
+        let near = keystore.generate("near", KeyType::Ed25519).unwrap();
+        (Arc::new(keystore), near.public_key)
+    }
+
+    #[test]
+    fn test_signer_requires_credentials() {
+        let client = NearClient::new("testnet", Some("artist.testnet"));
+        assert!(client.signer().is_err());
+        assert_eq!(client.account_id(), Some("artist.testnet"));
+        assert_eq!(client.rpc_url, "https://rpc.testnet.near.org");
+    }
+
+    #[test]
+    fn test_signer_comes_from_the_keystore() {
+        let (keystore, public_key) = keystore();
+        let signer = KeystoreSigner::new(keystore.clone(), "near", ChainType::Near).unwrap();
+        let client = NearClient::new("testnet", None)
+            .with_signer(Arc::new(signer.with_account_id("artist.testnet")))
+            .unwrap();
+
+        let (account_id, signer) = client.signer().unwrap();
+        assert_eq!(account_id, "artist.testnet");
+        assert_eq!(signer.public_key_bytes(), public_key);
+        assert_eq!(signer.sign(b"tx").unwrap().len(), 64);
+
+        let solana = KeystoreSigner::new(keystore, "near", ChainType::Solana).unwrap();
+        assert!(NearClient::new("testnet", None).with_signer(Arc::new(solana)).is_err());
+    }
+
+    #[test]
+    fn test_action_wire_discriminants() {
+        let deploy = borsh::to_vec(&Action::DeployContract { code: vec![] }).unwrap();
+        assert_eq!(deploy[0], 1);
+
+        let call = borsh::to_vec(&Action::FunctionCall {
+            method_name: String::new(),
+            args: vec![],
+            gas: 0,
+            deposit: 0,
+        }).unwrap();
+        assert_eq!(call[0], 2);
+    }
+}
@@ -0,0 +1,195 @@
+//! Save/load of the facade state
+//!
+//! State is written as versioned JSON so long-running studio tooling can
+//! resume where it stopped. Secrets (Filecoin auth tokens, NEAR keystore
+//! signers) are never written; after loading, call `initialize_filecoin` /
+//! `initialize_near` again to supply them.
+
+use crate::{DeploymentConfig, FilecoinClient, NearClient, NftBlockchainInteractive, NftCollection};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Current on-disk schema version
+pub const STATE_SCHEMA_VERSION: u32 = 1;
+
+/// Filecoin client settings that are safe to persist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilecoinConfig {
+    pub api_endpoint: String,
+}
+
+/// NEAR client settings that are safe to persist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NearConfig {
+    pub network_id: String,
+    pub account_id: Option<String>,
+}
+
+/// Client settings recorded by `initialize_filecoin` / `initialize_near`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClientConfigs {
+    pub filecoin: Option<FilecoinConfig>,
+    pub near: Option<NearConfig>,
+}
+
+/// Serialized facade state
+#[derive(Serialize, Deserialize)]
+pub struct PersistedState {
+    pub schema_version: u32,
+    pub saved_at: DateTime<Utc>,
+    pub collections: Vec<NftCollection>,
+    pub deployment_config: DeploymentConfig,
+    pub clients: ClientConfigs,
+}
+
+impl NftBlockchainInteractive {
+    /// Snapshot collections, deployment config and client configs
+    pub fn to_persisted_state(&self) -> PersistedState {
+        let mut collections: Vec<NftCollection> = self.collections.values().cloned().collect();
+        collections.sort_by(|a, b| a.name.cmp(&b.name));
+
+        PersistedState {
+            schema_version: STATE_SCHEMA_VERSION,
+            saved_at: Utc::now(),
+            collections,
+            deployment_config: self.deployment_config.clone(),
+            clients: self.client_configs.clone(),
+        }
+    }
+
+    /// Rebuild a facade from a snapshot
+    ///
+    /// Clients are recreated without credentials.
+    pub fn from_persisted_state(state: PersistedState) -> Result<Self, Box<dyn Error>> {
+        check_schema_version(state.schema_version)?;
+
+        let mut client = Self::new();
+        client.deployment_config = state.deployment_config;
+        client.collections = state
+            .collections
+            .into_iter()
+            .map(|c| (c.name.clone(), c))
+            .collect();
+
+        if let Some(filecoin) = state.clients.filecoin {
+            client.storage_backend = Some(Box::new(FilecoinClient::new(&filecoin.api_endpoint, None)));
+            client.client_configs.filecoin = Some(filecoin);
+        }
+
+        if let Some(near) = state.clients.near {
+            client.chain_backend = Some(Box::new(NearClient::new(&near.network_id, near.account_id.as_deref())));
+            client.client_configs.near = Some(near);
+        }
+
+        Ok(client)
+    }
+
+    /// Write the state to `path`, replacing any previous file atomically
+    pub fn save_state<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        let json = serde_json::to_vec_pretty(&self.to_persisted_state())?;
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Load state previously written by [`save_state`](Self::save_state)
+    pub fn load_state<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let bytes = fs::read(path)?;
+
+        // Check the version before the full parse so old or future files
+        // get a clear error instead of a field mismatch
+        let value: serde_json::Value = serde_json::from_slice(&bytes)?;
+        let version = value
+            .get("schema_version")
+            .and_then(|v| v.as_u64())
+            .ok_or("State file has no schema_version")?;
+        let version = u32::try_from(version).map_err(|_| {
+            format!("Unsupported state schema version {} (expected {})", version, STATE_SCHEMA_VERSION)
+        })?;
+        check_schema_version(version)?;
+
+        Self::from_persisted_state(serde_json::from_value(value)?)
+    }
+}
+
+fn check_schema_version(version: u32) -> Result<(), Box<dyn Error>> {
+    if version != STATE_SCHEMA_VERSION {
+        return Err(format!(
+            "Unsupported state schema version {} (expected {})",
+            version, STATE_SCHEMA_VERSION
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use nft_rust_client::{ChainType, Kdf, KeyType, Keystore, KeystoreSigner};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let mut client = NftBlockchainInteractive::new();
+        client.create_collection("Test Collection", "TEST", "ipfs://", Some(10)).unwrap();
+        client.mint_nft_to("Test Collection", 1, "alice.near", "{}").unwrap();
+        client.initialize_filecoin("http://localhost:5001", Some("secret-token")).unwrap();
+        let mut keystore = Keystore::create("secret", Kdf::Scrypt { log_n: 4, r: 8, p: 1 }).unwrap();
+        keystore.generate("near", KeyType::Ed25519).unwrap();
+        let signer = KeystoreSigner::new(Arc::new(keystore), "near", ChainType::Near).unwrap();
+        client.initialize_near("testnet", None, Some(Arc::new(signer.with_account_id("studio.testnet")))).unwrap();
+        assert_eq!(client.client_configs.near.as_ref().unwrap().account_id.as_deref(), Some("studio.testnet"));
+
+        let dir = temp_dir("round-trip");
+        let path = dir.join("state.json");
+        client.save_state(&path).unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains("secret"));
+
+        let restored = NftBlockchainInteractive::load_state(&path).unwrap();
+        let collection = restored.get_collection_info("Test Collection").unwrap();
+        assert_eq!(collection.max_supply, Some(10));
+        assert_eq!(collection.minted_count, 1);
+        assert_eq!(collection.ledger().owner_of(1), Some("alice.near"));
+        assert!(collection.ledger().is_taken(1));
+        assert_eq!(restored.client_configs, client.client_configs);
+        assert!(restored.storage_backend.is_some());
+        assert!(restored.chain_backend.is_some());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_rejects_other_schema_versions() {
+        let dir = temp_dir("version");
+        let path = dir.join("state.json");
+        fs::write(&path, r#"{"schema_version": 999}"#).unwrap();
+
+        let err = NftBlockchainInteractive::load_state(&path).err().unwrap();
+        assert!(err.to_string().contains("schema version 999"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_rejects_versions_beyond_u32() {
+        // 2^32 + 1 would read as version 1 if truncated
+        let dir = temp_dir("wide-version");
+        let path = dir.join("state.json");
+        fs::write(&path, r#"{"schema_version": 4294967297}"#).unwrap();
+
+        let err = NftBlockchainInteractive::load_state(&path).err().unwrap();
+        assert!(err.to_string().contains("schema version 4294967297"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,67 @@
+//! Conversions between `EmotionalMetadata` and `emotion_core`
+//!
+//! Timestamps here are whole seconds, so readings with a sub-second part are
+//! rejected rather than truncated.
+
+use crate::EmotionalMetadata;
+use emotion_core::{EmotionError, EmotionReading, Vad};
+
+impl TryFrom<&EmotionalMetadata> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(metadata: &EmotionalMetadata) -> Result<Self, Self::Error> {
+        let secs = i64::try_from(metadata.timestamp).map_err(|_| EmotionError::TimestampOutOfRange)?;
+        EmotionReading::new(Vad::new(metadata.valence, metadata.arousal, metadata.dominance)?)
+            .with_confidence(metadata.confidence)?
+            .with_timestamp_secs(secs)
+    }
+}
+
+impl TryFrom<EmotionReading> for EmotionalMetadata {
+    type Error = EmotionError;
+
+    fn try_from(reading: EmotionReading) -> Result<Self, Self::Error> {
+        reading.validate()?;
+        let secs = reading.require_timestamp_secs()?;
+        Ok(Self {
+            valence: reading.vad.valence,
+            arousal: reading.vad.arousal,
+            dominance: reading.vad.dominance,
+            confidence: reading.require_confidence()?,
+            timestamp: u64::try_from(secs).map_err(|_| EmotionError::TimestampOutOfRange)?,
+            emotional_category: reading.category().to_string(),
+            emotional_trajectory: vec![],
+            predicted_emotion: None,
+            emotional_complexity: 0.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let metadata = EmotionalMetadata::new(0.7, 0.8, 0.3);
+        let reading = EmotionReading::try_from(&metadata).unwrap();
+        let restored = EmotionalMetadata::try_from(reading).unwrap();
+
+        assert_eq!(restored.timestamp, metadata.timestamp);
+        assert_eq!(restored.emotional_category, "Happy");
+        assert_eq!(restored.emotional_category, metadata.emotional_category);
+    }
+
+    #[test]
+    fn test_sub_second_timestamp_rejected() {
+        let reading = EmotionReading::new(Vad::NEUTRAL)
+            .with_confidence(0.5)
+            .unwrap()
+            .with_timestamp_ns(1_700_000_000_500_000_000);
+        assert_eq!(
+            EmotionalMetadata::try_from(reading).err(),
+            Some(EmotionError::PrecisionLoss { field: "timestamp" })
+        );
+        assert!(EmotionalMetadata::try_from(reading.truncate_to_seconds()).is_ok());
+    }
+}
@@ -4,6 +4,7 @@
 
 use serde::{Deserialize, Serialize};
 use crate::{EmotionalMetadata, BridgeInfo};
+use emotion_core::Vad;
 
 /// Emotional bridge configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,7 +120,11 @@ impl EmotionalBridgeProcessor {
             dominance: (latest.dominance + dominance_delta).clamp(0.0, 1.0),
             confidence: (latest.confidence + confidence_delta).clamp(0.0, 1.0),
             timestamp: latest.timestamp + 3600, // Predict 1 hour ahead
-            emotional_category: EmotionalMetadata::get_emotional_category(latest.valence + valence_delta, latest.arousal + arousal_delta),
+            emotional_category: EmotionalMetadata::get_emotional_category(
+                latest.valence + valence_delta,
+                latest.arousal + arousal_delta,
+                latest.dominance + dominance_delta,
+            ),
             emotional_trajectory: latest.emotional_trajectory.clone(),
             predicted_emotion: None, // Would need recursive handling in a real implementation
             emotional_complexity: latest.emotional_complexity,
@@ -128,25 +133,6 @@ impl EmotionalBridgeProcessor {
 
     /// Calculate emotional complexity score
     pub fn calculate_emotional_complexity(history: &[EmotionalMetadata]) -> f32 {
-        if history.is_empty() {
-            return 0.0;
-        }
-
-        let len = history.len();
-
-        // Calculate variance in emotional dimensions
-        let avg_valence: f32 = history.iter().map(|e| e.valence).sum::<f32>() / len as f32;
-        let avg_arousal: f32 = history.iter().map(|e| e.arousal).sum::<f32>() / len as f32;
-        let avg_dominance: f32 = history.iter().map(|e| e.dominance).sum::<f32>() / len as f32;
-
-        let valence_variance: f32 = history.iter().map(|e| (e.valence - avg_valence).powi(2)).sum::<f32>() / len as f32;
-        let arousal_variance: f32 = history.iter().map(|e| (e.arousal - avg_arousal).powi(2)).sum::<f32>() / len as f32;
-        let dominance_variance: f32 = history.iter().map(|e| (e.dominance - avg_dominance).powi(2)).sum::<f32>() / len as f32;
-
-        // Complexity is higher when there's more variation
-        let total_variance = (valence_variance + arousal_variance + dominance_variance).sqrt();
-
-        // Normalize to 0-1 range
-        total_variance.clamp(0.0, 1.0)
+        emotion_core::complexity(history.iter().map(|e| Vad::clamped(e.valence, e.arousal, e.dominance)))
     }
 }
\ No newline at end of file
@@ -8,11 +8,14 @@ use subxt::{OnlineClient, PolkadotConfig};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use emotion_core::{EmotionTaxonomy, PadOctants, Vad};
 
+mod emotion;
 mod emotional_bridge;
 mod soulbound;
 
 pub use emotional_bridge::*;
+pub use emotion_core;
 pub use soulbound::*;
 
 /// Polkadot client for creative NFT operations
@@ -192,7 +195,7 @@ impl EmotionalMetadata {
             .unwrap()
             .as_secs();
             
-        let category = Self::get_emotional_category(valence, arousal);
+        let category = Self::get_emotional_category(valence, arousal, dominance);
         
         Self {
             valence,
@@ -208,13 +211,18 @@ impl EmotionalMetadata {
     }
     
     /// Get human-readable emotional category
-    pub fn get_emotional_category(valence: f32, arousal: f32) -> String {
-        match (valence, arousal) {
-            (v, a) if v > 0.5 && a > 0.5 => "Excited".to_string(),
-            (v, a) if v > 0.5 && a <= 0.5 => "Happy".to_string(),
-            (v, a) if v <= 0.5 && a > 0.5 => "Anxious".to_string(),
-            _ => "Calm".to_string(),
-        }
+    pub fn get_emotional_category(valence: f32, arousal: f32, dominance: f32) -> String {
+        Self::get_emotional_category_with(valence, arousal, dominance, &PadOctants)
+    }
+    
+    /// Get emotional category under a chosen taxonomy
+    pub fn get_emotional_category_with(
+        valence: f32,
+        arousal: f32,
+        dominance: f32,
+        taxonomy: &dyn EmotionTaxonomy,
+    ) -> String {
+        Vad::clamped(valence, arousal, dominance).classify(taxonomy).label.to_string()
     }
     
     /// Add point to emotional trajectory
@@ -231,22 +239,13 @@ impl EmotionalMetadata {
     
     /// Calculate emotional complexity based on trajectory
     pub fn calculate_complexity(&mut self) {
-        if self.emotional_trajectory.len() < 2 {
-            self.emotional_complexity = 0.0;
-            return;
-        }
-        
-        let mut total_distance = 0.0;
-        for i in 1..self.emotional_trajectory.len() {
-            let prev = &self.emotional_trajectory[i-1];
-            let curr = &self.emotional_trajectory[i];
-            let distance = ((curr.valence - prev.valence).powi(2) + 
-                           (curr.arousal - prev.arousal).powi(2)).sqrt();
-            total_distance += distance;
-        }
-        
-        // Normalize by number of points
-        self.emotional_complexity = (total_distance / self.emotional_trajectory.len() as f32).clamp(0.0, 1.0);
+        // Trajectory points carry no dominance, so the current one stands in
+        let dominance = self.dominance;
+        self.emotional_complexity = emotion_core::complexity(
+            self.emotional_trajectory
+                .iter()
+                .map(|p| Vad::clamped(p.valence, p.arousal, dominance)),
+        );
     }
 }
 
@@ -362,4 +361,22 @@ mod tests {
         assert!(analytics.engagement_score >= 0.0);
         assert!(analytics.engagement_score <= 1.0);
     }
+
+    #[test]
+    fn test_reputation_engine_events() {
+        use reputation_core::{ReputationConfig, ReputationEvent, Source};
+
+        let config = ReputationConfig::default();
+        let mut reputation = AdvancedReputation::default();
+        for (i, quality) in [1.0, 0.8, 0.9].into_iter().enumerate() {
+            let event = ReputationEvent::new(Source::Collaboration, quality, i as u64);
+            SoulboundTokenClient::record_reputation_event(&mut reputation, &config, event).unwrap();
+        }
+
+        assert_eq!(reputation.total_interactions, 3);
+        assert!(reputation.score > 50.0);
+        assert!((reputation.collaboration_score - 0.9).abs() < 1e-6);
+        assert_eq!(reputation.reputation_trajectory.len(), 3);
+        assert_eq!(reputation.history.verify(&config), Ok(()));
+    }
 }
\ No newline at end of file
@@ -5,6 +5,7 @@
 use serde::{Deserialize, Serialize};
 use subxt::utils::AccountId32;
 use crate::EmotionalMetadata;
+use reputation_core::{AuditEntry, Reputation, ReputationConfig, ReputationError, ReputationEvent, Source};
 
 /// Soulbound token structure
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -49,6 +50,9 @@ pub struct AdvancedReputation {
     pub creativity_index: f32,
     pub engagement_score: f32,
     pub reputation_trajectory: Vec<ReputationPoint>,
+    /// Evidence and audit log behind `score`, which is its score × 100
+    #[serde(default)]
+    pub history: Reputation,
 }
 
 /// Point in reputation trajectory
@@ -231,6 +235,41 @@ impl SoulboundTokenClient {
                 .as_secs(),
         });
         
+        Self::refresh_reputation_metrics(reputation);
+        Ok(())
+    }
+    
+    /// Record a rated event through the reputation engine and refresh the
+    /// derived score, badges and metrics
+    pub fn record_reputation_event(
+        reputation: &mut AdvancedReputation,
+        config: &ReputationConfig,
+        event: ReputationEvent,
+    ) -> Result<AuditEntry, ReputationError> {
+        if reputation.total_interactions > 0 && reputation.history.updated_at().is_none() {
+            // Carry over a score earned before histories were kept
+            reputation.history = Reputation::with_score(config, reputation.score / 100.0, event.timestamp);
+        }
+        let entry = reputation.history.record(config, event)?;
+        
+        reputation.score = entry.score_after * 100.0;
+        reputation.total_interactions += 1;
+        if event.source == Source::Collaboration {
+            let collaborations = reputation.history.audit_log().iter().filter(|e| e.source == Source::Collaboration);
+            let (count, sum) = collaborations.fold((0u32, 0.0f32), |(n, s), e| (n + 1, s + e.quality));
+            reputation.collaboration_score = sum / count as f32;
+        }
+        reputation.reputation_trajectory.push(ReputationPoint {
+            score: reputation.score,
+            timestamp: event.timestamp,
+        });
+        
+        Self::refresh_reputation_metrics(reputation);
+        Ok(entry)
+    }
+    
+    /// Badges and metrics that follow from score, interactions and trajectory
+    fn refresh_reputation_metrics(reputation: &mut AdvancedReputation) {
         // Award badges based on achievements
         if reputation.total_interactions >= 100 && !reputation.badges.contains(&Badge::Pioneer) {
             reputation.badges.push(Badge::Pioneer);
@@ -244,8 +283,6 @@ impl SoulboundTokenClient {
         reputation.emotional_complexity = Self::calculate_reputation_complexity(&reputation.reputation_trajectory);
         reputation.creativity_index = Self::calculate_creativity_index(&reputation.reputation_trajectory);
         reputation.engagement_score = Self::calculate_engagement_score(reputation.total_interactions, reputation.emotional_complexity);
-        
-        Ok(())
     }
     
     /// Calculate reputation complexity based on trajectory
@@ -0,0 +1,39 @@
+//! Audit log entries
+
+use crate::Source;
+
+/// One recorded event and what it did to the score
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub struct AuditEntry {
+    /// Position in the log, from 0
+    pub sequence: u64,
+    /// Event time, Unix seconds
+    pub timestamp: u64,
+    pub source: Source,
+    /// Event quality, 0.0 (bad) to 1.0 (good)
+    pub quality: f32,
+    /// Weight of the source when the event was recorded
+    pub weight: f32,
+    /// Share of earlier evidence kept after decaying since the last event
+    pub decay: f32,
+    /// Score after the previous event
+    pub score_before: f32,
+    /// Score after decay, before this event
+    pub score_decayed: f32,
+    /// Score after this event
+    pub score_after: f32,
+}
+
+impl AuditEntry {
+    /// Change caused by the passage of time
+    pub fn decay_change(&self) -> f32 {
+        self.score_decayed - self.score_before
+    }
+
+    /// Change caused by the event itself
+    pub fn event_change(&self) -> f32 {
+        self.score_after - self.score_decayed
+    }
+}
@@ -0,0 +1,94 @@
+//! Scoring rules
+
+use crate::{ReputationError, SourceWeights};
+
+/// Rating every Elo reputation starts from and decays back to
+pub const ELO_BASE_RATING: f32 = 1500.0;
+
+/// Rating difference at which the expected outcome is 10:1
+pub const ELO_SCALE: f32 = 400.0;
+
+/// Ninety days, in seconds
+pub const DEFAULT_HALF_LIFE_SECS: u64 = 90 * 24 * 60 * 60;
+
+/// How events turn into a score between 0.0 and 1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub enum ReputationModel {
+    /// Beta distribution over the chance of a good outcome. An event of
+    /// quality `q` and weight `w` adds `w·q` to α and `w·(1 − q)` to β; the
+    /// score is the mean α / (α + β).
+    Beta { prior_alpha: f32, prior_beta: f32 },
+    /// Elo-style rating. An event of quality `q` and weight `w` moves the
+    /// rating by `k·w·(q − score)`; the score is the expected outcome
+    /// against [`ELO_BASE_RATING`].
+    Elo { k_factor: f32 },
+}
+
+impl Default for ReputationModel {
+    /// Uniform prior, so a new reputation scores 0.5
+    fn default() -> Self {
+        ReputationModel::Beta {
+            prior_alpha: 1.0,
+            prior_beta: 1.0,
+        }
+    }
+}
+
+/// Model, decay and weights applied to every event
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub struct ReputationConfig {
+    pub model: ReputationModel,
+    /// Seconds for accumulated evidence to lose half its weight; 0 disables
+    /// decay
+    pub half_life_secs: u64,
+    pub weights: SourceWeights,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        Self {
+            model: ReputationModel::default(),
+            half_life_secs: DEFAULT_HALF_LIFE_SECS,
+            weights: SourceWeights::default(),
+        }
+    }
+}
+
+impl ReputationConfig {
+    pub fn elo(k_factor: f32) -> Self {
+        Self {
+            model: ReputationModel::Elo { k_factor },
+            ..Self::default()
+        }
+    }
+
+    /// Share of accumulated evidence left after `elapsed_secs`
+    pub fn decay(&self, elapsed_secs: u64) -> f32 {
+        if self.half_life_secs == 0 {
+            return 1.0;
+        }
+        libm::exp2f(-(elapsed_secs as f32) / self.half_life_secs as f32)
+    }
+
+    pub fn validate(&self) -> Result<(), ReputationError> {
+        match self.model {
+            ReputationModel::Beta { prior_alpha, prior_beta } => {
+                let valid = |p: f32| p.is_finite() && p > 0.0;
+                if !valid(prior_alpha) || !valid(prior_beta) {
+                    return Err(ReputationError::InvalidConfig { field: "prior" });
+                }
+            }
+            ReputationModel::Elo { k_factor } => {
+                if !k_factor.is_finite() || k_factor <= 0.0 {
+                    return Err(ReputationError::InvalidConfig { field: "k_factor" });
+                }
+            }
+        }
+        self.weights.validate()
+    }
+}
@@ -0,0 +1,41 @@
+//! Reputation errors
+
+use core::fmt;
+
+/// Reason an event or configuration was rejected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReputationError {
+    /// A value is NaN or infinite
+    NotFinite { field: &'static str },
+    /// Event quality is outside 0.0-1.0
+    QualityOutOfRange { value: f32 },
+    /// An event is older than the last one recorded
+    OutOfOrder { last: u64, timestamp: u64 },
+    /// A configuration value is unusable
+    InvalidConfig { field: &'static str },
+    /// The reputation was built under a different scoring model
+    ModelMismatch,
+    /// No source has the requested name
+    UnknownSource,
+    /// Replaying the audit log disagrees with the stored entry
+    AuditMismatch { sequence: u64 },
+}
+
+impl fmt::Display for ReputationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFinite { field } => write!(f, "{} is not a finite number", field),
+            Self::QualityOutOfRange { value } => write!(f, "quality {} is outside 0.0-1.0", value),
+            Self::OutOfOrder { last, timestamp } => {
+                write!(f, "event at {} is older than the last recorded event at {}", timestamp, last)
+            }
+            Self::InvalidConfig { field } => write!(f, "reputation config {} is invalid", field),
+            Self::ModelMismatch => write!(f, "reputation was built under a different scoring model"),
+            Self::UnknownSource => write!(f, "unknown reputation source (expected collaboration, sale or interaction)"),
+            Self::AuditMismatch { sequence } => write!(f, "audit log disagrees with replay at entry {}", sequence),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReputationError {}
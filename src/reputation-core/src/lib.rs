@@ -0,0 +1,31 @@
+//! # Reputation Core
+//!
+//! Creator and token reputation shared by the Rust client, the NEAR
+//! contracts and the Polkadot client.
+//!
+//! A [`Reputation`] is scored by a Bayesian Beta model or an Elo-style
+//! rating, chosen in [`ReputationConfig`]. Evidence fades with an
+//! exponential half-life, each [`Source`] of evidence carries its own
+//! weight, and every recorded event appends an [`AuditEntry`] that explains
+//! the change. [`Reputation::verify`] replays the log to check it matches
+//! the stored score.
+//!
+//! The crate is `no_std` (with `alloc`) with the default `std` feature
+//! turned off. Codec derives are behind the `serde`, `borsh` and `scale`
+//! features.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod audit;
+mod config;
+mod error;
+mod reputation;
+mod source;
+
+pub use audit::*;
+pub use config::*;
+pub use error::*;
+pub use reputation::*;
+pub use source::*;
@@ -0,0 +1,500 @@
+//! Reputation state and its audit log
+
+use crate::{AuditEntry, ReputationConfig, ReputationError, ReputationModel, Source, ELO_BASE_RATING, ELO_SCALE};
+use alloc::vec::Vec;
+
+/// Closest a score built by [`Reputation::with_score`] gets to 0.0 or 1.0
+/// under the Elo model, whose rating is unbounded at the ends
+const ELO_SCORE_LIMIT: f32 = 1e-4;
+
+/// Model-specific scoring state
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub enum Standing {
+    Beta { alpha: f32, beta: f32 },
+    Elo { rating: f32 },
+}
+
+impl Standing {
+    /// Starting point of `model`: the prior, or the base rating
+    pub fn initial(model: &ReputationModel) -> Self {
+        match *model {
+            ReputationModel::Beta { prior_alpha, prior_beta } => Standing::Beta {
+                alpha: prior_alpha,
+                beta: prior_beta,
+            },
+            ReputationModel::Elo { .. } => Standing::Elo { rating: ELO_BASE_RATING },
+        }
+    }
+
+    /// Standing that scores `score`, carrying as much evidence as the prior
+    pub fn with_score(model: &ReputationModel, score: f32) -> Self {
+        let score = score.clamp(0.0, 1.0);
+        match *model {
+            ReputationModel::Beta { prior_alpha, prior_beta } => {
+                let total = prior_alpha + prior_beta;
+                Standing::Beta {
+                    alpha: total * score,
+                    beta: total * (1.0 - score),
+                }
+            }
+            ReputationModel::Elo { .. } => {
+                let score = score.clamp(ELO_SCORE_LIMIT, 1.0 - ELO_SCORE_LIMIT);
+                Standing::Elo {
+                    rating: ELO_BASE_RATING + ELO_SCALE * libm::log10f(score / (1.0 - score)),
+                }
+            }
+        }
+    }
+
+    /// Score between 0.0 and 1.0
+    pub fn score(&self) -> f32 {
+        match *self {
+            Standing::Beta { alpha, beta } => {
+                if alpha + beta <= 0.0 {
+                    0.5
+                } else {
+                    alpha / (alpha + beta)
+                }
+            }
+            Standing::Elo { rating } => 1.0 / (1.0 + libm::powf(10.0, (ELO_BASE_RATING - rating) / ELO_SCALE)),
+        }
+    }
+
+    fn matches(&self, model: &ReputationModel) -> bool {
+        matches!(
+            (self, model),
+            (Standing::Beta { .. }, ReputationModel::Beta { .. }) | (Standing::Elo { .. }, ReputationModel::Elo { .. })
+        )
+    }
+
+    /// Fade evidence towards the starting point, keeping `share` of it
+    fn decayed(&self, model: &ReputationModel, share: f32) -> Self {
+        match (*self, *model) {
+            (Standing::Beta { alpha, beta }, ReputationModel::Beta { prior_alpha, prior_beta }) => Standing::Beta {
+                alpha: prior_alpha + (alpha - prior_alpha) * share,
+                beta: prior_beta + (beta - prior_beta) * share,
+            },
+            (Standing::Elo { rating }, _) => Standing::Elo {
+                rating: ELO_BASE_RATING + (rating - ELO_BASE_RATING) * share,
+            },
+            (standing, _) => standing,
+        }
+    }
+
+    fn apply(&self, model: &ReputationModel, quality: f32, weight: f32) -> Self {
+        match (*self, *model) {
+            (Standing::Beta { alpha, beta }, _) => Standing::Beta {
+                alpha: alpha + weight * quality,
+                beta: beta + weight * (1.0 - quality),
+            },
+            (Standing::Elo { rating }, ReputationModel::Elo { k_factor }) => Standing::Elo {
+                rating: rating + k_factor * weight * (quality - self.score()),
+            },
+            (standing, _) => standing,
+        }
+    }
+}
+
+/// Something that happened to a creator or token
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub struct ReputationEvent {
+    pub source: Source,
+    /// 0.0 (bad) to 1.0 (good)
+    pub quality: f32,
+    /// Unix seconds
+    pub timestamp: u64,
+}
+
+impl ReputationEvent {
+    pub fn new(source: Source, quality: f32, timestamp: u64) -> Self {
+        Self {
+            source,
+            quality,
+            timestamp,
+        }
+    }
+
+    fn validate(&self) -> Result<(), ReputationError> {
+        if !self.quality.is_finite() {
+            return Err(ReputationError::NotFinite { field: "quality" });
+        }
+        if !(0.0..=1.0).contains(&self.quality) {
+            return Err(ReputationError::QualityOutOfRange { value: self.quality });
+        }
+        Ok(())
+    }
+}
+
+/// Reputation of one creator or token, with the log of how it got there
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub struct Reputation {
+    initial: Standing,
+    standing: Standing,
+    /// Time the initial standing was set, if it was
+    created_at: Option<u64>,
+    /// Events folded into the initial standing by [`Reputation::compact`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    compacted: u64,
+    log: Vec<AuditEntry>,
+}
+
+impl Default for Reputation {
+    fn default() -> Self {
+        Self::new(&ReputationConfig::default())
+    }
+}
+
+impl Reputation {
+    /// Reputation at the model's starting point, with no history
+    pub fn new(config: &ReputationConfig) -> Self {
+        let initial = Standing::initial(&config.model);
+        Self {
+            initial,
+            standing: initial,
+            created_at: None,
+            compacted: 0,
+            log: Vec::new(),
+        }
+    }
+
+    /// Reputation seeded with `score` at `timestamp`, for carrying over a
+    /// score kept elsewhere; the seed decays like any other evidence
+    pub fn with_score(config: &ReputationConfig, score: f32, timestamp: u64) -> Self {
+        let initial = Standing::with_score(&config.model, score);
+        Self {
+            initial,
+            standing: initial,
+            created_at: Some(timestamp),
+            compacted: 0,
+            log: Vec::new(),
+        }
+    }
+
+    /// Score as of the last event
+    pub fn score(&self) -> f32 {
+        self.standing.score()
+    }
+
+    /// Score at `now`, with decay since the last event applied
+    pub fn score_at(&self, config: &ReputationConfig, now: u64) -> f32 {
+        match self.updated_at() {
+            Some(last) if now > last && self.standing.matches(&config.model) => {
+                self.standing.decayed(&config.model, config.decay(now - last)).score()
+            }
+            _ => self.score(),
+        }
+    }
+
+    pub fn standing(&self) -> &Standing {
+        &self.standing
+    }
+
+    /// Number of recorded events, including compacted ones
+    pub fn interactions(&self) -> u64 {
+        self.compacted + self.log.len() as u64
+    }
+
+    /// Number of events dropped from the audit log by [`Reputation::compact`]
+    pub fn compacted(&self) -> u64 {
+        self.compacted
+    }
+
+    /// Time of the last event, or of the seed score
+    pub fn updated_at(&self) -> Option<u64> {
+        self.log.last().map(|entry| entry.timestamp).or(self.created_at)
+    }
+
+    /// Recorded events still in the log, oldest first
+    pub fn audit_log(&self) -> &[AuditEntry] {
+        &self.log
+    }
+
+    /// Decay to the event's time, apply the event and log the change
+    pub fn record(&mut self, config: &ReputationConfig, event: ReputationEvent) -> Result<AuditEntry, ReputationError> {
+        config.validate()?;
+        event.validate()?;
+        if !self.standing.matches(&config.model) {
+            return Err(ReputationError::ModelMismatch);
+        }
+        if let Some(last) = self.updated_at() {
+            if event.timestamp < last {
+                return Err(ReputationError::OutOfOrder {
+                    last,
+                    timestamp: event.timestamp,
+                });
+            }
+        }
+
+        let weight = config.weights.weight(event.source);
+        let decay = self.updated_at().map_or(1.0, |last| config.decay(event.timestamp - last));
+        let (standing, entry) = step(config, &self.standing, &event, weight, decay, self.interactions());
+        self.standing = standing;
+        self.log.push(entry);
+        Ok(entry)
+    }
+
+    /// Replay the audit log from the initial standing and check every entry
+    /// and the current standing against it
+    pub fn verify(&self, config: &ReputationConfig) -> Result<(), ReputationError> {
+        self.replay(config, self.log.len()).and_then(|standing| {
+            if standing != self.standing {
+                return Err(ReputationError::AuditMismatch {
+                    sequence: self.interactions(),
+                });
+            }
+            Ok(())
+        })
+    }
+
+    /// Keep only the last `keep` audit entries, folding older ones into the
+    /// initial standing so that scores stay put and the rest of the log still
+    /// verifies. The log is checked before anything is dropped.
+    pub fn compact(&mut self, config: &ReputationConfig, keep: usize) -> Result<(), ReputationError> {
+        let drop = self.log.len().saturating_sub(keep);
+        if drop == 0 {
+            return Ok(());
+        }
+        self.verify(config)?;
+        self.initial = self.replay(config, drop)?;
+        self.created_at = Some(self.log[drop - 1].timestamp);
+        self.compacted += drop as u64;
+        self.log.drain(..drop);
+        Ok(())
+    }
+
+    /// Standing after the first `count` logged entries, checking each one
+    fn replay(&self, config: &ReputationConfig, count: usize) -> Result<Standing, ReputationError> {
+        if !self.initial.matches(&config.model) {
+            return Err(ReputationError::ModelMismatch);
+        }
+
+        let mut standing = self.initial;
+        for (index, logged) in self.log[..count].iter().enumerate() {
+            let sequence = self.compacted + index as u64;
+            let event = ReputationEvent::new(logged.source, logged.quality, logged.timestamp);
+            if !(0.0..=1.0).contains(&logged.decay) {
+                return Err(ReputationError::AuditMismatch { sequence });
+            }
+            // Weights and the half-life may have changed since; the logged
+            // weight and decay are what applied
+            let (next, entry) = step(config, &standing, &event, logged.weight, logged.decay, sequence);
+            if entry != *logged {
+                return Err(ReputationError::AuditMismatch { sequence });
+            }
+            standing = next;
+        }
+        Ok(standing)
+    }
+}
+
+fn step(
+    config: &ReputationConfig,
+    standing: &Standing,
+    event: &ReputationEvent,
+    weight: f32,
+    decay: f32,
+    sequence: u64,
+) -> (Standing, AuditEntry) {
+    let decayed = standing.decayed(&config.model, decay);
+    let next = decayed.apply(&config.model, event.quality, weight);
+
+    let entry = AuditEntry {
+        sequence,
+        timestamp: event.timestamp,
+        source: event.source,
+        quality: event.quality,
+        weight,
+        decay,
+        score_before: standing.score(),
+        score_decayed: decayed.score(),
+        score_after: next.score(),
+    };
+    (next, entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_beta_scoring() {
+        let config = ReputationConfig::default();
+        let mut reputation = Reputation::new(&config);
+        assert_eq!(reputation.score(), 0.5);
+
+        for day in 0..10 {
+            reputation.record(&config, ReputationEvent::new(Source::Interaction, 1.0, day * 60)).unwrap();
+        }
+        assert!(reputation.score() > 0.9);
+        assert_eq!(reputation.interactions(), 10);
+
+        let entry = reputation.record(&config, ReputationEvent::new(Source::Sale, 0.0, 600)).unwrap();
+        assert!(entry.event_change() < 0.0);
+        assert_eq!(entry.weight, config.weights.sale);
+    }
+
+    #[test]
+    fn test_decay_returns_to_prior() {
+        let config = ReputationConfig {
+            half_life_secs: 30 * DAY,
+            ..ReputationConfig::default()
+        };
+        let mut reputation = Reputation::new(&config);
+        for _ in 0..5 {
+            reputation.record(&config, ReputationEvent::new(Source::Sale, 1.0, 0)).unwrap();
+        }
+        let fresh = reputation.score();
+        let month = reputation.score_at(&config, 30 * DAY);
+        let year = reputation.score_at(&config, 365 * DAY);
+        assert!(fresh > month && month > year);
+        assert!((year - 0.5).abs() < 0.01);
+
+        // Half the evidence is left after one half-life
+        let entry = reputation.record(&config, ReputationEvent::new(Source::Interaction, 0.5, 30 * DAY)).unwrap();
+        assert!((entry.decay - 0.5).abs() < 1e-6);
+        assert_eq!(entry.score_decayed, month);
+    }
+
+    #[test]
+    fn test_source_weights() {
+        let config = ReputationConfig::default();
+        let rate = |source| {
+            let mut reputation = Reputation::new(&config);
+            reputation.record(&config, ReputationEvent::new(source, 1.0, 0)).unwrap();
+            reputation.score()
+        };
+        assert!(rate(Source::Sale) > rate(Source::Collaboration));
+        assert!(rate(Source::Collaboration) > rate(Source::Interaction));
+        assert_eq!("Sale".parse::<Source>(), Ok(Source::Sale));
+        assert_eq!("tip".parse::<Source>(), Err(ReputationError::UnknownSource));
+    }
+
+    #[test]
+    fn test_elo_scoring() {
+        let config = ReputationConfig::elo(32.0);
+        let mut reputation = Reputation::with_score(&config, 0.8, 0);
+        assert!((reputation.score() - 0.8).abs() < 1e-4);
+
+        reputation.record(&config, ReputationEvent::new(Source::Collaboration, 0.0, 10)).unwrap();
+        assert!(reputation.score() < 0.8);
+        assert_eq!(
+            reputation.record(&ReputationConfig::default(), ReputationEvent::new(Source::Sale, 1.0, 20)),
+            Err(ReputationError::ModelMismatch)
+        );
+    }
+
+    #[test]
+    fn test_rejected_events() {
+        let config = ReputationConfig::default();
+        let mut reputation = Reputation::new(&config);
+        reputation.record(&config, ReputationEvent::new(Source::Sale, 1.0, 100)).unwrap();
+        assert_eq!(
+            reputation.record(&config, ReputationEvent::new(Source::Sale, 1.0, 99)),
+            Err(ReputationError::OutOfOrder { last: 100, timestamp: 99 })
+        );
+        assert_eq!(
+            reputation.record(&config, ReputationEvent::new(Source::Sale, 1.5, 100)),
+            Err(ReputationError::QualityOutOfRange { value: 1.5 })
+        );
+        assert_eq!(reputation.interactions(), 1);
+    }
+
+    #[test]
+    fn test_audit_log_replays() {
+        let mut config = ReputationConfig::default();
+        let mut reputation = Reputation::with_score(&config, 0.3, 0);
+        for (i, quality) in [0.9, 0.2, 1.0, 0.7].into_iter().enumerate() {
+            let source = Source::ALL[i % 3];
+            reputation.record(&config, ReputationEvent::new(source, quality, i as u64 * DAY)).unwrap();
+        }
+        assert_eq!(reputation.audit_log().len(), 4);
+        assert_eq!(reputation.audit_log()[3].score_after, reputation.score());
+
+        // Reweighting later does not invalidate history
+        config.weights.sale = 10.0;
+        assert_eq!(reputation.verify(&config), Ok(()));
+
+        let mut tampered = reputation.clone();
+        tampered.log[1].quality = 1.0;
+        assert_eq!(tampered.verify(&config), Err(ReputationError::AuditMismatch { sequence: 1 }));
+
+        let mut tampered = reputation.clone();
+        tampered.log[2].decay = 1.5;
+        assert_eq!(tampered.verify(&config), Err(ReputationError::AuditMismatch { sequence: 2 }));
+    }
+
+    #[test]
+    fn test_audit_log_survives_half_life_change() {
+        let mut config = ReputationConfig {
+            half_life_secs: 30 * DAY,
+            ..ReputationConfig::default()
+        };
+        let mut reputation = Reputation::new(&config);
+        for day in [0, 3, 10, 40] {
+            reputation.record(&config, ReputationEvent::new(Source::Sale, 0.9, day * DAY)).unwrap();
+        }
+        assert!(reputation.audit_log()[3].decay < 1.0);
+
+        // The decay that applied is logged, so a new half-life keeps history valid
+        config.half_life_secs = 7 * DAY;
+        assert_eq!(reputation.verify(&config), Ok(()));
+        reputation.record(&config, ReputationEvent::new(Source::Sale, 0.1, 50 * DAY)).unwrap();
+        assert_eq!(reputation.verify(&config), Ok(()));
+    }
+
+    #[test]
+    fn test_compaction_keeps_score_and_sequence() {
+        let config = ReputationConfig::default();
+        let mut reputation = Reputation::with_score(&config, 0.4, 0);
+        for day in 0..10 {
+            let quality = if day % 3 == 0 { 0.2 } else { 0.9 };
+            reputation.record(&config, ReputationEvent::new(Source::Sale, quality, day * DAY)).unwrap();
+        }
+        let full = reputation.clone();
+
+        reputation.compact(&config, 4).unwrap();
+        assert_eq!(reputation.audit_log(), &full.audit_log()[6..]);
+        assert_eq!(reputation.compacted(), 6);
+        assert_eq!(reputation.interactions(), 10);
+        assert_eq!(reputation.score_at(&config, 20 * DAY), full.score_at(&config, 20 * DAY));
+        assert_eq!(reputation.verify(&config), Ok(()));
+
+        let entry = reputation.record(&config, ReputationEvent::new(Source::Interaction, 0.5, 10 * DAY)).unwrap();
+        assert_eq!(entry.sequence, 10);
+        assert_eq!(reputation.verify(&config), Ok(()));
+
+        // Everything can go, leaving the time of the last event
+        reputation.compact(&config, 0).unwrap();
+        assert!(reputation.audit_log().is_empty());
+        assert_eq!(reputation.updated_at(), Some(10 * DAY));
+        assert_eq!(reputation.score(), entry.score_after);
+
+        // A tampered log is not folded in
+        let mut tampered = full.clone();
+        tampered.log[2].quality = 1.0;
+        assert_eq!(tampered.compact(&config, 4), Err(ReputationError::AuditMismatch { sequence: 2 }));
+        assert_eq!(tampered.audit_log().len(), 10);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let config = ReputationConfig::default();
+        let mut reputation = Reputation::new(&config);
+        reputation.record(&config, ReputationEvent::new(Source::Sale, 0.8, 5)).unwrap();
+
+        let json = serde_json::to_string(&reputation).unwrap();
+        let restored: Reputation = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, reputation);
+        assert_eq!(restored.verify(&config), Ok(()));
+    }
+}
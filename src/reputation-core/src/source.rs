@@ -0,0 +1,91 @@
+//! Kinds of reputation evidence
+
+use crate::ReputationError;
+use core::fmt;
+use core::str::FromStr;
+
+/// Where a piece of reputation evidence came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+#[cfg_attr(feature = "scale", derive(parity_scale_codec::Encode, parity_scale_codec::Decode))]
+pub enum Source {
+    /// Rating from a collaborator
+    Collaboration,
+    /// A completed sale, scored by how it went
+    Sale,
+    /// Any other interaction with the creator or token
+    Interaction,
+}
+
+impl Source {
+    pub const ALL: [Source; 3] = [Source::Collaboration, Source::Sale, Source::Interaction];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Collaboration => "collaboration",
+            Source::Sale => "sale",
+            Source::Interaction => "interaction",
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Source {
+    type Err = ReputationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Source::ALL
+            .into_iter()
+            .find(|source| source.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or(ReputationError::UnknownSource)
+    }
+}
+
+/// How much one event from each source counts
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub struct SourceWeights {
+    pub collaboration: f32,
+    pub sale: f32,
+    pub interaction: f32,
+}
+
+impl Default for SourceWeights {
+    /// A sale outweighs a collaboration rating, which outweighs a casual
+    /// interaction
+    fn default() -> Self {
+        Self {
+            collaboration: 2.0,
+            sale: 3.0,
+            interaction: 1.0,
+        }
+    }
+}
+
+impl SourceWeights {
+    pub fn weight(&self, source: Source) -> f32 {
+        match source {
+            Source::Collaboration => self.collaboration,
+            Source::Sale => self.sale,
+            Source::Interaction => self.interaction,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), ReputationError> {
+        for source in Source::ALL {
+            let weight = self.weight(source);
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(ReputationError::InvalidConfig { field: "weights" });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,566 @@
+//! Frame-time statistics and adaptive render quality.
+//!
+//! [`FrameStats`] keeps a histogram of every frame time, percentiles over a
+//! rolling window and a count of dropped frames, i.e. display refreshes a
+//! slow frame missed. Beside the frame interval it keeps the render cost of
+//! each frame, since intervals are locked to the display's refresh and never
+//! show how much faster a frame could have been.
+//!
+//! [`AdaptiveQuality`] watches the p95 interval against the frame budget and
+//! the p95 render cost against the headroom: over budget it gives up
+//! supersampling, then fractal iterations, then resolution; with headroom it
+//! restores them in reverse. Raising needs several healthy checks in a row and
+//! lowering only one, so quality does not flap around the budget.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+pub use session_core::{FrameTimePercentiles, PerformanceMetrics};
+
+/// Frames the percentiles are taken over
+pub const DEFAULT_STATS_WINDOW: usize = 240;
+
+/// Width of a histogram bucket
+pub const HISTOGRAM_BUCKET_MS: f32 = 2.0;
+
+/// Buckets before the overflow bucket, covering 0-100 ms
+pub const HISTOGRAM_BUCKETS: usize = 50;
+
+/// Share of a budget a frame may run over, as timer jitter, before it counts as
+/// a missed refresh
+pub const DROP_TOLERANCE: f32 = 0.05;
+
+/// A frame budget that is zero, negative or not finite
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InvalidBudget(pub f32);
+
+impl fmt::Display for InvalidBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "frame budget must be a positive number of milliseconds, got {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidBudget {}
+
+/// Counts of frame times in fixed-width buckets, the last one open-ended
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FrameTimeHistogram {
+    pub bucket_ms: f32,
+    pub counts: Vec<u64>,
+}
+
+impl Default for FrameTimeHistogram {
+    fn default() -> Self {
+        FrameTimeHistogram { bucket_ms: HISTOGRAM_BUCKET_MS, counts: vec![0; HISTOGRAM_BUCKETS + 1] }
+    }
+}
+
+impl FrameTimeHistogram {
+    pub fn record(&mut self, frame_ms: f32) {
+        let last = self.counts.len() - 1;
+        let bucket = ((frame_ms.max(0.0) / self.bucket_ms) as usize).min(last);
+        self.counts[bucket] += 1;
+    }
+
+    /// `(start_ms, end_ms, count)` of each bucket, without an end for the last
+    pub fn buckets(&self) -> impl Iterator<Item = (f32, Option<f32>, u64)> + '_ {
+        let last = self.counts.len() - 1;
+        self.counts.iter().enumerate().map(move |(i, count)| {
+            let start = i as f32 * self.bucket_ms;
+            (start, (i < last).then_some(start + self.bucket_ms), *count)
+        })
+    }
+}
+
+/// Frame times of a render loop against a frame budget
+#[derive(Clone, Debug)]
+pub struct FrameStats {
+    budget_ms: f32,
+    window: VecDeque<f32>,
+    /// Render cost of the frames, when the loop measures it
+    render_window: VecDeque<f32>,
+    window_size: usize,
+    histogram: FrameTimeHistogram,
+    total_frames: u64,
+    dropped_frames: u64,
+    total_ms: f64,
+    min_ms: Option<f32>,
+    render_frames: u64,
+    render_total_ms: f64,
+}
+
+impl Default for FrameStats {
+    /// Stats for a 60 Hz display
+    fn default() -> Self {
+        Self::for_fps(60.0).expect("60 fps is a valid budget")
+    }
+}
+
+impl FrameStats {
+    pub fn new(budget_ms: f32) -> Result<Self, InvalidBudget> {
+        if !(budget_ms.is_finite() && budget_ms > 0.0) {
+            return Err(InvalidBudget(budget_ms));
+        }
+        Ok(FrameStats {
+            budget_ms,
+            window: VecDeque::with_capacity(DEFAULT_STATS_WINDOW),
+            render_window: VecDeque::with_capacity(DEFAULT_STATS_WINDOW),
+            window_size: DEFAULT_STATS_WINDOW,
+            histogram: FrameTimeHistogram::default(),
+            total_frames: 0,
+            dropped_frames: 0,
+            total_ms: 0.0,
+            min_ms: None,
+            render_frames: 0,
+            render_total_ms: 0.0,
+        })
+    }
+
+    /// Stats for a display refreshing `target_fps` times a second
+    pub fn for_fps(target_fps: f32) -> Result<Self, InvalidBudget> {
+        Self::new(1000.0 / target_fps)
+    }
+
+    pub fn with_window(mut self, frames: usize) -> Self {
+        self.window_size = frames.max(1);
+        self.window.truncate(self.window_size);
+        self.render_window.truncate(self.window_size);
+        self
+    }
+
+    pub fn budget_ms(&self) -> f32 {
+        self.budget_ms
+    }
+
+    /// Add the interval since the last frame and return how many refreshes it
+    /// made the display miss
+    pub fn record(&mut self, frame_ms: f32) -> u64 {
+        let frame_ms = frame_ms.max(0.0);
+        push_bounded(&mut self.window, self.window_size, frame_ms);
+        self.histogram.record(frame_ms);
+        self.total_frames += 1;
+        self.total_ms += frame_ms as f64;
+        self.min_ms = Some(self.min_ms.map_or(frame_ms, |min| min.min(frame_ms)));
+
+        let dropped = ((frame_ms / self.budget_ms - DROP_TOLERANCE).ceil() as u64).saturating_sub(1);
+        self.dropped_frames = self.dropped_frames.saturating_add(dropped);
+        dropped
+    }
+
+    /// Add the time a frame took to render, apart from waiting for the display
+    pub fn record_render(&mut self, render_ms: f32) {
+        let render_ms = render_ms.max(0.0);
+        push_bounded(&mut self.render_window, self.window_size, render_ms);
+        self.render_frames += 1;
+        self.render_total_ms += render_ms as f64;
+    }
+
+    /// Forget the windows, e.g. after a quality change made them stale
+    pub fn clear_window(&mut self) {
+        self.window.clear();
+        self.render_window.clear();
+    }
+
+    /// Frames in the window
+    pub fn window_len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Nearest-rank percentile of the window's frame times
+    pub fn percentile(&self, p: f32) -> Option<f32> {
+        nearest_rank(&self.window, p)
+    }
+
+    /// Nearest-rank percentile of the window's render costs
+    pub fn render_percentile(&self, p: f32) -> Option<f32> {
+        nearest_rank(&self.render_window, p)
+    }
+
+    pub fn percentiles(&self) -> Option<FrameTimePercentiles> {
+        Some(FrameTimePercentiles {
+            p50_ms: self.percentile(50.0)?,
+            p95_ms: self.percentile(95.0)?,
+            p99_ms: self.percentile(99.0)?,
+        })
+    }
+
+    pub fn histogram(&self) -> &FrameTimeHistogram {
+        &self.histogram
+    }
+
+    pub fn total_frames(&self) -> u64 {
+        self.total_frames
+    }
+
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames
+    }
+
+    /// Mean frame time since the first frame
+    pub fn average_ms(&self) -> f32 {
+        match self.total_frames {
+            0 => 0.0,
+            n => (self.total_ms / n as f64) as f32,
+        }
+    }
+
+    /// Mean render cost since the first measured frame
+    pub fn average_render_ms(&self) -> f32 {
+        match self.render_frames {
+            0 => 0.0,
+            n => (self.render_total_ms / n as f64) as f32,
+        }
+    }
+
+    pub fn average_fps(&self) -> f32 {
+        fps(self.average_ms())
+    }
+
+    /// Rate of the fastest frame
+    pub fn peak_fps(&self) -> f32 {
+        self.min_ms.map_or(0.0, fps)
+    }
+
+    /// The stats as the `PerformanceMetrics` of a session bundle
+    pub fn performance_metrics(&self) -> PerformanceMetrics {
+        PerformanceMetrics {
+            avg_fps: self.average_fps(),
+            peak_fps: self.peak_fps(),
+            total_frames: self.total_frames,
+            dropped_frames: self.dropped_frames.min(u32::MAX as u64) as u32,
+            avg_render_time_ms: self.average_render_ms(),
+            frame_times: self.percentiles(),
+        }
+    }
+}
+
+fn push_bounded(window: &mut VecDeque<f32>, size: usize, value: f32) {
+    if window.len() == size {
+        window.pop_front();
+    }
+    window.push_back(value);
+}
+
+fn nearest_rank(window: &VecDeque<f32>, p: f32) -> Option<f32> {
+    let mut sorted: Vec<f32> = window.iter().copied().collect();
+    sorted.sort_by(f32::total_cmp);
+    let rank = ((p.clamp(0.0, 100.0) / 100.0) * sorted.len() as f32).ceil() as usize;
+    sorted.get(rank.max(1) - 1).copied()
+}
+
+fn fps(frame_ms: f32) -> f32 {
+    if frame_ms > 0.0 {
+        1000.0 / frame_ms
+    } else {
+        0.0
+    }
+}
+
+/// Settings traded for frame time
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QualityLevel {
+    /// Share of the requested `u_max_iter` to render with
+    pub iteration_scale: f32,
+    /// Drawing buffer size relative to the canvas, before supersampling
+    pub resolution_scale: f32,
+    /// Samples per pixel along each axis
+    pub supersampling: u32,
+}
+
+impl QualityLevel {
+    pub const FULL: QualityLevel = QualityLevel { iteration_scale: 1.0, resolution_scale: 1.0, supersampling: 1 };
+
+    /// Drawing buffer size relative to the canvas
+    pub fn render_scale(&self) -> f32 {
+        self.resolution_scale * self.supersampling as f32
+    }
+
+    /// Iterations to render for a requested `u_max_iter`
+    pub fn max_iter(&self, requested: i32) -> i32 {
+        ((requested as f32 * self.iteration_scale).round() as i32).max(1)
+    }
+}
+
+/// How far [`AdaptiveQuality`] may go each way
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QualityLimits {
+    pub min: QualityLevel,
+    pub max: QualityLevel,
+}
+
+impl Default for QualityLimits {
+    fn default() -> Self {
+        QualityLimits {
+            min: QualityLevel { iteration_scale: 0.25, resolution_scale: 0.5, supersampling: 1 },
+            max: QualityLevel { supersampling: 2, ..QualityLevel::FULL },
+        }
+    }
+}
+
+/// Factor iterations are cut by per step
+const ITERATION_STEP: f32 = 0.75;
+
+/// Resolution scale change per step
+const RESOLUTION_STEP: f32 = 0.125;
+
+const EPSILON: f32 = 1e-4;
+
+/// Lowers quality while frames run over budget and raises it with headroom
+#[derive(Clone, Debug)]
+pub struct AdaptiveQuality {
+    level: QualityLevel,
+    limits: QualityLimits,
+    /// Frames between checks
+    check_interval: u32,
+    /// p95 interval above budget × (1 + tolerance) lowers quality
+    tolerance: f32,
+    /// p95 render cost below budget × headroom counts towards raising it
+    headroom: f32,
+    /// Healthy checks in a row needed to raise quality
+    raise_after: u32,
+    /// Multiplier on `raise_after`, doubled each time a raise is reverted at
+    /// the next check
+    raise_backoff: u32,
+    /// Whether the last change was a raise no check has confirmed yet
+    unconfirmed_raise: bool,
+    frames_since_check: u32,
+    healthy_checks: u32,
+}
+
+/// Largest `raise_backoff`
+const MAX_RAISE_BACKOFF: u32 = 64;
+
+impl Default for AdaptiveQuality {
+    fn default() -> Self {
+        Self::new(QualityLevel::FULL, QualityLimits::default())
+    }
+}
+
+impl AdaptiveQuality {
+    pub fn new(level: QualityLevel, limits: QualityLimits) -> Self {
+        AdaptiveQuality {
+            level,
+            limits,
+            check_interval: 60,
+            tolerance: 0.1,
+            headroom: 0.7,
+            raise_after: 3,
+            raise_backoff: 1,
+            unconfirmed_raise: false,
+            frames_since_check: 0,
+            healthy_checks: 0,
+        }
+    }
+
+    pub fn with_check_interval(mut self, frames: u32) -> Self {
+        self.check_interval = frames.max(1);
+        self
+    }
+
+    pub fn with_thresholds(mut self, tolerance: f32, headroom: f32) -> Self {
+        self.tolerance = tolerance;
+        self.headroom = headroom;
+        self
+    }
+
+    pub fn with_raise_after(mut self, checks: u32) -> Self {
+        self.raise_after = checks.max(1);
+        self
+    }
+
+    pub fn level(&self) -> QualityLevel {
+        self.level
+    }
+
+    pub fn limits(&self) -> &QualityLimits {
+        &self.limits
+    }
+
+    /// Call once per recorded frame; returns the new level when it changes.
+    /// Headroom is judged on render costs, or on intervals when none were
+    /// recorded. Costs that miss work, e.g. CPU time of asynchronous GPU
+    /// calls, make every level look cheap; a raise that goes over budget at
+    /// once doubles the healthy checks the next raise needs, so such a loop
+    /// settles instead of flapping. The windows of `stats` are cleared on a
+    /// change, since their frames were rendered at the old level.
+    pub fn update(&mut self, stats: &mut FrameStats) -> Option<QualityLevel> {
+        self.frames_since_check += 1;
+        if self.frames_since_check < self.check_interval {
+            return None;
+        }
+        self.frames_since_check = 0;
+        let p95 = stats.percentile(95.0)?;
+        let cost = stats.render_percentile(95.0).unwrap_or(p95);
+        let budget = stats.budget_ms();
+
+        let over_budget = p95 > budget * (1.0 + self.tolerance);
+        if std::mem::take(&mut self.unconfirmed_raise) && over_budget {
+            self.raise_backoff = (self.raise_backoff * 2).min(MAX_RAISE_BACKOFF);
+        }
+        let changed = if over_budget {
+            self.healthy_checks = 0;
+            self.lower()
+        } else if cost < budget * self.headroom {
+            self.healthy_checks += 1;
+            let raised = self.healthy_checks >= self.raise_after * self.raise_backoff && self.raise();
+            self.unconfirmed_raise = raised;
+            raised
+        } else {
+            self.healthy_checks = 0;
+            false
+        };
+        if !changed {
+            return None;
+        }
+        self.healthy_checks = 0;
+        stats.clear_window();
+        Some(self.level)
+    }
+
+    /// Step down: supersampling, then iterations, then resolution
+    fn lower(&mut self) -> bool {
+        let (level, min) = (&mut self.level, &self.limits.min);
+        if level.supersampling > min.supersampling {
+            level.supersampling = (level.supersampling / 2).max(min.supersampling);
+        } else if level.iteration_scale > min.iteration_scale + EPSILON {
+            level.iteration_scale = (level.iteration_scale * ITERATION_STEP).max(min.iteration_scale);
+        } else if level.resolution_scale > min.resolution_scale + EPSILON {
+            level.resolution_scale = (level.resolution_scale - RESOLUTION_STEP).max(min.resolution_scale);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Step up in the reverse order of [`lower`](Self::lower)
+    fn raise(&mut self) -> bool {
+        let (level, max) = (&mut self.level, &self.limits.max);
+        if level.resolution_scale < max.resolution_scale - EPSILON {
+            level.resolution_scale = (level.resolution_scale + RESOLUTION_STEP).min(max.resolution_scale);
+        } else if level.iteration_scale < max.iteration_scale - EPSILON {
+            level.iteration_scale = (level.iteration_scale / ITERATION_STEP).min(max.iteration_scale);
+        } else if level.supersampling < max.supersampling {
+            level.supersampling = (level.supersampling * 2).min(max.supersampling);
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_track_percentiles_drops_and_histogram() {
+        let mut stats = FrameStats::default().with_window(100);
+        for i in 0..100 {
+            // 90 smooth frames, then 10 slow ones
+            stats.record(if i < 90 { 16.0 } else { 40.0 + i as f32 - 90.0 });
+        }
+        let percentiles = stats.percentiles().unwrap();
+        assert_eq!((percentiles.p50_ms, percentiles.p95_ms, percentiles.p99_ms), (16.0, 44.0, 48.0));
+        // At a 16.7 ms budget 40-49 ms frames miss two refreshes
+        assert_eq!(stats.dropped_frames(), 10 * 2);
+        assert_eq!(stats.record(5.0), 0);
+        assert_eq!(stats.window_len(), 100);
+
+        let buckets: Vec<_> = stats.histogram().buckets().filter(|(_, _, n)| *n > 0).collect();
+        assert_eq!(buckets[0], (4.0, Some(6.0), 1));
+        assert_eq!(buckets[1], (16.0, Some(18.0), 90));
+        assert_eq!(stats.record(250.0), 14);
+        assert_eq!(stats.histogram().buckets().last(), Some((100.0, None, 1)));
+        // Slightly late frames are jitter, but anything past that misses a refresh
+        assert_eq!(stats.record(17.2), 0);
+        assert_eq!(stats.record(24.0), 1);
+
+        let metrics = stats.performance_metrics();
+        assert_eq!((metrics.total_frames, metrics.avg_render_time_ms, metrics.peak_fps), (104, 0.0, 200.0));
+        assert_eq!(metrics.frame_times.map(|times| times.p50_ms), Some(16.0));
+        assert_eq!(FrameStats::new(16.0).unwrap().performance_metrics().frame_times, None);
+
+        stats.record_render(3.0);
+        stats.record_render(5.0);
+        assert_eq!(stats.render_percentile(50.0), Some(3.0));
+        assert_eq!(stats.performance_metrics().avg_render_time_ms, 4.0);
+    }
+
+    #[test]
+    fn test_quality_steps_down_under_load_and_back_up_with_headroom() {
+        let mut quality = AdaptiveQuality::new(QualityLimits::default().max, QualityLimits::default())
+            .with_check_interval(10)
+            .with_raise_after(2);
+        let mut stats = FrameStats::default().with_window(10);
+        let mut run = |quality: &mut AdaptiveQuality, frame_ms: f32, frames: usize| {
+            let mut levels = vec![];
+            for _ in 0..frames {
+                stats.record(frame_ms);
+                levels.extend(quality.update(&mut stats));
+            }
+            levels
+        };
+
+        let lowered = run(&mut quality, 30.0, 40);
+        assert_eq!(lowered.len(), 4);
+        assert_eq!(lowered[0].supersampling, 1);
+        assert_eq!(lowered[1].iteration_scale, 0.75);
+        assert_eq!(quality.level().max_iter(200), 84);
+        assert_eq!(quality.level().render_scale(), 1.0);
+
+        // In budget but without headroom: nothing changes
+        assert!(run(&mut quality, 15.0, 50).is_empty());
+
+        let raised = run(&mut quality, 5.0, 40);
+        assert_eq!(raised.len(), 2);
+        assert_eq!(raised[1].iteration_scale, 0.75);
+
+        let floor = run(&mut quality, 100.0, 200).pop().unwrap();
+        assert_eq!(floor, QualityLimits::default().min);
+        assert_eq!(floor.render_scale(), 0.5);
+    }
+
+    #[test]
+    fn test_quality_rises_on_render_cost_under_vsync() {
+        let mut quality = AdaptiveQuality::new(QualityLimits::default().min, QualityLimits::default());
+        let mut stats = FrameStats::default();
+        let mut changes = 0;
+        // Intervals sit at the refresh rate while each frame takes 5 ms to render
+        for _ in 0..10_000 {
+            stats.record(16.7);
+            stats.record_render(5.0);
+            changes += quality.update(&mut stats).is_some() as u32;
+        }
+        assert!(changes > 0);
+        assert_eq!(quality.level(), QualityLimits::default().max);
+        assert_eq!(stats.dropped_frames(), 0);
+    }
+
+    #[test]
+    fn test_quality_settles_when_render_costs_miss_the_gpu() {
+        // Without GPU timers only the CPU side of a frame is measured: every
+        // level costs nearly nothing, while supersampling misses the budget
+        let mut quality = AdaptiveQuality::new(QualityLimits::default().max, QualityLimits::default());
+        let mut stats = FrameStats::default();
+        let mut changes = 0;
+        for _ in 0..20_000 {
+            stats.record(if quality.level().supersampling > 1 { 33.4 } else { 16.7 });
+            stats.record_render(0.2);
+            changes += quality.update(&mut stats).is_some() as u32;
+        }
+        // Over 333 checks, raising after 3, 6, 12, ... 96 healthy ones rather
+        // than raising and reverting every 4
+        assert_eq!(changes, 13);
+        assert_eq!(quality.level().supersampling, 1);
+    }
+
+    #[test]
+    fn test_budgets_must_be_positive_and_finite() {
+        for budget in [0.0, -16.0, f32::NAN, f32::INFINITY] {
+            assert!(FrameStats::new(budget).is_err());
+        }
+        assert_eq!(FrameStats::for_fps(0.0).unwrap_err(), InvalidBudget(f32::INFINITY));
+        assert_eq!(FrameStats::for_fps(30.0).unwrap().budget_ms(), 1000.0 / 30.0);
+    }
+}
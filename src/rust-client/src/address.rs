@@ -0,0 +1,390 @@
+//! Per-chain address validation and normalization
+
+use crate::blockchain_integration::ChainType;
+use crate::chain_adapter::ChainError;
+use blake2::{Blake2b512, Digest};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use sha2::Sha256;
+use sha3::Keccak256;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// SS58 prefix of the Polkadot relay chain and Asset Hub
+pub const POLKADOT_SS58_PREFIX: u16 = 0;
+/// SS58 prefix of Kusama
+pub const KUSAMA_SS58_PREFIX: u16 = 2;
+/// SS58 prefix used by generic Substrate chains and test networks
+pub const SUBSTRATE_SS58_PREFIX: u16 = 42;
+
+/// Why an address was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressError {
+    pub chain: ChainType,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {} address: {}", self.chain.as_str(), self.reason)
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+impl From<AddressError> for ChainError {
+    fn from(e: AddressError) -> Self {
+        ChainError::InvalidRequest(e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Account {
+    /// Named NEAR account such as `alice.near`
+    Near,
+    /// Implicit NEAR account, the hex of an ed25519 public key
+    NearImplicit([u8; 32]),
+    Solana([u8; 32]),
+    Evm([u8; 20]),
+    Substrate { prefix: u16, account_id: [u8; 32] },
+}
+
+/// Address checked against its chain's rules and kept in canonical form:
+/// lowercase NEAR ids, EIP-55 checksummed EVM addresses, and SS58 with the
+/// network prefix it was pasted with.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChainAddress {
+    chain: ChainType,
+    account: Account,
+    text: String,
+}
+
+impl ChainAddress {
+    /// Validate `input` for `chain`, ignoring surrounding whitespace
+    pub fn parse(chain: ChainType, input: &str) -> Result<Self, AddressError> {
+        let input = input.trim();
+        let error = |reason| AddressError { chain, reason };
+        if input.is_empty() {
+            return Err(error("empty"));
+        }
+        let (account, text) = match chain {
+            ChainType::Near => parse_near(input).map_err(error)?,
+            ChainType::Solana => parse_solana(input).map_err(error)?,
+            ChainType::Ethereum | ChainType::Polygon => parse_evm(input).map_err(error)?,
+            ChainType::Polkadot => parse_substrate(input).map_err(error)?,
+        };
+        Ok(ChainAddress { chain, account, text })
+    }
+
+    pub fn chain(&self) -> ChainType {
+        self.chain
+    }
+
+    /// Canonical text form
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Raw account bytes; empty for named NEAR accounts
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.account {
+            Account::Near => &[],
+            Account::NearImplicit(key) | Account::Solana(key) => key,
+            Account::Evm(address) => address,
+            Account::Substrate { account_id, .. } => account_id,
+        }
+    }
+
+    /// 32-byte Substrate account id
+    pub fn account_id32(&self) -> Option<[u8; 32]> {
+        match self.account {
+            Account::Substrate { account_id, .. } => Some(account_id),
+            _ => None,
+        }
+    }
+
+    /// 20-byte EVM address
+    pub fn evm_address(&self) -> Option<[u8; 20]> {
+        match self.account {
+            Account::Evm(address) => Some(address),
+            _ => None,
+        }
+    }
+
+    /// The same Substrate account under another network's prefix
+    pub fn to_ss58(&self, prefix: u16) -> Option<String> {
+        match &self.account {
+            Account::Substrate { account_id, .. } => ss58_encode(prefix, account_id),
+            _ => None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl ChainAddress {
+    /// Chain name, e.g. "near"
+    #[wasm_bindgen(getter)]
+    pub fn chain_name(&self) -> String {
+        self.chain.as_str().to_string()
+    }
+
+    /// Canonical text form
+    #[wasm_bindgen(getter)]
+    pub fn normalized(&self) -> String {
+        self.text.clone()
+    }
+
+    /// Implicit NEAR account (hex public key) rather than a named one
+    #[wasm_bindgen(getter)]
+    pub fn is_implicit(&self) -> bool {
+        matches!(self.account, Account::NearImplicit(_))
+    }
+
+    /// SS58 network prefix of a Polkadot address
+    #[wasm_bindgen(getter)]
+    pub fn ss58_prefix(&self) -> Option<u16> {
+        match self.account {
+            Account::Substrate { prefix, .. } => Some(prefix),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ChainAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// NEAR account id rules: 2-64 chars, dot-separated parts of `a-z0-9`
+/// joined by single `-` or `_`. Uppercase ids are rejected, not rewritten.
+fn parse_near(account_id: &str) -> Result<(Account, String), &'static str> {
+    if account_id.len() < 2 || account_id.len() > 64 {
+        return Err("account ids are 2 to 64 characters");
+    }
+    for part in account_id.split('.') {
+        if part.is_empty() {
+            return Err("empty part between dots");
+        }
+        let mut previous_separator = true;
+        for c in part.chars() {
+            match c {
+                'a'..='z' | '0'..='9' => previous_separator = false,
+                'A'..='Z' => return Err("account ids are lowercase"),
+                '-' | '_' if !previous_separator => previous_separator = true,
+                '-' | '_' => return Err("separators must sit between letters or digits"),
+                _ => return Err("only a-z, 0-9, '-', '_' and '.' are allowed"),
+            }
+        }
+        if previous_separator {
+            return Err("separators must sit between letters or digits");
+        }
+    }
+    if account_id.len() == 64 && account_id.chars().all(|c| c.is_ascii_hexdigit()) {
+        let mut key = [0u8; 32];
+        hex::decode_to_slice(account_id, &mut key).map_err(|_| "bad implicit account")?;
+        return Ok((Account::NearImplicit(key), account_id.to_string()));
+    }
+    Ok((Account::Near, account_id.to_string()))
+}
+
+/// Base58 of an ed25519 public key; program-derived addresses are off the curve
+fn parse_solana(input: &str) -> Result<(Account, String), &'static str> {
+    let bytes = bs58::decode(input).into_vec().map_err(|_| "not base58")?;
+    let key: [u8; 32] = bytes.try_into().map_err(|_| "public keys are 32 bytes")?;
+    if CompressedEdwardsY(key).decompress().is_none() {
+        return Err("not on the ed25519 curve, so no wallet can sign for it");
+    }
+    Ok((Account::Solana(key), bs58::encode(key).into_string()))
+}
+
+/// Solana program-derived address: the first bump, counting down from 255,
+/// whose hash is off the ed25519 curve
+pub(crate) fn find_program_address(seeds: &[&[u8]], program_id: &[u8; 32]) -> Option<[u8; 32]> {
+    (0..=255u8).rev().find_map(|bump| {
+        let mut hasher = Sha256::new();
+        for seed in seeds {
+            hasher.update(seed);
+        }
+        hasher.update([bump]);
+        hasher.update(program_id);
+        hasher.update(b"ProgramDerivedAddress");
+        let address: [u8; 32] = hasher.finalize().into();
+        CompressedEdwardsY(address).decompress().is_none().then_some(address)
+    })
+}
+
+/// `0x` and 40 hex digits; mixed case must carry a valid EIP-55 checksum
+fn parse_evm(input: &str) -> Result<(Account, String), &'static str> {
+    let digits = input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")).ok_or("missing 0x prefix")?;
+    if digits.len() != 40 {
+        return Err("addresses are 40 hex digits");
+    }
+    let mut address = [0u8; 20];
+    hex::decode_to_slice(digits, &mut address).map_err(|_| "not hex")?;
+    let checksummed = eip55(&address);
+    let mixed_case = digits.chars().any(|c| c.is_ascii_lowercase()) && digits.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case && checksummed[2..] != *digits {
+        return Err("EIP-55 checksum mismatch");
+    }
+    Ok((Account::Evm(address), checksummed))
+}
+
+/// EIP-55 mixed-case checksum encoding
+fn eip55(address: &[u8; 20]) -> String {
+    let lower = hex::encode(address);
+    let hash = Keccak256::digest(lower.as_bytes());
+    let mut out = String::from("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+        out.push(if nibble >= 8 { c.to_ascii_uppercase() } else { c });
+    }
+    out
+}
+
+/// SS58, or `0x` and a 32-byte account id which is then written for Polkadot
+fn parse_substrate(input: &str) -> Result<(Account, String), &'static str> {
+    if let Some(digits) = input.strip_prefix("0x") {
+        let mut account_id = [0u8; 32];
+        hex::decode_to_slice(digits, &mut account_id).map_err(|_| "account ids are 32 bytes of hex")?;
+        let text = ss58_encode(POLKADOT_SS58_PREFIX, &account_id).expect("valid prefix");
+        return Ok((Account::Substrate { prefix: POLKADOT_SS58_PREFIX, account_id }, text));
+    }
+    let bytes = bs58::decode(input).into_vec().map_err(|_| "not base58")?;
+    let (prefix, prefix_len) = match bytes.first() {
+        Some(&b) if b < 64 => (b as u16, 1),
+        Some(&b) if b < 128 && bytes.len() > 1 => {
+            let lower = (b << 2) | (bytes[1] >> 6);
+            let upper = bytes[1] & 0b0011_1111;
+            (lower as u16 | (upper as u16) << 8, 2)
+        }
+        _ => return Err("unknown SS58 prefix format"),
+    };
+    if bytes.len() != prefix_len + 32 + 2 {
+        return Err("only 32-byte account ids are supported");
+    }
+    let (body, checksum) = bytes.split_at(prefix_len + 32);
+    if ss58_checksum(body) != checksum {
+        return Err("SS58 checksum mismatch");
+    }
+    let account_id: [u8; 32] = body[prefix_len..].try_into().expect("32 bytes");
+    let text = ss58_encode(prefix, &account_id).ok_or("reserved SS58 prefix")?;
+    Ok((Account::Substrate { prefix, account_id }, text))
+}
+
+fn ss58_checksum(body: &[u8]) -> [u8; 2] {
+    let mut hasher = Blake2b512::new();
+    hasher.update(b"SS58PRE");
+    hasher.update(body);
+    let hash = hasher.finalize();
+    [hash[0], hash[1]]
+}
+
+/// SS58 text for `account_id` under `prefix`; `None` for reserved or out-of-range prefixes
+fn ss58_encode(prefix: u16, account_id: &[u8; 32]) -> Option<String> {
+    let mut body = match prefix {
+        46 | 47 => return None,
+        0..=63 => vec![prefix as u8],
+        64..=16_383 => {
+            let first = ((prefix & 0b0000_0000_1111_1100) >> 2) as u8;
+            let second = ((prefix >> 8) as u8) | (((prefix & 0b0000_0000_0000_0011) as u8) << 6);
+            vec![first | 0b0100_0000, second]
+        }
+        _ => return None,
+    };
+    body.extend_from_slice(account_id);
+    let checksum = ss58_checksum(&body);
+    body.extend_from_slice(&checksum);
+    Some(bs58::encode(body).into_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::ED25519_BASEPOINT_COMPRESSED;
+
+    fn parse(chain: ChainType, input: &str) -> Result<String, &'static str> {
+        ChainAddress::parse(chain, input).map(|a| a.to_string()).map_err(|e| e.reason)
+    }
+
+    #[test]
+    fn test_near_accounts() {
+        assert_eq!(parse(ChainType::Near, " alice.near "), Ok("alice.near".to_string()));
+        assert_eq!(parse(ChainType::Near, "Alice.near"), Err("account ids are lowercase"));
+        assert!(parse(ChainType::Near, &"AB".repeat(32)).is_err());
+        assert!(parse(ChainType::Near, "near").is_ok());
+        assert!(parse(ChainType::Near, "app_1-x.alice.testnet").is_ok());
+        let too_long = "a".repeat(65);
+        for bad in ["a", "alice..near", ".alice", "alice-.near", "al__ice.near", "alice@near", too_long.as_str()] {
+            assert!(parse(ChainType::Near, bad).is_err(), "{}", bad);
+        }
+        let implicit = ChainAddress::parse(ChainType::Near, &"ab".repeat(32)).unwrap();
+        assert!(implicit.is_implicit());
+        assert_eq!(implicit.as_bytes(), &[0xab; 32]);
+    }
+
+    #[test]
+    fn test_evm_checksums() {
+        for good in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert_eq!(parse(ChainType::Ethereum, good), Ok(good.to_string()));
+            assert_eq!(parse(ChainType::Polygon, &good.to_lowercase()), Ok(good.to_string()));
+        }
+        assert_eq!(
+            parse(ChainType::Ethereum, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err("EIP-55 checksum mismatch")
+        );
+        assert!(parse(ChainType::Ethereum, "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+        assert!(parse(ChainType::Ethereum, "0x1234").is_err());
+    }
+
+    #[test]
+    fn test_solana_keys_must_be_on_curve() {
+        let on_curve = bs58::encode(ED25519_BASEPOINT_COMPRESSED.as_bytes()).into_string();
+        assert_eq!(parse(ChainType::Solana, &on_curve), Ok(on_curve.clone()));
+
+        let off_curve = (0u8..)
+            .map(|i| Sha256::digest([i]).into())
+            .find(|key: &[u8; 32]| CompressedEdwardsY(*key).decompress().is_none())
+            .unwrap();
+        let off_curve = bs58::encode(off_curve).into_string();
+        assert!(parse(ChainType::Solana, &off_curve).is_err());
+        assert_eq!(parse(ChainType::Solana, "0OIl"), Err("not base58"));
+        assert_eq!(parse(ChainType::Solana, "3yZe7d"), Err("public keys are 32 bytes"));
+    }
+
+    #[test]
+    fn test_ss58_prefixes_round_trip() {
+        let alice = ChainAddress::parse(ChainType::Polkadot, "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY").unwrap();
+        assert_eq!(alice.ss58_prefix(), Some(SUBSTRATE_SS58_PREFIX));
+        assert_eq!(
+            hex::encode(alice.account_id32().unwrap()),
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+        assert_eq!(
+            alice.to_ss58(POLKADOT_SS58_PREFIX).as_deref(),
+            Some("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5")
+        );
+        assert_eq!(
+            alice.to_ss58(KUSAMA_SS58_PREFIX).as_deref(),
+            Some("HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F")
+        );
+
+        // Two-byte prefixes survive a round trip
+        let wide = alice.to_ss58(1284).unwrap();
+        let reparsed = ChainAddress::parse(ChainType::Polkadot, &wide).unwrap();
+        assert_eq!((reparsed.ss58_prefix(), reparsed.account_id32()), (Some(1284), alice.account_id32()));
+
+        let hex = format!("0x{}", hex::encode(alice.account_id32().unwrap()));
+        assert_eq!(parse(ChainType::Polkadot, &hex), Ok("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5".to_string()));
+        assert_eq!(
+            parse(ChainType::Polkadot, "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQZ"),
+            Err("SS58 checksum mismatch")
+        );
+        assert_eq!(alice.to_ss58(46), None);
+    }
+}
@@ -1,20 +1,39 @@
 //! Blockchain integration for creative tools and NFTs
 
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use wasm_bindgen::prelude::*;
-use web_sys::window;
 use std::collections::HashMap;
+use std::sync::Arc;
+use emotion_core::{EmotionError, EmotionReading, PadOctants, TaxonomyModel, Vad};
+use reputation_core::{Reputation, ReputationConfig, ReputationEvent, Source};
+use crate::address::ChainAddress;
+use crate::bridge::{BridgeError, BridgeMessage, NftBridge, NftOrigin};
+use crate::chain_adapter::{
+    ChainAdapter, ChainAdapters, ChainError, FeeEstimate, FeeOperation, MintRequest, NftToken, Signer,
+    TransferRequest,
+};
+use crate::chain_rpc::{EvmRpcAdapter, NearRpcAdapter, PolkadotRpcAdapter, SolanaRpcAdapter};
+use crate::fees::default_cost_model;
+use crate::indexer::{
+    all_tokens, AdapterIndexer, CachedIndexer, NearIndexer, NftIndexer, NftQuery, SolanaIndexer, DEFAULT_PAGE_SIZE,
+};
+use crate::metadata_template::{
+    MetadataContext, MetadataGenerator, MetadataSchemas, TemplateEmotion, TemplateError, TemplateReputation,
+};
+
+/// Most emotional states kept for metadata templates
+const MAX_EMOTIONAL_TRAJECTORY: usize = 256;
 
 /// Multi-chain NFT interface
 #[wasm_bindgen]
 pub struct BlockchainConnector {
-    near_connection: Option<NearConnection>,
-    solana_connection: Option<SolanaConnection>,
-    ethereum_connection: Option<EthereumConnection>,
+    adapters: ChainAdapters,
+    indexers: HashMap<ChainType, CachedIndexer>,
     current_chain: ChainType,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ChainType {
     Near,
     Solana,
@@ -23,47 +42,198 @@ pub enum ChainType {
     Polkadot, // Add Polkadot variant
 }
 
+impl ChainType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChainType::Near => "near",
+            ChainType::Solana => "solana",
+            ChainType::Ethereum => "ethereum",
+            ChainType::Polygon => "polygon",
+            ChainType::Polkadot => "polkadot",
+        }
+    }
+
+    /// Token fees are paid in
+    pub fn native_symbol(&self) -> &'static str {
+        match self {
+            ChainType::Near => "NEAR",
+            ChainType::Solana => "SOL",
+            ChainType::Ethereum => "ETH",
+            ChainType::Polygon => "POL",
+            ChainType::Polkadot => "DOT",
+        }
+    }
+
+    /// Decimal places of the native token's smallest unit
+    pub fn native_decimals(&self) -> u8 {
+        match self {
+            ChainType::Near => 24,
+            ChainType::Solana => 9,
+            ChainType::Ethereum | ChainType::Polygon => 18,
+            ChainType::Polkadot => 10,
+        }
+    }
+}
+
+impl std::str::FromStr for ChainType {
+    type Err = ChainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "near" => Ok(ChainType::Near),
+            "solana" => Ok(ChainType::Solana),
+            "ethereum" => Ok(ChainType::Ethereum),
+            "polygon" => Ok(ChainType::Polygon),
+            "polkadot" => Ok(ChainType::Polkadot),
+            other => Err(ChainError::InvalidRequest(format!("unknown chain '{}'", other))),
+        }
+    }
+}
+
+impl From<ChainError> for JsValue {
+    fn from(e: ChainError) -> Self {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
+impl From<BridgeError> for JsValue {
+    fn from(e: BridgeError) -> Self {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
+impl From<TemplateError> for JsValue {
+    fn from(e: TemplateError) -> Self {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
+/// Signer for the wallet the page injected for `chain`, if any
+#[cfg(target_arch = "wasm32")]
+fn browser_signer(chain: ChainType) -> Option<Arc<dyn Signer>> {
+    let name = match chain {
+        ChainType::Near => "nearWallet",
+        ChainType::Solana => "solanaWallet",
+        ChainType::Ethereum | ChainType::Polygon => "ethereumWallet",
+        ChainType::Polkadot => "polkadotClient",
+    };
+    crate::chain_adapter::WalletSigner::from_window(name).map(|signer| Arc::new(signer) as Arc<dyn Signer>)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn browser_signer(_chain: ChainType) -> Option<Arc<dyn Signer>> {
+    None
+}
+
+/// JSON-RPC adapter for `chain`, signing through the page's wallet. `contract`
+/// is the Solana program or EVM contract, `collection` the Polkadot collection.
+fn rpc_adapter(
+    chain: ChainType,
+    network: &str,
+    contract: &str,
+    collection: u32,
+) -> Result<Box<dyn ChainAdapter>, ChainError> {
+    let signer = browser_signer(chain);
+    Ok(match chain {
+        ChainType::Near => Box::new(NearRpcAdapter::for_network(network).with_signer(signer)),
+        ChainType::Solana => Box::new(SolanaRpcAdapter::for_network(network, contract)?.with_signer(signer)),
+        ChainType::Ethereum | ChainType::Polygon => {
+            Box::new(EvmRpcAdapter::for_network(chain, network, contract)?.with_signer(signer))
+        }
+        ChainType::Polkadot => Box::new(PolkadotRpcAdapter::for_network(network, collection).with_signer(signer)),
+    })
+}
+
+/// NFT indexer for `chain`; chains without a paging RPC list through an adapter
+fn rpc_indexer(
+    chain: ChainType,
+    network: &str,
+    contract: &str,
+    collection: u32,
+) -> Result<Box<dyn NftIndexer>, ChainError> {
+    Ok(match chain {
+        ChainType::Near => Box::new(NearIndexer::for_network(network)),
+        ChainType::Solana => Box::new(SolanaIndexer::for_network(network)),
+        _ => Box::new(AdapterIndexer::new(rpc_adapter(chain, network, contract, collection)?)),
+    })
+}
+
+fn from_js(value: &JsValue) -> Result<serde_json::Value, JsValue> {
+    if value.is_undefined() || value.is_null() {
+        return Ok(serde_json::Value::Null);
+    }
+    let text = String::from(js_sys::JSON::stringify(value)?);
+    serde_json::from_str(&text).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    let text = serde_json::to_string(value).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    js_sys::JSON::parse(&text)
+}
+
+/// Mint request from JS arguments. `metadata` is a JSON object or a plain title;
+/// `interactive_params.token_id` picks the token id, otherwise a UUID is used.
+fn mint_request(metadata: &str, ipfs_cid: &str, interactive_params: &JsValue) -> Result<MintRequest, JsValue> {
+    let metadata = match serde_json::from_str(metadata) {
+        Ok(value @ serde_json::Value::Object(_)) => value,
+        _ => json!({ "title": metadata }),
+    };
+    let params = from_js(interactive_params)?;
+    let token_id = match &params["token_id"] {
+        serde_json::Value::String(id) => id.clone(),
+        serde_json::Value::Number(id) => id.to_string(),
+        _ => uuid::Uuid::new_v4().to_string(),
+    };
+    Ok(MintRequest {
+        token_id,
+        metadata,
+        media_cid: ipfs_cid.to_string(),
+        params,
+    })
+}
+
 #[wasm_bindgen]
 impl BlockchainConnector {
     #[wasm_bindgen(constructor)]
     pub fn new() -> BlockchainConnector {
         BlockchainConnector {
-            near_connection: None,
-            solana_connection: None,
-            ethereum_connection: None,
+            adapters: ChainAdapters::default(),
+            indexers: HashMap::new(),
             current_chain: ChainType::Near,
         }
     }
 
-    /// Connect to NEAR wallet
+    /// Connect to NEAR ("mainnet", "testnet" or an RPC URL)
     #[wasm_bindgen]
     pub async fn connect_near(&mut self, network: &str) -> Result<(), JsValue> {
-        // Initialize NEAR connection
-        let connection = NearConnection::new(network).await?;
-        self.near_connection = Some(connection);
-        self.current_chain = ChainType::Near;
-        Ok(())
+        self.connect(ChainType::Near, network, "", 0)
     }
 
-    /// Connect to Solana wallet
+    /// Connect to the program `program_id` on Solana ("mainnet", devnet by default, or an RPC URL)
     #[wasm_bindgen]
-    pub async fn connect_solana(&mut self) -> Result<(), JsValue> {
-        let connection = SolanaConnection::new().await?;
-        self.solana_connection = Some(connection);
-        self.current_chain = ChainType::Solana;
-        Ok(())
+    pub async fn connect_solana(&mut self, program_id: &str, network: Option<String>) -> Result<(), JsValue> {
+        self.connect(ChainType::Solana, network.as_deref().unwrap_or("devnet"), program_id, 0)
     }
 
-    /// Connect to Ethereum wallet
+    /// Connect to the ERC-721 `contract` on Ethereum ("mainnet", Sepolia by default, or an RPC URL)
     #[wasm_bindgen]
-    pub async fn connect_ethereum(&mut self) -> Result<(), JsValue> {
-        let connection = EthereumConnection::new().await?;
-        self.ethereum_connection = Some(connection);
-        self.current_chain = ChainType::Ethereum;
-        Ok(())
+    pub async fn connect_ethereum(&mut self, contract: &str, network: Option<String>) -> Result<(), JsValue> {
+        self.connect(ChainType::Ethereum, network.as_deref().unwrap_or("sepolia"), contract, 0)
+    }
+
+    /// Connect to the ERC-721 `contract` on Polygon ("mainnet", Amoy by default, or an RPC URL)
+    #[wasm_bindgen]
+    pub async fn connect_polygon(&mut self, contract: &str, network: Option<String>) -> Result<(), JsValue> {
+        self.connect(ChainType::Polygon, network.as_deref().unwrap_or("amoy"), contract, 0)
+    }
+
+    /// Connect to a pallet-nfts collection on Polkadot Asset Hub ("mainnet", Westend, or a node URL)
+    #[wasm_bindgen]
+    pub async fn connect_polkadot(&mut self, url: &str, collection: Option<u32>) -> Result<(), JsValue> {
+        self.connect(ChainType::Polkadot, url, "", collection.unwrap_or(0))
     }
 
-    /// Mint interactive NFT
+    /// Mint interactive NFT and return the transaction hash
     #[wasm_bindgen]
     pub async fn mint_interactive_nft(
         &self,
@@ -71,276 +241,186 @@ impl BlockchainConnector {
         ipfs_cid: &str,
         interactive_params: JsValue
     ) -> Result<String, JsValue> {
-        match self.current_chain {
-            ChainType::Near => {
-                if let Some(conn) = &self.near_connection {
-                    conn.mint_interactive_nft(metadata, ipfs_cid, interactive_params).await
-                } else {
-                    Err(JsValue::from_str("NEAR not connected"))
-                }
-            }
-            ChainType::Solana => {
-                if let Some(conn) = &self.solana_connection {
-                    conn.mint_interactive_nft(metadata, ipfs_cid, interactive_params).await
-                } else {
-                    Err(JsValue::from_str("Solana not connected"))
-                }
-            }
-            ChainType::Ethereum => {
-                if let Some(conn) = &self.ethereum_connection {
-                    conn.mint_interactive_nft(metadata, ipfs_cid, interactive_params).await
-                } else {
-                    Err(JsValue::from_str("Ethereum not connected"))
-                }
-            }
-            _ => Err(JsValue::from_str("Unsupported chain"))
-        }
+        let request = mint_request(metadata, ipfs_cid, &interactive_params)?;
+        let hash = self.adapter()?.mint(&request).await?;
+        self.forget_nfts();
+        Ok(hash)
+    }
+
+    /// Transfer an NFT and return the transaction hash
+    #[wasm_bindgen]
+    pub async fn transfer_nft(&self, token_id: &str, receiver: &str) -> Result<String, JsValue> {
+        let receiver = ChainAddress::parse(self.current_chain, receiver).map_err(ChainError::from)?;
+        let request = TransferRequest {
+            token_id: token_id.to_string(),
+            receiver: receiver.to_string(),
+            memo: None,
+        };
+        let hash = self.adapter()?.transfer(&request).await?;
+        self.forget_nfts();
+        Ok(hash)
     }
 
-    /// Create collaboration session
+    /// Create collaboration session and return its id
     #[wasm_bindgen]
     pub async fn create_session(&self, tool_type: &str, params: JsValue) -> Result<String, JsValue> {
-        match self.current_chain {
-            ChainType::Near => {
-                if let Some(conn) = &self.near_connection {
-                    conn.create_collaboration_session(tool_type, params).await
-                } else {
-                    Err(JsValue::from_str("NEAR not connected"))
-                }
-            }
-            _ => Err(JsValue::from_str("Collaboration only supported on NEAR"))
-        }
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let args = json!({ "session_id": session_id, "tool_type": tool_type, "initial_params": from_js(&params)? });
+        self.near_collaboration()?.call_method("create_session", args, 0).await?;
+        Ok(session_id)
     }
 
     /// Join collaboration session
     #[wasm_bindgen]
     pub async fn join_session(&self, session_id: &str) -> Result<(), JsValue> {
-        // Simplified for compilation - would need proper implementation in production
-        let _promise = JsValue::NULL;
-        let _result = JsValue::NULL;
-
+        let args = json!({ "session_id": session_id });
+        self.near_collaboration()?.call_method("join_session", args, 0).await?;
         Ok(())
     }
 
-    /// Publish creative patch
+    /// Publish creative patch (`{ session_id, patch_id }`) and return the transaction hash
     #[wasm_bindgen]
     pub async fn publish_patch(&self, patch_data: JsValue) -> Result<String, JsValue> {
-        match self.current_chain {
-            ChainType::Near => {
-                if let Some(conn) = &self.near_connection {
-                    conn.publish_patch(patch_data).await
-                } else {
-                    Err(JsValue::from_str("NEAR not connected"))
-                }
-            }
-            _ => Err(JsValue::from_str("Patch publishing only supported on NEAR"))
-        }
+        Ok(self.near_collaboration()?.call_method("publish_patch", from_js(&patch_data)?, 0).await?)
     }
 
-    /// Get user NFTs
+    /// Get user NFTs as an array of `{ token_id, owner, metadata, collection? }`
     #[wasm_bindgen]
     pub async fn get_user_nfts(&self, address: &str) -> Result<JsValue, JsValue> {
-        match self.current_chain {
-            ChainType::Near => {
-                if let Some(conn) = &self.near_connection {
-                    conn.get_user_nfts(address).await
-                } else {
-                    Err(JsValue::from_str("NEAR not connected"))
-                }
-            }
-            ChainType::Solana => {
-                if let Some(conn) = &self.solana_connection {
-                    conn.get_user_nfts(address).await
-                } else {
-                    Err(JsValue::from_str("Solana not connected"))
-                }
-            }
-            ChainType::Ethereum => {
-                if let Some(conn) = &self.ethereum_connection {
-                    conn.get_user_nfts(address).await
-                } else {
-                    Err(JsValue::from_str("Ethereum not connected"))
-                }
-            }
-            _ => Err(JsValue::from_str("Unsupported chain"))
-        }
+        let address = ChainAddress::parse(self.current_chain, address).map_err(ChainError::from)?;
+        to_js(&self.user_nfts(address.as_str()).await?)
     }
 
-    /// Get current chain
+    /// One page of user NFTs as `{ tokens, next_cursor }`, optionally from one collection only.
+    /// Pass `next_cursor` back for the following page; it is `null` on the last one.
     #[wasm_bindgen]
-    pub fn get_current_chain(&self) -> String {
-        match self.current_chain {
-            ChainType::Near => "near".to_string(),
-            ChainType::Solana => "solana".to_string(),
-            ChainType::Ethereum => "ethereum".to_string(),
-            ChainType::Polygon => "polygon".to_string(),
-            ChainType::Polkadot => "polkadot".to_string(), // Add Polkadot
-        }
+    pub async fn get_user_nfts_page(
+        &self,
+        address: &str,
+        collection: Option<String>,
+        cursor: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<JsValue, JsValue> {
+        let address = ChainAddress::parse(self.current_chain, address).map_err(ChainError::from)?;
+        let query = NftQuery {
+            owner: address.as_str().to_string(),
+            collection,
+            cursor,
+            limit: limit.map_or(DEFAULT_PAGE_SIZE, |limit| limit as usize),
+        };
+        to_js(&self.indexer()?.tokens_for_owner(&query).await?)
     }
 
-    /// Switch chain
+    /// Transaction status as `{ status, reason? }`
     #[wasm_bindgen]
-    pub fn switch_chain(&mut self, chain: &str) {
-        self.current_chain = match chain {
-            "near" => ChainType::Near,
-            "solana" => ChainType::Solana,
-            "ethereum" => ChainType::Ethereum,
-            "polygon" => ChainType::Polygon,
-            "polkadot" => ChainType::Polkadot, // Add Polkadot
-            _ => ChainType::Near,
-        };
+    pub async fn get_tx_status(&self, tx_hash: &str) -> Result<JsValue, JsValue> {
+        to_js(&self.adapter()?.get_tx_status(tx_hash).await?)
     }
-}
-
-/// NEAR blockchain connection
-pub struct NearConnection {
-    wallet_connection: JsValue,
-    contract_id: String,
-}
 
-impl NearConnection {
-    pub async fn new(network: &str) -> Result<Self, JsValue> {
-        // Initialize NEAR wallet connection
-        let wallet_connection = js_sys::Reflect::get(&window().unwrap(), &"nearWallet".into())?;
-
-        Ok(NearConnection {
-            wallet_connection,
-            contract_id: match network {
-                "mainnet" => "nft.compiling.near".to_string(),
-                _ => "nft.compiling.testnet".to_string(),
-            },
-        })
+    /// Fee for "mint" or "transfer" carrying `payload_bytes` of metadata, at the node's
+    /// current prices. `amount` and `deposit` are strings in the smallest unit.
+    #[wasm_bindgen]
+    pub async fn estimate_fee(&self, operation: &str, payload_bytes: Option<u32>) -> Result<JsValue, JsValue> {
+        let operation: FeeOperation = operation.parse()?;
+        to_js(&self.adapter()?.estimate_fee(operation, payload_bytes.unwrap_or(0) as u64).await?)
     }
 
-    pub async fn mint_interactive_nft(&self, metadata: &str, ipfs_cid: &str, interactive_params: JsValue) -> Result<String, JsValue> {
-        // Simplified for compilation - would need proper implementation in production
-        let _promise = JsValue::NULL;
-        let result = JsValue::NULL;
-
-        Ok(result.as_string().unwrap_or_default())
+    /// Fee for minting exactly what `mint_interactive_nft` would send with these arguments
+    #[wasm_bindgen]
+    pub async fn estimate_mint_fee(
+        &self,
+        metadata: &str,
+        ipfs_cid: &str,
+        interactive_params: JsValue
+    ) -> Result<JsValue, JsValue> {
+        let request = mint_request(metadata, ipfs_cid, &interactive_params)?;
+        to_js(&self.adapter()?.estimate_fee(FeeOperation::Mint, request.payload_bytes()).await?)
     }
 
-    pub async fn create_collaboration_session(&self, tool_type: &str, params: JsValue) -> Result<String, JsValue> {
-        let promise = js_sys::Reflect::get(&self.wallet_connection, &"callMethod".into())?;
-        // Simplified for compilation - would need proper implementation in production
-        let result = JsValue::NULL;
-
-        Ok(result.as_string().unwrap_or_default())
+    /// Get current chain
+    #[wasm_bindgen]
+    pub fn get_current_chain(&self) -> String {
+        self.current_chain.as_str().to_string()
     }
 
-    pub async fn join_session(&self, session_id: &str) -> Result<(), JsValue> {
-        // Simplified for compilation - would need proper implementation in production
-        let _promise = JsValue::NULL;
-        let _result = JsValue::NULL;
-
-        Ok(())
+    /// Switch chain
+    #[wasm_bindgen]
+    pub fn switch_chain(&mut self, chain: &str) {
+        self.current_chain = chain.parse().unwrap_or(ChainType::Near);
     }
+}
 
-    pub async fn publish_patch(&self, patch_data: JsValue) -> Result<String, JsValue> {
-        let promise = js_sys::Reflect::get(&self.wallet_connection, &"callMethod".into())?;
-        // Simplified for compilation - would need proper implementation in production
-        let result = JsValue::NULL;
-
-        Ok(result.as_string().unwrap_or_default())
+impl BlockchainConnector {
+    /// Use `adapter` for its chain and switch to that chain. NFTs are listed
+    /// through the adapter until an indexer is set for the chain.
+    pub fn set_adapter(&mut self, adapter: Box<dyn ChainAdapter>) {
+        self.current_chain = self.adapters.insert(adapter);
+        self.indexers.remove(&self.current_chain);
     }
 
-    pub async fn get_user_nfts(&self, address: &str) -> Result<JsValue, JsValue> {
-        let promise = js_sys::Reflect::get(&self.wallet_connection, &"viewMethod".into())?;
-        // Simplified for compilation - would need proper implementation in production
-        let result = JsValue::NULL;
-
-        Ok(result)
+    /// List NFTs on the indexer's chain through `indexer`, behind a cache
+    pub fn set_indexer(&mut self, indexer: Box<dyn NftIndexer>) {
+        self.indexers.insert(indexer.chain(), CachedIndexer::new(indexer));
     }
-}
-
-/// Solana blockchain connection
-pub struct SolanaConnection {
-    wallet: JsValue,
-    program_id: String,
-}
-
-impl SolanaConnection {
-    pub async fn new() -> Result<Self, JsValue> {
-        let wallet = js_sys::Reflect::get(&window().unwrap(), &"solanaWallet".into())?;
 
-        Ok(SolanaConnection {
-            wallet,
-            program_id: "CompilingNFT1111111111111111111111111111111".to_string(),
-        })
+    /// Adapter for the current chain
+    pub fn adapter(&self) -> Result<&dyn ChainAdapter, ChainError> {
+        self.adapters.get(self.current_chain)
     }
 
-    pub async fn mint_interactive_nft(&self, metadata: &str, ipfs_cid: &str, interactive_params: JsValue) -> Result<String, JsValue> {
-        // Call Solana program
-        let promise = js_sys::Reflect::get(&self.wallet, &"sendTransaction".into())?;
-        // Simplified for compilation - would need proper implementation in production
-        let result = JsValue::NULL;
-
-        Ok(result.as_string().unwrap_or_default())
+    /// Indexer for the current chain
+    pub fn indexer(&self) -> Result<&CachedIndexer, ChainError> {
+        self.indexers.get(&self.current_chain).ok_or(ChainError::NotConnected(self.current_chain))
     }
 
-    pub async fn get_user_nfts(&self, address: &str) -> Result<JsValue, JsValue> {
-        // Query Solana program for user's NFTs
-        // Simplified for compilation - would need proper implementation in production
-        let result = JsValue::NULL;
-
-        Ok(result)
+    /// Every NFT `owner` holds on the current chain
+    pub async fn user_nfts(&self, owner: &str) -> Result<Vec<NftToken>, ChainError> {
+        match self.indexers.get(&self.current_chain) {
+            Some(indexer) => all_tokens(indexer, &NftQuery::new(owner)).await,
+            None => self.adapter()?.get_user_nfts(owner).await,
+        }
     }
-}
-
-/// Ethereum/Polygon connection
-pub struct EthereumConnection {
-    provider: JsValue,
-    contract_address: String,
-}
 
-impl EthereumConnection {
-    pub async fn new() -> Result<Self, JsValue> {
-        let provider = JsValue::NULL;
-
-        Ok(EthereumConnection {
-            provider,
-            contract_address: "0x1234567890123456789012345678901234567890".to_string(),
-        })
+    fn connect(&mut self, chain: ChainType, network: &str, contract: &str, collection: u32) -> Result<(), JsValue> {
+        self.set_adapter(rpc_adapter(chain, network, contract, collection)?);
+        self.set_indexer(rpc_indexer(chain, network, contract, collection)?);
+        Ok(())
     }
 
-    pub async fn mint_interactive_nft(&self, metadata: &str, ipfs_cid: &str, interactive_params: JsValue) -> Result<String, JsValue> {
-        // Simplified for compilation - would need proper implementation in production
-        let _contract = js_sys::Reflect::get(&self.provider, &"Contract".into())?;
-        let contract_instance = JsValue::NULL;
-
-        let _mint_method = JsValue::NULL;
-        let result = JsValue::NULL;
-
-        Ok(result.as_string().unwrap_or_default())
+    /// Drop cached listings of the current chain after a transaction changed owners
+    fn forget_nfts(&self) {
+        if let Some(indexer) = self.indexers.get(&self.current_chain) {
+            indexer.clear();
+        }
     }
 
-    pub async fn get_user_nfts(&self, address: &str) -> Result<JsValue, JsValue> {
-        // Simplified for compilation - would need proper implementation in production
-        let _contract = js_sys::Reflect::get(&self.provider, &"Contract".into())?;
-        let contract_instance = JsValue::NULL;
-
-        let _balance_method = JsValue::NULL;
-        let balance = JsValue::NULL;
-
-        Ok(balance)
+    /// Collaboration contracts only exist on NEAR
+    fn near_collaboration(&self) -> Result<&dyn ChainAdapter, JsValue> {
+        match self.current_chain {
+            ChainType::Near => Ok(self.adapter()?),
+            _ => Err(JsValue::from_str("Collaboration only supported on NEAR")),
+        }
     }
 }
 
 /// Advanced multi-chain NFT interface with emotional computing
 #[wasm_bindgen]
 pub struct AdvancedBlockchainConnector {
-    near_connection: Option<NearConnection>,
-    solana_connection: Option<SolanaConnection>,
-    ethereum_connection: Option<EthereumConnection>,
-    polkadot_connection: Option<PolkadotConnection>,
+    adapters: ChainAdapters,
     current_chain: ChainType,
     // Add emotional computing integration
     emotional_state: Option<EmotionalVector>,
+    /// Emotional states set so far, oldest first, for metadata templates
+    emotional_trajectory: Vec<EmotionalVector>,
     emotional_modulation_enabled: bool,
+    emotion_taxonomy: TaxonomyModel,
     // Add advanced features
     cross_chain_bridge: Option<CrossChainBridge>,
+    bridge: NftBridge,
     reputation_tracker: Option<ReputationTracker>,
+    reputation_config: ReputationConfig,
     metadata_generator: Option<MetadataGenerator>,
+    metadata_schemas: MetadataSchemas,
 }
 
 /// Cross-chain bridge for NFT transfers
@@ -349,7 +429,7 @@ pub struct CrossChainBridge {
     pub source_chain: String,
     pub target_chain: String,
     pub bridge_contract: String,
-    pub status: String, // "pending", "completed", "failed"
+    pub status: String, // BridgeState of the latest transfer: "pending", "locked", ..., "minted", "failed"
     pub timestamp: u64,
 }
 
@@ -360,14 +440,9 @@ pub struct ReputationTracker {
     pub reputation_score: f32,
     pub interaction_count: u64,
     pub last_updated: u64,
-}
-
-/// Advanced metadata generator
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MetadataGenerator {
-    pub template: String,
-    pub parameters: HashMap<String, String>,
-    pub version: String,
+    /// Evidence and audit log behind `reputation_score`
+    #[serde(default)]
+    pub history: Reputation,
 }
 
 #[wasm_bindgen]
@@ -375,38 +450,80 @@ impl AdvancedBlockchainConnector {
     #[wasm_bindgen(constructor)]
     pub fn new() -> AdvancedBlockchainConnector {
         AdvancedBlockchainConnector {
-            near_connection: None,
-            solana_connection: None,
-            ethereum_connection: None,
-            polkadot_connection: None,
+            adapters: ChainAdapters::default(),
             current_chain: ChainType::Near,
             emotional_state: None,
+            emotional_trajectory: Vec::new(),
             emotional_modulation_enabled: false,
+            emotion_taxonomy: TaxonomyModel::default(),
             cross_chain_bridge: None,
+            bridge: NftBridge::default(),
             reputation_tracker: None,
+            reputation_config: ReputationConfig::default(),
             metadata_generator: None,
+            metadata_schemas: MetadataSchemas::default(),
         }
     }
 
-    /// Connect to Polkadot chain
+    /// Connect to NEAR ("mainnet", "testnet" or an RPC URL)
     #[wasm_bindgen]
-    pub async fn connect_polkadot(&mut self, url: &str) -> Result<(), JsValue> {
-        let connection = PolkadotConnection::new(url).await?;
-        self.polkadot_connection = Some(connection);
-        self.current_chain = ChainType::Polkadot;
-        Ok(())
+    pub async fn connect_near(&mut self, network: &str) -> Result<(), JsValue> {
+        self.connect(ChainType::Near, network, "", 0)
+    }
+
+    /// Connect to the program `program_id` on Solana ("mainnet", devnet by default, or an RPC URL)
+    #[wasm_bindgen]
+    pub async fn connect_solana(&mut self, program_id: &str, network: Option<String>) -> Result<(), JsValue> {
+        self.connect(ChainType::Solana, network.as_deref().unwrap_or("devnet"), program_id, 0)
+    }
+
+    /// Connect to the ERC-721 `contract` on Ethereum ("mainnet", Sepolia by default, or an RPC URL)
+    #[wasm_bindgen]
+    pub async fn connect_ethereum(&mut self, contract: &str, network: Option<String>) -> Result<(), JsValue> {
+        self.connect(ChainType::Ethereum, network.as_deref().unwrap_or("sepolia"), contract, 0)
+    }
+
+    /// Connect to the ERC-721 `contract` on Polygon ("mainnet", Amoy by default, or an RPC URL)
+    #[wasm_bindgen]
+    pub async fn connect_polygon(&mut self, contract: &str, network: Option<String>) -> Result<(), JsValue> {
+        self.connect(ChainType::Polygon, network.as_deref().unwrap_or("amoy"), contract, 0)
+    }
+
+    /// Connect to Polkadot chain (a pallet-nfts collection on Asset Hub)
+    #[wasm_bindgen]
+    pub async fn connect_polkadot(&mut self, url: &str, collection: Option<u32>) -> Result<(), JsValue> {
+        self.connect(ChainType::Polkadot, url, "", collection.unwrap_or(0))
     }
     
     /// Set emotional state for modulation
     #[wasm_bindgen]
     pub fn set_emotional_state(&mut self, valence: f32, arousal: f32, dominance: f32) {
-        self.emotional_state = Some(EmotionalVector {
+        let emotion = EmotionalVector {
             valence: valence.clamp(-1.0, 1.0),
             arousal: arousal.clamp(0.0, 1.0),
             dominance: dominance.clamp(0.0, 1.0),
             confidence: 1.0, // Default confidence
             timestamp: js_sys::Date::now() as u64, // Current timestamp
-        });
+        };
+        if self.emotional_trajectory.len() == MAX_EMOTIONAL_TRAJECTORY {
+            self.emotional_trajectory.remove(0);
+        }
+        self.emotional_trajectory.push(emotion.clone());
+        self.emotional_state = Some(emotion);
+    }
+
+    /// Forget earlier emotional states; the current one stays
+    #[wasm_bindgen]
+    pub fn clear_emotional_trajectory(&mut self) {
+        self.emotional_trajectory.clear();
+    }
+    
+    /// Choose the taxonomy used for emotional categories
+    /// ("pad", "circumplex" or "plutchik")
+    #[wasm_bindgen]
+    pub fn set_emotion_taxonomy(&mut self, model: &str) -> Result<(), JsValue> {
+        self.emotion_taxonomy = model.parse().map_err(|e: EmotionError| JsValue::from_str(&e.to_string()))?;
+        Ok(())
     }
     
     /// Enable/disable emotional modulation
@@ -434,37 +551,8 @@ impl AdvancedBlockchainConnector {
             interactive_params
         };
         
-        match self.current_chain {
-            ChainType::Near => {
-                if let Some(conn) = &self.near_connection {
-                    conn.mint_interactive_nft(metadata, ipfs_cid, modulated_params).await
-                } else {
-                    Err(JsValue::from_str("NEAR not connected"))
-                }
-            }
-            ChainType::Solana => {
-                if let Some(conn) = &self.solana_connection {
-                    conn.mint_interactive_nft(metadata, ipfs_cid, modulated_params).await
-                } else {
-                    Err(JsValue::from_str("Solana not connected"))
-                }
-            }
-            ChainType::Ethereum => {
-                if let Some(conn) = &self.ethereum_connection {
-                    conn.mint_interactive_nft(metadata, ipfs_cid, modulated_params).await
-                } else {
-                    Err(JsValue::from_str("Ethereum not connected"))
-                }
-            }
-            ChainType::Polkadot => {
-                if let Some(conn) = &self.polkadot_connection {
-                    conn.mint_interactive_nft(metadata, ipfs_cid, modulated_params).await
-                } else {
-                    Err(JsValue::from_str("Polkadot not connected"))
-                }
-            }
-            _ => Err(JsValue::from_str("Unsupported chain"))
-        }
+        let request = mint_request(metadata, ipfs_cid, &modulated_params)?;
+        Ok(self.adapter()?.mint(&request).await?)
     }
     
     /// Apply emotional modulation to interactive parameters
@@ -489,26 +577,65 @@ impl AdvancedBlockchainConnector {
         }
     }
 
-    /// Cross-chain bridge NFT with emotional metadata
+    /// Lock `nft_id` on the current chain for a wrapped copy on `target_chain`, delivered
+    /// to `recipient`. `emotional_metadata` is `{ valence, arousal, dominance, confidence,
+    /// timestamp }` or null for the current emotional state. Returns the bridge message id;
+    /// a relayer completes the transfer.
     #[wasm_bindgen]
     pub async fn bridge_nft_with_emotion(
-        &self,
+        &mut self,
         nft_id: &str,
         target_chain: &str,
+        recipient: &str,
         emotional_metadata: JsValue
     ) -> Result<String, JsValue> {
-        // In a real implementation, this would:
-        // 1. Lock the NFT on the source chain
-        // 2. Create a new NFT on the target chain with the emotional metadata
-        // 3. Return the new NFT ID
-        
-        let result = format!("Bridged NFT {} to {} with emotional metadata", nft_id, target_chain);
-        Ok(result)
+        let target_chain: ChainType = target_chain.parse()?;
+        let adapter = self.adapters.get(self.current_chain)?;
+        let sender = adapter.account_id().ok_or(ChainError::NoSigner)?;
+        let token = adapter
+            .get_user_nfts(&sender)
+            .await?
+            .into_iter()
+            .find(|token| token.token_id == nft_id)
+            .ok_or_else(|| JsValue::from_str(&format!("{} does not own {}", sender, nft_id)))?;
+        let media = token.metadata["media"].as_str().unwrap_or_default();
+        let media_cid = media.strip_prefix("ipfs://").unwrap_or(media).to_string();
+
+        let emotion = match from_js(&emotional_metadata)? {
+            serde_json::Value::Null => self.emotional_state.clone(),
+            value => Some(serde_json::from_value(value).map_err(|e| JsValue::from_str(&e.to_string()))?),
+        };
+        let origin = NftOrigin { chain: self.current_chain, contract: adapter.collection(), token_id: nft_id.to_string() };
+        let nonce = uuid::Uuid::new_v4().as_u64_pair().0;
+        let mut message = BridgeMessage::lock_and_mint(origin, target_chain, &sender, recipient, nonce)?
+            .with_content(token.metadata, &media_cid);
+        if let Some(emotion) = &emotion {
+            let reading = EmotionReading::try_from(emotion).map_err(|e| JsValue::from_str(&e.to_string()))?;
+            message = message.with_emotion(reading, Vec::new());
+        }
+
+        let message_id = self.bridge.initiate(adapter, message).await?;
+        if let (Some(config), Some(transfer)) = (&mut self.cross_chain_bridge, self.bridge.get(&message_id)) {
+            config.status = transfer.state.as_str().to_string();
+        }
+        Ok(message_id)
+    }
+
+    /// Bridge transfer as `{ message_id, message, state, source_tx, attestations, target_txs, failure, history }`
+    #[wasm_bindgen]
+    pub fn get_bridge_transfer(&self, message_id: &str) -> Result<JsValue, JsValue> {
+        let transfer = self
+            .bridge
+            .get(message_id)
+            .ok_or_else(|| BridgeError::UnknownMessage(message_id.to_string()))?;
+        to_js(transfer)
     }
     
-    /// Set cross-chain bridge configuration
+    /// Set cross-chain bridge configuration; `bridge_contract` is the bridge account
+    /// on the source chain that escrows locked NFTs
     #[wasm_bindgen]
-    pub fn set_cross_chain_bridge(&mut self, source_chain: &str, target_chain: &str, bridge_contract: &str) {
+    pub fn set_cross_chain_bridge(&mut self, source_chain: &str, target_chain: &str, bridge_contract: &str) -> Result<(), JsValue> {
+        self.bridge.set_bridge_account(source_chain.parse()?, bridge_contract)?;
         self.cross_chain_bridge = Some(CrossChainBridge {
             source_chain: source_chain.to_string(),
             target_chain: target_chain.to_string(),
@@ -516,41 +643,65 @@ impl AdvancedBlockchainConnector {
             status: "pending".to_string(),
             timestamp: js_sys::Date::now() as u64,
         });
+        Ok(())
     }
     
     /// Set reputation tracker for creator
     #[wasm_bindgen]
     pub fn set_reputation_tracker(&mut self, creator_id: &str, initial_score: f32) {
+        let now_ms = js_sys::Date::now() as u64;
+        let history = Reputation::with_score(&self.reputation_config, initial_score, now_ms / 1000);
         self.reputation_tracker = Some(ReputationTracker {
             creator_id: creator_id.to_string(),
-            reputation_score: initial_score.clamp(0.0, 1.0),
+            reputation_score: history.score(),
             interaction_count: 0,
-            last_updated: js_sys::Date::now() as u64,
+            last_updated: now_ms,
+            history,
         });
     }
     
-    /// Update creator reputation based on interaction quality
+    /// Update creator reputation based on interaction quality (0.0-1.0)
     #[wasm_bindgen]
-    pub fn update_reputation(&mut self, interaction_quality: f32) {
-        if let Some(ref mut reputation) = self.reputation_tracker {
-            if interaction_quality > 0.5 {
-                reputation.reputation_score = (reputation.reputation_score + 0.1).min(1.0);
-            } else {
-                reputation.reputation_score = (reputation.reputation_score - 0.05).max(0.0);
-            }
-            reputation.interaction_count += 1;
-            reputation.last_updated = js_sys::Date::now() as u64;
-        }
+    pub fn update_reputation(&mut self, interaction_quality: f32) -> Result<(), JsValue> {
+        self.record_reputation_event("interaction", interaction_quality).map(|_| ())
     }
     
-    /// Set metadata generator template
+    /// Record a rated collaboration, sale or interaction and return the new score
     #[wasm_bindgen]
-    pub fn set_metadata_generator(&mut self, template: &str, version: &str) {
-        self.metadata_generator = Some(MetadataGenerator {
-            template: template.to_string(),
-            parameters: HashMap::new(),
-            version: version.to_string(),
-        });
+    pub fn record_reputation_event(&mut self, source: &str, quality: f32) -> Result<f32, JsValue> {
+        let source: Source = source.parse().map_err(|e: reputation_core::ReputationError| JsValue::from_str(&e.to_string()))?;
+        let reputation = self
+            .reputation_tracker
+            .as_mut()
+            .ok_or_else(|| JsValue::from_str("No reputation tracker set"))?;
+        
+        let now_ms = js_sys::Date::now() as u64;
+        let entry = reputation
+            .history
+            .record(&self.reputation_config, ReputationEvent::new(source, quality, now_ms / 1000))
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        reputation.reputation_score = entry.score_after;
+        reputation.interaction_count += 1;
+        reputation.last_updated = now_ms;
+        Ok(entry.score_after)
+    }
+    
+    /// Audit log of the tracked reputation as a JSON array
+    #[wasm_bindgen]
+    pub fn get_reputation_audit_log(&self) -> Result<String, JsValue> {
+        let log = self.reputation_tracker.as_ref().map(|r| r.history.audit_log()).unwrap_or(&[]);
+        serde_json::to_string(log).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+    
+    /// Set the metadata template and the schema version its output must match.
+    /// An empty template renders the built-in "1.0" layout.
+    #[wasm_bindgen]
+    pub fn set_metadata_generator(&mut self, template: &str, version: &str) -> Result<(), JsValue> {
+        if self.metadata_schemas.schema(version).is_none() {
+            return Err(TemplateError::UnknownVersion(version.to_string()).into());
+        }
+        self.metadata_generator = Some(MetadataGenerator::new(template, version)?);
+        Ok(())
     }
     
     /// Add parameter to metadata generator
@@ -560,71 +711,48 @@ impl AdvancedBlockchainConnector {
             generator.parameters.insert(key.to_string(), value.to_string());
         }
     }
+
+    /// Add or replace the JSON Schema that metadata of `version` must satisfy
+    #[wasm_bindgen]
+    pub fn register_metadata_schema(&mut self, version: &str, schema: &str) -> Result<(), JsValue> {
+        let schema = serde_json::from_str(schema).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(self.metadata_schemas.register(version, schema)?)
+    }
     
-    /// Generate enhanced metadata with emotional context
+    /// Render `base_metadata` through the metadata template with the current emotion,
+    /// its trajectory and the creator's reputation, checked against the template's
+    /// schema. Without a template the base metadata is returned as is.
     #[wasm_bindgen]
     pub fn generate_enhanced_metadata(&self, base_metadata: &str) -> Result<String, JsValue> {
-        if let Some(generator) = &self.metadata_generator {
-            let mut metadata = base_metadata.to_string();
-            
-            // Add emotional context if available
-            if let Some(emotion) = &self.emotional_state {
-                // Extract values to avoid borrowing issues
-                let valence = emotion.valence;
-                let arousal = emotion.arousal;
-                let dominance = emotion.dominance;
-                let category = self.get_emotional_category(valence, arousal);
-                
-                let emotional_context = format!(
-                    r#","emotional_context": {{
-                        "valence": {},
-                        "arousal": {},
-                        "dominance": {},
-                        "category": "{}"
-                    }}"#,
-                    valence, arousal, dominance, category
-                );
-                
-                // Insert emotional context before the closing brace
-                if let Some(pos) = metadata.rfind('}') {
-                    metadata.insert_str(pos, &emotional_context);
-                }
-            }
-            
-            // Add reputation context if available
-            if let Some(reputation) = &self.reputation_tracker {
-                // Extract values to avoid borrowing issues
-                let score = reputation.reputation_score;
-                let interactions = reputation.interaction_count;
-                
-                let reputation_context = format!(
-                    r#","creator_reputation": {{
-                        "score": {},
-                        "interactions": {}
-                    }}"#,
-                    score, interactions
-                );
-                
-                // Insert reputation context before the closing brace
-                if let Some(pos) = metadata.rfind('}') {
-                    metadata.insert_str(pos, &reputation_context);
-                }
-            }
-            
-            Ok(metadata)
-        } else {
-            Ok(base_metadata.to_string())
-        }
+        let Some(generator) = &self.metadata_generator else {
+            return Ok(base_metadata.to_string());
+        };
+        let base = match base_metadata.trim() {
+            "" => serde_json::Value::Object(Default::default()),
+            text => serde_json::from_str(text).map_err(|e| JsValue::from_str(&format!("base metadata: {}", e)))?,
+        };
+        let context = MetadataContext {
+            base,
+            emotion: self.emotional_state.as_ref().map(|e| self.template_emotion(e)),
+            trajectory: self.emotional_trajectory.iter().map(|e| self.template_emotion(e)).collect(),
+            reputation: self
+                .reputation_tracker
+                .as_ref()
+                .map(|r| TemplateReputation::new(r.reputation_score, r.interaction_count)),
+        };
+        let metadata = generator.generate(&context, &self.metadata_schemas)?;
+        Ok(metadata.to_string())
+    }
+
+    fn template_emotion(&self, emotion: &EmotionalVector) -> TemplateEmotion {
+        let category = self.get_emotional_category(emotion.valence, emotion.arousal, emotion.dominance);
+        let vad = [emotion.valence, emotion.arousal, emotion.dominance];
+        TemplateEmotion::new(vad, emotion.confidence, emotion.timestamp, &category)
     }
     
     /// Get emotional category based on VAD values
-    fn get_emotional_category(&self, valence: f32, arousal: f32) -> String {
-        match (valence, arousal) {
-            (v, a) if v > 0.5 && a > 0.5 => "Excited".to_string(),
-            (v, a) if v > 0.5 && a <= 0.5 => "Happy".to_string(),
-            (v, a) if v <= 0.5 && a > 0.5 => "Anxious".to_string(),
-            _ => "Calm".to_string(),
-        }
+    fn get_emotional_category(&self, valence: f32, arousal: f32, dominance: f32) -> String {
+        Vad::clamped(valence, arousal, dominance).classify(&self.emotion_taxonomy).label.to_string()
     }
     
     /// Get current emotional state
@@ -645,45 +773,28 @@ impl AdvancedBlockchainConnector {
     #[wasm_bindgen]
     pub fn get_reputation_score(&self) -> f32 {
         if let Some(reputation) = &self.reputation_tracker {
-            reputation.reputation_score
+            // Decayed to now, so a creator gone quiet drifts back to neutral
+            reputation.history.score_at(&self.reputation_config, js_sys::Date::now() as u64 / 1000)
         } else {
             0.5 // Default neutral reputation
         }
     }
 }
 
-/// Polkadot blockchain connection
-pub struct PolkadotConnection {
-    client: JsValue,
-    url: String,
-}
-
-impl PolkadotConnection {
-    pub async fn new(url: &str) -> Result<Self, JsValue> {
-        // Initialize Polkadot connection
-        let client = js_sys::Reflect::get(&web_sys::window().unwrap(), &"polkadotClient".into())?;
-        
-        Ok(PolkadotConnection {
-            client,
-            url: url.to_string(),
-        })
+impl AdvancedBlockchainConnector {
+    /// Use `adapter` for its chain and switch to that chain
+    pub fn set_adapter(&mut self, adapter: Box<dyn ChainAdapter>) {
+        self.current_chain = self.adapters.insert(adapter);
     }
-    
-    pub async fn mint_interactive_nft(&self, metadata: &str, ipfs_cid: &str, interactive_params: JsValue) -> Result<String, JsValue> {
-        // Call Polkadot pallet for NFT minting
-        let promise = js_sys::Reflect::get(&self.client, &"mintNFT".into())?;
-        // Simplified for compilation - would need proper implementation in production
-        let result = JsValue::NULL;
-        
-        Ok(result.as_string().unwrap_or_default())
+
+    /// Adapter for the current chain
+    pub fn adapter(&self) -> Result<&dyn ChainAdapter, ChainError> {
+        self.adapters.get(self.current_chain)
     }
-    
-    pub async fn get_user_nfts(&self, address: &str) -> Result<JsValue, JsValue> {
-        // Query Polkadot pallet for user's NFTs
-        // Simplified for compilation - would need proper implementation in production
-        let result = JsValue::NULL;
-        
-        Ok(result)
+
+    fn connect(&mut self, chain: ChainType, network: &str, contract: &str, collection: u32) -> Result<(), JsValue> {
+        self.set_adapter(rpc_adapter(chain, network, contract, collection)?);
+        Ok(())
     }
 }
 
@@ -718,25 +829,28 @@ pub fn generate_nft_metadata(
     )
 }
 
+/// Gas, compute units or weight `ref_time` at default prices; 0 for unknown chains or operations
 #[wasm_bindgen]
-pub fn calculate_gas_estimate(chain: &str, operation: &str) -> u64 {
-    match (chain, operation) {
-        ("near", "mint") => 300_000_000_000_000,
-        ("near", "transfer") => 100_000_000_000_000,
-        ("solana", "mint") => 5000, // lamports
-        ("ethereum", "mint") => 100_000, // gas units
-        _ => 0
-    }
+pub fn calculate_gas_estimate(chain: &str, operation: &str, payload_bytes: u32) -> u64 {
+    offline_fee(chain, operation, payload_bytes).map(|fee| fee.units).unwrap_or(0)
 }
 
+/// Fee at default prices, without a node; `amount` and `deposit` are strings in the smallest unit
 #[wasm_bindgen]
-pub fn validate_address(chain: &str, address: &str) -> bool {
-    match chain {
-        "near" => address.contains('.') && address.len() >= 2,
-        "solana" => address.len() == 44, // Base58 encoded
-        "ethereum" => address.starts_with("0x") && address.len() == 42,
-        _ => false
-    }
+pub fn calculate_fee_estimate(chain: &str, operation: &str, payload_bytes: u32) -> Result<JsValue, JsValue> {
+    to_js(&offline_fee(chain, operation, payload_bytes)?)
+}
+
+fn offline_fee(chain: &str, operation: &str, payload_bytes: u32) -> Result<FeeEstimate, ChainError> {
+    let chain: ChainType = chain.parse()?;
+    Ok(default_cost_model(chain).estimate(chain, operation.parse()?, payload_bytes as u64))
+}
+
+/// Check an address pasted for `chain` and return it in canonical form
+#[wasm_bindgen]
+pub fn validate_address(chain: &str, address: &str) -> Result<ChainAddress, JsValue> {
+    let chain: ChainType = chain.parse()?;
+    ChainAddress::parse(chain, address).map_err(|e| JsValue::from_str(&e.to_string()))
 }
 
 /// Generate emotional metadata for NFTs
@@ -762,26 +876,22 @@ pub fn generate_emotional_metadata(
             "timestamp": timestamp,
         },
         "creative_traits": creative_traits, // Use string directly
-        "emotional_category": match (valence, arousal) {
-            (v, a) if v > 0.5 && a > 0.5 => "Excited",
-            (v, a) if v > 0.5 && a <= 0.5 => "Happy",
-            (v, a) if v <= 0.5 && a > 0.5 => "Anxious",
-            _ => "Calm",
-        }
+        "emotional_category": Vad::clamped(valence, arousal, dominance).classify(&PadOctants).label,
     });
     metadata.to_string()
 }
 
-/// Calculate cross-chain bridge fee with emotional weighting
+/// Calculate cross-chain bridge fee with emotional weighting: minting `metadata_bytes`
+/// on `chain` at default prices, as a string in the smallest unit
 #[wasm_bindgen]
-pub fn calculate_emotional_bridge_fee(chain: &str, valence: f32, arousal: f32, dominance: f32) -> u64 {
-    let base_fee = match chain {
-        "near" => 300_000_000_000_000,
-        "solana" => 5000,
-        "ethereum" => 100_000,
-        "polkadot" => 1_000_000_000,
-        _ => 0
-    };
+pub fn calculate_emotional_bridge_fee(
+    chain: &str,
+    valence: f32,
+    arousal: f32,
+    dominance: f32,
+    metadata_bytes: u32,
+) -> String {
+    let base_fee = offline_fee(chain, "mint", metadata_bytes).map(|fee| fee.amount).unwrap_or(0);
     
     // Emotional complexity modifier
     let neutral_valence = 0.0;
@@ -792,7 +902,77 @@ pub fn calculate_emotional_bridge_fee(chain: &str, valence: f32, arousal: f32, d
                    (arousal - neutral_arousal).powi(2) + 
                    (dominance - neutral_dominance).powi(2)).sqrt();
     
-    let emotional_modifier = (distance * 0.2 * base_fee as f32) as u64;
+    // Parts per thousand, so large native amounts keep their precision
+    let emotional_modifier = base_fee / 1000 * (distance * 200.0) as u128;
     
-    base_fee + emotional_modifier
+    (base_fee + emotional_modifier).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_adapter::{MockAdapter, TxStatus};
+    use crate::indexer::MockIndexer;
+
+    #[tokio::test]
+    async fn test_connector_routes_through_current_adapter() {
+        let mut connector = BlockchainConnector::new();
+        assert_eq!(connector.adapter().err(), Some(ChainError::NotConnected(ChainType::Near)));
+
+        connector.set_adapter(Box::new(MockAdapter::new(ChainType::Polygon, "0xabc")));
+        assert_eq!(connector.get_current_chain(), "polygon");
+        let request = MintRequest {
+            token_id: "1".to_string(),
+            metadata: json!({ "title": "Loop" }),
+            media_cid: "bafy".to_string(),
+            params: serde_json::Value::Null,
+        };
+        let hash = connector.adapter().unwrap().mint(&request).await.unwrap();
+        assert_eq!(connector.adapter().unwrap().get_tx_status(&hash).await.unwrap(), TxStatus::Confirmed);
+
+        connector.switch_chain("solana");
+        assert!(connector.adapter().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connector_lists_nfts_through_the_indexer() {
+        let mut connector = BlockchainConnector::new();
+        connector.set_adapter(Box::new(MockAdapter::new(ChainType::Near, "art.near")));
+        assert!(connector.indexer().is_err());
+        assert!(connector.user_nfts("alice.near").await.unwrap().is_empty());
+
+        let fixture = r#"[{ "token_id": "7", "owner": "alice.near", "collection": "art.near" }]"#;
+        let mock = Arc::new(MockIndexer::from_fixture(ChainType::Near, fixture).unwrap());
+        connector.set_indexer(Box::new(mock.clone()));
+        assert_eq!(connector.user_nfts("alice.near").await.unwrap()[0].token_id, "7");
+        connector.user_nfts("alice.near").await.unwrap();
+        assert_eq!(mock.calls(), 1);
+    }
+
+    #[test]
+    fn test_bridge_fee_grows_with_metadata_and_emotion() {
+        let small = calculate_emotional_bridge_fee("near", 0.0, 0.5, 0.5, 256);
+        let large = calculate_emotional_bridge_fee("near", 0.0, 0.5, 0.5, 64_000);
+        assert_eq!(small, offline_fee("near", "mint", 256).unwrap().amount.to_string());
+        assert!(large.parse::<u128>().unwrap() > small.parse::<u128>().unwrap());
+        let excited = calculate_emotional_bridge_fee("near", 1.0, 1.0, 1.0, 256);
+        assert!(excited.parse::<u128>().unwrap() > small.parse::<u128>().unwrap());
+        assert_eq!(calculate_emotional_bridge_fee("bitcoin", 0.0, 0.5, 0.5, 256), "0");
+        assert_eq!(calculate_gas_estimate("ethereum", "transfer", 0), 85_000);
+    }
+
+    #[test]
+    fn test_every_path_labels_a_reading_alike() {
+        let connector = AdvancedBlockchainConnector::new();
+        for (v, a, d) in [(0.7, 0.8, 0.3), (0.6, 0.9, 0.8), (-0.4, 0.2, 0.7), (0.3, 0.4, 0.2)] {
+            let expected = Vad::clamped(v, a, d).category().as_str();
+            let metadata: serde_json::Value =
+                serde_json::from_str(&generate_emotional_metadata("n", "d", v, a, d, 1.0, 0, "")).unwrap();
+            assert_eq!(metadata["emotional_category"], expected);
+            assert_eq!(connector.get_emotional_category(v, a, d), expected);
+            assert_eq!(crate::EmotionalVector::get_emotional_category(v, a, d), expected);
+            assert_eq!(crate::EmotionalData::get_emotional_category(v, a, d), expected);
+            assert_eq!(crate::webgpu_engine::get_emotional_category(v, a, d), expected);
+        }
+    }
 }
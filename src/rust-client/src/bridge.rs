@@ -0,0 +1,941 @@
+//! Cross-chain NFT bridge.
+//!
+//! NFTs leave their home chain by lock → attest → mint: the original goes to
+//! the bridge account in escrow and a wrapped copy is minted on the target.
+//! They come back by burn → attest → release. Each transfer is a
+//! [`BridgeMessage`] named by the hash of its canonical encoding, so
+//! submitting, attesting or relaying the same message twice changes nothing.
+//!
+//! [`LocalRelayer`] plays the relayer in-process over any chain adapters; with
+//! [`MockAdapter`](crate::chain_adapter::MockAdapter)s the whole flow runs offline.
+
+use crate::address::{AddressError, ChainAddress};
+use crate::blockchain_integration::ChainType;
+use crate::chain_adapter::{ChainAdapter, ChainAdapters, ChainError, MintRequest, TransferRequest, TxHash, TxStatus};
+use crate::session_export::canonical_json;
+use emotion_core::EmotionReading;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Layout version of [`BridgeMessage`]
+pub const BRIDGE_MESSAGE_VERSION: u8 = 1;
+
+/// `0x`-prefixed SHA-256 of a message's canonical encoding
+pub type MessageId = String;
+
+/// Errors from the bridge and its relayers
+#[derive(Debug, Clone, PartialEq)]
+pub enum BridgeError {
+    /// The message is malformed or contradicts the bridge's records
+    InvalidMessage(String),
+    UnknownMessage(MessageId),
+    /// The event does not apply in the transfer's current state
+    InvalidTransition { state: BridgeState, event: &'static str },
+    /// The event repeats a step with different data
+    Conflict(String),
+    /// The attestation is from an unknown relayer or does not verify
+    UntrustedAttestation(String),
+    /// No bridge account is configured on the chain
+    NoBridgeAccount(ChainType),
+    Chain(ChainError),
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeError::InvalidMessage(msg) => write!(f, "invalid bridge message: {}", msg),
+            BridgeError::UnknownMessage(id) => write!(f, "unknown bridge message {}", id),
+            BridgeError::InvalidTransition { state, event } => write!(f, "cannot apply {} to a {} transfer", event, state),
+            BridgeError::Conflict(msg) => write!(f, "conflicting bridge event: {}", msg),
+            BridgeError::UntrustedAttestation(relayer) => write!(f, "untrusted attestation from {}", relayer),
+            BridgeError::NoBridgeAccount(chain) => write!(f, "no bridge account on {}", chain.as_str()),
+            BridgeError::Chain(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BridgeError {}
+
+impl From<ChainError> for BridgeError {
+    fn from(e: ChainError) -> Self {
+        BridgeError::Chain(e)
+    }
+}
+
+impl From<AddressError> for BridgeError {
+    fn from(e: AddressError) -> Self {
+        BridgeError::InvalidMessage(e.to_string())
+    }
+}
+
+/// Which way an NFT crosses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BridgeDirection {
+    /// Lock the original on its home chain, mint a wrapped copy on the target
+    LockMint,
+    /// Burn a wrapped copy, release the original on its home chain
+    BurnRelease,
+}
+
+/// Where an NFT was first minted; wrapped copies keep pointing here
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftOrigin {
+    pub chain: ChainType,
+    /// Contract, program or collection on `chain`
+    pub contract: String,
+    pub token_id: String,
+}
+
+/// Canonical cross-chain NFT message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeMessage {
+    pub version: u8,
+    pub direction: BridgeDirection,
+    pub origin: NftOrigin,
+    pub source_chain: ChainType,
+    pub target_chain: ChainType,
+    /// Token given up on the source chain: the original, or the wrapped copy on the way back
+    pub token_id: String,
+    /// Normalized source-chain address giving up the token
+    pub sender: String,
+    /// Normalized target-chain address receiving it
+    pub recipient: String,
+    /// Token metadata carried to the wrapped copy
+    #[serde(default)]
+    pub metadata: Value,
+    #[serde(default)]
+    pub media_cid: String,
+    /// Emotional state the NFT carries
+    #[serde(default)]
+    pub emotion: Option<EmotionReading>,
+    /// Readings leading up to `emotion`, oldest first
+    #[serde(default)]
+    pub trajectory: Vec<EmotionReading>,
+    /// Tells repeat bridges of the same token apart
+    pub nonce: u64,
+}
+
+impl BridgeMessage {
+    /// Message taking `origin` from its home chain to `target_chain`
+    pub fn lock_and_mint(
+        origin: NftOrigin,
+        target_chain: ChainType,
+        sender: &str,
+        recipient: &str,
+        nonce: u64,
+    ) -> Result<Self, BridgeError> {
+        let (source_chain, token_id) = (origin.chain, origin.token_id.clone());
+        Self::new(BridgeDirection::LockMint, origin, source_chain, target_chain, token_id, sender, recipient, nonce)
+    }
+
+    /// Message bringing `origin` home by burning `wrapped_token_id` on `source_chain`
+    pub fn burn_and_release(
+        origin: NftOrigin,
+        source_chain: ChainType,
+        wrapped_token_id: &str,
+        sender: &str,
+        recipient: &str,
+        nonce: u64,
+    ) -> Result<Self, BridgeError> {
+        let target_chain = origin.chain;
+        let token_id = wrapped_token_id.to_string();
+        Self::new(BridgeDirection::BurnRelease, origin, source_chain, target_chain, token_id, sender, recipient, nonce)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        direction: BridgeDirection,
+        origin: NftOrigin,
+        source_chain: ChainType,
+        target_chain: ChainType,
+        token_id: String,
+        sender: &str,
+        recipient: &str,
+        nonce: u64,
+    ) -> Result<Self, BridgeError> {
+        let message = BridgeMessage {
+            version: BRIDGE_MESSAGE_VERSION,
+            direction,
+            origin,
+            source_chain,
+            target_chain,
+            token_id,
+            sender: ChainAddress::parse(source_chain, sender)?.to_string(),
+            recipient: ChainAddress::parse(target_chain, recipient)?.to_string(),
+            metadata: Value::Null,
+            media_cid: String::new(),
+            emotion: None,
+            trajectory: Vec::new(),
+            nonce,
+        };
+        message.validate()?;
+        Ok(message)
+    }
+
+    pub fn with_content(mut self, metadata: Value, media_cid: &str) -> Self {
+        self.metadata = metadata;
+        self.media_cid = media_cid.to_string();
+        self
+    }
+
+    pub fn with_emotion(mut self, emotion: EmotionReading, trajectory: Vec<EmotionReading>) -> Self {
+        self.emotion = Some(emotion);
+        self.trajectory = trajectory;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), BridgeError> {
+        let invalid = |msg: &str| Err(BridgeError::InvalidMessage(msg.to_string()));
+        if self.version != BRIDGE_MESSAGE_VERSION {
+            return invalid("unsupported message version");
+        }
+        if self.source_chain == self.target_chain {
+            return invalid("source and target chain are the same");
+        }
+        if self.token_id.is_empty() || self.origin.token_id.is_empty() || self.origin.contract.is_empty() {
+            return invalid("token ids and origin contract must not be empty");
+        }
+        match self.direction {
+            BridgeDirection::LockMint if self.source_chain != self.origin.chain || self.token_id != self.origin.token_id => {
+                return invalid("lock-and-mint must start from the original token");
+            }
+            BridgeDirection::BurnRelease if self.target_chain != self.origin.chain => {
+                return invalid("burn-and-release must end on the origin chain");
+            }
+            _ => {}
+        }
+        if ChainAddress::parse(self.source_chain, &self.sender)?.as_str() != self.sender
+            || ChainAddress::parse(self.target_chain, &self.recipient)?.as_str() != self.recipient
+        {
+            return invalid("addresses must be normalized");
+        }
+        for reading in self.emotion.iter().chain(&self.trajectory) {
+            reading.validate().map_err(|e| BridgeError::InvalidMessage(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// JSON with object keys sorted and no whitespace; the bytes behind [`BridgeMessage::id`]
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        canonical_json(self).expect("message serializes")
+    }
+
+    pub fn id(&self) -> MessageId {
+        format!("0x{}", hex::encode(Sha256::digest(self.canonical_bytes())))
+    }
+
+    /// Token the target chain delivers: a wrapped id derived from the message,
+    /// or the original on the way back. Wrapped ids are 128-bit decimals, except
+    /// on Polkadot, whose pallet-nfts item ids are u32; `initiate` refuses a
+    /// message whose wrapped id is already in circulation.
+    pub fn target_token_id(&self) -> String {
+        match self.direction {
+            BridgeDirection::LockMint => {
+                let digest = Sha256::digest(self.canonical_bytes());
+                match self.target_chain {
+                    ChainType::Polkadot => u32::from_be_bytes(digest[..4].try_into().expect("4 bytes")).to_string(),
+                    _ => u128::from_be_bytes(digest[..16].try_into().expect("16 bytes")).to_string(),
+                }
+            }
+            BridgeDirection::BurnRelease => self.origin.token_id.clone(),
+        }
+    }
+
+    /// Carried metadata plus bridge provenance and the emotional payload
+    pub fn wrapped_metadata(&self) -> Value {
+        let mut metadata = match &self.metadata {
+            Value::Object(_) => self.metadata.clone(),
+            _ => json!({}),
+        };
+        metadata["bridge"] = json!({ "message_id": self.id(), "origin": self.origin });
+        if let Some(emotion) = &self.emotion {
+            metadata["emotion"] = json!(emotion);
+        }
+        if !self.trajectory.is_empty() {
+            metadata["emotional_trajectory"] = json!(self.trajectory);
+        }
+        metadata
+    }
+}
+
+/// Where a bridge transfer stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BridgeState {
+    /// Recorded, nothing sent yet
+    Pending,
+    /// Original sent to the bridge account on its home chain
+    Locked,
+    /// Wrapped copy handed back to the bridge account, out of circulation
+    Burned,
+    /// Enough relayers vouched for the lock or burn
+    Attested,
+    /// Wrapped copy delivered on the target chain
+    Minted,
+    /// Original delivered back on its home chain
+    Released,
+    Failed,
+}
+
+impl BridgeState {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, BridgeState::Minted | BridgeState::Released | BridgeState::Failed)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BridgeState::Pending => "pending",
+            BridgeState::Locked => "locked",
+            BridgeState::Burned => "burned",
+            BridgeState::Attested => "attested",
+            BridgeState::Minted => "minted",
+            BridgeState::Released => "released",
+            BridgeState::Failed => "failed",
+        }
+    }
+
+    /// `bridge_status` string of the NEAR contract's `CrossChainInfo`
+    pub fn legacy_status(&self) -> &'static str {
+        match self {
+            BridgeState::Minted | BridgeState::Released => "bridged",
+            BridgeState::Failed => "failed",
+            _ => "pending",
+        }
+    }
+
+    /// `bridge_status` code of the Solana program's `CrossChainInfo`
+    pub fn legacy_code(&self) -> u8 {
+        match self.legacy_status() {
+            "bridged" => 1,
+            "failed" => 2,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for BridgeState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A relayer's word that a message's lock or burn transaction is final.
+///
+/// Signed with a key the relayer shares with the bridge (keyed SHA-256),
+/// standing in for chain signatures in the local relayer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attestation {
+    pub message_id: MessageId,
+    pub source_tx: TxHash,
+    pub relayer: String,
+    pub signature: String,
+}
+
+impl Attestation {
+    pub fn sign(message_id: &str, source_tx: &str, relayer: &str, key: &[u8; 32]) -> Self {
+        Attestation {
+            message_id: message_id.to_string(),
+            source_tx: source_tx.to_string(),
+            relayer: relayer.to_string(),
+            signature: attestation_digest(key, message_id, source_tx, relayer),
+        }
+    }
+
+    pub fn verify(&self, key: &[u8; 32]) -> bool {
+        self.signature == attestation_digest(key, &self.message_id, &self.source_tx, &self.relayer)
+    }
+}
+
+fn attestation_digest(key: &[u8; 32], message_id: &str, source_tx: &str, relayer: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key);
+    for part in [message_id, source_tx, relayer] {
+        hasher.update([0]);
+        hasher.update(part.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Something that happened to a transfer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BridgeEvent {
+    Locked { tx: TxHash },
+    Burned { tx: TxHash },
+    Attested(Attestation),
+    /// A target-chain transaction went out: mint, delivery or release
+    TargetSubmitted { tx: TxHash },
+    Minted,
+    Released,
+    Failed { reason: String },
+}
+
+impl BridgeEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            BridgeEvent::Locked { .. } => "locked",
+            BridgeEvent::Burned { .. } => "burned",
+            BridgeEvent::Attested(_) => "attested",
+            BridgeEvent::TargetSubmitted { .. } => "target_submitted",
+            BridgeEvent::Minted => "minted",
+            BridgeEvent::Released => "released",
+            BridgeEvent::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// One message and how far it got
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeTransfer {
+    pub message_id: MessageId,
+    pub message: BridgeMessage,
+    pub state: BridgeState,
+    /// Lock or burn transaction on the source chain
+    pub source_tx: Option<TxHash>,
+    pub attestations: Vec<Attestation>,
+    /// Target-chain transactions in order: mint then delivery, or the release
+    pub target_txs: Vec<TxHash>,
+    pub failure: Option<String>,
+    /// States passed through, oldest first
+    pub history: Vec<BridgeState>,
+}
+
+impl BridgeTransfer {
+    pub fn new(message: BridgeMessage) -> Self {
+        BridgeTransfer {
+            message_id: message.id(),
+            message,
+            state: BridgeState::Pending,
+            source_tx: None,
+            attestations: Vec::new(),
+            target_txs: Vec::new(),
+            failure: None,
+            history: vec![BridgeState::Pending],
+        }
+    }
+
+    /// Target-chain transactions the transfer needs
+    pub fn target_steps(&self) -> usize {
+        match self.message.direction {
+            BridgeDirection::LockMint => 2,
+            BridgeDirection::BurnRelease => 1,
+        }
+    }
+
+    /// Apply `event`, needing `threshold` distinct attestations. Returns
+    /// `Ok(false)` when the event was already applied.
+    pub fn apply(&mut self, event: BridgeEvent, threshold: usize) -> Result<bool, BridgeError> {
+        use BridgeState::*;
+        let state = self.state;
+        let direction = self.message.direction;
+        let next = match (state, event) {
+            (Pending, BridgeEvent::Locked { tx }) if direction == BridgeDirection::LockMint => {
+                self.source_tx = Some(tx);
+                Locked
+            }
+            (Pending, BridgeEvent::Burned { tx }) if direction == BridgeDirection::BurnRelease => {
+                self.source_tx = Some(tx);
+                Burned
+            }
+            (Locked, BridgeEvent::Locked { tx }) | (Burned, BridgeEvent::Burned { tx }) => {
+                return match self.source_tx.as_deref() == Some(tx.as_str()) {
+                    true => Ok(false),
+                    false => Err(BridgeError::Conflict(format!("source transaction is already {:?}", self.source_tx))),
+                };
+            }
+            (Locked | Burned, BridgeEvent::Attested(attestation)) => {
+                if self.source_tx.as_deref() != Some(attestation.source_tx.as_str()) {
+                    return Err(BridgeError::Conflict(format!("attestation is for {}", attestation.source_tx)));
+                }
+                if self.attestations.iter().any(|a| a.relayer == attestation.relayer) {
+                    return Ok(false);
+                }
+                self.attestations.push(attestation);
+                if self.attestations.len() < threshold {
+                    return Ok(true);
+                }
+                Attested
+            }
+            // Attestations arriving after the quorum add nothing
+            (Attested | Minted | Released, BridgeEvent::Attested(_)) => return Ok(false),
+            (Attested, BridgeEvent::TargetSubmitted { tx }) if !self.target_txs.contains(&tx) => {
+                if self.target_txs.len() == self.target_steps() {
+                    return Err(BridgeError::InvalidTransition { state, event: "target_submitted" });
+                }
+                self.target_txs.push(tx);
+                return Ok(true);
+            }
+            (Attested | Minted | Released, BridgeEvent::TargetSubmitted { tx }) if self.target_txs.contains(&tx) => {
+                return Ok(false);
+            }
+            (Attested, BridgeEvent::Minted)
+                if direction == BridgeDirection::LockMint && self.target_txs.len() == self.target_steps() =>
+            {
+                Minted
+            }
+            (Attested, BridgeEvent::Released)
+                if direction == BridgeDirection::BurnRelease && self.target_txs.len() == self.target_steps() =>
+            {
+                Released
+            }
+            (Minted, BridgeEvent::Minted) | (Released, BridgeEvent::Released) | (Failed, BridgeEvent::Failed { .. }) => {
+                return Ok(false);
+            }
+            (state, BridgeEvent::Failed { reason }) if !state.is_terminal() => {
+                self.failure = Some(reason);
+                Failed
+            }
+            (state, event) => return Err(BridgeError::InvalidTransition { state, event: event.name() }),
+        };
+        self.state = next;
+        self.history.push(next);
+        Ok(true)
+    }
+}
+
+/// Bridge accounts, trusted relayers and every transfer's state
+#[derive(Debug, Clone)]
+pub struct NftBridge {
+    /// Account on each chain that escrows originals and mints wrapped copies
+    accounts: HashMap<ChainType, String>,
+    relayers: BTreeMap<String, [u8; 32]>,
+    threshold: usize,
+    transfers: BTreeMap<MessageId, BridgeTransfer>,
+    /// Wrapped copies in circulation, by chain and token id, with the message that minted them
+    wrapped: HashMap<(ChainType, String), MessageId>,
+}
+
+impl Default for NftBridge {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl NftBridge {
+    /// Bridge needing `threshold` relayer attestations per transfer
+    pub fn new(threshold: usize) -> Self {
+        NftBridge {
+            accounts: HashMap::new(),
+            relayers: BTreeMap::new(),
+            threshold: threshold.max(1),
+            transfers: BTreeMap::new(),
+            wrapped: HashMap::new(),
+        }
+    }
+
+    pub fn set_bridge_account(&mut self, chain: ChainType, account: &str) -> Result<(), BridgeError> {
+        self.accounts.insert(chain, ChainAddress::parse(chain, account)?.to_string());
+        Ok(())
+    }
+
+    pub fn bridge_account(&self, chain: ChainType) -> Result<&str, BridgeError> {
+        self.accounts.get(&chain).map(String::as_str).ok_or(BridgeError::NoBridgeAccount(chain))
+    }
+
+    pub fn trust_relayer(&mut self, relayer: &str, key: [u8; 32]) {
+        self.relayers.insert(relayer.to_string(), key);
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn get(&self, message_id: &str) -> Option<&BridgeTransfer> {
+        self.transfers.get(message_id)
+    }
+
+    pub fn transfers(&self) -> impl Iterator<Item = &BridgeTransfer> {
+        self.transfers.values()
+    }
+
+    /// Ids of transfers that have not finished or failed
+    pub fn open_transfers(&self) -> Vec<MessageId> {
+        self.transfers.values().filter(|t| !t.state.is_terminal()).map(|t| t.message_id.clone()).collect()
+    }
+
+    /// Record `message` and hand the token to the bridge account on the source chain.
+    /// Repeating a message that already left `Pending` returns its id and sends nothing.
+    pub async fn initiate(&mut self, source: &dyn ChainAdapter, message: BridgeMessage) -> Result<MessageId, BridgeError> {
+        message.validate()?;
+        if source.chain() != message.source_chain {
+            return Err(BridgeError::InvalidMessage(format!("adapter is for {}", source.chain().as_str())));
+        }
+        if let Some(account) = source.account_id() {
+            if ChainAddress::parse(message.source_chain, &account)?.as_str() != message.sender {
+                return Err(BridgeError::InvalidMessage("adapter does not sign for the sender".to_string()));
+            }
+        }
+        let id = message.id();
+        if self.transfers.get(&id).is_some_and(|t| t.state != BridgeState::Pending) {
+            return Ok(id);
+        }
+        if message.direction == BridgeDirection::LockMint {
+            let wrapped = (message.target_chain, message.target_token_id());
+            if self.wrapped.get(&wrapped).is_some_and(|lock_id| *lock_id != id) {
+                return Err(BridgeError::InvalidMessage(format!(
+                    "wrapped id {} is already in circulation on {}",
+                    wrapped.1,
+                    wrapped.0.as_str()
+                )));
+            }
+        }
+        if message.direction == BridgeDirection::BurnRelease {
+            let minted_by = self
+                .wrapped
+                .get(&(message.source_chain, message.token_id.clone()))
+                .and_then(|lock_id| self.transfers.get(lock_id));
+            if minted_by.map(|lock| &lock.message.origin) != Some(&message.origin) {
+                return Err(BridgeError::InvalidMessage(format!(
+                    "{} is not a wrapped copy of {} in circulation",
+                    message.token_id, message.origin.token_id
+                )));
+            }
+        }
+        let account = self.bridge_account(message.source_chain)?.to_string();
+        let request = TransferRequest { token_id: message.token_id.clone(), receiver: account, memo: Some(id.clone()) };
+        let direction = message.direction;
+        self.transfers.entry(id.clone()).or_insert_with(|| BridgeTransfer::new(message));
+        let tx = source.transfer(&request).await?;
+        let event = match direction {
+            BridgeDirection::LockMint => BridgeEvent::Locked { tx },
+            BridgeDirection::BurnRelease => BridgeEvent::Burned { tx },
+        };
+        self.apply(&id, event)?;
+        Ok(id)
+    }
+
+    /// Apply `event` to a transfer, checking attestations against trusted relayers
+    pub fn apply(&mut self, message_id: &str, event: BridgeEvent) -> Result<bool, BridgeError> {
+        if let BridgeEvent::Attested(attestation) = &event {
+            let key = self.relayers.get(&attestation.relayer);
+            if attestation.message_id != message_id || !key.is_some_and(|key| attestation.verify(key)) {
+                return Err(BridgeError::UntrustedAttestation(attestation.relayer.clone()));
+            }
+        }
+        let transfer =
+            self.transfers.get_mut(message_id).ok_or_else(|| BridgeError::UnknownMessage(message_id.to_string()))?;
+        let changed = transfer.apply(event, self.threshold)?;
+        let message = &transfer.message;
+        match transfer.state {
+            BridgeState::Minted if changed => {
+                self.wrapped.insert((message.target_chain, message.target_token_id()), message_id.to_string());
+            }
+            BridgeState::Released if changed => {
+                self.wrapped.remove(&(message.source_chain, message.token_id.clone()));
+            }
+            _ => {}
+        }
+        Ok(changed)
+    }
+}
+
+/// What a relay pass did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RelayPass {
+    /// Transfers that moved a step
+    pub moved: usize,
+    /// Transfers that could not move, left as they were for a later pass
+    pub errors: Vec<(MessageId, BridgeError)>,
+}
+
+/// In-process relayer: attests finished locks and burns, then mints,
+/// delivers and releases through adapters signing as the bridge account
+pub struct LocalRelayer {
+    id: String,
+    key: [u8; 32],
+    adapters: ChainAdapters,
+}
+
+impl LocalRelayer {
+    pub fn new(id: &str, key: [u8; 32]) -> Self {
+        LocalRelayer { id: id.to_string(), key, adapters: ChainAdapters::default() }
+    }
+
+    /// Adapter signing as the bridge account on its chain
+    pub fn with_adapter(mut self, adapter: Box<dyn ChainAdapter>) -> Self {
+        self.adapters.insert(adapter);
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn attest(&self, message_id: &str, source_tx: &str) -> Attestation {
+        Attestation::sign(message_id, source_tx, &self.id, &self.key)
+    }
+
+    /// Move every open transfer at most one step. A transfer that errors,
+    /// e.g. on a chain without an adapter, stays put and the pass goes on.
+    pub async fn relay_once(&self, bridge: &mut NftBridge) -> RelayPass {
+        let mut pass = RelayPass::default();
+        for id in bridge.open_transfers() {
+            match self.relay(bridge, &id).await {
+                Ok(true) => pass.moved += 1,
+                Ok(false) => {}
+                Err(e) => pass.errors.push((id, e)),
+            }
+        }
+        pass
+    }
+
+    /// Relay until nothing moves, e.g. every transfer is done or waiting on a
+    /// chain; `errors` are those of the last pass
+    pub async fn run(&self, bridge: &mut NftBridge) -> RelayPass {
+        let mut total = 0;
+        loop {
+            let pass = self.relay_once(bridge).await;
+            if pass.moved == 0 {
+                return RelayPass { moved: total, errors: pass.errors };
+            }
+            total += pass.moved;
+        }
+    }
+
+    async fn relay(&self, bridge: &mut NftBridge, id: &str) -> Result<bool, BridgeError> {
+        let transfer = bridge.get(id).cloned().ok_or_else(|| BridgeError::UnknownMessage(id.to_string()))?;
+        match self.next_event(&transfer).await? {
+            Some(event) => bridge.apply(id, event),
+            None => Ok(false),
+        }
+    }
+
+    async fn next_event(&self, transfer: &BridgeTransfer) -> Result<Option<BridgeEvent>, BridgeError> {
+        let message = &transfer.message;
+        match transfer.state {
+            BridgeState::Locked | BridgeState::Burned => {
+                if transfer.attestations.iter().any(|a| a.relayer == self.id) {
+                    return Ok(None);
+                }
+                let tx = transfer.source_tx.as_deref().unwrap_or_default();
+                Ok(match self.adapters.get(message.source_chain)?.get_tx_status(tx).await? {
+                    TxStatus::Confirmed => Some(BridgeEvent::Attested(self.attest(&transfer.message_id, tx))),
+                    TxStatus::Failed(reason) => Some(BridgeEvent::Failed { reason }),
+                    TxStatus::Pending | TxStatus::Unknown => None,
+                })
+            }
+            BridgeState::Attested => {
+                let target = self.adapters.get(message.target_chain)?;
+                if let Some(last) = transfer.target_txs.last() {
+                    match target.get_tx_status(last).await? {
+                        TxStatus::Confirmed if transfer.target_txs.len() == transfer.target_steps() => {
+                            return Ok(Some(match message.direction {
+                                BridgeDirection::LockMint => BridgeEvent::Minted,
+                                BridgeDirection::BurnRelease => BridgeEvent::Released,
+                            }));
+                        }
+                        TxStatus::Confirmed => {}
+                        TxStatus::Failed(reason) => return Ok(Some(BridgeEvent::Failed { reason })),
+                        TxStatus::Pending | TxStatus::Unknown => return Ok(None),
+                    }
+                }
+                let deliver = TransferRequest {
+                    token_id: message.target_token_id(),
+                    receiver: message.recipient.clone(),
+                    memo: Some(transfer.message_id.clone()),
+                };
+                let tx = match (message.direction, transfer.target_txs.len()) {
+                    (BridgeDirection::LockMint, 0) => {
+                        let request = MintRequest {
+                            token_id: message.target_token_id(),
+                            metadata: message.wrapped_metadata(),
+                            media_cid: message.media_cid.clone(),
+                            params: json!({ "bridge_message_id": transfer.message_id }),
+                        };
+                        target.mint(&request).await?
+                    }
+                    _ => target.transfer(&deliver).await?,
+                };
+                Ok(Some(BridgeEvent::TargetSubmitted { tx }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_adapter::{MockAdapter, NftToken};
+    use emotion_core::Vad;
+
+    const BRIDGE_POLYGON: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    const CAROL_POLYGON: &str = "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+
+    struct Chains {
+        near: MockAdapter,
+        polygon: MockAdapter,
+        bridge: NftBridge,
+        relayer: LocalRelayer,
+    }
+
+    fn chains(threshold: usize) -> Chains {
+        let near = MockAdapter::new(ChainType::Near, "alice.near").with_token(NftToken {
+            token_id: "loop-1".to_string(),
+            owner: "alice.near".to_string(),
+            metadata: json!({ "title": "Loop" }),
+            collection: None,
+        });
+        let polygon = MockAdapter::new(ChainType::Polygon, CAROL_POLYGON);
+        let mut bridge = NftBridge::new(threshold);
+        bridge.set_bridge_account(ChainType::Near, "bridge.near").unwrap();
+        bridge.set_bridge_account(ChainType::Polygon, BRIDGE_POLYGON).unwrap();
+        bridge.trust_relayer("r1", [1; 32]);
+        let relayer = LocalRelayer::new("r1", [1; 32])
+            .with_adapter(Box::new(near.for_account("bridge.near")))
+            .with_adapter(Box::new(polygon.for_account(BRIDGE_POLYGON)));
+        Chains { near, polygon, bridge, relayer }
+    }
+
+    fn origin() -> NftOrigin {
+        NftOrigin { chain: ChainType::Near, contract: "nft.compiling.near".to_string(), token_id: "loop-1".to_string() }
+    }
+
+    fn lock_message(nonce: u64) -> BridgeMessage {
+        let emotion = EmotionReading::new(Vad::new(0.7, 0.8, 0.6).unwrap()).with_confidence(0.9).unwrap();
+        BridgeMessage::lock_and_mint(origin(), ChainType::Polygon, "alice.near", &CAROL_POLYGON.to_lowercase(), nonce)
+            .unwrap()
+            .with_content(json!({ "title": "Loop" }), "bafyloop")
+            .with_emotion(emotion, vec![EmotionReading::new(Vad::NEUTRAL)])
+    }
+
+    #[tokio::test]
+    async fn test_lock_mint_then_burn_release_round_trip() {
+        let Chains { near, polygon, mut bridge, relayer } = chains(1);
+        let message = lock_message(7);
+        let wrapped_id = message.target_token_id();
+        let id = bridge.initiate(&near, message).await.unwrap();
+        assert_eq!(bridge.get(&id).unwrap().state, BridgeState::Locked);
+        assert_eq!(near.get_user_nfts("bridge.near").await.unwrap()[0].token_id, "loop-1");
+
+        assert!(relayer.run(&mut bridge).await.errors.is_empty());
+        let transfer = bridge.get(&id).unwrap();
+        assert_eq!(transfer.history, [BridgeState::Pending, BridgeState::Locked, BridgeState::Attested, BridgeState::Minted]);
+        let wrapped = polygon.get_user_nfts(CAROL_POLYGON).await.unwrap();
+        assert_eq!(wrapped[0].token_id, wrapped_id);
+        assert_eq!(wrapped[0].metadata["bridge"]["message_id"], id.as_str());
+        let valence = wrapped[0].metadata["emotion"]["vad"]["valence"].as_f64().unwrap();
+        assert!((valence - 0.7).abs() < 1e-6);
+        assert_eq!(wrapped[0].metadata["media"], "ipfs://bafyloop");
+
+        let back = BridgeMessage::burn_and_release(origin(), ChainType::Polygon, &wrapped_id, CAROL_POLYGON, "bob.near", 8)
+            .unwrap();
+        let back_id = bridge.initiate(&polygon, back).await.unwrap();
+        assert!(relayer.run(&mut bridge).await.errors.is_empty());
+        assert_eq!(bridge.get(&back_id).unwrap().state, BridgeState::Released);
+        assert_eq!(near.get_user_nfts("bob.near").await.unwrap()[0].token_id, "loop-1");
+        assert!(polygon.get_user_nfts(CAROL_POLYGON).await.unwrap().is_empty());
+
+        // The wrapped copy is out of circulation now
+        let again = BridgeMessage::burn_and_release(origin(), ChainType::Polygon, &wrapped_id, CAROL_POLYGON, "bob.near", 9)
+            .unwrap();
+        assert!(matches!(bridge.initiate(&polygon, again).await, Err(BridgeError::InvalidMessage(_))));
+    }
+
+    #[tokio::test]
+    async fn test_messages_are_idempotent() {
+        let Chains { near, polygon, mut bridge, relayer } = chains(1);
+        let id = bridge.initiate(&near, lock_message(7)).await.unwrap();
+        assert_eq!(bridge.initiate(&near, lock_message(7)).await.unwrap(), id);
+        let source_tx = bridge.get(&id).unwrap().source_tx.clone().unwrap();
+        assert_eq!(bridge.apply(&id, BridgeEvent::Locked { tx: source_tx }), Ok(false));
+        assert!(matches!(bridge.apply(&id, BridgeEvent::Locked { tx: "other".into() }), Err(BridgeError::Conflict(_))));
+
+        assert!(relayer.run(&mut bridge).await.errors.is_empty());
+        assert_eq!(relayer.run(&mut bridge).await, RelayPass::default());
+        assert_eq!(bridge.apply(&id, BridgeEvent::Minted), Ok(false));
+        assert_eq!(polygon.get_user_nfts(CAROL_POLYGON).await.unwrap().len(), 1);
+
+        // A wrapped id another message already minted is refused
+        let colliding = lock_message(8);
+        bridge.wrapped.insert((ChainType::Polygon, colliding.target_token_id()), id);
+        assert!(matches!(bridge.initiate(&near, colliding).await, Err(BridgeError::InvalidMessage(_))));
+    }
+
+    #[tokio::test]
+    async fn test_attestations_need_trusted_quorum() {
+        let Chains { near, polygon, mut bridge, relayer } = chains(2);
+        let id = bridge.initiate(&near, lock_message(7)).await.unwrap();
+        let tx = bridge.get(&id).unwrap().source_tx.clone().unwrap();
+
+        let forged = Attestation::sign(&id, &tx, "r1", &[9; 32]);
+        assert!(matches!(bridge.apply(&id, BridgeEvent::Attested(forged)), Err(BridgeError::UntrustedAttestation(_))));
+
+        assert!(relayer.run(&mut bridge).await.errors.is_empty());
+        assert_eq!(bridge.get(&id).unwrap().state, BridgeState::Locked);
+        assert_eq!(bridge.apply(&id, BridgeEvent::Minted), Err(BridgeError::InvalidTransition {
+            state: BridgeState::Locked,
+            event: "minted"
+        }));
+
+        bridge.trust_relayer("r2", [2; 32]);
+        let second = LocalRelayer::new("r2", [2; 32])
+            .with_adapter(Box::new(near.for_account("bridge.near")))
+            .with_adapter(Box::new(polygon.for_account(BRIDGE_POLYGON)));
+        assert!(second.run(&mut bridge).await.errors.is_empty());
+        let transfer = bridge.get(&id).unwrap();
+        assert_eq!((transfer.state, transfer.attestations.len()), (BridgeState::Minted, 2));
+    }
+
+    #[tokio::test]
+    async fn test_failed_lock_fails_the_transfer() {
+        let Chains { near, mut bridge, relayer, .. } = chains(1);
+        let mut message = lock_message(7);
+        message.origin.token_id = "missing".to_string();
+        message.token_id = "missing".to_string();
+        let id = bridge.initiate(&near, message).await.unwrap();
+        assert!(relayer.run(&mut bridge).await.errors.is_empty());
+        let transfer = bridge.get(&id).unwrap();
+        assert_eq!((transfer.state, transfer.failure.as_deref()), (BridgeState::Failed, Some("unknown token")));
+        assert_eq!(transfer.state.legacy_code(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unroutable_transfer_does_not_stop_the_pass() {
+        let Chains { near, polygon, mut bridge, relayer } = chains(1);
+        let near = near.with_token(NftToken {
+            token_id: "loop-2".to_string(),
+            owner: "alice.near".to_string(),
+            metadata: json!({ "title": "Loop 2" }),
+            collection: None,
+        });
+        // The relayer has no Solana adapter
+        let origin = NftOrigin { token_id: "loop-2".to_string(), ..origin() };
+        let recipient = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+        let stuck = BridgeMessage::lock_and_mint(origin, ChainType::Solana, "alice.near", recipient, 1).unwrap();
+        let stuck = bridge.initiate(&near, stuck).await.unwrap();
+        let id = bridge.initiate(&near, lock_message(7)).await.unwrap();
+
+        let pass = relayer.run(&mut bridge).await;
+        assert_eq!(pass.errors, [(stuck.clone(), BridgeError::Chain(ChainError::NotConnected(ChainType::Solana)))]);
+        assert_eq!(bridge.get(&id).unwrap().state, BridgeState::Minted);
+        assert_eq!(bridge.get(&stuck).unwrap().state, BridgeState::Attested);
+        assert_eq!(polygon.get_user_nfts(CAROL_POLYGON).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_canonical_id_ignores_key_order_and_validates() {
+        let a = lock_message(1).with_content(serde_json::from_str(r#"{"a":1,"b":{"y":2,"x":3}}"#).unwrap(), "c");
+        let b = lock_message(1).with_content(serde_json::from_str(r#"{"b":{"x":3,"y":2},"a":1}"#).unwrap(), "c");
+        assert_eq!(a.id(), b.id());
+        assert_ne!(a.id(), lock_message(2).id());
+        let roundtrip: BridgeMessage = serde_json::from_slice(&a.canonical_bytes()).unwrap();
+        assert_eq!(roundtrip.id(), a.id());
+
+        // Wrapped ids use 128 bits of the hash where the target chain allows
+        assert!(a.target_token_id().parse::<u128>().unwrap() > u128::from(u64::MAX));
+        let mut to_polkadot = a.clone();
+        to_polkadot.target_chain = ChainType::Polkadot;
+        assert!(to_polkadot.target_token_id().parse::<u32>().is_ok());
+
+        assert!(BridgeMessage::lock_and_mint(origin(), ChainType::Near, "alice.near", "bob.near", 1).is_err());
+        assert!(BridgeMessage::lock_and_mint(origin(), ChainType::Polygon, "alice.near", "bob.near", 1).is_err());
+        let mut unnormalized = lock_message(1);
+        unnormalized.recipient = CAROL_POLYGON.to_lowercase();
+        assert!(unnormalized.validate().is_err());
+    }
+}
@@ -0,0 +1,666 @@
+//! Chain adapters: one async interface for minting, transfers, NFT lookups,
+//! transaction status and fees, whichever chain a connector talks to.
+//!
+//! Futures are `Send` on native targets so backend services can spawn them,
+//! and `?Send` on wasm32 where they wrap browser promises.
+
+use crate::blockchain_integration::ChainType;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// `Send + Sync` on native targets, no bound on wasm32
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send + Sync {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + Sync + ?Sized> MaybeSend for T {}
+
+/// `Send + Sync` on native targets, no bound on wasm32
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// Errors from chain adapters, signers and transports
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainError {
+    /// No adapter is connected for the chain
+    NotConnected(ChainType),
+    /// Minting and transfers need a signer
+    NoSigner,
+    /// The adapter cannot do this operation
+    Unsupported(&'static str),
+    /// The request cannot be expressed on this chain
+    InvalidRequest(String),
+    /// The node could not be reached
+    Transport(String),
+    /// The node answered with a JSON-RPC error
+    Rpc { code: i64, message: String },
+    /// The node answered with something we could not read
+    InvalidResponse(String),
+    /// The signer refused or failed
+    Signer(String),
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::NotConnected(chain) => write!(f, "{} not connected", chain.as_str()),
+            ChainError::NoSigner => write!(f, "no signer configured"),
+            ChainError::Unsupported(what) => write!(f, "{} is not supported on this chain", what),
+            ChainError::InvalidRequest(msg) => write!(f, "invalid request: {}", msg),
+            ChainError::Transport(msg) => write!(f, "transport error: {}", msg),
+            ChainError::Rpc { code, message } => write!(f, "RPC error {}: {}", code, message),
+            ChainError::InvalidResponse(msg) => write!(f, "invalid RPC response: {}", msg),
+            ChainError::Signer(msg) => write!(f, "signer error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
+
+/// Transaction hash in the chain's usual text form
+pub type TxHash = String;
+
+/// Large amounts travel as decimal strings so JavaScript keeps every digit
+pub(crate) mod amount_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(amount: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&amount.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Request to mint one NFT to the signer's account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MintRequest {
+    pub token_id: String,
+    /// Token metadata object; `media` is filled in from `media_cid`
+    pub metadata: Value,
+    /// IPFS CID of the media or metadata document
+    pub media_cid: String,
+    /// Extra arguments passed through to the contract, e.g. `initial_emotion`
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl MintRequest {
+    pub fn media_uri(&self) -> String {
+        format!("ipfs://{}", self.media_cid)
+    }
+
+    /// Metadata with `media` pointing at `media_cid` unless already set
+    pub fn token_metadata(&self) -> Value {
+        let mut metadata = self.metadata.clone();
+        if let Value::Object(fields) = &mut metadata {
+            fields.entry("media").or_insert_with(|| Value::String(self.media_uri()));
+        }
+        metadata
+    }
+
+    /// Bytes of metadata and extra arguments the mint puts on chain
+    pub fn payload_bytes(&self) -> u64 {
+        let params = match &self.params {
+            Value::Null => 0,
+            params => params.to_string().len(),
+        };
+        (self.token_metadata().to_string().len() + params) as u64
+    }
+}
+
+/// Request to move one NFT from the signer to `receiver`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransferRequest {
+    pub token_id: String,
+    pub receiver: String,
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+/// NFT held by an account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NftToken {
+    pub token_id: String,
+    pub owner: String,
+    #[serde(default)]
+    pub metadata: Value,
+    /// Contract, program or collection the token belongs to, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+}
+
+/// Where a submitted transaction stands
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum TxStatus {
+    Pending,
+    Confirmed,
+    Failed(String),
+    /// The node has no record of the hash
+    Unknown,
+}
+
+/// Operations a fee can be estimated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeOperation {
+    Mint,
+    Transfer,
+}
+
+impl FromStr for FeeOperation {
+    type Err = ChainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mint" => Ok(FeeOperation::Mint),
+            "transfer" => Ok(FeeOperation::Transfer),
+            other => Err(ChainError::InvalidRequest(format!("unknown operation '{}'", other))),
+        }
+    }
+}
+
+/// Expected cost of an operation in the chain's smallest unit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    pub chain: ChainType,
+    pub operation: FeeOperation,
+    /// Everything the sender needs, `deposit` included
+    #[serde(with = "amount_string")]
+    pub amount: u128,
+    /// Part of `amount` locked as storage staking, rent or deposits rather than spent
+    #[serde(with = "amount_string", default)]
+    pub deposit: u128,
+    /// Gas, compute units or weight `ref_time` the operation uses
+    #[serde(default)]
+    pub units: u64,
+    pub symbol: String,
+    /// Decimal places between `amount` and one whole `symbol`
+    pub decimals: u8,
+}
+
+impl FeeEstimate {
+    pub fn new(chain: ChainType, operation: FeeOperation, amount: u128) -> Self {
+        FeeEstimate {
+            chain,
+            operation,
+            amount,
+            deposit: 0,
+            units: 0,
+            symbol: chain.native_symbol().to_string(),
+            decimals: chain.native_decimals(),
+        }
+    }
+}
+
+/// Chain state a signer needs on top of the call itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "chain", rename_all = "snake_case")]
+pub enum TxContext {
+    Near {
+        /// Next access-key nonce, when the signer exposed its public key
+        nonce: Option<u64>,
+        block_hash: String,
+        /// Gas attached to the function call
+        gas: u64,
+    },
+    Solana {
+        recent_blockhash: String,
+    },
+    Evm {
+        chain_id: u64,
+        nonce: u64,
+        #[serde(with = "amount_string")]
+        gas_price: u128,
+        gas_limit: u64,
+    },
+    Polkadot {
+        nonce: u64,
+        genesis_hash: String,
+        block_hash: String,
+        spec_version: u32,
+        transaction_version: u32,
+    },
+}
+
+/// Contract call for a signer to encode and sign
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub signer_id: String,
+    /// Contract, program or pallet receiving the call
+    pub receiver_id: String,
+    pub method: String,
+    pub args: Value,
+    /// Attached value in the chain's smallest unit
+    #[serde(with = "amount_string")]
+    pub deposit: u128,
+    pub context: TxContext,
+}
+
+/// Turns prepared calls into signed transactions ready for broadcast
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait Signer: MaybeSend {
+    /// Account the signer acts for
+    fn account_id(&self) -> String;
+
+    /// Public key in the chain's usual text form, if the signer exposes it
+    fn public_key(&self) -> Option<String> {
+        None
+    }
+
+    /// Encode and sign `tx` in the chain's wire format
+    async fn sign_transaction(&self, tx: &UnsignedTransaction) -> Result<Vec<u8>, ChainError>;
+}
+
+/// One chain's NFT operations
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait ChainAdapter: MaybeSend {
+    fn chain(&self) -> ChainType;
+
+    /// Contract, program or collection the adapter mints into
+    fn collection(&self) -> String;
+
+    /// Account the adapter signs for, if it has a signer
+    fn account_id(&self) -> Option<String> {
+        None
+    }
+
+    /// Mint to the signer's account and return the transaction hash
+    async fn mint(&self, request: &MintRequest) -> Result<TxHash, ChainError>;
+
+    async fn transfer(&self, request: &TransferRequest) -> Result<TxHash, ChainError>;
+
+    async fn get_user_nfts(&self, owner: &str) -> Result<Vec<NftToken>, ChainError>;
+
+    async fn get_tx_status(&self, tx_hash: &str) -> Result<TxStatus, ChainError>;
+
+    /// Fee for `operation` carrying `payload_bytes` of serialized metadata,
+    /// at prices refreshed from the node
+    async fn estimate_fee(&self, operation: FeeOperation, payload_bytes: u64) -> Result<FeeEstimate, ChainError>;
+
+    /// Call any other contract method; only chains with named methods support it
+    async fn call_method(&self, method: &str, args: Value, deposit: u128) -> Result<TxHash, ChainError> {
+        let _ = (method, args, deposit);
+        Err(ChainError::Unsupported("call_method"))
+    }
+}
+
+/// Adapters by chain, one per chain
+#[derive(Default)]
+pub struct ChainAdapters {
+    adapters: HashMap<ChainType, Box<dyn ChainAdapter>>,
+}
+
+impl ChainAdapters {
+    /// Add an adapter, replacing any earlier one for the same chain
+    pub fn insert(&mut self, adapter: Box<dyn ChainAdapter>) -> ChainType {
+        let chain = adapter.chain();
+        self.adapters.insert(chain, adapter);
+        chain
+    }
+
+    pub fn get(&self, chain: ChainType) -> Result<&dyn ChainAdapter, ChainError> {
+        self.adapters
+            .get(&chain)
+            .map(|adapter| adapter.as_ref())
+            .ok_or(ChainError::NotConnected(chain))
+    }
+
+    pub fn remove(&mut self, chain: ChainType) -> Option<Box<dyn ChainAdapter>> {
+        self.adapters.remove(&chain)
+    }
+
+    pub fn is_connected(&self, chain: ChainType) -> bool {
+        self.adapters.contains_key(&chain)
+    }
+}
+
+/// Moves JSON-RPC request bodies to a node and back
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait RpcTransport: MaybeSend {
+    async fn post(&self, url: &str, body: String) -> Result<String, ChainError>;
+}
+
+/// HTTP transport; uses `fetch` on wasm32 and a native client elsewhere
+#[derive(Debug, Clone, Default)]
+pub struct HttpTransport {
+    client: reqwest::Client,
+}
+
+impl HttpTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl RpcTransport for HttpTransport {
+    async fn post(&self, url: &str, body: String) -> Result<String, ChainError> {
+        let response = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| ChainError::Transport(e.to_string()))?;
+        let status = response.status();
+        let text = response.text().await.map_err(|e| ChainError::Transport(e.to_string()))?;
+        if status.is_success() {
+            Ok(text)
+        } else {
+            Err(ChainError::Transport(format!("HTTP {}: {}", status, text)))
+        }
+    }
+}
+
+/// JSON-RPC 2.0 client for one node
+pub struct RpcClient {
+    url: String,
+    transport: Arc<dyn RpcTransport>,
+    next_id: AtomicU64,
+}
+
+impl RpcClient {
+    pub fn new(url: &str, transport: Arc<dyn RpcTransport>) -> Self {
+        RpcClient {
+            url: url.to_string(),
+            transport,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Client over HTTP
+    pub fn http(url: &str) -> Self {
+        Self::new(url, Arc::new(HttpTransport::new()))
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Call `method` and return its `result`, which may be `null`
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value, ChainError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let text = self.transport.post(&self.url, body.to_string()).await?;
+        let mut response: Value =
+            serde_json::from_str(&text).map_err(|e| ChainError::InvalidResponse(e.to_string()))?;
+        if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+            // NEAR puts the useful part in `cause.name`
+            let message = error["cause"]["name"]
+                .as_str()
+                .or_else(|| error["message"].as_str())
+                .unwrap_or("unknown error");
+            return Err(ChainError::Rpc {
+                code: error["code"].as_i64().unwrap_or(0),
+                message: message.to_string(),
+            });
+        }
+        match response.get_mut("result") {
+            Some(result) => Ok(result.take()),
+            None => Err(ChainError::InvalidResponse(format!("{} returned no result", method))),
+        }
+    }
+}
+
+/// Signer backed by a wallet object injected into the page, e.g. `window.nearWallet`.
+///
+/// The wallet needs an `accountId` string and a `signTransaction(tx)` method
+/// taking an [`UnsignedTransaction`] as JSON and resolving to a `Uint8Array`.
+#[cfg(target_arch = "wasm32")]
+pub struct WalletSigner {
+    wallet: wasm_bindgen::JsValue,
+    account_id: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WalletSigner {
+    /// Wallet at `window[name]`, if the page has one with an account
+    pub fn from_window(name: &str) -> Option<Self> {
+        let wallet = js_sys::Reflect::get(&web_sys::window()?, &name.into()).ok()?;
+        let account_id = js_sys::Reflect::get(&wallet, &"accountId".into()).ok()?.as_string()?;
+        Some(WalletSigner { wallet, account_id })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[async_trait(?Send)]
+impl Signer for WalletSigner {
+    fn account_id(&self) -> String {
+        self.account_id.clone()
+    }
+
+    async fn sign_transaction(&self, tx: &UnsignedTransaction) -> Result<Vec<u8>, ChainError> {
+        use wasm_bindgen::JsCast;
+
+        let js_error = |e: wasm_bindgen::JsValue| ChainError::Signer(format!("{:?}", e));
+        let request = serde_json::to_string(tx).map_err(|e| ChainError::Signer(e.to_string()))?;
+        let request = js_sys::JSON::parse(&request).map_err(js_error)?;
+        let sign: js_sys::Function = js_sys::Reflect::get(&self.wallet, &"signTransaction".into())
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(|_| ChainError::Signer("wallet has no signTransaction method".to_string()))?;
+        let promise: js_sys::Promise = sign.call1(&self.wallet, &request).map_err(js_error)?.into();
+        let signed = wasm_bindgen_futures::JsFuture::from(promise).await.map_err(js_error)?;
+        Ok(js_sys::Uint8Array::new(&signed).to_vec())
+    }
+}
+
+/// Deterministic in-memory chain for tests and offline demos.
+///
+/// Transaction hashes are derived from the chain and a sequence number, so
+/// the same calls always produce the same hashes.
+pub struct MockAdapter {
+    chain: ChainType,
+    account_id: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    tokens: BTreeMap<String, NftToken>,
+    transactions: BTreeMap<TxHash, TxStatus>,
+    calls: Vec<(String, Value)>,
+    fees: HashMap<FeeOperation, u128>,
+    sequence: u64,
+}
+
+impl MockState {
+    fn submit(&mut self, chain: ChainType, kind: &str, subject: &str, status: TxStatus) -> TxHash {
+        self.sequence += 1;
+        let digest = Sha256::digest(format!("{}:{}:{}:{}", chain.as_str(), self.sequence, kind, subject));
+        let hash = hex::encode(digest);
+        self.transactions.insert(hash.clone(), status);
+        hash
+    }
+}
+
+impl MockAdapter {
+    /// Mock chain where `account_id` signs every transaction
+    pub fn new(chain: ChainType, account_id: &str) -> Self {
+        let mut state = MockState::default();
+        state.fees.insert(FeeOperation::Mint, 1_000);
+        state.fees.insert(FeeOperation::Transfer, 500);
+        MockAdapter {
+            chain,
+            account_id: account_id.to_string(),
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Another signer on the same mock chain, sharing its tokens and transactions
+    pub fn for_account(&self, account_id: &str) -> Self {
+        MockAdapter {
+            chain: self.chain,
+            account_id: account_id.to_string(),
+            state: self.state.clone(),
+        }
+    }
+
+    /// Seed a token, e.g. one owned by another account
+    pub fn with_token(self, token: NftToken) -> Self {
+        self.lock().tokens.insert(token.token_id.clone(), token);
+        self
+    }
+
+    pub fn with_fee(self, operation: FeeOperation, amount: u128) -> Self {
+        self.lock().fees.insert(operation, amount);
+        self
+    }
+
+    /// Methods passed to `call_method`, in order
+    pub fn calls(&self) -> Vec<(String, Value)> {
+        self.lock().calls.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ChainAdapter for MockAdapter {
+    fn chain(&self) -> ChainType {
+        self.chain
+    }
+
+    fn collection(&self) -> String {
+        format!("mock-{}", self.chain.as_str())
+    }
+
+    fn account_id(&self) -> Option<String> {
+        Some(self.account_id.clone())
+    }
+
+    async fn mint(&self, request: &MintRequest) -> Result<TxHash, ChainError> {
+        let mut state = self.lock();
+        let status = if state.tokens.contains_key(&request.token_id) {
+            TxStatus::Failed("token already exists".to_string())
+        } else {
+            state.tokens.insert(
+                request.token_id.clone(),
+                NftToken {
+                    token_id: request.token_id.clone(),
+                    owner: self.account_id.clone(),
+                    metadata: request.token_metadata(),
+                    collection: Some(self.collection()),
+                },
+            );
+            TxStatus::Confirmed
+        };
+        Ok(state.submit(self.chain, "mint", &request.token_id, status))
+    }
+
+    async fn transfer(&self, request: &TransferRequest) -> Result<TxHash, ChainError> {
+        let mut state = self.lock();
+        let status = match state.tokens.get_mut(&request.token_id) {
+            Some(token) if token.owner == self.account_id => {
+                token.owner = request.receiver.clone();
+                TxStatus::Confirmed
+            }
+            Some(_) => TxStatus::Failed("token not owned by signer".to_string()),
+            None => TxStatus::Failed("unknown token".to_string()),
+        };
+        Ok(state.submit(self.chain, "transfer", &request.token_id, status))
+    }
+
+    async fn get_user_nfts(&self, owner: &str) -> Result<Vec<NftToken>, ChainError> {
+        Ok(self.lock().tokens.values().filter(|t| t.owner == owner).cloned().collect())
+    }
+
+    async fn get_tx_status(&self, tx_hash: &str) -> Result<TxStatus, ChainError> {
+        Ok(self.lock().transactions.get(tx_hash).cloned().unwrap_or(TxStatus::Unknown))
+    }
+
+    /// Flat fees from `with_fee`, whatever the payload
+    async fn estimate_fee(&self, operation: FeeOperation, _payload_bytes: u64) -> Result<FeeEstimate, ChainError> {
+        let amount = self.lock().fees.get(&operation).copied().unwrap_or(0);
+        Ok(FeeEstimate::new(self.chain, operation, amount))
+    }
+
+    async fn call_method(&self, method: &str, args: Value, _deposit: u128) -> Result<TxHash, ChainError> {
+        let mut state = self.lock();
+        state.calls.push((method.to_string(), args));
+        Ok(state.submit(self.chain, "call", method, TxStatus::Confirmed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mint_request(token_id: &str) -> MintRequest {
+        MintRequest {
+            token_id: token_id.to_string(),
+            metadata: json!({ "title": "Session" }),
+            media_cid: "bafy123".to_string(),
+            params: Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_mints_transfers_and_tracks_status() {
+        let mock = MockAdapter::new(ChainType::Near, "alice.near");
+        let minted = mock.mint(&mint_request("t1")).await.unwrap();
+        assert_eq!(mock.get_tx_status(&minted).await.unwrap(), TxStatus::Confirmed);
+
+        let tokens = mock.get_user_nfts("alice.near").await.unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].metadata["media"], "ipfs://bafy123");
+
+        let transfer = TransferRequest { token_id: "t1".to_string(), receiver: "bob.near".to_string(), memo: None };
+        let moved = mock.transfer(&transfer).await.unwrap();
+        assert_eq!(mock.get_tx_status(&moved).await.unwrap(), TxStatus::Confirmed);
+        assert!(mock.get_user_nfts("alice.near").await.unwrap().is_empty());
+        assert_eq!(mock.get_user_nfts("bob.near").await.unwrap().len(), 1);
+
+        // No longer ours, so a second transfer fails on chain
+        let again = mock.transfer(&transfer).await.unwrap();
+        assert_eq!(
+            mock.get_tx_status(&again).await.unwrap(),
+            TxStatus::Failed("token not owned by signer".to_string())
+        );
+        assert_eq!(mock.get_tx_status("missing").await.unwrap(), TxStatus::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_mock_hashes_are_deterministic() {
+        let a = MockAdapter::new(ChainType::Solana, "alice");
+        let b = MockAdapter::new(ChainType::Solana, "alice");
+        assert_eq!(a.mint(&mint_request("t1")).await.unwrap(), b.mint(&mint_request("t1")).await.unwrap());
+        assert_ne!(a.mint(&mint_request("t2")).await.unwrap(), b.mint(&mint_request("t3")).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_mock_fees_use_native_units() {
+        let mock = MockAdapter::new(ChainType::Polkadot, "alice").with_fee(FeeOperation::Mint, 42);
+        let fee = mock.estimate_fee(FeeOperation::Mint, 1024).await.unwrap();
+        assert_eq!((fee.amount, fee.symbol.as_str(), fee.decimals), (42, "DOT", 10));
+        let json = serde_json::to_value(&fee).unwrap();
+        assert_eq!(json["amount"], "42");
+    }
+
+    #[test]
+    fn test_registry_reports_missing_chains() {
+        let mut adapters = ChainAdapters::default();
+        adapters.insert(Box::new(MockAdapter::new(ChainType::Polygon, "0xabc")));
+        assert!(adapters.get(ChainType::Polygon).is_ok());
+        assert_eq!(adapters.get(ChainType::Near).err(), Some(ChainError::NotConnected(ChainType::Near)));
+    }
+}
@@ -0,0 +1,1201 @@
+//! JSON-RPC chain adapters for NEAR, Solana, EVM chains and Polkadot Asset Hub.
+//!
+//! Adapters read straight from a node. Mints and transfers are prepared here,
+//! signed by a [`Signer`] in the chain's wire format, then broadcast.
+
+use crate::address::ChainAddress;
+use crate::blockchain_integration::ChainType;
+use crate::chain_adapter::*;
+use crate::fees::{CostModel, EvmPrices, NearPrices, PolkadotPrices, SolanaPrices};
+use async_trait::async_trait;
+use base64::Engine as _;
+use blake2::digest::consts::U16;
+use blake2::{Blake2b, Digest};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::hash::Hasher;
+use std::sync::{Arc, Mutex};
+
+pub const NEAR_MAINNET_RPC: &str = "https://rpc.mainnet.near.org";
+pub const NEAR_TESTNET_RPC: &str = "https://rpc.testnet.near.org";
+pub const SOLANA_MAINNET_RPC: &str = "https://api.mainnet-beta.solana.com";
+pub const SOLANA_DEVNET_RPC: &str = "https://api.devnet.solana.com";
+pub const ETHEREUM_MAINNET_RPC: &str = "https://ethereum-rpc.publicnode.com";
+pub const ETHEREUM_SEPOLIA_RPC: &str = "https://ethereum-sepolia-rpc.publicnode.com";
+pub const POLYGON_MAINNET_RPC: &str = "https://polygon-rpc.com";
+pub const POLYGON_AMOY_RPC: &str = "https://rpc-amoy.polygon.technology";
+pub const POLKADOT_ASSET_HUB_RPC: &str = "https://polkadot-asset-hub-rpc.polkadot.io";
+pub const WESTEND_ASSET_HUB_RPC: &str = "https://westend-asset-hub-rpc.polkadot.io";
+
+/// Gas attached to NEAR mints
+pub const NEAR_MINT_GAS: u64 = 300_000_000_000_000;
+/// Gas attached to other NEAR calls, transfers included
+pub const NEAR_CALL_GAS: u64 = 100_000_000_000_000;
+const NEAR_PAGE_SIZE: usize = 100;
+
+pub const SPL_TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const SPL_ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWW25efTNsLJA8knL";
+
+const ERC721_BALANCE_OF: &str = "70a08231";
+const ERC721_TOKEN_OF_OWNER_BY_INDEX: &str = "2f745c59";
+const ERC721_TOKEN_URI: &str = "c87b56dd";
+/// Tokens `EvmRpcAdapter::get_user_nfts` lists before stopping; the balance
+/// comes from the contract, so it bounds the calls made on its word
+pub const EVM_MAX_LISTED_TOKENS: usize = 1000;
+
+const POLKADOT_PAGE_SIZE: usize = 1000;
+/// Blocks searched back from the best block when looking for an extrinsic
+const POLKADOT_STATUS_LOOKBACK: usize = 20;
+
+/// `network` itself when it is a URL, else the mainnet or test endpoint
+pub(crate) fn endpoint(network: &str, mainnet: &str, testnet: &str) -> String {
+    if network.contains("://") {
+        network.to_string()
+    } else if network == "mainnet" {
+        mainnet.to_string()
+    } else {
+        testnet.to_string()
+    }
+}
+
+fn signer_of(signer: &Option<Arc<dyn Signer>>) -> Result<&dyn Signer, ChainError> {
+    signer.as_deref().ok_or(ChainError::NoSigner)
+}
+
+pub(crate) fn invalid(e: impl std::fmt::Display) -> ChainError {
+    ChainError::InvalidResponse(e.to_string())
+}
+
+pub(crate) fn str_at<'a>(value: &'a Value, pointer: &str) -> Result<&'a str, ChainError> {
+    value.pointer(pointer).and_then(Value::as_str).ok_or_else(|| invalid(format!("missing {}", pointer)))
+}
+
+fn u64_at(value: &Value, pointer: &str) -> Result<u64, ChainError> {
+    value.pointer(pointer).and_then(Value::as_u64).ok_or_else(|| invalid(format!("missing {}", pointer)))
+}
+
+fn u32_at(value: &Value, pointer: &str) -> Result<u32, ChainError> {
+    u32::try_from(u64_at(value, pointer)?).map_err(|_| invalid(format!("{} does not fit in 32 bits", pointer)))
+}
+
+pub(crate) fn string_result(value: Value) -> Result<String, ChainError> {
+    value.as_str().map(str::to_string).ok_or_else(|| invalid(format!("expected a string, got {}", value)))
+}
+
+pub(crate) fn hex_bytes(text: &str) -> Result<Vec<u8>, ChainError> {
+    hex::decode(text.trim_start_matches("0x")).map_err(invalid)
+}
+
+pub(crate) fn hex_u128(text: &str) -> Result<u128, ChainError> {
+    u128::from_str_radix(text.trim_start_matches("0x"), 16).map_err(invalid)
+}
+
+/// Copy `params` fields into `args` without overwriting what is already there
+fn merge_params(mut args: Value, params: &Value) -> Value {
+    if let (Value::Object(args), Value::Object(params)) = (&mut args, params) {
+        for (key, value) in params {
+            args.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    args
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|p| p.into_inner())
+}
+
+/// Refresh `prices` from the node, keep them for later calls, and price the operation
+async fn refreshed_fee<P: CostModel + Clone>(
+    rpc: &RpcClient,
+    prices: &Mutex<P>,
+    chain: ChainType,
+    operation: FeeOperation,
+    payload_bytes: u64,
+) -> Result<FeeEstimate, ChainError> {
+    let mut current = lock(prices).clone();
+    current.refresh(rpc).await?;
+    *lock(prices) = current.clone();
+    Ok(current.estimate(chain, operation, payload_bytes))
+}
+
+/// Our NEAR NFT contract on `network`
+pub(crate) fn near_contract(network: &str) -> &'static str {
+    if network == "mainnet" {
+        "nft.compiling.near"
+    } else {
+        "nft.compiling.testnet"
+    }
+}
+
+/// Call a NEAR view method and parse its JSON result
+pub(crate) async fn near_view(rpc: &RpcClient, contract_id: &str, method: &str, args: Value) -> Result<Value, ChainError> {
+    let result = rpc
+        .call(
+            "query",
+            json!({
+                "request_type": "call_function",
+                "finality": "final",
+                "account_id": contract_id,
+                "method_name": method,
+                "args_base64": base64::engine::general_purpose::STANDARD.encode(args.to_string()),
+            }),
+        )
+        .await?;
+    if let Some(error) = result.get("error").and_then(Value::as_str) {
+        return Err(ChainError::Rpc { code: 0, message: error.to_string() });
+    }
+    let bytes: Vec<u8> = serde_json::from_value(result["result"].clone()).map_err(invalid)?;
+    serde_json::from_slice(&bytes).map_err(invalid)
+}
+
+/// NEAR NFT contract over NEAR JSON-RPC
+pub struct NearRpcAdapter {
+    rpc: RpcClient,
+    contract_id: String,
+    signer: Option<Arc<dyn Signer>>,
+    prices: Mutex<NearPrices>,
+}
+
+/// Token as NEP-171 view methods return it
+#[derive(Deserialize)]
+pub(crate) struct NearToken {
+    token_id: String,
+    owner_id: String,
+    #[serde(default)]
+    metadata: Value,
+}
+
+impl NearToken {
+    pub(crate) fn into_token(self, contract_id: &str) -> NftToken {
+        NftToken {
+            token_id: self.token_id,
+            owner: self.owner_id,
+            metadata: self.metadata,
+            collection: Some(contract_id.to_string()),
+        }
+    }
+}
+
+impl NearRpcAdapter {
+    pub fn new(rpc: RpcClient, contract_id: &str) -> Self {
+        NearRpcAdapter { rpc, contract_id: contract_id.to_string(), signer: None, prices: Mutex::default() }
+    }
+
+    /// Our contract on "mainnet", otherwise on testnet; `network` may be an RPC URL
+    pub fn for_network(network: &str) -> Self {
+        Self::new(RpcClient::http(&endpoint(network, NEAR_MAINNET_RPC, NEAR_TESTNET_RPC)), near_contract(network))
+    }
+
+    /// Signer for mints and transfers; `None` leaves the adapter read-only
+    pub fn with_signer(mut self, signer: Option<Arc<dyn Signer>>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// Starting prices; `estimate_fee` refreshes the gas price and storage cost
+    pub fn with_prices(self, prices: NearPrices) -> Self {
+        *lock(&self.prices) = prices;
+        self
+    }
+
+    /// Prices as of the last refresh
+    pub fn prices(&self) -> NearPrices {
+        lock(&self.prices).clone()
+    }
+
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    async fn view(&self, method: &str, args: Value) -> Result<Value, ChainError> {
+        near_view(&self.rpc, &self.contract_id, method, args).await
+    }
+
+    async fn submit(&self, method: &str, args: Value, gas: u64, deposit: u128) -> Result<TxHash, ChainError> {
+        let signer = signer_of(&self.signer)?;
+        let block = self.rpc.call("block", json!({ "finality": "final" })).await?;
+        let block_hash = str_at(&block, "/header/hash")?.to_string();
+        let nonce = match signer.public_key() {
+            Some(public_key) => {
+                let key = self
+                    .rpc
+                    .call(
+                        "query",
+                        json!({
+                            "request_type": "view_access_key",
+                            "finality": "final",
+                            "account_id": signer.account_id(),
+                            "public_key": public_key,
+                        }),
+                    )
+                    .await?;
+                Some(u64_at(&key, "/nonce")? + 1)
+            }
+            None => None,
+        };
+        let tx = UnsignedTransaction {
+            signer_id: signer.account_id(),
+            receiver_id: self.contract_id.clone(),
+            method: method.to_string(),
+            args,
+            deposit,
+            context: TxContext::Near { nonce, block_hash, gas },
+        };
+        let signed = signer.sign_transaction(&tx).await?;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(signed);
+        string_result(self.rpc.call("broadcast_tx_async", json!([encoded])).await?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ChainAdapter for NearRpcAdapter {
+    fn chain(&self) -> ChainType {
+        ChainType::Near
+    }
+
+    fn collection(&self) -> String {
+        self.contract_id.clone()
+    }
+
+    fn account_id(&self) -> Option<String> {
+        self.signer.as_ref().map(|signer| signer.account_id())
+    }
+
+    /// Attaches the storage deposit the metadata needs at the last known storage cost
+    async fn mint(&self, request: &MintRequest) -> Result<TxHash, ChainError> {
+        let deposit = self.prices().storage_deposit(request.payload_bytes());
+        let args = json!({ "token_id": request.token_id, "metadata": request.token_metadata() });
+        self.submit("mint_interactive_nft", merge_params(args, &request.params), NEAR_MINT_GAS, deposit).await
+    }
+
+    async fn transfer(&self, request: &TransferRequest) -> Result<TxHash, ChainError> {
+        let args = json!({ "receiver_id": request.receiver, "token_id": request.token_id, "memo": request.memo });
+        // NEP-171 asks for exactly one yoctoNEAR
+        self.submit("nft_transfer", args, NEAR_CALL_GAS, 1).await
+    }
+
+    async fn get_user_nfts(&self, owner: &str) -> Result<Vec<NftToken>, ChainError> {
+        let mut tokens = Vec::new();
+        loop {
+            let args = json!({ "account_id": owner, "from_index": tokens.len().to_string(), "limit": NEAR_PAGE_SIZE });
+            let page: Vec<NearToken> =
+                serde_json::from_value(self.view("nft_tokens_for_owner", args).await?).map_err(invalid)?;
+            let last_page = page.len() < NEAR_PAGE_SIZE;
+            tokens.extend(page.into_iter().map(|t| t.into_token(&self.contract_id)));
+            if last_page {
+                return Ok(tokens);
+            }
+        }
+    }
+
+    /// NEAR looks transactions up by hash and sender, so this needs a signer
+    async fn get_tx_status(&self, tx_hash: &str) -> Result<TxStatus, ChainError> {
+        let signer = signer_of(&self.signer)?;
+        match self.rpc.call("tx", json!([tx_hash, signer.account_id()])).await {
+            Ok(outcome) => Ok(match outcome.get("status") {
+                Some(status) if status.get("Failure").is_some() => TxStatus::Failed(status["Failure"].to_string()),
+                Some(status) if status.get("SuccessValue").is_some() || status.get("SuccessReceiptId").is_some() => {
+                    TxStatus::Confirmed
+                }
+                _ => TxStatus::Pending,
+            }),
+            Err(ChainError::Rpc { message, .. }) if message == "UNKNOWN_TRANSACTION" => Ok(TxStatus::Unknown),
+            Err(ChainError::Rpc { message, .. }) if message == "TIMEOUT_ERROR" => Ok(TxStatus::Pending),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gas burnt at the current price plus storage staking; unused prepaid gas is refunded
+    async fn estimate_fee(&self, operation: FeeOperation, payload_bytes: u64) -> Result<FeeEstimate, ChainError> {
+        refreshed_fee(&self.rpc, &self.prices, ChainType::Near, operation, payload_bytes).await
+    }
+
+    async fn call_method(&self, method: &str, args: Value, deposit: u128) -> Result<TxHash, ChainError> {
+        self.submit(method, args, NEAR_CALL_GAS, deposit).await
+    }
+}
+
+/// Solana NFT program and SPL token accounts over Solana JSON-RPC
+pub struct SolanaRpcAdapter {
+    rpc: RpcClient,
+    program_id: String,
+    signer: Option<Arc<dyn Signer>>,
+    prices: Mutex<SolanaPrices>,
+}
+
+impl SolanaRpcAdapter {
+    pub fn new(rpc: RpcClient, program_id: &str) -> Self {
+        SolanaRpcAdapter { rpc, program_id: program_id.to_string(), signer: None, prices: Mutex::default() }
+    }
+
+    /// `program_id` on "mainnet", otherwise on devnet; `network` may be an RPC URL
+    pub fn for_network(network: &str, program_id: &str) -> Result<Self, ChainError> {
+        let program = ChainAddress::parse(ChainType::Solana, program_id)?;
+        let rpc = RpcClient::http(&endpoint(network, SOLANA_MAINNET_RPC, SOLANA_DEVNET_RPC));
+        Ok(Self::new(rpc, program.as_str()))
+    }
+
+    /// Signer for mints and transfers; `None` leaves the adapter read-only
+    pub fn with_signer(mut self, signer: Option<Arc<dyn Signer>>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// Starting prices; `estimate_fee` refreshes rent and the priority fee
+    pub fn with_prices(self, prices: SolanaPrices) -> Self {
+        *lock(&self.prices) = prices;
+        self
+    }
+
+    /// Prices as of the last refresh
+    pub fn prices(&self) -> SolanaPrices {
+        lock(&self.prices).clone()
+    }
+
+    async fn submit(&self, program_id: &str, method: &str, args: Value) -> Result<TxHash, ChainError> {
+        let signer = signer_of(&self.signer)?;
+        let blockhash = self.rpc.call("getLatestBlockhash", json!([{ "commitment": "finalized" }])).await?;
+        let tx = UnsignedTransaction {
+            signer_id: signer.account_id(),
+            receiver_id: program_id.to_string(),
+            method: method.to_string(),
+            args,
+            deposit: 0,
+            context: TxContext::Solana { recent_blockhash: str_at(&blockhash, "/value/blockhash")?.to_string() },
+        };
+        let signed = signer.sign_transaction(&tx).await?;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(signed);
+        string_result(self.rpc.call("sendTransaction", json!([encoded, { "encoding": "base64" }])).await?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ChainAdapter for SolanaRpcAdapter {
+    fn chain(&self) -> ChainType {
+        ChainType::Solana
+    }
+
+    fn collection(&self) -> String {
+        self.program_id.clone()
+    }
+
+    fn account_id(&self) -> Option<String> {
+        self.signer.as_ref().map(|signer| signer.account_id())
+    }
+
+    async fn mint(&self, request: &MintRequest) -> Result<TxHash, ChainError> {
+        let args = json!({
+            "token_id": request.token_id,
+            "uri": request.media_uri(),
+            "metadata": request.token_metadata(),
+        });
+        let program_id = self.program_id.clone();
+        self.submit(&program_id, "mint_nft", merge_params(args, &request.params)).await
+    }
+
+    async fn transfer(&self, request: &TransferRequest) -> Result<TxHash, ChainError> {
+        let args = json!({ "mint": request.token_id, "destination_owner": request.receiver, "amount": 1, "decimals": 0 });
+        self.submit(SPL_TOKEN_PROGRAM_ID, "transfer_checked", args).await
+    }
+
+    /// Token accounts holding exactly one unit of a zero-decimal mint
+    async fn get_user_nfts(&self, owner: &str) -> Result<Vec<NftToken>, ChainError> {
+        let accounts = self
+            .rpc
+            .call(
+                "getTokenAccountsByOwner",
+                json!([owner, { "programId": SPL_TOKEN_PROGRAM_ID }, { "encoding": "jsonParsed" }]),
+            )
+            .await?;
+        let accounts = accounts.pointer("/value").and_then(Value::as_array).ok_or_else(|| invalid("missing /value"))?;
+        Ok(accounts
+            .iter()
+            .filter_map(|account| {
+                let info = account.pointer("/account/data/parsed/info")?;
+                let amount = info.pointer("/tokenAmount/amount")?.as_str()?;
+                let decimals = info.pointer("/tokenAmount/decimals")?.as_u64()?;
+                (amount == "1" && decimals == 0).then(|| NftToken {
+                    token_id: info["mint"].as_str().unwrap_or_default().to_string(),
+                    owner: owner.to_string(),
+                    metadata: json!({ "token_account": account["pubkey"] }),
+                    collection: None,
+                })
+            })
+            .collect())
+    }
+
+    async fn get_tx_status(&self, tx_hash: &str) -> Result<TxStatus, ChainError> {
+        let statuses = self
+            .rpc
+            .call("getSignatureStatuses", json!([[tx_hash], { "searchTransactionHistory": true }]))
+            .await?;
+        let status = statuses.pointer("/value/0").cloned().unwrap_or(Value::Null);
+        if status.is_null() {
+            return Ok(TxStatus::Unknown);
+        }
+        if !status["err"].is_null() {
+            return Ok(TxStatus::Failed(status["err"].to_string()));
+        }
+        Ok(match status["confirmationStatus"].as_str() {
+            Some("finalized") => TxStatus::Confirmed,
+            _ => TxStatus::Pending,
+        })
+    }
+
+    /// Signatures and compute at the current priority fee plus rent for the accounts created
+    async fn estimate_fee(&self, operation: FeeOperation, payload_bytes: u64) -> Result<FeeEstimate, ChainError> {
+        refreshed_fee(&self.rpc, &self.prices, ChainType::Solana, operation, payload_bytes).await
+    }
+}
+
+/// ERC-721 contract over Ethereum-style JSON-RPC, for Ethereum and Polygon
+pub struct EvmRpcAdapter {
+    chain: ChainType,
+    rpc: RpcClient,
+    contract: String,
+    signer: Option<Arc<dyn Signer>>,
+    prices: Mutex<EvmPrices>,
+}
+
+impl EvmRpcAdapter {
+    pub fn new(chain: ChainType, rpc: RpcClient, contract: &str) -> Result<Self, ChainError> {
+        match chain {
+            ChainType::Ethereum | ChainType::Polygon => {
+                Ok(EvmRpcAdapter { chain, rpc, contract: contract.to_string(), signer: None, prices: Mutex::default() })
+            }
+            other => Err(ChainError::InvalidRequest(format!("{} is not an EVM chain", other.as_str()))),
+        }
+    }
+
+    /// `contract` on "mainnet", otherwise on Sepolia or Amoy; `network` may be an RPC URL
+    pub fn for_network(chain: ChainType, network: &str, contract: &str) -> Result<Self, ChainError> {
+        let url = match chain {
+            ChainType::Polygon => endpoint(network, POLYGON_MAINNET_RPC, POLYGON_AMOY_RPC),
+            _ => endpoint(network, ETHEREUM_MAINNET_RPC, ETHEREUM_SEPOLIA_RPC),
+        };
+        let contract = ChainAddress::parse(chain, contract)?;
+        Self::new(chain, RpcClient::http(&url), contract.as_str())
+    }
+
+    /// Signer for mints and transfers; `None` leaves the adapter read-only
+    pub fn with_signer(mut self, signer: Option<Arc<dyn Signer>>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// Starting prices; `estimate_fee` refreshes the gas price
+    pub fn with_prices(self, prices: EvmPrices) -> Self {
+        *lock(&self.prices) = prices;
+        self
+    }
+
+    /// Prices as of the last refresh
+    pub fn prices(&self) -> EvmPrices {
+        lock(&self.prices).clone()
+    }
+
+    /// Up to `limit` of `owner`'s tokens from `from_index` on, walking ERC-721
+    /// Enumerable `tokenOfOwnerByIndex` and reading each `tokenURI`, with the
+    /// owner's balance
+    pub async fn get_user_nfts_page(
+        &self,
+        owner: &str,
+        from_index: u128,
+        limit: usize,
+    ) -> Result<(Vec<NftToken>, u128), ChainError> {
+        let owner_word = abi_address(self.chain, owner)?;
+        let balance = self.eth_call(ERC721_BALANCE_OF, std::slice::from_ref(&owner_word)).await?;
+        let balance = abi_u128(abi_word(&balance, 0)?)?;
+        let end = balance.min(from_index.saturating_add(limit as u128));
+        let mut tokens = Vec::new();
+        for index in from_index..end {
+            let id = self.eth_call(ERC721_TOKEN_OF_OWNER_BY_INDEX, &[owner_word.clone(), format!("{:064x}", index)]).await?;
+            let id = abi_word(&id, 0)?.to_vec();
+            // Contracts without metadata revert here; the token is still listed
+            let uri = match self.eth_call(ERC721_TOKEN_URI, &[hex::encode(&id)]).await {
+                Ok(data) => abi_string(&data).ok(),
+                Err(_) => None,
+            };
+            tokens.push(NftToken {
+                token_id: abi_token_id(&id),
+                owner: owner.to_string(),
+                metadata: uri.map(|uri| json!({ "token_uri": uri })).unwrap_or(Value::Null),
+                collection: Some(self.contract.clone()),
+            });
+        }
+        Ok((tokens, balance))
+    }
+
+    async fn eth_call(&self, selector: &str, words: &[String]) -> Result<Vec<u8>, ChainError> {
+        let data = format!("0x{}{}", selector, words.concat());
+        let result = self.rpc.call("eth_call", json!([{ "to": self.contract, "data": data }, "latest"])).await?;
+        hex_bytes(&string_result(result)?)
+    }
+
+    async fn gas_price(&self) -> Result<u128, ChainError> {
+        hex_u128(&string_result(self.rpc.call("eth_gasPrice", json!([])).await?)?)
+    }
+
+    async fn submit(&self, method: &str, args: Value, gas_limit: u64) -> Result<TxHash, ChainError> {
+        let signer = signer_of(&self.signer)?;
+        let chain_id = hex_u128(&string_result(self.rpc.call("eth_chainId", json!([])).await?)?)? as u64;
+        let nonce = self.rpc.call("eth_getTransactionCount", json!([signer.account_id(), "pending"])).await?;
+        let tx = UnsignedTransaction {
+            signer_id: signer.account_id(),
+            receiver_id: self.contract.clone(),
+            method: method.to_string(),
+            args,
+            deposit: 0,
+            context: TxContext::Evm {
+                chain_id,
+                nonce: hex_u128(&string_result(nonce)?)? as u64,
+                gas_price: self.gas_price().await?,
+                gas_limit,
+            },
+        };
+        let signed = signer.sign_transaction(&tx).await?;
+        string_result(self.rpc.call("eth_sendRawTransaction", json!([format!("0x{}", hex::encode(signed))])).await?)
+    }
+}
+
+/// Address as a 32-byte ABI word
+fn abi_address(chain: ChainType, address: &str) -> Result<String, ChainError> {
+    let address = ChainAddress::parse(chain, address)?;
+    Ok(format!("{:0>64}", hex::encode(address.as_bytes())))
+}
+
+fn abi_word(data: &[u8], index: usize) -> Result<&[u8], ChainError> {
+    data.get(index * 32..(index + 1) * 32).ok_or_else(|| invalid("ABI data too short"))
+}
+
+fn abi_u128(word: &[u8]) -> Result<u128, ChainError> {
+    if word[..16].iter().any(|b| *b != 0) {
+        return Err(invalid("ABI value does not fit in 128 bits"));
+    }
+    Ok(u128::from_be_bytes(word[16..32].try_into().expect("16 bytes")))
+}
+
+/// Token id in decimal when it fits in 128 bits, hex otherwise
+fn abi_token_id(word: &[u8]) -> String {
+    abi_u128(word).map(|id| id.to_string()).unwrap_or_else(|_| format!("0x{}", hex::encode(word)))
+}
+
+fn abi_string(data: &[u8]) -> Result<String, ChainError> {
+    let out_of_range = || invalid("ABI string offset out of range");
+    let offset = usize::try_from(abi_u128(abi_word(data, 0)?)?).map_err(|_| out_of_range())?;
+    let start = offset.checked_add(32).ok_or_else(out_of_range)?;
+    let length_word = data.get(offset..start).ok_or_else(out_of_range)?;
+    let too_short = || invalid("ABI string too short");
+    let length = usize::try_from(abi_u128(length_word)?).map_err(|_| too_short())?;
+    let bytes = data.get(start..start.checked_add(length).ok_or_else(too_short)?).ok_or_else(too_short)?;
+    String::from_utf8(bytes.to_vec()).map_err(invalid)
+}
+
+/// Calldata for `signature`, e.g. `mint(address,string)`, with `args` as a
+/// JSON array. Handles the `address`, `uint*`, `bool`, `string` and `bytes`
+/// parameters our contracts take.
+pub(crate) fn abi_encode_call(chain: ChainType, signature: &str, args: &Value) -> Result<Vec<u8>, ChainError> {
+    let bad_request = |msg: String| ChainError::InvalidRequest(msg);
+    let params = signature
+        .strip_suffix(')')
+        .and_then(|s| s.split_once('('))
+        .map(|(_, params)| params)
+        .ok_or_else(|| bad_request(format!("'{}' is not a function signature", signature)))?;
+    let types: Vec<&str> = params.split(',').filter(|t| !t.is_empty()).collect();
+    let values = args.as_array().map(Vec::as_slice).unwrap_or_default();
+    if values.len() != types.len() {
+        return Err(bad_request(format!("{} takes {} arguments, got {}", signature, types.len(), values.len())));
+    }
+
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    for (ty, value) in types.iter().zip(values) {
+        let mut word = [0u8; 32];
+        match *ty {
+            "address" => {
+                let address = ChainAddress::parse(chain, value.as_str().unwrap_or_default())?;
+                word[12..].copy_from_slice(address.as_bytes());
+            }
+            "bool" => word[31] = value.as_bool().ok_or_else(|| bad_request(format!("{} is not a bool", value)))? as u8,
+            ty if ty.starts_with("uint") => {
+                let number = match value {
+                    Value::Number(n) => n.as_u64().map(u128::from),
+                    Value::String(s) => match s.strip_prefix("0x") {
+                        Some(digits) => u128::from_str_radix(digits, 16).ok(),
+                        None => s.parse().ok(),
+                    },
+                    _ => None,
+                };
+                let number = number.ok_or_else(|| bad_request(format!("{} is not a {}", value, ty)))?;
+                word[16..].copy_from_slice(&number.to_be_bytes());
+            }
+            "string" | "bytes" => {
+                let bytes = match (*ty, value.as_str()) {
+                    ("string", Some(s)) => s.as_bytes().to_vec(),
+                    ("bytes", Some(s)) => hex_bytes(s)?,
+                    _ => return Err(bad_request(format!("{} is not a {}", value, ty))),
+                };
+                let offset = (types.len() * 32 + tail.len()) as u128;
+                word[16..].copy_from_slice(&offset.to_be_bytes());
+                let mut length = [0u8; 32];
+                length[16..].copy_from_slice(&(bytes.len() as u128).to_be_bytes());
+                tail.extend_from_slice(&length);
+                tail.extend_from_slice(&bytes);
+                tail.resize(tail.len().div_ceil(32) * 32, 0);
+            }
+            other => return Err(bad_request(format!("unsupported ABI type {}", other))),
+        }
+        head.extend_from_slice(&word);
+    }
+
+    let mut data = sha3::Keccak256::digest(signature.as_bytes())[..4].to_vec();
+    data.extend(head);
+    data.extend(tail);
+    Ok(data)
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ChainAdapter for EvmRpcAdapter {
+    fn chain(&self) -> ChainType {
+        self.chain
+    }
+
+    fn collection(&self) -> String {
+        self.contract.clone()
+    }
+
+    fn account_id(&self) -> Option<String> {
+        self.signer.as_ref().map(|signer| signer.account_id())
+    }
+
+    /// EVM contracts keep metadata at the token URI, so `media_cid` should name the metadata document
+    async fn mint(&self, request: &MintRequest) -> Result<TxHash, ChainError> {
+        let owner = signer_of(&self.signer)?.account_id();
+        let uri = request.media_uri();
+        let gas_limit = self.prices().gas(FeeOperation::Mint, uri.len() as u64);
+        self.submit("mint(address,string)", json!([owner, uri]), gas_limit).await
+    }
+
+    async fn transfer(&self, request: &TransferRequest) -> Result<TxHash, ChainError> {
+        let from = signer_of(&self.signer)?.account_id();
+        let args = json!([from, request.receiver, request.token_id]);
+        let gas_limit = self.prices().gas(FeeOperation::Transfer, 0);
+        self.submit("safeTransferFrom(address,address,uint256)", args, gas_limit).await
+    }
+
+    /// The first [`EVM_MAX_LISTED_TOKENS`] tokens; page through the rest with
+    /// [`EvmRpcAdapter::get_user_nfts_page`]
+    async fn get_user_nfts(&self, owner: &str) -> Result<Vec<NftToken>, ChainError> {
+        Ok(self.get_user_nfts_page(owner, 0, EVM_MAX_LISTED_TOKENS).await?.0)
+    }
+
+    async fn get_tx_status(&self, tx_hash: &str) -> Result<TxStatus, ChainError> {
+        let receipt = self.rpc.call("eth_getTransactionReceipt", json!([tx_hash])).await?;
+        if receipt.is_null() {
+            let tx = self.rpc.call("eth_getTransactionByHash", json!([tx_hash])).await?;
+            return Ok(if tx.is_null() { TxStatus::Unknown } else { TxStatus::Pending });
+        }
+        Ok(match receipt["status"].as_str() {
+            Some("0x1") => TxStatus::Confirmed,
+            _ => TxStatus::Failed("reverted".to_string()),
+        })
+    }
+
+    /// `payload_bytes` is what the contract stores on chain, usually just the token URI
+    async fn estimate_fee(&self, operation: FeeOperation, payload_bytes: u64) -> Result<FeeEstimate, ChainError> {
+        refreshed_fee(&self.rpc, &self.prices, self.chain, operation, payload_bytes).await
+    }
+}
+
+/// pallet-nfts collection over Substrate JSON-RPC
+pub struct PolkadotRpcAdapter {
+    rpc: RpcClient,
+    collection: u32,
+    signer: Option<Arc<dyn Signer>>,
+    /// Extrinsics we submitted, by hash, so their status can be found later
+    submitted: Mutex<BTreeMap<TxHash, String>>,
+    prices: Mutex<PolkadotPrices>,
+}
+
+impl PolkadotRpcAdapter {
+    pub fn new(rpc: RpcClient, collection: u32) -> Self {
+        PolkadotRpcAdapter {
+            rpc,
+            collection,
+            signer: None,
+            submitted: Mutex::new(BTreeMap::new()),
+            prices: Mutex::default(),
+        }
+    }
+
+    /// Asset Hub on "mainnet", otherwise Westend Asset Hub; `network` may be a node URL.
+    /// WebSocket URLs are rewritten to the node's HTTP endpoint.
+    pub fn for_network(network: &str, collection: u32) -> Self {
+        let url = endpoint(network, POLKADOT_ASSET_HUB_RPC, WESTEND_ASSET_HUB_RPC);
+        let url = match url.split_once("://") {
+            Some(("wss", rest)) => format!("https://{}", rest),
+            Some(("ws", rest)) => format!("http://{}", rest),
+            _ => url,
+        };
+        Self::new(RpcClient::http(&url), collection)
+    }
+
+    /// Signer for mints and transfers; `None` leaves the adapter read-only
+    pub fn with_signer(mut self, signer: Option<Arc<dyn Signer>>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// Starting prices; `estimate_fee` refreshes the weight and length fees
+    pub fn with_prices(self, prices: PolkadotPrices) -> Self {
+        *lock(&self.prices) = prices;
+        self
+    }
+
+    /// Prices as of the last refresh
+    pub fn prices(&self) -> PolkadotPrices {
+        lock(&self.prices).clone()
+    }
+
+    fn item_of(token_id: &str) -> Result<u32, ChainError> {
+        token_id
+            .parse()
+            .map_err(|_| ChainError::InvalidRequest(format!("pallet-nfts item ids are u32, got '{}'", token_id)))
+    }
+
+    async fn submit(&self, method: &str, args: Value) -> Result<TxHash, ChainError> {
+        let signer = signer_of(&self.signer)?;
+        let nonce = self.rpc.call("system_accountNextIndex", json!([signer.account_id()])).await?;
+        let genesis_hash = string_result(self.rpc.call("chain_getBlockHash", json!([0])).await?)?;
+        let block_hash = string_result(self.rpc.call("chain_getFinalizedHead", json!([])).await?)?;
+        let version = self.rpc.call("state_getRuntimeVersion", json!([])).await?;
+        let tx = UnsignedTransaction {
+            signer_id: signer.account_id(),
+            receiver_id: "Nfts".to_string(),
+            method: method.to_string(),
+            args,
+            deposit: 0,
+            context: TxContext::Polkadot {
+                nonce: nonce.as_u64().ok_or_else(|| invalid("nonce is not a number"))?,
+                genesis_hash,
+                block_hash,
+                spec_version: u32_at(&version, "/specVersion")?,
+                transaction_version: u32_at(&version, "/transactionVersion")?,
+            },
+        };
+        let extrinsic = format!("0x{}", hex::encode(signer.sign_transaction(&tx).await?));
+        let hash = string_result(self.rpc.call("author_submitExtrinsic", json!([extrinsic])).await?)?;
+        lock(&self.submitted).insert(hash.clone(), extrinsic);
+        Ok(hash)
+    }
+}
+
+/// Substrate `Twox128` storage hasher
+fn twox128(data: &[u8]) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    for (seed, chunk) in out.chunks_mut(8).enumerate() {
+        let mut hasher = twox_hash::XxHash64::with_seed(seed as u64);
+        hasher.write(data);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    out
+}
+
+/// Substrate `Blake2_128Concat` storage hasher
+fn blake2_128_concat(data: &[u8]) -> Vec<u8> {
+    let mut out = Blake2b::<U16>::digest(data).to_vec();
+    out.extend_from_slice(data);
+    out
+}
+
+/// SCALE compact encoding of an unsigned integer
+pub(crate) fn scale_compact(value: u64, out: &mut Vec<u8>) {
+    match value {
+        0..=0x3f => out.push((value as u8) << 2),
+        0x40..=0x3fff => out.extend_from_slice(&(((value as u16) << 2) | 0b01).to_le_bytes()),
+        0x4000..=0x3fff_ffff => out.extend_from_slice(&(((value as u32) << 2) | 0b10).to_le_bytes()),
+        _ => {
+            let len = 8 - value.leading_zeros() as usize / 8;
+            out.push((((len - 4) as u8) << 2) | 0b11);
+            out.extend_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ChainAdapter for PolkadotRpcAdapter {
+    fn chain(&self) -> ChainType {
+        ChainType::Polkadot
+    }
+
+    fn collection(&self) -> String {
+        self.collection.to_string()
+    }
+
+    fn account_id(&self) -> Option<String> {
+        self.signer.as_ref().map(|signer| signer.account_id())
+    }
+
+    /// Token ids are item numbers in the adapter's collection; signers attach
+    /// `metadata` with `Nfts.set_metadata` in the same batch
+    async fn mint(&self, request: &MintRequest) -> Result<TxHash, ChainError> {
+        let owner = signer_of(&self.signer)?.account_id();
+        let args = json!({
+            "collection": self.collection,
+            "item": Self::item_of(&request.token_id)?,
+            "mint_to": owner,
+            "metadata": request.media_uri(),
+        });
+        self.submit("mint", args).await
+    }
+
+    async fn transfer(&self, request: &TransferRequest) -> Result<TxHash, ChainError> {
+        let args = json!({ "collection": self.collection, "item": Self::item_of(&request.token_id)?, "dest": request.receiver });
+        self.submit("transfer", args).await
+    }
+
+    /// Reads the `Nfts.Account` keys under the owner and collection
+    async fn get_user_nfts(&self, owner: &str) -> Result<Vec<NftToken>, ChainError> {
+        let account = ChainAddress::parse(ChainType::Polkadot, owner)?;
+        let mut prefix = twox128(b"Nfts").to_vec();
+        prefix.extend_from_slice(&twox128(b"Account"));
+        prefix.extend(blake2_128_concat(account.as_bytes()));
+        prefix.extend(blake2_128_concat(&self.collection.to_le_bytes()));
+        let prefix = format!("0x{}", hex::encode(prefix));
+
+        let mut tokens = Vec::new();
+        let mut start_key: Option<String> = None;
+        loop {
+            let keys = self.rpc.call("state_getKeysPaged", json!([prefix, POLKADOT_PAGE_SIZE, start_key])).await?;
+            let keys: Vec<String> = serde_json::from_value(keys).map_err(invalid)?;
+            for key in &keys {
+                let key = hex_bytes(key)?;
+                let item = key.len().checked_sub(4).map(|at| &key[at..]).ok_or_else(|| invalid("short storage key"))?;
+                tokens.push(NftToken {
+                    token_id: u32::from_le_bytes(item.try_into().expect("4 bytes")).to_string(),
+                    owner: owner.to_string(),
+                    metadata: json!({ "collection": self.collection }),
+                    collection: Some(self.collection.to_string()),
+                });
+            }
+            if keys.len() < POLKADOT_PAGE_SIZE {
+                return Ok(tokens);
+            }
+            start_key = keys.last().cloned();
+        }
+    }
+
+    /// Only extrinsics submitted through this adapter can be found. Confirmed
+    /// means included in a recent block; dispatch errors live in events we do not decode.
+    async fn get_tx_status(&self, tx_hash: &str) -> Result<TxStatus, ChainError> {
+        let extrinsic = lock(&self.submitted).get(tx_hash).cloned();
+        let Some(extrinsic) = extrinsic else {
+            return Ok(TxStatus::Unknown);
+        };
+        let pending: Vec<String> =
+            serde_json::from_value(self.rpc.call("author_pendingExtrinsics", json!([])).await?).map_err(invalid)?;
+        if pending.contains(&extrinsic) {
+            return Ok(TxStatus::Pending);
+        }
+        let mut block_hash: Option<String> = None;
+        for _ in 0..POLKADOT_STATUS_LOOKBACK {
+            let block = self.rpc.call("chain_getBlock", json!([block_hash])).await?;
+            let extrinsics = block.pointer("/block/extrinsics").and_then(Value::as_array);
+            if extrinsics.is_some_and(|list| list.iter().any(|x| x.as_str() == Some(extrinsic.as_str()))) {
+                return Ok(TxStatus::Confirmed);
+            }
+            block_hash = Some(str_at(&block, "/block/header/parentHash")?.to_string());
+        }
+        Ok(TxStatus::Unknown)
+    }
+
+    /// Weight and length fees from the runtime's `TransactionPaymentApi` plus pallet-nfts deposits
+    async fn estimate_fee(&self, operation: FeeOperation, payload_bytes: u64) -> Result<FeeEstimate, ChainError> {
+        refreshed_fee(&self.rpc, &self.prices, ChainType::Polkadot, operation, payload_bytes).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::{HashMap, VecDeque};
+
+    /// Canned replies by method; the last reply for a method repeats
+    #[derive(Default)]
+    pub(crate) struct Fixture {
+        replies: Mutex<HashMap<String, VecDeque<Value>>>,
+        requests: Mutex<Vec<Value>>,
+    }
+
+    impl Fixture {
+        pub(crate) fn reply(self, method: &str, reply: Value) -> Self {
+            self.replies.lock().unwrap().entry(method.to_string()).or_default().push_back(reply);
+            self
+        }
+
+        pub(crate) fn requests(&self, method: &str) -> Vec<Value> {
+            self.requests.lock().unwrap().iter().filter(|r| r["method"] == method).cloned().collect()
+        }
+    }
+
+    #[async_trait]
+    impl RpcTransport for Fixture {
+        async fn post(&self, _url: &str, body: String) -> Result<String, ChainError> {
+            let request: Value = serde_json::from_str(&body).unwrap();
+            self.requests.lock().unwrap().push(request.clone());
+            let mut replies = self.replies.lock().unwrap();
+            let queue = replies.get_mut(request["method"].as_str().unwrap()).expect("unexpected method");
+            let mut reply = if queue.len() > 1 { queue.pop_front().unwrap() } else { queue[0].clone() };
+            reply["id"] = request["id"].clone();
+            Ok(reply.to_string())
+        }
+    }
+
+    pub(crate) fn ok(result: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "result": result })
+    }
+
+    struct TestSigner {
+        seen: Mutex<Vec<UnsignedTransaction>>,
+    }
+
+    #[async_trait]
+    impl Signer for TestSigner {
+        fn account_id(&self) -> String {
+            "alice.testnet".to_string()
+        }
+
+        async fn sign_transaction(&self, tx: &UnsignedTransaction) -> Result<Vec<u8>, ChainError> {
+            self.seen.lock().unwrap().push(tx.clone());
+            Ok(b"signed".to_vec())
+        }
+    }
+
+    fn client(fixture: &Arc<Fixture>) -> RpcClient {
+        RpcClient::new("http://node", fixture.clone())
+    }
+
+    #[tokio::test]
+    async fn test_near_mints_through_signer_and_lists_tokens() {
+        let tokens = json!([{ "token_id": "t1", "owner_id": "alice.testnet", "metadata": { "title": "A" } }]);
+        let fixture = Arc::new(
+            Fixture::default()
+                .reply("block", ok(json!({ "header": { "hash": "GJ2rdvfZ" } })))
+                .reply("broadcast_tx_async", ok(json!("9Fh2tx")))
+                .reply("query", ok(json!({ "result": tokens.to_string().into_bytes(), "logs": [] }))),
+        );
+        let signer = Arc::new(TestSigner { seen: Mutex::new(Vec::new()) });
+        let near = NearRpcAdapter::new(client(&fixture), "nft.compiling.testnet").with_signer(Some(signer.clone()));
+
+        let request = MintRequest {
+            token_id: "t1".to_string(),
+            metadata: json!({ "title": "A" }),
+            media_cid: "bafy".to_string(),
+            params: json!({ "initial_emotion": { "valence": 0.5 } }),
+        };
+        assert_eq!(near.mint(&request).await.unwrap(), "9Fh2tx");
+        let seen = signer.seen.lock().unwrap()[0].clone();
+        assert_eq!(seen.method, "mint_interactive_nft");
+        assert_eq!(seen.args["metadata"]["media"], "ipfs://bafy");
+        assert_eq!(seen.args["initial_emotion"]["valence"], 0.5);
+        assert_eq!(seen.context, TxContext::Near { nonce: None, block_hash: "GJ2rdvfZ".to_string(), gas: NEAR_MINT_GAS });
+        assert_eq!(fixture.requests("broadcast_tx_async")[0]["params"], json!(["c2lnbmVk"]));
+
+        let listed = near.get_user_nfts("alice.testnet").await.unwrap();
+        assert_eq!(listed[0].token_id, "t1");
+        let query = &fixture.requests("query")[0]["params"];
+        let args = base64::engine::general_purpose::STANDARD.decode(query["args_base64"].as_str().unwrap()).unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&args).unwrap()["account_id"], "alice.testnet");
+    }
+
+    #[tokio::test]
+    async fn test_near_reads_rpc_errors() {
+        let error = json!({ "jsonrpc": "2.0", "error": {
+            "code": -32000, "message": "Server error", "cause": { "name": "UNKNOWN_TRANSACTION" } } });
+        let fixture = Arc::new(Fixture::default().reply("tx", error));
+        let signer: Arc<dyn Signer> = Arc::new(TestSigner { seen: Mutex::new(Vec::new()) });
+        let near = NearRpcAdapter::new(client(&fixture), "nft.compiling.testnet");
+        assert_eq!(near.get_tx_status("abc").await, Err(ChainError::NoSigner));
+        let near = near.with_signer(Some(signer));
+        assert_eq!(near.get_tx_status("abc").await.unwrap(), TxStatus::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_near_fee_uses_node_prices_and_mint_stakes_storage() {
+        let fixture = Arc::new(
+            Fixture::default()
+                .reply("gas_price", ok(json!({ "gas_price": "200000000" })))
+                .reply("EXPERIMENTAL_protocol_config", ok(json!({
+                    "runtime_config": { "storage_amount_per_byte": "20000000000000000000" } })))
+                .reply("block", ok(json!({ "header": { "hash": "GJ2rdvfZ" } })))
+                .reply("broadcast_tx_async", ok(json!("9Fh2tx"))),
+        );
+        let signer = Arc::new(TestSigner { seen: Mutex::new(Vec::new()) });
+        let near = NearRpcAdapter::new(client(&fixture), "nft.compiling.testnet").with_signer(Some(signer.clone()));
+        let fee = near.estimate_fee(FeeOperation::Mint, 1_000).await.unwrap();
+        assert_eq!(fee.deposit, 1_400 * 20_000_000_000_000_000_000);
+        assert_eq!(fee.amount, fee.deposit + fee.units as u128 * 200_000_000);
+
+        let request = MintRequest {
+            token_id: "t1".to_string(),
+            metadata: json!({ "trajectory": "x".repeat(4_000) }),
+            media_cid: "bafy".to_string(),
+            params: Value::Null,
+        };
+        near.mint(&request).await.unwrap();
+        let deposit = signer.seen.lock().unwrap()[0].deposit;
+        assert_eq!(deposit, near.prices().storage_deposit(request.payload_bytes()));
+    }
+
+    #[tokio::test]
+    async fn test_solana_maps_signature_statuses() {
+        let fixture = Arc::new(
+            Fixture::default()
+                .reply("getSignatureStatuses", ok(json!({ "value": [{ "err": null, "confirmationStatus": "finalized" }] })))
+                .reply("getSignatureStatuses", ok(json!({ "value": [{ "err": { "InstructionError": [0, "Custom"] } }] })))
+                .reply("getSignatureStatuses", ok(json!({ "value": [null] }))),
+        );
+        let solana = SolanaRpcAdapter::new(client(&fixture), "Prog1111");
+        assert_eq!(solana.get_tx_status("s").await.unwrap(), TxStatus::Confirmed);
+        assert!(matches!(solana.get_tx_status("s").await.unwrap(), TxStatus::Failed(_)));
+        assert_eq!(solana.get_tx_status("s").await.unwrap(), TxStatus::Unknown);
+        assert!(SolanaRpcAdapter::for_network("devnet", "CompilingNFT1111111111111111111111111111111").is_err());
+        assert!(SolanaRpcAdapter::for_network("devnet", SPL_TOKEN_PROGRAM_ID).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_evm_enumerates_tokens_and_receipts() {
+        let mut uri = format!("{:064x}{:064x}", 32, 8);
+        uri.push_str(&format!("{:0<64}", hex::encode("ipfs://x")));
+        let fixture = Arc::new(
+            Fixture::default()
+                .reply("eth_call", ok(json!(format!("0x{:064x}", 1))))
+                .reply("eth_call", ok(json!(format!("0x{:064x}", 7))))
+                .reply("eth_call", ok(json!(format!("0x{}", uri))))
+                .reply("eth_getTransactionReceipt", ok(json!({ "status": "0x0" }))),
+        );
+        let evm = EvmRpcAdapter::new(ChainType::Polygon, client(&fixture), "0xc0ffee").unwrap();
+        let owner = "0x00000000000000000000000000000000000000aa";
+        let tokens = evm.get_user_nfts(owner).await.unwrap();
+        assert_eq!(tokens[0].token_id, "7");
+        assert_eq!(tokens[0].metadata["token_uri"], "ipfs://x");
+        let calls = fixture.requests("eth_call");
+        assert_eq!(calls[0]["params"][0]["data"], format!("0x70a08231{:0>64}", "aa"));
+
+        assert_eq!(evm.get_tx_status("0x1").await.unwrap(), TxStatus::Failed("reverted".to_string()));
+        assert!(evm.get_user_nfts("not-an-address").await.is_err());
+        assert!(EvmRpcAdapter::new(ChainType::Near, client(&fixture), "x").is_err());
+        assert!(EvmRpcAdapter::for_network(ChainType::Ethereum, "sepolia", "0xc0ffee").is_err());
+        assert!(EvmRpcAdapter::for_network(ChainType::Ethereum, "sepolia", owner).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_evm_balances_are_paged_and_capped() {
+        // A contract claiming 2^100 tokens, each call after the balance
+        // answering with token 7
+        let claiming = || {
+            let fixture = Fixture::default()
+                .reply("eth_call", ok(json!(format!("0x{:064x}", 1u128 << 100))))
+                .reply("eth_call", ok(json!(format!("0x{:064x}", 7))));
+            let fixture = Arc::new(fixture);
+            (EvmRpcAdapter::new(ChainType::Ethereum, client(&fixture), "0xc0ffee").unwrap(), fixture)
+        };
+        let owner = "0x00000000000000000000000000000000000000aa";
+        let (evm, fixture) = claiming();
+        let (page, balance) = evm.get_user_nfts_page(owner, 5, 2).await.unwrap();
+        assert_eq!((page.len(), balance), (2, 1u128 << 100));
+        let calls = fixture.requests("eth_call");
+        let indexes: Vec<_> = calls[1..].iter().step_by(2).map(|c| c["params"][0]["data"].clone()).collect();
+        let index_call = |index: u128| format!("0x{}{:0>64}{:064x}", ERC721_TOKEN_OF_OWNER_BY_INDEX, "aa", index);
+        assert_eq!(indexes, [index_call(5), index_call(6)]);
+
+        let (evm, _) = claiming();
+        assert_eq!(evm.get_user_nfts(owner).await.unwrap().len(), EVM_MAX_LISTED_TOKENS);
+        let (evm, _) = claiming();
+        assert!(evm.get_user_nfts_page(owner, u128::MAX, 10).await.unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn test_abi_strings_reject_out_of_range_offsets() {
+        let word = |n: u128| format!("{:064x}", n);
+        let decode = |text: String| abi_string(&hex::decode(text).unwrap());
+        assert_eq!(decode(format!("{}{}{:0<64}", word(32), word(2), "6869")).unwrap(), "hi");
+        assert!(decode(word(u128::MAX)).is_err());
+        assert!(decode(format!("{}{}", word(u64::MAX as u128 - 16), word(0))).is_err());
+        assert!(decode(format!("{}{}", word(32), word(u64::MAX as u128))).is_err());
+        assert!(decode(format!("{}{}", word(32), word(u64::MAX as u128 - 40))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_polkadot_fee_and_storage_keys() {
+        let le = |amount: u128| json!(format!("0x{}", hex::encode(amount.to_le_bytes())));
+        let fixture = Arc::new(
+            Fixture::default()
+                .reply("state_call", ok(le(2_000_000_000)))
+                .reply("state_call", ok(le(100_000_000)))
+                .reply("state_getKeysPaged", ok(json!([format!("0x{}{}", "00".repeat(100), "05000000")]))),
+        );
+        let dot = PolkadotRpcAdapter::new(client(&fixture), 3);
+        let fee = dot.estimate_fee(FeeOperation::Mint, 100).await.unwrap();
+        let prices = dot.prices();
+        assert_eq!((prices.weight_fee_per_second, prices.length_fee_per_byte), (2_000_000_000, 100_000));
+        assert_eq!(fee, prices.estimate(ChainType::Polkadot, FeeOperation::Mint, 100));
+        let calls = fixture.requests("state_call");
+        assert_eq!(calls[0]["params"][0], "TransactionPaymentApi_query_weight_to_fee");
+        assert_eq!(calls[1]["params"][1], "0xe8030000");
+
+        let alice = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let tokens = dot.get_user_nfts(alice).await.unwrap();
+        assert_eq!(tokens[0].token_id, "5");
+        let prefix = fixture.requests("state_getKeysPaged")[0]["params"][0].as_str().unwrap().to_string();
+        assert!(prefix.ends_with(&hex::encode(blake2_128_concat(&3u32.to_le_bytes()))));
+        assert_eq!(dot.get_tx_status("0xunknown").await.unwrap(), TxStatus::Unknown);
+
+        let version = json!({ "specVersion": 1_002_000, "transactionVersion": 1u64 << 32 });
+        assert_eq!(u32_at(&version, "/specVersion").unwrap(), 1_002_000);
+        assert!(u32_at(&version, "/transactionVersion").is_err());
+    }
+
+    #[test]
+    fn test_substrate_hashers_match_known_keys() {
+        let alice = ChainAddress::parse(ChainType::Polkadot, "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY").unwrap();
+        let mut key = twox128(b"System").to_vec();
+        key.extend_from_slice(&twox128(b"Account"));
+        key.extend(blake2_128_concat(alice.as_bytes()));
+        assert_eq!(
+            hex::encode(key),
+            "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9\
+             de1e86a9a8c739864cf3cc5ec2bea59fd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+    }
+
+    #[test]
+    fn test_scale_compact_modes() {
+        for (value, expected) in [(1u64, "04"), (64, "0101"), (16_384, "02000100"), (1 << 30, "0300000040")] {
+            let mut out = Vec::new();
+            scale_compact(value, &mut out);
+            assert_eq!(hex::encode(out), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_abi_calls_put_dynamic_values_after_the_head() {
+        let owner = "0x000000000000000000000000000000000000dead";
+        let balance = abi_encode_call(ChainType::Ethereum, "balanceOf(address)", &json!([owner])).unwrap();
+        assert_eq!(hex::encode(&balance[..4]), ERC721_BALANCE_OF);
+        assert_eq!(hex::encode(&balance[4..]), format!("{:0>64}", "dead"));
+
+        let mint = abi_encode_call(ChainType::Ethereum, "mint(address,string)", &json!([owner, "ipfs://cid"])).unwrap();
+        assert_eq!(mint.len(), 4 + 4 * 32);
+        assert_eq!(abi_u128(abi_word(&mint[4..], 1).unwrap()).unwrap(), 64);
+        assert_eq!(abi_u128(abi_word(&mint[4..], 2).unwrap()).unwrap(), 10);
+        assert_eq!(&mint[4 + 96..4 + 106], b"ipfs://cid");
+
+        assert!(abi_encode_call(ChainType::Ethereum, "mint(address,string)", &json!([owner])).is_err());
+    }
+}
@@ -0,0 +1,655 @@
+//! CPU reference renderer for the `ShaderEngine` fractal presets.
+//!
+//! Evaluates the same fragment programs as the WebGL presets, per pixel and in
+//! `f32` like `highp`, so thumbnails, previews and regression images can be
+//! made on servers without a GPU. Images are RGBA8 and round-trip through PNG.
+//!
+//! [`compare`] and [`check_golden`] form the image-diff harness: renders are
+//! compared to golden PNGs with a per-channel tolerance, and a small share of
+//! differing pixels is allowed since escape-time boundaries are chaotic.
+
+use crate::webgpu_engine::UniformValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Iteration cap of the preset shaders' loops
+pub const SHADER_LOOP_LIMIT: u32 = 1000;
+
+/// Errors from rendering and comparing images
+#[derive(Debug)]
+pub enum RenderError {
+    UnknownPreset(String),
+    /// A uniform set with a type the preset does not declare
+    UniformType(String),
+    /// Width or height of zero, or a pixel buffer of the wrong length
+    InvalidSize { width: u32, height: u32 },
+    SizeMismatch { expected: (u32, u32), actual: (u32, u32) },
+    Png(String),
+    Io(std::io::Error),
+    /// No golden image to compare against and [`UPDATE_GOLDEN_ENV`] unset
+    MissingGolden(String),
+    /// A render differs from its golden image by more than the tolerance
+    GoldenMismatch { path: String, diff: ImageDiff },
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::UnknownPreset(name) => write!(f, "unknown fractal preset '{}'", name),
+            RenderError::UniformType(name) => write!(f, "wrong value type for uniform '{}'", name),
+            RenderError::InvalidSize { width, height } => write!(f, "invalid image size {}x{}", width, height),
+            RenderError::SizeMismatch { expected, actual } => write!(
+                f,
+                "image is {}x{}, expected {}x{}",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            RenderError::Png(msg) => write!(f, "PNG error: {}", msg),
+            RenderError::Io(e) => write!(f, "I/O error: {}", e),
+            RenderError::MissingGolden(path) => {
+                write!(f, "no golden image at {}; set {} to record it", path, UPDATE_GOLDEN_ENV)
+            }
+            RenderError::GoldenMismatch { path, diff } => write!(
+                f,
+                "render differs from {}: {} pixels differ, by up to {}",
+                path, diff.mismatched_pixels, diff.max_channel_delta
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<std::io::Error> for RenderError {
+    fn from(e: std::io::Error) -> Self {
+        RenderError::Io(e)
+    }
+}
+
+impl From<png::EncodingError> for RenderError {
+    fn from(e: png::EncodingError) -> Self {
+        RenderError::Png(e.to_string())
+    }
+}
+
+impl From<png::DecodingError> for RenderError {
+    fn from(e: png::DecodingError) -> Self {
+        RenderError::Png(e.to_string())
+    }
+}
+
+/// Fractal presets of `ShaderEngine::load_fractal_shader`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FractalPreset {
+    Mandelbrot,
+    Julia,
+    BurningShip,
+    Newton,
+    Phoenix,
+    EmotionalMandelbrot,
+}
+
+impl FractalPreset {
+    pub const ALL: [FractalPreset; 6] = [
+        FractalPreset::Mandelbrot,
+        FractalPreset::Julia,
+        FractalPreset::BurningShip,
+        FractalPreset::Newton,
+        FractalPreset::Phoenix,
+        FractalPreset::EmotionalMandelbrot,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FractalPreset::Mandelbrot => "mandelbrot",
+            FractalPreset::Julia => "julia",
+            FractalPreset::BurningShip => "burning_ship",
+            FractalPreset::Newton => "newton",
+            FractalPreset::Phoenix => "phoenix",
+            FractalPreset::EmotionalMandelbrot => "emotional_mandelbrot",
+        }
+    }
+}
+
+impl FromStr for FractalPreset {
+    type Err = RenderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FractalPreset::ALL
+            .into_iter()
+            .find(|preset| preset.as_str() == s)
+            .ok_or_else(|| RenderError::UnknownPreset(s.to_string()))
+    }
+}
+
+/// Uniforms the preset shaders read.
+///
+/// WebGL leaves unset uniforms at zero, which renders nothing useful, so the
+/// defaults here frame the whole set instead.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FractalUniforms {
+    pub time: f32,
+    pub zoom: f32,
+    pub offset: [f32; 2],
+    /// Julia constant (`u_c`)
+    pub c: [f32; 2],
+    pub max_iter: u32,
+    pub color1: [f32; 3],
+    pub color2: [f32; 3],
+    pub emotion_valence: f32,
+    pub emotion_arousal: f32,
+    pub emotion_dominance: f32,
+    pub emotion_confidence: f32,
+    pub emotion_complexity: f32,
+}
+
+impl Default for FractalUniforms {
+    fn default() -> Self {
+        FractalUniforms {
+            time: 0.0,
+            zoom: 2.5,
+            offset: [-0.5, 0.0],
+            c: [-0.8, 0.156],
+            max_iter: 100,
+            color1: [0.0, 0.0, 0.0],
+            color2: [1.0, 1.0, 1.0],
+            emotion_valence: 0.0,
+            emotion_arousal: 0.0,
+            emotion_dominance: 0.0,
+            emotion_confidence: 0.0,
+            emotion_complexity: 0.0,
+        }
+    }
+}
+
+impl FractalUniforms {
+    /// Defaults overridden by the uniforms an engine recorded; unknown names are ignored
+    pub fn from_uniforms(uniforms: &HashMap<String, UniformValue>) -> Result<Self, RenderError> {
+        let mut result = Self::default();
+        for (name, value) in uniforms {
+            result.set(name, value)?;
+        }
+        Ok(result)
+    }
+
+    /// Set a uniform by its GLSL name. JS numbers arrive as floats, so
+    /// `u_max_iter` takes either.
+    pub fn set(&mut self, name: &str, value: &UniformValue) -> Result<(), RenderError> {
+        let wrong_type = || RenderError::UniformType(name.to_string());
+        let float = || match value {
+            UniformValue::Float(f) => Ok(*f),
+            _ => Err(wrong_type()),
+        };
+        match name {
+            "u_time" => self.time = float()?,
+            "u_zoom" => self.zoom = float()?,
+            "u_offset" | "u_c" => {
+                let UniformValue::Vec2(v) = value else { return Err(wrong_type()) };
+                if name == "u_offset" {
+                    self.offset = *v;
+                } else {
+                    self.c = *v;
+                }
+            }
+            "u_max_iter" => {
+                self.max_iter = match value {
+                    UniformValue::Int(i) => (*i).max(0) as u32,
+                    UniformValue::Float(f) => f.max(0.0) as u32,
+                    _ => return Err(wrong_type()),
+                }
+            }
+            "u_color1" | "u_color2" => {
+                let UniformValue::Vec3(v) = value else { return Err(wrong_type()) };
+                if name == "u_color1" {
+                    self.color1 = *v;
+                } else {
+                    self.color2 = *v;
+                }
+            }
+            "u_emotion_valence" => self.emotion_valence = float()?,
+            "u_emotion_arousal" => self.emotion_arousal = float()?,
+            "u_emotion_dominance" => self.emotion_dominance = float()?,
+            "u_emotion_confidence" => self.emotion_confidence = float()?,
+            "u_emotion_complexity" => self.emotion_complexity = float()?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// 8-bit RGBA image, rows top to bottom
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, RenderError> {
+        if width == 0 || height == 0 || pixels.len() != width as usize * height as usize * 4 {
+            return Err(RenderError::InvalidSize { width, height });
+        }
+        Ok(RgbaImage { width, height, pixels })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Pixel at column `x` of row `y`, counting rows from the top
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[i..i + 4].try_into().expect("4 channels")
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, RenderError> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)?;
+        Ok(out)
+    }
+
+    /// Decode an 8-bit RGBA PNG
+    pub fn from_png(bytes: &[u8]) -> Result<Self, RenderError> {
+        let mut reader = png::Decoder::new(bytes).read_info()?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels)?;
+        if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+            let format = format!("{:?} {:?}", info.color_type, info.bit_depth);
+            return Err(RenderError::Png(format!("expected 8-bit RGBA, got {}", format)));
+        }
+        pixels.truncate(info.buffer_size());
+        Self::new(info.width, info.height, pixels)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> Result<(), RenderError> {
+        Ok(std::fs::write(path, self.to_png()?)?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_png(path: impl AsRef<std::path::Path>) -> Result<Self, RenderError> {
+        Self::from_png(&std::fs::read(path)?)
+    }
+}
+
+/// Render `preset` at `width`x`height` as the WebGL engine would draw it
+pub fn render_fractal(
+    preset: FractalPreset,
+    uniforms: &FractalUniforms,
+    width: u32,
+    height: u32,
+) -> Result<RgbaImage, RenderError> {
+    if width == 0 || height == 0 {
+        return Err(RenderError::InvalidSize { width, height });
+    }
+    let resolution = [width as f32, height as f32];
+    let scale = resolution[0].min(resolution[1]);
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for row in 0..height {
+        // gl_FragCoord counts rows from the bottom and samples pixel centres
+        let frag_y = (height - 1 - row) as f32 + 0.5;
+        for column in 0..width {
+            let frag_x = column as f32 + 0.5;
+            let uv = [(frag_x - 0.5 * resolution[0]) / scale, (frag_y - 0.5 * resolution[1]) / scale];
+            let color = shade(preset, uniforms, uv);
+            pixels.extend(color.map(to_unorm8));
+            pixels.push(255);
+        }
+    }
+    RgbaImage::new(width, height, pixels)
+}
+
+/// `gl_FragColor.rgb` of one fragment
+fn shade(preset: FractalPreset, u: &FractalUniforms, uv: [f32; 2]) -> [f32; 3] {
+    let limit = u.max_iter.min(SHADER_LOOP_LIMIT);
+    let start = [uv[0] * u.zoom + u.offset[0], uv[1] * u.zoom + u.offset[1]];
+    let iter = match preset {
+        FractalPreset::Mandelbrot => {
+            escape_time(limit, [0.0, 0.0], |[x, y]| [x * x - y * y + start[0], 2.0 * x * y + start[1]])
+        }
+        FractalPreset::Julia => {
+            let z = [uv[0] * u.zoom, uv[1] * u.zoom];
+            escape_time(limit, z, |[x, y]| [x * x - y * y + u.c[0], 2.0 * x * y + u.c[1]])
+        }
+        FractalPreset::BurningShip => {
+            escape_time(limit, [0.0, 0.0], |[x, y]| [x * x - y * y + start[0], 2.0 * (x * y).abs() + start[1]])
+        }
+        FractalPreset::Newton => newton(limit, start),
+        FractalPreset::Phoenix => {
+            let mut previous = [0.0f32, 0.0];
+            escape_time(limit, [0.0, 0.0], |[x, y]| {
+                let next = [
+                    x * x - y * y + start[0] + 0.56667 * previous[0],
+                    2.0 * x * y + start[1] - 0.5 * previous[1],
+                ];
+                previous = [x, y];
+                next
+            })
+        }
+        FractalPreset::EmotionalMandelbrot => {
+            let c = [
+                start[0] + u.emotion_valence * 0.1 * u.emotion_confidence,
+                start[1] + u.emotion_arousal * 0.05 * u.emotion_dominance,
+            ];
+            escape_time(limit, [0.0, 0.0], |[x, y]| [x * x - y * y + c[0], 2.0 * x * y + c[1]])
+        }
+    };
+
+    let t = iter as f32 / u.max_iter as f32;
+    let base = mix(u.color1, u.color2, t);
+    if preset != FractalPreset::EmotionalMandelbrot {
+        return base;
+    }
+    let emotion = [
+        u.emotion_valence.abs() * u.emotion_confidence,
+        u.emotion_arousal * u.emotion_dominance,
+        u.emotion_complexity,
+    ];
+    let blend = 0.3 * (u.emotion_valence * u.emotion_valence + u.emotion_arousal * u.emotion_arousal).sqrt();
+    mix(base, emotion, blend)
+}
+
+/// The presets' loop: stop at the limit or once |z|² > 4; `iter` is the last
+/// index that stepped
+fn escape_time(limit: u32, mut z: [f32; 2], mut step: impl FnMut([f32; 2]) -> [f32; 2]) -> u32 {
+    let mut iter = 0;
+    for i in 0..limit {
+        if z[0] * z[0] + z[1] * z[1] > 4.0 {
+            break;
+        }
+        z = step(z);
+        iter = i;
+    }
+    iter
+}
+
+/// Newton's method on z³ - 1; `iter` is only set when it converges
+fn newton(limit: u32, mut z: [f32; 2]) -> u32 {
+    for i in 0..limit {
+        let z2 = [z[0] * z[0] - z[1] * z[1], 2.0 * z[0] * z[1]];
+        let z3 = [z2[0] * z[0] - z2[1] * z[1], z2[0] * z[1] + z2[1] * z[0]];
+        let fz = [z3[0] - 1.0, z3[1]];
+        let dfz = [3.0 * z2[0], 3.0 * z2[1]];
+        let denom = dfz[0] * dfz[0] + dfz[1] * dfz[1];
+        if denom < 0.0001 {
+            break;
+        }
+        let next = [
+            z[0] - (fz[0] * dfz[0] + fz[1] * dfz[1]) / denom,
+            z[1] - (fz[1] * dfz[0] - fz[0] * dfz[1]) / denom,
+        ];
+        if ((z[0] - next[0]).powi(2) + (z[1] - next[1]).powi(2)).sqrt() < 0.0001 {
+            return i;
+        }
+        z = next;
+    }
+    0
+}
+
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [0, 1, 2].map(|i| a[i] * (1.0 - t) + b[i] * t)
+}
+
+/// Colour channel to a normalized 8-bit value, as GL writes it
+fn to_unorm8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// How far an image may stray from its reference
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DiffTolerance {
+    /// Largest channel difference that still counts as equal
+    pub channel: u8,
+    /// Share of pixels, 0 to 1, allowed to differ by more
+    pub mismatched_ratio: f32,
+}
+
+impl Default for DiffTolerance {
+    fn default() -> Self {
+        DiffTolerance { channel: 2, mismatched_ratio: 0.005 }
+    }
+}
+
+impl DiffTolerance {
+    pub fn exact() -> Self {
+        DiffTolerance { channel: 0, mismatched_ratio: 0.0 }
+    }
+}
+
+/// Difference between two images of the same size
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImageDiff {
+    /// Pixels with a channel differing by more than the tolerance
+    pub mismatched_pixels: usize,
+    pub total_pixels: usize,
+    pub max_channel_delta: u8,
+    /// Mean absolute difference over all channels, 0 to 255
+    pub mean_abs_error: f32,
+    pub within_tolerance: bool,
+}
+
+/// Compare `actual` to `expected` under `tolerance`
+pub fn compare(expected: &RgbaImage, actual: &RgbaImage, tolerance: DiffTolerance) -> Result<ImageDiff, RenderError> {
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        return Err(RenderError::SizeMismatch {
+            expected: (expected.width, expected.height),
+            actual: (actual.width, actual.height),
+        });
+    }
+    let mut mismatched_pixels = 0;
+    let mut max_channel_delta = 0;
+    let mut total_error = 0u64;
+    for (a, b) in expected.pixels.chunks_exact(4).zip(actual.pixels.chunks_exact(4)) {
+        let delta = a.iter().zip(b).map(|(a, b)| a.abs_diff(*b)).fold(0, u8::max);
+        total_error += a.iter().zip(b).map(|(a, b)| a.abs_diff(*b) as u64).sum::<u64>();
+        max_channel_delta = max_channel_delta.max(delta);
+        if delta > tolerance.channel {
+            mismatched_pixels += 1;
+        }
+    }
+    let total_pixels = expected.pixels.len() / 4;
+    Ok(ImageDiff {
+        mismatched_pixels,
+        total_pixels,
+        max_channel_delta,
+        mean_abs_error: total_error as f32 / expected.pixels.len() as f32,
+        within_tolerance: mismatched_pixels as f32 <= tolerance.mismatched_ratio * total_pixels as f32,
+    })
+}
+
+/// Differing pixels in red over a dimmed copy of `expected`, for inspecting failures
+pub fn diff_image(
+    expected: &RgbaImage,
+    actual: &RgbaImage,
+    tolerance: DiffTolerance,
+) -> Result<RgbaImage, RenderError> {
+    compare(expected, actual, tolerance)?;
+    let pixels = expected
+        .pixels
+        .chunks_exact(4)
+        .zip(actual.pixels.chunks_exact(4))
+        .flat_map(|(a, b)| {
+            if a.iter().zip(b).any(|(a, b)| a.abs_diff(*b) > tolerance.channel) {
+                [255, 0, 0, 255]
+            } else {
+                [a[0] / 4, a[1] / 4, a[2] / 4, 255]
+            }
+        })
+        .collect();
+    RgbaImage::new(expected.width, expected.height, pixels)
+}
+
+/// Set to re-record golden images instead of comparing against them
+pub const UPDATE_GOLDEN_ENV: &str = "UPDATE_GOLDEN";
+
+/// Compare `actual` to the golden PNG at `path`.
+///
+/// With [`UPDATE_GOLDEN_ENV`] set `actual` is recorded as the new golden;
+/// otherwise a missing golden is an error. On a mismatch the diff is written next to the golden as
+/// `<name>.diff.png` and the actual render as `<name>.actual.png`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn check_golden(
+    path: impl AsRef<std::path::Path>,
+    actual: &RgbaImage,
+    tolerance: DiffTolerance,
+) -> Result<ImageDiff, RenderError> {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        actual.save_png(path)?;
+        return compare(actual, actual, tolerance);
+    }
+    if !path.exists() {
+        return Err(RenderError::MissingGolden(path.display().to_string()));
+    }
+    let expected = RgbaImage::open_png(path)?;
+    let diff = compare(&expected, actual, tolerance)?;
+    if !diff.within_tolerance {
+        diff_image(&expected, actual, tolerance)?.save_png(path.with_extension("diff.png"))?;
+        actual.save_png(path.with_extension("actual.png"))?;
+        return Err(RenderError::GoldenMismatch { path: path.display().to_string(), diff });
+    }
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgpu_engine::fractal_fragment;
+    use sha2::{Digest, Sha256};
+
+    fn golden(preset: FractalPreset) -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.png", preset.as_str()))
+    }
+
+    /// The golden images are rendered here on the CPU, so they only stand for
+    /// the WebGL preset while its GLSL is the text the renderer was checked against
+    fn check_fragment_pin(preset: FractalPreset) -> Result<(), String> {
+        let pin = golden(preset).with_extension("frag.sha256");
+        let hash = hex::encode(Sha256::digest(fractal_fragment(preset)));
+        if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+            return std::fs::write(&pin, hash + "\n").map_err(|e| e.to_string());
+        }
+        match std::fs::read_to_string(&pin) {
+            Ok(pinned) if pinned.trim() == hash => Ok(()),
+            Ok(_) => Err(format!(
+                "fragment shader changed since its golden was recorded; update the CPU renderer to match and \
+                 re-record with {}=1",
+                UPDATE_GOLDEN_ENV
+            )),
+            Err(e) => Err(format!("{}: {}", pin.display(), e)),
+        }
+    }
+
+    fn preview_uniforms(preset: FractalPreset) -> FractalUniforms {
+        let mut uniforms = FractalUniforms { color1: [0.05, 0.0, 0.2], color2: [1.0, 0.8, 0.3], ..Default::default() };
+        match preset {
+            FractalPreset::Julia => uniforms.zoom = 1.5,
+            FractalPreset::BurningShip => uniforms.offset = [-0.4, -0.5],
+            FractalPreset::Newton => uniforms.offset = [0.0, 0.0],
+            FractalPreset::EmotionalMandelbrot => {
+                uniforms.emotion_valence = 0.6;
+                uniforms.emotion_arousal = 0.8;
+                uniforms.emotion_dominance = 0.5;
+                uniforms.emotion_confidence = 0.9;
+                uniforms.emotion_complexity = 0.4;
+            }
+            _ => {}
+        }
+        uniforms
+    }
+
+    #[test]
+    fn test_presets_match_golden_images() {
+        for preset in FractalPreset::ALL {
+            let image = render_fractal(preset, &preview_uniforms(preset), 96, 64).unwrap();
+            if let Err(e) = check_golden(golden(preset), &image, DiffTolerance::default()) {
+                panic!("{}: {}", preset.as_str(), e);
+            }
+            if let Err(e) = check_fragment_pin(preset) {
+                panic!("{}: {}", preset.as_str(), e);
+            }
+        }
+
+        if std::env::var_os(UPDATE_GOLDEN_ENV).is_none() {
+            let image = render_fractal(FractalPreset::Julia, &FractalUniforms::default(), 8, 8).unwrap();
+            let missing = golden(FractalPreset::Julia).with_file_name("missing.png");
+            let result = check_golden(&missing, &image, DiffTolerance::default());
+            assert!(matches!(result, Err(RenderError::MissingGolden(_))) && !missing.exists());
+        }
+    }
+
+    #[test]
+    fn test_renders_follow_gl_conventions() {
+        let uniforms = FractalUniforms::default();
+        let image = render_fractal(FractalPreset::Mandelbrot, &uniforms, 64, 64).unwrap();
+        // The centre is inside the set and runs the whole loop; corners escape at once
+        assert_eq!(image.pixel(40, 32), [252, 252, 252, 255]);
+        assert_eq!(image.pixel(0, 0), [0, 0, 0, 255]);
+
+        // Rows count from the bottom in GL, so an offset up shows up at the top of the image
+        let up = FractalUniforms { offset: [-0.5, 0.6], ..uniforms.clone() };
+        let down = FractalUniforms { offset: [-0.5, -0.6], ..uniforms };
+        let up = render_fractal(FractalPreset::Mandelbrot, &up, 32, 32).unwrap();
+        let down = render_fractal(FractalPreset::Mandelbrot, &down, 32, 32).unwrap();
+        assert_eq!(up.pixel(10, 5), down.pixel(10, 26));
+
+        assert!(render_fractal(FractalPreset::Julia, &FractalUniforms::default(), 0, 8).is_err());
+    }
+
+    #[test]
+    fn test_uniforms_come_from_engine_values() {
+        let recorded = HashMap::from([
+            ("u_zoom".to_string(), UniformValue::Float(0.5)),
+            ("u_max_iter".to_string(), UniformValue::Float(250.0)),
+            ("u_c".to_string(), UniformValue::Vec2([0.3, 0.5])),
+            ("u_time".to_string(), UniformValue::Float(1.0)),
+            ("u_resolution".to_string(), UniformValue::Vec2([800.0, 600.0])),
+        ]);
+        let uniforms = FractalUniforms::from_uniforms(&recorded).unwrap();
+        assert_eq!((uniforms.zoom, uniforms.max_iter, uniforms.c), (0.5, 250, [0.3, 0.5]));
+
+        let wrong = HashMap::from([("u_color1".to_string(), UniformValue::Float(1.0))]);
+        assert!(matches!(FractalUniforms::from_uniforms(&wrong), Err(RenderError::UniformType(_))));
+        assert_eq!("burning_ship".parse::<FractalPreset>().unwrap(), FractalPreset::BurningShip);
+        assert!("sierpinski".parse::<FractalPreset>().is_err());
+    }
+
+    #[test]
+    fn test_diffs_count_pixels_beyond_tolerance() {
+        let image = render_fractal(FractalPreset::Phoenix, &FractalUniforms::default(), 20, 10).unwrap();
+        let png = image.to_png().unwrap();
+        assert_eq!(RgbaImage::from_png(&png).unwrap(), image);
+
+        let mut pixels = image.pixels().to_vec();
+        pixels[0] = pixels[0].wrapping_add(1);
+        pixels[4] = pixels[4].wrapping_add(40);
+        let changed = RgbaImage::new(20, 10, pixels).unwrap();
+        let strict = DiffTolerance { mismatched_ratio: 0.0, ..Default::default() };
+        let diff = compare(&image, &changed, strict).unwrap();
+        assert_eq!((diff.mismatched_pixels, diff.max_channel_delta), (1, 40));
+        assert!(!diff.within_tolerance);
+        assert!(compare(&image, &changed, DiffTolerance::default()).unwrap().within_tolerance);
+        assert_eq!(compare(&image, &changed, DiffTolerance::exact()).unwrap().mismatched_pixels, 2);
+        assert_eq!(diff_image(&image, &changed, DiffTolerance::default()).unwrap().pixel(1, 0), [255, 0, 0, 255]);
+
+        let small = render_fractal(FractalPreset::Phoenix, &FractalUniforms::default(), 10, 10).unwrap();
+        assert!(matches!(compare(&image, &small, DiffTolerance::exact()), Err(RenderError::SizeMismatch { .. })));
+    }
+}
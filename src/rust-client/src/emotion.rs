@@ -0,0 +1,152 @@
+//! Conversions between this crate's emotion types and `emotion_core`
+//!
+//! Only the reading itself (VAD, confidence, timestamp) crosses over.
+//! Trajectories and predictions stay behind, and the category is always
+//! recomputed with `emotion_core`.
+
+use crate::blockchain_integration;
+use crate::webgpu_engine;
+use crate::{EmotionalData, EmotionalVector};
+use chrono::{DateTime, TimeZone, Utc};
+use emotion_core::{EmotionError, EmotionReading, Vad};
+
+fn timestamp_ns(timestamp: &DateTime<Utc>) -> Result<i64, EmotionError> {
+    timestamp.timestamp_nanos_opt().ok_or(EmotionError::TimestampOutOfRange)
+}
+
+fn reading(vad: Vad, confidence: f32, timestamp: &DateTime<Utc>) -> Result<EmotionReading, EmotionError> {
+    Ok(EmotionReading::new(vad)
+        .with_confidence(confidence)?
+        .with_timestamp_ns(timestamp_ns(timestamp)?))
+}
+
+/// Both `EmotionalVector`s share a layout, so they share conversions
+macro_rules! emotional_vector_conversions {
+    ($ty:ty) => {
+        impl TryFrom<&$ty> for EmotionReading {
+            type Error = EmotionError;
+
+            fn try_from(v: &$ty) -> Result<Self, Self::Error> {
+                reading(Vad::new(v.valence, v.arousal, v.dominance)?, v.confidence, &v.timestamp)
+            }
+        }
+
+        impl TryFrom<EmotionReading> for $ty {
+            type Error = EmotionError;
+
+            fn try_from(reading: EmotionReading) -> Result<Self, Self::Error> {
+                reading.validate()?;
+                Ok(Self {
+                    valence: reading.vad.valence,
+                    arousal: reading.vad.arousal,
+                    dominance: reading.vad.dominance,
+                    confidence: reading.require_confidence()?,
+                    timestamp: Utc.timestamp_nanos(reading.require_timestamp_ns()?),
+                    emotional_category: reading.category().to_string(),
+                    emotional_trajectory: vec![],
+                    predicted_emotion: None,
+                    emotional_complexity: 0.0,
+                })
+            }
+        }
+    };
+}
+
+emotional_vector_conversions!(EmotionalVector);
+emotional_vector_conversions!(webgpu_engine::EmotionalVector);
+
+impl TryFrom<&EmotionalData> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(data: &EmotionalData) -> Result<Self, Self::Error> {
+        reading(Vad::new(data.valence, data.arousal, data.dominance)?, data.confidence, &data.timestamp)
+    }
+}
+
+impl TryFrom<EmotionReading> for EmotionalData {
+    type Error = EmotionError;
+
+    fn try_from(reading: EmotionReading) -> Result<Self, Self::Error> {
+        reading.validate()?;
+        Ok(Self {
+            timestamp: Utc.timestamp_nanos(reading.require_timestamp_ns()?),
+            valence: reading.vad.valence,
+            arousal: reading.vad.arousal,
+            dominance: reading.vad.dominance,
+            confidence: reading.require_confidence()?,
+            raw_vector: vec![],
+            emotional_category: reading.category().to_string(),
+            emotional_trajectory: vec![],
+            predicted_emotion: None,
+            emotional_complexity: 0.0,
+        })
+    }
+}
+
+impl TryFrom<&blockchain_integration::EmotionalVector> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(v: &blockchain_integration::EmotionalVector) -> Result<Self, Self::Error> {
+        // Timestamps come from JavaScript `Date.now()`
+        let millis = i64::try_from(v.timestamp).map_err(|_| EmotionError::TimestampOutOfRange)?;
+        EmotionReading::new(Vad::new(v.valence, v.arousal, v.dominance)?)
+            .with_confidence(v.confidence)?
+            .with_timestamp_millis(millis)
+    }
+}
+
+impl TryFrom<EmotionReading> for blockchain_integration::EmotionalVector {
+    type Error = EmotionError;
+
+    fn try_from(reading: EmotionReading) -> Result<Self, Self::Error> {
+        reading.validate()?;
+        let millis = reading.require_timestamp_millis()?;
+        Ok(Self {
+            valence: reading.vad.valence,
+            arousal: reading.vad.arousal,
+            dominance: reading.vad.dominance,
+            confidence: reading.require_confidence()?,
+            timestamp: u64::try_from(millis).map_err(|_| EmotionError::TimestampOutOfRange)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_emotional_data;
+
+    #[test]
+    fn test_emotional_vector_round_trip() {
+        let original = EmotionalVector::new(0.7, 0.8, 0.3);
+        let reading = EmotionReading::try_from(&original).unwrap();
+        let restored = EmotionalVector::try_from(reading).unwrap();
+
+        assert_eq!(restored.valence, original.valence);
+        assert_eq!(restored.confidence, original.confidence);
+        assert_eq!(restored.timestamp, original.timestamp);
+        assert_eq!(restored.emotional_category, "Happy");
+    }
+
+    #[test]
+    fn test_same_label_across_types() {
+        let data = generate_emotional_data(0.7, 0.8, 0.3, vec![]);
+        let vector = webgpu_engine::EmotionalVector::new(0.7, 0.8, 0.3);
+        assert_eq!(data.emotional_category, vector.emotional_category);
+
+        let reading = EmotionReading::try_from(&data).unwrap();
+        assert_eq!(reading.category().as_str(), data.emotional_category);
+    }
+
+    #[test]
+    fn test_millisecond_target_rejects_finer_timestamps() {
+        let reading = EmotionReading::new(Vad::NEUTRAL)
+            .with_confidence(0.5)
+            .unwrap()
+            .with_timestamp_ns(1_500_000);
+        assert_eq!(
+            blockchain_integration::EmotionalVector::try_from(reading).err(),
+            Some(EmotionError::PrecisionLoss { field: "timestamp" })
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Fee estimation: a cost model per chain over a configurable price table.
+//!
+//! Models price an operation from the size of the serialized metadata it
+//! carries, so large emotional trajectories show their real cost before
+//! minting. Tables start from typical mainnet prices and `refresh` pulls the
+//! live ones from a node.
+
+use crate::blockchain_integration::ChainType;
+use crate::chain_adapter::{amount_string, ChainError, FeeEstimate, FeeOperation, MaybeSend, RpcClient};
+use crate::chain_rpc::{hex_bytes, hex_u128, invalid, scale_compact, str_at, string_result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Bytes Solana charges rent for on top of every account's data
+pub const SOLANA_ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
+/// SPL mint account, created on mint
+pub const SPL_MINT_ACCOUNT_SIZE: u64 = 82;
+/// SPL token account, created on mint and for receivers that have none
+pub const SPL_TOKEN_ACCOUNT_SIZE: u64 = 165;
+
+/// Gas every EVM transaction pays before executing
+pub const EVM_INTRINSIC_GAS: u64 = 21_000;
+/// Calldata gas per non-zero byte, charged for every byte as an upper bound
+pub const EVM_CALLDATA_GAS_PER_BYTE: u64 = 16;
+/// `SSTORE` of a fresh 32-byte word
+pub const EVM_STORAGE_WORD_GAS: u64 = 20_000;
+
+/// Signature, address, extensions and call indices of a signed extrinsic
+pub const POLKADOT_EXTRINSIC_OVERHEAD: u64 = 128;
+/// One second of `ref_time`, in picoseconds
+const POLKADOT_WEIGHT_PER_SECOND: u64 = 1_000_000_000_000;
+/// Extrinsic length used to read the node's length fee
+const POLKADOT_LENGTH_PROBE: u32 = 1_000;
+
+/// Prices one chain's operations and keeps its prices current
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait CostModel: MaybeSend {
+    /// Fee for `operation` carrying `payload_bytes` of serialized metadata
+    fn estimate(&self, chain: ChainType, operation: FeeOperation, payload_bytes: u64) -> FeeEstimate;
+
+    /// Replace the prices a node can report with its current ones
+    async fn refresh(&mut self, rpc: &RpcClient) -> Result<(), ChainError>;
+}
+
+/// Cost model with default prices for `chain`
+pub fn default_cost_model(chain: ChainType) -> Box<dyn CostModel> {
+    match chain {
+        ChainType::Near => Box::new(NearPrices::default()),
+        ChainType::Solana => Box::new(SolanaPrices::default()),
+        ChainType::Ethereum | ChainType::Polygon => Box::new(EvmPrices::default()),
+        ChainType::Polkadot => Box::new(PolkadotPrices::default()),
+    }
+}
+
+fn priced(chain: ChainType, operation: FeeOperation, spent: u128, deposit: u128, units: u64) -> FeeEstimate {
+    FeeEstimate { deposit, units, ..FeeEstimate::new(chain, operation, spent + deposit) }
+}
+
+/// NEAR: gas for the call plus storage staking for the bytes it writes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NearPrices {
+    /// yoctoNEAR per gas unit
+    #[serde(with = "amount_string")]
+    pub gas_price: u128,
+    /// yoctoNEAR staked per byte of contract state
+    #[serde(with = "amount_string")]
+    pub storage_byte_cost: u128,
+    pub mint_gas: u64,
+    pub transfer_gas: u64,
+    /// Gas per byte of metadata passed in and written to state
+    pub gas_per_byte: u64,
+    /// State a minted token takes besides its metadata: owner, id and indices
+    pub token_storage_bytes: u64,
+}
+
+impl Default for NearPrices {
+    fn default() -> Self {
+        NearPrices {
+            gas_price: 100_000_000,
+            storage_byte_cost: 10_000_000_000_000_000_000,
+            mint_gas: 15_000_000_000_000,
+            transfer_gas: 8_000_000_000_000,
+            gas_per_byte: 40_000_000,
+            token_storage_bytes: 400,
+        }
+    }
+}
+
+impl NearPrices {
+    pub fn gas(&self, operation: FeeOperation, payload_bytes: u64) -> u64 {
+        let base = match operation {
+            FeeOperation::Mint => self.mint_gas,
+            FeeOperation::Transfer => self.transfer_gas,
+        };
+        base + self.gas_per_byte * payload_bytes
+    }
+
+    /// Deposit a mint attaches to cover the storage it uses
+    pub fn storage_deposit(&self, payload_bytes: u64) -> u128 {
+        (self.token_storage_bytes + payload_bytes) as u128 * self.storage_byte_cost
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl CostModel for NearPrices {
+    fn estimate(&self, chain: ChainType, operation: FeeOperation, payload_bytes: u64) -> FeeEstimate {
+        let gas = self.gas(operation, payload_bytes);
+        let deposit = match operation {
+            FeeOperation::Mint => self.storage_deposit(payload_bytes),
+            // NEP-171 asks for exactly one yoctoNEAR
+            FeeOperation::Transfer => 1,
+        };
+        priced(chain, operation, gas as u128 * self.gas_price, deposit, gas)
+    }
+
+    /// Gas price and storage cost; per-call gas stays as configured
+    async fn refresh(&mut self, rpc: &RpcClient) -> Result<(), ChainError> {
+        let price = rpc.call("gas_price", json!([null])).await?;
+        self.gas_price = str_at(&price, "/gas_price")?.parse().map_err(invalid)?;
+        let config = rpc.call("EXPERIMENTAL_protocol_config", json!({ "finality": "final" })).await?;
+        self.storage_byte_cost = str_at(&config, "/runtime_config/storage_amount_per_byte")?.parse().map_err(invalid)?;
+        Ok(())
+    }
+}
+
+/// Solana: signatures, compute units at the priority price, and rent-exempt
+/// balances for the accounts an operation creates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolanaPrices {
+    pub lamports_per_signature: u64,
+    /// Rent-exempt lamports per byte, i.e. two years of rent
+    pub rent_exempt_lamports_per_byte: u64,
+    /// Priority fee in micro-lamports per compute unit
+    pub micro_lamports_per_compute_unit: u64,
+    pub mint_compute_units: u64,
+    pub transfer_compute_units: u64,
+    /// Compute units per byte of metadata copied and serialized
+    pub compute_units_per_byte: u64,
+}
+
+impl Default for SolanaPrices {
+    fn default() -> Self {
+        SolanaPrices {
+            lamports_per_signature: 5_000,
+            rent_exempt_lamports_per_byte: 6_960,
+            micro_lamports_per_compute_unit: 0,
+            mint_compute_units: 120_000,
+            transfer_compute_units: 30_000,
+            compute_units_per_byte: 15,
+        }
+    }
+}
+
+impl SolanaPrices {
+    /// Lamports an account of `size` data bytes needs to be rent-exempt
+    pub fn rent_exempt_minimum(&self, size: u64) -> u128 {
+        (SOLANA_ACCOUNT_STORAGE_OVERHEAD + size) as u128 * self.rent_exempt_lamports_per_byte as u128
+    }
+
+    pub fn compute_units(&self, operation: FeeOperation, payload_bytes: u64) -> u64 {
+        let base = match operation {
+            FeeOperation::Mint => self.mint_compute_units,
+            FeeOperation::Transfer => self.transfer_compute_units,
+        };
+        base + self.compute_units_per_byte * payload_bytes
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl CostModel for SolanaPrices {
+    /// Mints create the mint, the owner's token account and a metadata
+    /// account holding the payload; transfers may create the receiver's token account
+    fn estimate(&self, chain: ChainType, operation: FeeOperation, payload_bytes: u64) -> FeeEstimate {
+        let (signatures, rent) = match operation {
+            FeeOperation::Mint => (
+                2,
+                self.rent_exempt_minimum(SPL_MINT_ACCOUNT_SIZE)
+                    + self.rent_exempt_minimum(SPL_TOKEN_ACCOUNT_SIZE)
+                    + self.rent_exempt_minimum(payload_bytes),
+            ),
+            FeeOperation::Transfer => (1, self.rent_exempt_minimum(SPL_TOKEN_ACCOUNT_SIZE)),
+        };
+        let units = self.compute_units(operation, payload_bytes);
+        let priority = (units as u128 * self.micro_lamports_per_compute_unit as u128).div_ceil(1_000_000);
+        let spent = signatures * self.lamports_per_signature as u128 + priority;
+        priced(chain, operation, spent, rent, units)
+    }
+
+    /// Rent from the node's empty-account minimum, priority from the median recent fee
+    async fn refresh(&mut self, rpc: &RpcClient) -> Result<(), ChainError> {
+        let empty = rpc.call("getMinimumBalanceForRentExemption", json!([0])).await?;
+        let empty = empty.as_u64().ok_or_else(|| invalid("rent minimum is not a number"))?;
+        self.rent_exempt_lamports_per_byte = empty / SOLANA_ACCOUNT_STORAGE_OVERHEAD;
+
+        let recent = rpc.call("getRecentPrioritizationFees", json!([])).await?;
+        let mut fees: Vec<u64> = recent
+            .as_array()
+            .ok_or_else(|| invalid("prioritization fees are not a list"))?
+            .iter()
+            .filter_map(|entry| entry["prioritizationFee"].as_u64())
+            .collect();
+        fees.sort_unstable();
+        self.micro_lamports_per_compute_unit = fees.get(fees.len() / 2).copied().unwrap_or(0);
+        Ok(())
+    }
+}
+
+/// EVM chains: intrinsic, calldata, execution and storage gas at the node's gas price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvmPrices {
+    /// wei per gas, priority tip included
+    #[serde(with = "amount_string")]
+    pub gas_price: u128,
+    /// Execution gas of `mint` besides storing the token URI
+    pub mint_gas: u64,
+    pub transfer_gas: u64,
+}
+
+impl Default for EvmPrices {
+    fn default() -> Self {
+        EvmPrices { gas_price: 20_000_000_000, mint_gas: 80_000, transfer_gas: 64_000 }
+    }
+}
+
+impl EvmPrices {
+    /// Gas limit for `operation`; mints also store the payload one word at a time
+    pub fn gas(&self, operation: FeeOperation, payload_bytes: u64) -> u64 {
+        let calldata = EVM_INTRINSIC_GAS + EVM_CALLDATA_GAS_PER_BYTE * payload_bytes;
+        match operation {
+            FeeOperation::Mint => calldata + self.mint_gas + EVM_STORAGE_WORD_GAS * payload_bytes.div_ceil(32),
+            FeeOperation::Transfer => calldata + self.transfer_gas,
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl CostModel for EvmPrices {
+    fn estimate(&self, chain: ChainType, operation: FeeOperation, payload_bytes: u64) -> FeeEstimate {
+        let gas = self.gas(operation, payload_bytes);
+        priced(chain, operation, gas as u128 * self.gas_price, 0, gas)
+    }
+
+    async fn refresh(&mut self, rpc: &RpcClient) -> Result<(), ChainError> {
+        self.gas_price = hex_u128(&string_result(rpc.call("eth_gasPrice", json!([])).await?)?)?;
+        Ok(())
+    }
+}
+
+/// Polkadot Asset Hub: weight fee plus length fee, and the pallet-nfts deposits
+/// reserved for the item and its metadata. The congestion multiplier is not applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolkadotPrices {
+    /// Planck per second of `ref_time`
+    #[serde(with = "amount_string")]
+    pub weight_fee_per_second: u128,
+    /// Planck per byte of encoded extrinsic
+    #[serde(with = "amount_string")]
+    pub length_fee_per_byte: u128,
+    /// `ref_time` every extrinsic pays for, in picoseconds
+    pub extrinsic_base_weight: u64,
+    pub mint_weight: u64,
+    pub transfer_weight: u64,
+    /// `ref_time` per byte of metadata written
+    pub weight_per_byte: u64,
+    /// `ItemDeposit`, reserved per minted item
+    #[serde(with = "amount_string")]
+    pub item_deposit: u128,
+    /// `MetadataDepositBase`, reserved once per item with metadata
+    #[serde(with = "amount_string")]
+    pub metadata_deposit_base: u128,
+    /// `DepositPerByte`, reserved per byte of metadata
+    #[serde(with = "amount_string")]
+    pub deposit_per_byte: u128,
+}
+
+impl Default for PolkadotPrices {
+    fn default() -> Self {
+        PolkadotPrices {
+            weight_fee_per_second: 10_000_000_000,
+            length_fee_per_byte: 100_000,
+            extrinsic_base_weight: 125_000_000,
+            mint_weight: 1_000_000_000,
+            transfer_weight: 500_000_000,
+            weight_per_byte: 20_000,
+            item_deposit: 100_000_000,
+            metadata_deposit_base: 2_012_900_000,
+            deposit_per_byte: 100_000,
+        }
+    }
+}
+
+impl PolkadotPrices {
+    pub fn weight(&self, operation: FeeOperation, payload_bytes: u64) -> u64 {
+        let base = match operation {
+            FeeOperation::Mint => self.mint_weight,
+            FeeOperation::Transfer => self.transfer_weight,
+        };
+        self.extrinsic_base_weight + base + self.weight_per_byte * payload_bytes
+    }
+
+    /// Encoded extrinsic length with the payload inline
+    pub fn length(&self, payload_bytes: u64) -> u64 {
+        POLKADOT_EXTRINSIC_OVERHEAD + payload_bytes
+    }
+
+    async fn state_call_u128(rpc: &RpcClient, method: &str, args: &[u8]) -> Result<u128, ChainError> {
+        let result = rpc.call("state_call", json!([method, format!("0x{}", hex::encode(args))])).await?;
+        let bytes: [u8; 16] = hex_bytes(&string_result(result)?)?.try_into().map_err(|_| invalid("fee is not a u128"))?;
+        Ok(u128::from_le_bytes(bytes))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl CostModel for PolkadotPrices {
+    fn estimate(&self, chain: ChainType, operation: FeeOperation, payload_bytes: u64) -> FeeEstimate {
+        let weight = self.weight(operation, payload_bytes);
+        let spent = weight as u128 * self.weight_fee_per_second / POLKADOT_WEIGHT_PER_SECOND as u128
+            + self.length(payload_bytes) as u128 * self.length_fee_per_byte;
+        let deposit = match operation {
+            FeeOperation::Mint => {
+                self.item_deposit + self.metadata_deposit_base + payload_bytes as u128 * self.deposit_per_byte
+            }
+            FeeOperation::Transfer => 0,
+        };
+        priced(chain, operation, spent, deposit, weight)
+    }
+
+    /// Weight and length fees from the runtime's `TransactionPaymentApi`;
+    /// deposits are runtime constants and stay as configured
+    async fn refresh(&mut self, rpc: &RpcClient) -> Result<(), ChainError> {
+        let mut weight = Vec::new();
+        scale_compact(POLKADOT_WEIGHT_PER_SECOND, &mut weight);
+        scale_compact(0, &mut weight);
+        self.weight_fee_per_second =
+            Self::state_call_u128(rpc, "TransactionPaymentApi_query_weight_to_fee", &weight).await?;
+        let length = POLKADOT_LENGTH_PROBE.to_le_bytes();
+        let length_fee = Self::state_call_u128(rpc, "TransactionPaymentApi_query_length_to_fee", &length).await?;
+        self.length_fee_per_byte = length_fee / POLKADOT_LENGTH_PROBE as u128;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_near_stakes_storage_for_metadata() {
+        let prices = NearPrices::default();
+        let small = prices.estimate(ChainType::Near, FeeOperation::Mint, 100);
+        let large = prices.estimate(ChainType::Near, FeeOperation::Mint, 100_000);
+        assert_eq!(small.deposit, 500 * prices.storage_byte_cost);
+        assert_eq!(large.units, prices.mint_gas + 100_000 * prices.gas_per_byte);
+        assert_eq!(large.amount, large.units as u128 * prices.gas_price + large.deposit);
+        // 100 kB of trajectory stakes about one NEAR
+        assert_eq!(large.deposit / 10u128.pow(22), 100);
+        assert_eq!(prices.estimate(ChainType::Near, FeeOperation::Transfer, 0).deposit, 1);
+    }
+
+    #[test]
+    fn test_solana_rent_matches_runtime() {
+        let prices = SolanaPrices::default();
+        assert_eq!(prices.rent_exempt_minimum(0), 890_880);
+        assert_eq!(prices.rent_exempt_minimum(SPL_TOKEN_ACCOUNT_SIZE), 2_039_280);
+
+        let priced = SolanaPrices { micro_lamports_per_compute_unit: 1_000, ..prices.clone() };
+        let mint = priced.estimate(ChainType::Solana, FeeOperation::Mint, 1_000);
+        assert_eq!(mint.units, 135_000);
+        assert_eq!(mint.amount - mint.deposit, 2 * 5_000 + 135);
+        assert_eq!(mint.deposit, prices.rent_exempt_minimum(82) + 2_039_280 + prices.rent_exempt_minimum(1_000));
+    }
+
+    #[test]
+    fn test_evm_gas_grows_with_stored_words() {
+        let prices = EvmPrices::default();
+        assert_eq!(prices.gas(FeeOperation::Transfer, 0), 85_000);
+        assert_eq!(prices.gas(FeeOperation::Mint, 64), 21_000 + 16 * 64 + 80_000 + 2 * 20_000);
+        let fee = prices.estimate(ChainType::Polygon, FeeOperation::Mint, 64);
+        assert_eq!((fee.symbol.as_str(), fee.amount), ("POL", fee.units as u128 * prices.gas_price));
+    }
+
+    #[test]
+    fn test_polkadot_charges_weight_length_and_deposits() {
+        let prices = PolkadotPrices { weight_per_byte: 0, ..PolkadotPrices::default() };
+        let fee = prices.estimate(ChainType::Polkadot, FeeOperation::Mint, 872);
+        let weight_fee = 1_125_000_000u128 * 10_000_000_000 / 1_000_000_000_000;
+        assert_eq!(fee.amount - fee.deposit, weight_fee + 1_000 * 100_000);
+        assert_eq!(fee.deposit, 100_000_000 + 2_012_900_000 + 872 * 100_000);
+
+        let json = serde_json::to_value(&prices).unwrap();
+        assert_eq!(json["item_deposit"], "100000000");
+        assert_eq!(serde_json::from_value::<PolkadotPrices>(json).unwrap(), prices);
+    }
+}
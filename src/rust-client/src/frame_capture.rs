@@ -0,0 +1,411 @@
+//! Frame capture for minting VJ sessions as `NuweAssetBundle`s.
+//!
+//! A [`FrameRecorder`] is offered every rendered frame, from `ShaderEngine`
+//! or the CPU reference renderer, and keeps the ones its [`CapturePolicy`]
+//! asks for: one per interval of session time, and one each time the
+//! emotional intensity rises through a threshold. Kept frames are encoded as
+//! PNG or lossless WebP; [`CapturedFrame::frame_reference`] gives the
+//! `FrameReference` entry for them once the bytes are stored on IPFS.
+
+use crate::cpu_renderer::{RenderError, RgbaImage};
+use emotion_core::Vad;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+pub use session_core::FrameReference;
+
+/// Errors from capturing frames
+#[derive(Debug)]
+pub enum CaptureError {
+    UnknownFormat(String),
+    InvalidPolicy(&'static str),
+    Encode(String),
+    Render(RenderError),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::UnknownFormat(name) => write!(f, "unknown frame format '{}'", name),
+            CaptureError::InvalidPolicy(msg) => write!(f, "invalid capture policy: {}", msg),
+            CaptureError::Encode(msg) => write!(f, "frame encoding failed: {}", msg),
+            CaptureError::Render(e) => write!(f, "frame render failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+impl From<RenderError> for CaptureError {
+    fn from(e: RenderError) -> Self {
+        CaptureError::Render(e)
+    }
+}
+
+/// Image encoding of captured frames
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameFormat {
+    #[default]
+    Png,
+    /// Lossless WebP
+    WebP,
+}
+
+impl FrameFormat {
+    /// Name used in `FrameReference::format`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrameFormat::Png => "png",
+            FrameFormat::WebP => "webp",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            FrameFormat::Png => "image/png",
+            FrameFormat::WebP => "image/webp",
+        }
+    }
+
+    pub fn encode(&self, image: &RgbaImage) -> Result<Vec<u8>, CaptureError> {
+        match self {
+            FrameFormat::Png => Ok(image.to_png()?),
+            FrameFormat::WebP => {
+                let mut out = Vec::new();
+                image_webp::WebPEncoder::new(&mut out)
+                    .encode(image.pixels(), image.width(), image.height(), image_webp::ColorType::Rgba8)
+                    .map_err(|e| CaptureError::Encode(e.to_string()))?;
+                Ok(out)
+            }
+        }
+    }
+}
+
+impl FromStr for FrameFormat {
+    type Err = CaptureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(FrameFormat::Png),
+            "webp" => Ok(FrameFormat::WebP),
+            _ => Err(CaptureError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// Why a frame was captured
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureReason {
+    Interval,
+    EmotionalPeak,
+    Manual,
+}
+
+/// When a [`FrameRecorder`] captures
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CapturePolicy {
+    /// Session time between interval captures, `None` for none
+    pub interval_ms: Option<f64>,
+    /// Emotional intensity (0 to 1) that triggers a capture when crossed
+    /// upwards, `None` for no peak captures
+    pub peak_threshold: Option<f32>,
+    /// Session time after a peak capture before the next one
+    pub peak_cooldown_ms: f64,
+    pub format: FrameFormat,
+    /// Frames to keep before capturing stops
+    pub max_frames: Option<usize>,
+}
+
+impl Default for CapturePolicy {
+    fn default() -> Self {
+        CapturePolicy {
+            interval_ms: Some(1000.0),
+            peak_threshold: None,
+            peak_cooldown_ms: 2000.0,
+            format: FrameFormat::Png,
+            max_frames: None,
+        }
+    }
+}
+
+impl CapturePolicy {
+    /// Capture only on emotional peaks above `threshold`
+    pub fn on_peaks(threshold: f32) -> Self {
+        CapturePolicy { interval_ms: None, peak_threshold: Some(threshold), ..Default::default() }
+    }
+
+    pub fn with_interval(mut self, interval_ms: f64) -> Self {
+        self.interval_ms = Some(interval_ms).filter(|ms| *ms > 0.0);
+        self
+    }
+
+    pub fn with_peaks(mut self, threshold: f32, cooldown_ms: f64) -> Self {
+        self.peak_threshold = Some(threshold);
+        self.peak_cooldown_ms = cooldown_ms;
+        self
+    }
+
+    pub fn with_format(mut self, format: FrameFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_max_frames(mut self, max_frames: usize) -> Self {
+        self.max_frames = Some(max_frames);
+        self
+    }
+
+    /// Check fields set directly rather than through the builders
+    pub fn validate(&self) -> Result<(), CaptureError> {
+        if self.interval_ms.is_some_and(|ms| !(ms.is_finite() && ms > 0.0)) {
+            return Err(CaptureError::InvalidPolicy("interval must be a positive number of milliseconds"));
+        }
+        if self.peak_threshold.is_some_and(|t| !(0.0..=1.0).contains(&t)) {
+            return Err(CaptureError::InvalidPolicy("peak threshold must be between 0 and 1"));
+        }
+        if !(self.peak_cooldown_ms.is_finite() && self.peak_cooldown_ms >= 0.0) {
+            return Err(CaptureError::InvalidPolicy("peak cooldown must be a non-negative number of milliseconds"));
+        }
+        Ok(())
+    }
+}
+
+/// An encoded frame and where it falls in the session
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CapturedFrame {
+    /// Index of the rendered frame since recording started
+    pub frame_number: u64,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+    pub session_time_ms: f64,
+    pub format: FrameFormat,
+    pub resolution: (u32, u32),
+    pub reason: CaptureReason,
+    /// Emotional intensity at capture, when the renderer had a state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intensity: Option<f32>,
+    #[serde(skip)]
+    pub bytes: Vec<u8>,
+}
+
+impl CapturedFrame {
+    /// The frame's `FrameReference`, for the CID its bytes were stored under
+    pub fn frame_reference(&self, cid: &str) -> FrameReference {
+        FrameReference {
+            frame_number: self.frame_number,
+            timestamp: self.timestamp,
+            cid: cid.to_string(),
+            format: self.format.as_str().to_string(),
+            resolution: self.resolution,
+        }
+    }
+}
+
+/// Picks frames to keep from a stream of rendered ones and encodes them
+#[derive(Clone, Debug)]
+pub struct FrameRecorder {
+    policy: CapturePolicy,
+    /// Unix time in milliseconds of session time zero
+    started_at_ms: u64,
+    frames: Vec<CapturedFrame>,
+    /// Frames captured so far, including taken ones
+    captured: usize,
+    /// Rendered frames offered so far
+    frames_seen: u64,
+    next_interval_ms: f64,
+    last_peak_ms: Option<f64>,
+    /// Whether intensity has been below the threshold since the last peak
+    peak_armed: bool,
+}
+
+impl FrameRecorder {
+    pub fn new(policy: CapturePolicy, started_at_ms: u64) -> Result<Self, CaptureError> {
+        policy.validate()?;
+        Ok(FrameRecorder {
+            policy,
+            started_at_ms,
+            frames: vec![],
+            captured: 0,
+            frames_seen: 0,
+            next_interval_ms: 0.0,
+            last_peak_ms: None,
+            peak_armed: true,
+        })
+    }
+
+    pub fn policy(&self) -> &CapturePolicy {
+        &self.policy
+    }
+
+    pub fn frames(&self) -> &[CapturedFrame] {
+        &self.frames
+    }
+
+    /// Remove and return the frames captured so far, e.g. to upload them
+    pub fn take_frames(&mut self) -> Vec<CapturedFrame> {
+        std::mem::take(&mut self.frames)
+    }
+
+    fn is_full(&self) -> bool {
+        self.policy.max_frames.is_some_and(|max| self.captured >= max)
+    }
+
+    /// Count a rendered frame at `time_ms` of session time and say whether
+    /// to capture it
+    pub fn should_capture(&mut self, time_ms: f64, emotion: Option<&Vad>) -> Option<CaptureReason> {
+        self.frames_seen += 1;
+        let mut reason = None;
+        if let (Some(threshold), Some(emotion)) = (self.policy.peak_threshold, emotion) {
+            let above = emotion.intensity() >= threshold;
+            let cooled = self.last_peak_ms.is_none_or(|last| time_ms - last >= self.policy.peak_cooldown_ms);
+            if above && self.peak_armed && cooled {
+                self.last_peak_ms = Some(time_ms);
+                reason = Some(CaptureReason::EmotionalPeak);
+            }
+            self.peak_armed = !above;
+        }
+        if let Some(interval) = self.policy.interval_ms {
+            if time_ms >= self.next_interval_ms {
+                // Frames slower than the interval skip the intervals they missed
+                self.next_interval_ms = ((time_ms / interval).floor() + 1.0) * interval;
+                reason = reason.or(Some(CaptureReason::Interval));
+            }
+        }
+        reason.filter(|_| !self.is_full())
+    }
+
+    /// Encode and keep `image`, the frame last counted by `should_capture`
+    pub fn record(
+        &mut self,
+        image: &RgbaImage,
+        time_ms: f64,
+        reason: CaptureReason,
+        emotion: Option<&Vad>,
+    ) -> Result<&CapturedFrame, CaptureError> {
+        let bytes = self.policy.format.encode(image)?;
+        self.captured += 1;
+        self.frames.push(CapturedFrame {
+            frame_number: self.frames_seen.saturating_sub(1),
+            timestamp: self.started_at_ms.saturating_add(time_ms.max(0.0) as u64),
+            session_time_ms: time_ms,
+            format: self.policy.format,
+            resolution: (image.width(), image.height()),
+            reason,
+            intensity: emotion.map(Vad::intensity),
+            bytes,
+        });
+        Ok(self.frames.last().expect("frame just pushed"))
+    }
+
+    /// Offer a rendered frame; `render` runs only when the frame is captured
+    pub fn offer<F>(
+        &mut self,
+        time_ms: f64,
+        emotion: Option<&Vad>,
+        render: F,
+    ) -> Result<Option<&CapturedFrame>, CaptureError>
+    where
+        F: FnOnce() -> Result<RgbaImage, RenderError>,
+    {
+        match self.should_capture(time_ms, emotion) {
+            Some(reason) => Ok(Some(self.record(&render()?, time_ms, reason, emotion)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Capture a frame on request, outside the policy's schedule
+    pub fn capture(&mut self, image: &RgbaImage, time_ms: f64) -> Result<&CapturedFrame, CaptureError> {
+        self.record(image, time_ms, CaptureReason::Manual, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu_renderer::{render_fractal, FractalPreset, FractalUniforms};
+    use serde_json::json;
+
+    fn render() -> Result<RgbaImage, RenderError> {
+        render_fractal(FractalPreset::Mandelbrot, &FractalUniforms::default(), 16, 12)
+    }
+
+    #[test]
+    fn test_interval_captures_skip_missed_intervals() {
+        let mut recorder = FrameRecorder::new(CapturePolicy::default().with_interval(100.0), 1_700_000_000_000).unwrap();
+        let mut rendered = 0;
+        for time_ms in [0.0, 16.0, 99.0, 101.0, 150.0, 420.0, 499.0] {
+            recorder
+                .offer(time_ms, None, || {
+                    rendered += 1;
+                    render()
+                })
+                .unwrap();
+        }
+        assert_eq!(rendered, 3);
+        let frames = recorder.frames();
+        assert_eq!(frames.iter().map(|f| f.frame_number).collect::<Vec<_>>(), [0, 3, 5]);
+        assert_eq!(frames[1].timestamp, 1_700_000_000_101);
+        assert_eq!(frames[0].resolution, (16, 12));
+        assert_eq!(RgbaImage::from_png(&frames[0].bytes).unwrap(), render().unwrap());
+
+        let reference = serde_json::to_value(frames[2].frame_reference("bafyframe")).unwrap();
+        assert_eq!(
+            reference,
+            json!({"frame_number": 5, "timestamp": 1_700_000_000_420u64, "cid": "bafyframe",
+                   "format": "png", "resolution": [16, 12]})
+        );
+        assert_eq!(recorder.take_frames().len(), 3);
+        assert!(recorder.frames().is_empty());
+    }
+
+    #[test]
+    fn test_emotional_peaks_capture_once_per_rise() {
+        let policy = CapturePolicy::on_peaks(0.6).with_format(FrameFormat::WebP).with_max_frames(2);
+        let mut recorder = FrameRecorder::new(policy, 0).unwrap();
+        let calm = Vad::NEUTRAL;
+        let excited = Vad::clamped(0.9, 1.0, 0.9);
+        let states = [(0.0, calm), (100.0, excited), (200.0, excited), (300.0, calm), (400.0, excited)];
+        let reasons: Vec<_> = states.iter().map(|(t, vad)| recorder.should_capture(*t, Some(vad))).collect();
+        // The second rise is within the cooldown
+        assert_eq!(reasons, [None, Some(CaptureReason::EmotionalPeak), None, None, None]);
+
+        let later = [(2500.0, calm), (2600.0, excited), (5000.0, calm), (5100.0, excited)];
+        let mut captured = vec![];
+        for (time_ms, vad) in later {
+            if let Some(frame) = recorder.offer(time_ms, Some(&vad), render).unwrap() {
+                captured.push((frame.session_time_ms, frame.format, frame.intensity.unwrap() >= 0.6));
+            }
+        }
+        assert_eq!(captured, [(2600.0, FrameFormat::WebP, true), (5100.0, FrameFormat::WebP, true)]);
+        assert!(recorder.frames()[0].bytes.starts_with(b"RIFF"));
+        assert_eq!(recorder.frames()[1].frame_reference("cid").format, "webp");
+
+        // Full, also once the frames are taken
+        recorder.take_frames();
+        assert_eq!(recorder.should_capture(9000.0, Some(&calm)), None);
+        assert_eq!(recorder.should_capture(9100.0, Some(&excited)), None);
+        assert_eq!("gif".parse::<FrameFormat>().unwrap_err().to_string(), "unknown frame format 'gif'");
+    }
+
+    #[test]
+    fn test_invalid_policies_are_rejected() {
+        let zero = CapturePolicy { interval_ms: Some(0.0), ..Default::default() };
+        assert!(matches!(FrameRecorder::new(zero, 0), Err(CaptureError::InvalidPolicy(_))));
+        for policy in [
+            CapturePolicy { interval_ms: Some(-10.0), ..Default::default() },
+            CapturePolicy { interval_ms: Some(f64::NAN), ..Default::default() },
+            CapturePolicy::on_peaks(1.5),
+            CapturePolicy::on_peaks(0.5).with_peaks(0.5, f64::NEG_INFINITY),
+        ] {
+            assert!(FrameRecorder::new(policy, 0).is_err());
+        }
+
+        // Timestamps saturate rather than overflow
+        let mut recorder = FrameRecorder::new(CapturePolicy::default(), u64::MAX - 10).unwrap();
+        let frame = recorder.capture(&render().unwrap(), 1000.0).unwrap();
+        assert_eq!(frame.timestamp, u64::MAX);
+    }
+}
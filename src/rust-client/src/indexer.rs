@@ -0,0 +1,759 @@
+//! NFT indexers: an owner's tokens a page at a time, optionally narrowed to
+//! one collection, behind a TTL cache.
+//!
+//! Each indexer fetches a page with as few RPC calls as its chain allows.
+//! NEAR pages `nft_tokens_for_owner` on the node. Solana lists the owner's
+//! token accounts in one `getProgramAccounts` call, kept while the cursors
+//! walk it, and reads Metaplex metadata a hundred accounts per call. A portfolio of thousands of tokens
+//! therefore costs a handful of calls, and [`CachedIndexer`] serves repeat
+//! visits from memory.
+//!
+//! Cursors are opaque: pass a page's `next_cursor` back to get the next page.
+
+use crate::address::{find_program_address, ChainAddress};
+use crate::blockchain_integration::ChainType;
+use crate::chain_adapter::{ChainAdapter, ChainError, MaybeSend, NftToken, RpcClient};
+use crate::chain_rpc::{
+    endpoint, invalid, near_contract, near_view, NearToken, NEAR_MAINNET_RPC, NEAR_TESTNET_RPC, SOLANA_DEVNET_RPC,
+    SOLANA_MAINNET_RPC, SPL_TOKEN_PROGRAM_ID,
+};
+use async_trait::async_trait;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Tokens per page when the query does not say
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+/// Largest page an indexer returns
+pub const MAX_PAGE_SIZE: usize = 200;
+/// How long [`CachedIndexer`] keeps a page, in milliseconds
+pub const DEFAULT_INDEX_TTL_MS: u64 = 60_000;
+
+pub const METAPLEX_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+/// Most accounts `getMultipleAccounts` returns per call
+const SOLANA_MULTIPLE_ACCOUNTS_LIMIT: usize = 100;
+const SPL_TOKEN_ACCOUNT_LEN: u64 = 165;
+/// Offset of the owner in an SPL token account
+const SPL_TOKEN_OWNER_OFFSET: u64 = 32;
+
+/// One page of an owner's tokens
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NftQuery {
+    pub owner: String,
+    /// Only tokens of this contract, program or collection
+    #[serde(default)]
+    pub collection: Option<String>,
+    /// `next_cursor` of the previous page; `None` for the first
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default = "default_page_size")]
+    pub limit: usize,
+}
+
+fn default_page_size() -> usize {
+    DEFAULT_PAGE_SIZE
+}
+
+impl NftQuery {
+    /// First page of `owner`'s tokens
+    pub fn new(owner: &str) -> Self {
+        NftQuery { owner: owner.to_string(), collection: None, cursor: None, limit: DEFAULT_PAGE_SIZE }
+    }
+
+    pub fn with_collection(mut self, collection: &str) -> Self {
+        self.collection = Some(collection.to_string());
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: &str) -> Self {
+        self.cursor = Some(cursor.to_string());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// The same query for the page after `page`, or `None` if it was the last
+    pub fn after(&self, page: &NftPage) -> Option<NftQuery> {
+        let cursor = page.next_cursor.as_deref()?;
+        Some(self.clone().with_cursor(cursor))
+    }
+
+    /// `limit` within 1..=[`MAX_PAGE_SIZE`]
+    pub fn page_size(&self) -> usize {
+        self.limit.clamp(1, MAX_PAGE_SIZE)
+    }
+}
+
+/// Tokens of one page and where the next one starts
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct NftPage {
+    pub tokens: Vec<NftToken>,
+    /// Cursor for the next page; `None` on the last one
+    pub next_cursor: Option<String>,
+}
+
+/// Lists the NFTs an account holds
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait NftIndexer: MaybeSend {
+    fn chain(&self) -> ChainType;
+
+    async fn tokens_for_owner(&self, query: &NftQuery) -> Result<NftPage, ChainError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T: NftIndexer + ?Sized> NftIndexer for Arc<T> {
+    fn chain(&self) -> ChainType {
+        (**self).chain()
+    }
+
+    async fn tokens_for_owner(&self, query: &NftQuery) -> Result<NftPage, ChainError> {
+        (**self).tokens_for_owner(query).await
+    }
+}
+
+/// Every token matching `query`, following cursors from its first page
+pub async fn all_tokens(indexer: &dyn NftIndexer, query: &NftQuery) -> Result<Vec<NftToken>, ChainError> {
+    let mut query = NftQuery { cursor: None, limit: MAX_PAGE_SIZE, ..query.clone() };
+    let mut tokens = Vec::new();
+    loop {
+        let page = indexer.tokens_for_owner(&query).await?;
+        tokens.extend(page.tokens.iter().cloned());
+        match query.after(&page) {
+            Some(next) => query = next,
+            None => return Ok(tokens),
+        }
+    }
+}
+
+fn bad_cursor(cursor: &str) -> ChainError {
+    ChainError::InvalidRequest(format!("invalid cursor '{}'", cursor))
+}
+
+/// Page over a complete listing with the offset as cursor
+fn offset_page(tokens: impl Iterator<Item = NftToken>, query: &NftQuery) -> Result<NftPage, ChainError> {
+    let offset = match &query.cursor {
+        Some(cursor) => cursor.parse::<usize>().map_err(|_| bad_cursor(cursor))?,
+        None => 0,
+    };
+    let mut rest = tokens
+        .filter(|token| query.collection.is_none() || token.collection == query.collection)
+        .skip(offset)
+        .peekable();
+    let tokens: Vec<NftToken> = rest.by_ref().take(query.page_size()).collect();
+    let next_cursor = rest.peek().map(|_| (offset + tokens.len()).to_string());
+    Ok(NftPage { tokens, next_cursor })
+}
+
+/// NEP-171 contracts on NEAR, paged with `nft_tokens_for_owner`.
+///
+/// Without a collection filter the contracts are listed one after another;
+/// with one, only that contract is asked, whether or not it is in the list.
+pub struct NearIndexer {
+    rpc: RpcClient,
+    contracts: Vec<String>,
+}
+
+impl NearIndexer {
+    pub fn new(rpc: RpcClient, contracts: &[&str]) -> Self {
+        NearIndexer { rpc, contracts: contracts.iter().map(|c| c.to_string()).collect() }
+    }
+
+    /// Our contract on "mainnet", otherwise on testnet; `network` may be an RPC URL
+    pub fn for_network(network: &str) -> Self {
+        Self::new(RpcClient::http(&endpoint(network, NEAR_MAINNET_RPC, NEAR_TESTNET_RPC)), &[near_contract(network)])
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NftIndexer for NearIndexer {
+    fn chain(&self) -> ChainType {
+        ChainType::Near
+    }
+
+    /// The cursor is `<contract>:<from_index>`
+    async fn tokens_for_owner(&self, query: &NftQuery) -> Result<NftPage, ChainError> {
+        let contracts = match &query.collection {
+            Some(contract) => vec![contract.clone()],
+            None => self.contracts.clone(),
+        };
+        let (mut contract, mut from_index) = match &query.cursor {
+            Some(cursor) => {
+                let (contract, from) = cursor.rsplit_once(':').ok_or_else(|| bad_cursor(cursor))?;
+                let position = contracts.iter().position(|c| c == contract).ok_or_else(|| bad_cursor(cursor))?;
+                (position, from.parse::<u64>().map_err(|_| bad_cursor(cursor))?)
+            }
+            None => (0, 0),
+        };
+
+        let limit = query.page_size();
+        let mut tokens = Vec::new();
+        while contract < contracts.len() && tokens.len() < limit {
+            let wanted = limit - tokens.len();
+            let args = json!({ "account_id": query.owner, "from_index": from_index.to_string(), "limit": wanted });
+            let page: Vec<NearToken> =
+                serde_json::from_value(near_view(&self.rpc, &contracts[contract], "nft_tokens_for_owner", args).await?)
+                    .map_err(invalid)?;
+            let received = page.len();
+            tokens.extend(page.into_iter().map(|t| t.into_token(&contracts[contract])));
+            if received < wanted {
+                contract += 1;
+                from_index = 0;
+            } else {
+                from_index += received as u64;
+            }
+        }
+        let next_cursor = contracts.get(contract).map(|c| format!("{}:{}", c, from_index));
+        Ok(NftPage { tokens, next_cursor })
+    }
+}
+
+/// Zero-decimal SPL tokens on Solana, described by their Metaplex metadata.
+///
+/// Collections are Metaplex collection mints, and only verified membership
+/// counts, since anyone can name a collection in their own token's metadata.
+pub struct SolanaIndexer {
+    rpc: RpcClient,
+    /// Each owner's mints, from their first page until their last is served
+    listings: Mutex<HashMap<String, Arc<BTreeMap<String, String>>>>,
+}
+
+/// The Metaplex metadata fields the indexer reports
+#[derive(Debug, Clone, PartialEq)]
+struct MetaplexMetadata {
+    name: String,
+    symbol: String,
+    uri: String,
+    /// Collection mint, if verified
+    collection: Option<String>,
+}
+
+impl SolanaIndexer {
+    pub fn new(rpc: RpcClient) -> Self {
+        SolanaIndexer { rpc, listings: Mutex::default() }
+    }
+
+    /// "mainnet", otherwise devnet; `network` may be an RPC URL
+    pub fn for_network(network: &str) -> Self {
+        Self::new(RpcClient::http(&endpoint(network, SOLANA_MAINNET_RPC, SOLANA_DEVNET_RPC)))
+    }
+
+    /// NFT mints held by `owner` and the token account holding each, in one call
+    async fn nft_mints(&self, owner: &str) -> Result<BTreeMap<String, String>, ChainError> {
+        let filters = json!([
+            { "dataSize": SPL_TOKEN_ACCOUNT_LEN },
+            { "memcmp": { "offset": SPL_TOKEN_OWNER_OFFSET, "bytes": owner } },
+        ]);
+        let accounts = self
+            .rpc
+            .call("getProgramAccounts", json!([SPL_TOKEN_PROGRAM_ID, { "encoding": "jsonParsed", "filters": filters }]))
+            .await?;
+        let accounts = accounts.as_array().ok_or_else(|| invalid("getProgramAccounts did not return a list"))?;
+        Ok(accounts
+            .iter()
+            .filter_map(|account| {
+                let info = account.pointer("/account/data/parsed/info")?;
+                let amount = info.pointer("/tokenAmount/amount")?.as_str()?;
+                let decimals = info.pointer("/tokenAmount/decimals")?.as_u64()?;
+                (amount == "1" && decimals == 0)
+                    .then(|| Some((info["mint"].as_str()?.to_string(), account["pubkey"].as_str()?.to_string())))?
+            })
+            .collect())
+    }
+
+    /// `owner`'s mints as listed for their first page; a first page, or a
+    /// cursor whose listing is gone, lists them again
+    async fn listing(&self, owner: &str, first_page: bool) -> Result<Arc<BTreeMap<String, String>>, ChainError> {
+        if !first_page {
+            if let Some(mints) = self.lock_listings().get(owner) {
+                return Ok(mints.clone());
+            }
+        }
+        let mints = Arc::new(self.nft_mints(owner).await?);
+        self.lock_listings().insert(owner.to_string(), mints.clone());
+        Ok(mints)
+    }
+
+    fn lock_listings(&self) -> MutexGuard<'_, HashMap<String, Arc<BTreeMap<String, String>>>> {
+        self.listings.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Metaplex metadata of each mint, `None` where there is none
+    async fn metadata(&self, mints: &[&String]) -> Result<Vec<Option<MetaplexMetadata>>, ChainError> {
+        let addresses = mints
+            .iter()
+            .map(|mint| metadata_address(mint).map(|a| bs58::encode(a).into_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let result = self.rpc.call("getMultipleAccounts", json!([addresses, { "encoding": "base64" }])).await?;
+        let accounts = result.pointer("/value").and_then(Value::as_array).ok_or_else(|| invalid("missing /value"))?;
+        Ok(accounts
+            .iter()
+            .map(|account| {
+                let data = account.pointer("/data/0")?.as_str()?;
+                parse_metaplex(&base64::engine::general_purpose::STANDARD.decode(data).ok()?)
+            })
+            .chain(std::iter::repeat(None))
+            .take(mints.len())
+            .collect())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NftIndexer for SolanaIndexer {
+    fn chain(&self) -> ChainType {
+        ChainType::Solana
+    }
+
+    /// Tokens come in mint order and the cursor is the last mint returned.
+    /// The token accounts are listed for the first page and reused by the
+    /// pages after it; metadata is only read for the mints a page looks at.
+    async fn tokens_for_owner(&self, query: &NftQuery) -> Result<NftPage, ChainError> {
+        let owner = ChainAddress::parse(ChainType::Solana, &query.owner)?;
+        let mints = self.listing(owner.as_str(), query.cursor.is_none()).await?;
+        let start = match &query.cursor {
+            Some(cursor) => Bound::Excluded(cursor.clone()),
+            None => Bound::Unbounded,
+        };
+        let mut remaining = mints.range((start, Bound::Unbounded)).peekable();
+
+        let limit = query.page_size();
+        let mut tokens = Vec::new();
+        while tokens.len() < limit && remaining.peek().is_some() {
+            // Unfiltered pages need exactly as many accounts as are missing
+            let batch_size = match query.collection {
+                Some(_) => SOLANA_MULTIPLE_ACCOUNTS_LIMIT,
+                None => (limit - tokens.len()).min(SOLANA_MULTIPLE_ACCOUNTS_LIMIT),
+            };
+            let batch: Vec<(&String, &String)> = remaining.by_ref().take(batch_size).collect();
+            let mint_ids: Vec<&String> = batch.iter().map(|(mint, _)| *mint).collect();
+            let metadata = self.metadata(&mint_ids).await?;
+            for (position, ((mint, token_account), metadata)) in batch.iter().zip(metadata).enumerate() {
+                let collection = metadata.as_ref().and_then(|m| m.collection.clone());
+                if query.collection.is_some() && collection != query.collection {
+                    continue;
+                }
+                let mut fields = json!({ "token_account": token_account });
+                if let Some(metadata) = metadata {
+                    fields["name"] = json!(metadata.name);
+                    fields["symbol"] = json!(metadata.symbol);
+                    fields["uri"] = json!(metadata.uri);
+                }
+                tokens.push(NftToken {
+                    token_id: mint.to_string(),
+                    owner: query.owner.clone(),
+                    metadata: fields,
+                    collection,
+                });
+                if tokens.len() == limit && (position + 1 < batch.len() || remaining.peek().is_some()) {
+                    return Ok(NftPage { tokens, next_cursor: Some(mint.to_string()) });
+                }
+            }
+        }
+        self.lock_listings().remove(owner.as_str());
+        Ok(NftPage { tokens, next_cursor: None })
+    }
+}
+
+/// Metaplex metadata account of `mint`
+fn metadata_address(mint: &str) -> Result<[u8; 32], ChainError> {
+    let key = |text: &str| -> Option<[u8; 32]> { bs58::decode(text).into_vec().ok()?.try_into().ok() };
+    let program = key(METAPLEX_METADATA_PROGRAM_ID).expect("valid program id");
+    let mint_key = key(mint).ok_or_else(|| invalid(format!("invalid mint '{}'", mint)))?;
+    find_program_address(&[b"metadata", &program, &mint_key], &program)
+        .ok_or_else(|| invalid(format!("no metadata address for {}", mint)))
+}
+
+/// Borsh reader over a Metaplex metadata account
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_le_bytes(b.try_into().expect("4 bytes")))
+    }
+
+    /// Borsh string; Metaplex pads names with NULs
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        Some(String::from_utf8_lossy(self.take(len)?).trim_end_matches('\0').to_string())
+    }
+}
+
+/// Name, symbol, URI and verified collection of a `MetadataV1` account
+fn parse_metaplex(data: &[u8]) -> Option<MetaplexMetadata> {
+    const METADATA_V1: u8 = 4;
+    const CREATOR_LEN: usize = 34;
+
+    let mut reader = Reader(data);
+    if reader.u8()? != METADATA_V1 {
+        return None;
+    }
+    reader.take(64)?; // update authority and mint
+    let name = reader.string()?;
+    let symbol = reader.string()?;
+    let uri = reader.string()?;
+    let mut metadata = MetaplexMetadata { name, symbol, uri, collection: None };
+
+    // Older accounts end before the collection; they simply have none
+    let collection = (|| {
+        reader.take(2)?; // seller fee basis points
+        if reader.u8()? == 1 {
+            let creators = reader.u32()? as usize;
+            reader.take(creators * CREATOR_LEN)?;
+        }
+        reader.take(2)?; // primary sale happened, is mutable
+        for _ in 0..2 {
+            // edition nonce, token standard
+            if reader.u8()? == 1 {
+                reader.u8()?;
+            }
+        }
+        if reader.u8()? != 1 {
+            return None;
+        }
+        let verified = reader.u8()? == 1;
+        let key = reader.take(32)?;
+        verified.then(|| bs58::encode(key).into_string())
+    })();
+    metadata.collection = collection;
+    Some(metadata)
+}
+
+/// Indexer over a fixed set of tokens, for tests and offline demos
+pub struct MockIndexer {
+    chain: ChainType,
+    tokens: Vec<NftToken>,
+    calls: AtomicUsize,
+}
+
+impl MockIndexer {
+    pub fn new(chain: ChainType) -> Self {
+        MockIndexer { chain, tokens: Vec::new(), calls: AtomicUsize::new(0) }
+    }
+
+    /// Tokens from a JSON array of `{ token_id, owner, metadata?, collection? }`
+    pub fn from_fixture(chain: ChainType, fixture: &str) -> Result<Self, ChainError> {
+        let tokens = serde_json::from_str(fixture).map_err(|e| ChainError::InvalidRequest(e.to_string()))?;
+        Ok(MockIndexer { tokens, ..Self::new(chain) })
+    }
+
+    pub fn with_token(mut self, token: NftToken) -> Self {
+        self.tokens.push(token);
+        self
+    }
+
+    /// Pages served so far
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NftIndexer for MockIndexer {
+    fn chain(&self) -> ChainType {
+        self.chain
+    }
+
+    async fn tokens_for_owner(&self, query: &NftQuery) -> Result<NftPage, ChainError> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        offset_page(self.tokens.iter().filter(|t| t.owner == query.owner).cloned(), query)
+    }
+}
+
+/// Pages over an adapter's complete `get_user_nfts` listing, for chains
+/// without an indexer of their own. Every page reads the whole listing, so
+/// put it behind a [`CachedIndexer`].
+pub struct AdapterIndexer {
+    adapter: Box<dyn ChainAdapter>,
+}
+
+impl AdapterIndexer {
+    pub fn new(adapter: Box<dyn ChainAdapter>) -> Self {
+        AdapterIndexer { adapter }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NftIndexer for AdapterIndexer {
+    fn chain(&self) -> ChainType {
+        self.adapter.chain()
+    }
+
+    async fn tokens_for_owner(&self, query: &NftQuery) -> Result<NftPage, ChainError> {
+        offset_page(self.adapter.get_user_nfts(&query.owner).await?.into_iter(), query)
+    }
+}
+
+/// Milliseconds since the Unix epoch
+pub type Clock = Box<dyn Fn() -> u64 + Send + Sync>;
+
+struct CachedPage {
+    expires_at: u64,
+    page: NftPage,
+}
+
+/// Keeps pages from another indexer for a while, per query
+pub struct CachedIndexer {
+    inner: Box<dyn NftIndexer>,
+    ttl_ms: u64,
+    clock: Clock,
+    pages: Mutex<HashMap<NftQuery, CachedPage>>,
+}
+
+impl CachedIndexer {
+    /// Cache pages of `inner` for [`DEFAULT_INDEX_TTL_MS`]
+    pub fn new(inner: Box<dyn NftIndexer>) -> Self {
+        CachedIndexer {
+            inner,
+            ttl_ms: DEFAULT_INDEX_TTL_MS,
+            clock: Box::new(|| chrono::Utc::now().timestamp_millis() as u64),
+            pages: Mutex::default(),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl_ms: u64) -> Self {
+        self.ttl_ms = ttl_ms;
+        self
+    }
+
+    /// Time source for expiry, e.g. a fake clock in tests
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Drop the pages cached for `owner`, e.g. after a mint or transfer
+    pub fn invalidate(&self, owner: &str) {
+        self.lock().retain(|query, _| query.owner != owner);
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<NftQuery, CachedPage>> {
+        self.pages.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NftIndexer for CachedIndexer {
+    fn chain(&self) -> ChainType {
+        self.inner.chain()
+    }
+
+    async fn tokens_for_owner(&self, query: &NftQuery) -> Result<NftPage, ChainError> {
+        let now = (self.clock)();
+        if let Some(cached) = self.lock().get(query).filter(|cached| cached.expires_at > now) {
+            return Ok(cached.page.clone());
+        }
+        let page = self.inner.tokens_for_owner(query).await?;
+        let mut pages = self.lock();
+        pages.retain(|_, cached| cached.expires_at > now);
+        pages.insert(query.clone(), CachedPage { expires_at: now.saturating_add(self.ttl_ms), page: page.clone() });
+        Ok(page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_rpc::tests::{ok, Fixture};
+    use curve25519_dalek::edwards::CompressedEdwardsY;
+    use std::sync::atomic::AtomicU64;
+
+    fn near_page(ids: &[&str]) -> Value {
+        let tokens: Vec<Value> = ids.iter().map(|id| json!({ "token_id": id, "owner_id": "alice.near" })).collect();
+        ok(json!({ "result": serde_json::to_vec(&tokens).unwrap() }))
+    }
+
+    fn token(id: &str, owner: &str, collection: &str) -> NftToken {
+        NftToken {
+            token_id: id.to_string(),
+            owner: owner.to_string(),
+            metadata: Value::Null,
+            collection: Some(collection.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_near_pages_run_across_contracts() {
+        let fixture = Arc::new(
+            Fixture::default()
+                .reply("query", near_page(&["a1", "a2"]))
+                .reply("query", near_page(&["a3"]))
+                .reply("query", near_page(&["b1"])),
+        );
+        let indexer = NearIndexer::new(RpcClient::new("http://node", fixture.clone()), &["a.near", "b.near"]);
+
+        let query = NftQuery::new("alice.near").with_limit(2);
+        let first = indexer.tokens_for_owner(&query).await.unwrap();
+        assert_eq!(first.next_cursor.as_deref(), Some("a.near:2"));
+        assert_eq!(first.tokens[0].collection.as_deref(), Some("a.near"));
+
+        let second = indexer.tokens_for_owner(&query.after(&first).unwrap()).await.unwrap();
+        let ids: Vec<&str> = second.tokens.iter().map(|t| t.token_id.as_str()).collect();
+        assert_eq!(ids, ["a3", "b1"]);
+        assert_eq!(second.tokens[1].collection.as_deref(), Some("b.near"));
+
+        let requests = fixture.requests("query");
+        assert_eq!(requests.len(), 3);
+        let args = requests[1]["params"]["args_base64"].as_str().unwrap();
+        let args: Value = serde_json::from_slice(&base64::engine::general_purpose::STANDARD.decode(args).unwrap()).unwrap();
+        assert_eq!(args["from_index"], "2");
+        assert_eq!(requests[2]["params"]["account_id"], "b.near");
+
+        assert!(indexer.tokens_for_owner(&query.clone().with_cursor("c.near:0")).await.is_err());
+    }
+
+    /// `MetadataV1` account data with a verified collection
+    fn metaplex_account(name: &str, collection: &[u8; 32]) -> String {
+        let mut data = vec![4u8];
+        data.extend([0u8; 64]);
+        for field in [name, "LOOP", "ipfs://meta"] {
+            data.extend((field.len() as u32).to_le_bytes());
+            data.extend(field.as_bytes());
+        }
+        data.extend(500u16.to_le_bytes());
+        data.extend([1, 1, 0, 0, 0]); // one creator
+        data.extend([0u8; 34]);
+        data.extend([0, 1, 0, 1, 0]); // primary sale, mutable, no nonce, token standard 0
+        data.extend([1, 1]); // verified collection
+        data.extend(collection);
+        base64::engine::general_purpose::STANDARD.encode(data)
+    }
+
+    #[tokio::test]
+    async fn test_solana_reads_metadata_in_batches_and_filters_collections() {
+        let owner = bs58::encode(curve25519_dalek::constants::ED25519_BASEPOINT_COMPRESSED.as_bytes()).into_string();
+        let mints: Vec<String> = (1..=3u8).map(|i| bs58::encode([i; 32]).into_string()).collect();
+        let accounts: Vec<Value> = mints
+            .iter()
+            .map(|mint| {
+                json!({ "pubkey": format!("ta-{}", &mint[..4]), "account": { "data": { "parsed": { "info": {
+                    "mint": mint, "tokenAmount": { "amount": "1", "decimals": 0 }
+                } } } } })
+            })
+            .chain([json!({ "pubkey": "fungible", "account": { "data": { "parsed": { "info": {
+                "mint": "So11111111111111111111111111111111111111112", "tokenAmount": { "amount": "5", "decimals": 9 }
+            } } } } })])
+            .collect();
+        let art = [7u8; 32];
+        let metadata = json!({ "value": [
+            { "data": [metaplex_account("Loop #1", &art), "base64"] },
+            null,
+            { "data": [metaplex_account("Loop #3", &art), "base64"] },
+        ] });
+        let fixture = Arc::new(
+            Fixture::default()
+                .reply("getProgramAccounts", ok(json!(accounts)))
+                .reply("getMultipleAccounts", ok(metadata)),
+        );
+        let indexer = SolanaIndexer::new(RpcClient::new("http://node", fixture.clone()));
+
+        let collection = bs58::encode(art).into_string();
+        let query = NftQuery::new(&owner).with_collection(&collection);
+        let page = indexer.tokens_for_owner(&query).await.unwrap();
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(page.tokens.len(), 2);
+        assert_eq!(page.tokens[0].metadata["name"], "Loop #1");
+        assert_eq!(page.tokens[1].metadata["uri"], "ipfs://meta");
+        assert_eq!(page.tokens[1].collection.as_deref(), Some(collection.as_str()));
+
+        // Two calls for the whole portfolio: token accounts, then one metadata batch
+        let accounts = fixture.requests("getProgramAccounts");
+        let batches = fixture.requests("getMultipleAccounts");
+        assert_eq!((accounts.len(), batches.len()), (1, 1));
+        assert_eq!(accounts[0]["params"][1]["filters"][1]["memcmp"]["bytes"], owner.as_str());
+        let addresses = batches[0]["params"][0].as_array().unwrap();
+        assert_eq!(addresses[0], bs58::encode(metadata_address(&mints[0]).unwrap()).into_string());
+
+        // Paging one token at a time lists the accounts only for the first page
+        let mut query = NftQuery::new(&owner).with_limit(1);
+        let mut seen = vec![];
+        loop {
+            let page = indexer.tokens_for_owner(&query).await.unwrap();
+            seen.extend(page.tokens.iter().map(|t| t.token_id.clone()));
+            match query.after(&page) {
+                Some(next) => query = next,
+                None => break,
+            }
+        }
+        assert_eq!(seen.len(), 3);
+        assert_eq!(fixture.requests("getProgramAccounts").len(), 2);
+        assert!(indexer.lock_listings().is_empty());
+    }
+
+    #[test]
+    fn test_program_addresses_are_off_curve() {
+        let address = metadata_address("So11111111111111111111111111111111111111112").unwrap();
+        assert!(CompressedEdwardsY(address).decompress().is_none());
+        assert_eq!(parse_metaplex(&[3, 0, 0]), None);
+    }
+
+    #[tokio::test]
+    async fn test_cache_serves_pages_until_they_expire() {
+        let fixture = r#"[
+            { "token_id": "1", "owner": "alice.near", "collection": "art.near" },
+            { "token_id": "2", "owner": "alice.near", "collection": "music.near" },
+            { "token_id": "3", "owner": "alice.near", "collection": "art.near" },
+            { "token_id": "4", "owner": "bob.near", "collection": "art.near" }
+        ]"#;
+        let mock = MockIndexer::from_fixture(ChainType::Near, fixture).unwrap();
+        let mock = Arc::new(mock.with_token(token("5", "alice.near", "art.near")));
+        let now = Arc::new(AtomicU64::new(1_000));
+        let clock = now.clone();
+        let cached = CachedIndexer::new(Box::new(mock.clone()))
+            .with_ttl(500)
+            .with_clock(Box::new(move || clock.load(Ordering::Relaxed)));
+
+        let query = NftQuery::new("alice.near").with_collection("art.near").with_limit(2);
+        let first = cached.tokens_for_owner(&query).await.unwrap();
+        assert_eq!(first.tokens.iter().map(|t| t.token_id.as_str()).collect::<Vec<_>>(), ["1", "3"]);
+        assert_eq!(first.next_cursor.as_deref(), Some("2"));
+        assert_eq!(cached.tokens_for_owner(&query).await.unwrap(), first);
+        assert_eq!(mock.calls(), 1);
+
+        now.store(1_500, Ordering::Relaxed);
+        cached.tokens_for_owner(&query).await.unwrap();
+        assert_eq!(mock.calls(), 2);
+        cached.invalidate("alice.near");
+        cached.tokens_for_owner(&query).await.unwrap();
+        assert_eq!(mock.calls(), 3);
+
+        let all = all_tokens(&cached, &NftQuery::new("alice.near")).await.unwrap();
+        assert_eq!(all.len(), 4);
+
+        // A TTL reaching past the end of time keeps pages rather than overflowing
+        let calls = mock.calls();
+        let forever = CachedIndexer::new(Box::new(mock.clone())).with_ttl(u64::MAX);
+        forever.tokens_for_owner(&query).await.unwrap();
+        forever.tokens_for_owner(&query).await.unwrap();
+        assert_eq!(mock.calls(), calls + 1);
+    }
+}
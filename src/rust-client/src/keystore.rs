@@ -0,0 +1,1225 @@
+//! Encrypted keystore for ed25519 (NEAR, Solana), sr25519 (Polkadot) and
+//! secp256k1 (EVM) keys.
+//!
+//! Keys live in a password-protected JSON file. Each secret is sealed with
+//! ChaCha20-Poly1305 under a key stretched from the password with scrypt or
+//! Argon2id, and is only decrypted for the signature being made. Decrypted
+//! secrets and the stretched key are zeroized when dropped.
+//!
+//! [`KeystoreSigner`] puts a stored key behind the [`Signer`] trait the chain
+//! adapters sign with.
+
+use crate::address::{find_program_address, ChainAddress};
+use crate::blockchain_integration::ChainType;
+use crate::chain_adapter::{ChainError, Signer, TxContext, UnsignedTransaction};
+use crate::chain_rpc::{abi_encode_call, scale_compact, SPL_ASSOCIATED_TOKEN_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID};
+use async_trait::async_trait;
+use blake2::digest::consts::U32;
+use blake2::Blake2b;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::Signer as _;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Layout version of the keystore file
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Signing context Substrate uses for sr25519 signatures
+const SR25519_CONTEXT: &[u8] = b"substrate";
+/// Associated data sealed with the password check value
+const CHECK_AAD: &[u8] = b"nft-keystore-check";
+
+/// `TransferChecked` in the SPL token program's instruction enum
+const SPL_TRANSFER_CHECKED: u8 = 12;
+
+/// Pallet and call indices on Polkadot and Westend Asset Hub
+const UTILITY_PALLET: u8 = 40;
+const UTILITY_BATCH_ALL: u8 = 2;
+const NFTS_PALLET: u8 = 52;
+const NFTS_MINT: u8 = 3;
+const NFTS_TRANSFER: u8 = 6;
+const NFTS_SET_METADATA: u8 = 24;
+/// Signed extrinsic, format version 4
+const EXTRINSIC_SIGNED_V4: u8 = 0x84;
+/// Signing payloads longer than this are signed as their blake2-256 hash
+const MAX_UNHASHED_PAYLOAD: usize = 256;
+
+/// Errors from the keystore and its signers
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeystoreError {
+    /// The password does not open the keystore
+    WrongPassword,
+    UnknownKey(String),
+    DuplicateKey(String),
+    /// Secret or public key bytes that are not a valid key of their type
+    InvalidKey(String),
+    /// The key's curve cannot sign for the chain
+    WrongKeyType { key: KeyType, chain: ChainType },
+    UnsupportedVersion(u32),
+    /// Key stretching parameters the KDF rejects
+    Kdf(String),
+    /// The file is not a keystore, or a stored key has been altered
+    Format(String),
+    Io(String),
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystoreError::WrongPassword => write!(f, "wrong keystore password"),
+            KeystoreError::UnknownKey(name) => write!(f, "no key named '{}'", name),
+            KeystoreError::DuplicateKey(name) => write!(f, "a key named '{}' already exists", name),
+            KeystoreError::InvalidKey(msg) => write!(f, "invalid key: {}", msg),
+            KeystoreError::WrongKeyType { key, chain } => {
+                write!(f, "{} keys cannot sign for {}", key.as_str(), chain.as_str())
+            }
+            KeystoreError::UnsupportedVersion(version) => write!(f, "unsupported keystore version {}", version),
+            KeystoreError::Kdf(msg) => write!(f, "key derivation failed: {}", msg),
+            KeystoreError::Format(msg) => write!(f, "malformed keystore: {}", msg),
+            KeystoreError::Io(msg) => write!(f, "keystore I/O error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for KeystoreError {}
+
+impl From<std::io::Error> for KeystoreError {
+    fn from(e: std::io::Error) -> Self {
+        KeystoreError::Io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for KeystoreError {
+    fn from(e: serde_json::Error) -> Self {
+        KeystoreError::Format(e.to_string())
+    }
+}
+
+impl From<KeystoreError> for ChainError {
+    fn from(e: KeystoreError) -> Self {
+        ChainError::Signer(e.to_string())
+    }
+}
+
+/// Signature scheme of a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+    Ed25519,
+    Sr25519,
+    Secp256k1,
+}
+
+impl KeyType {
+    /// The scheme a chain's accounts sign with
+    pub fn for_chain(chain: ChainType) -> KeyType {
+        match chain {
+            ChainType::Near | ChainType::Solana => KeyType::Ed25519,
+            ChainType::Polkadot => KeyType::Sr25519,
+            ChainType::Ethereum | ChainType::Polygon => KeyType::Secp256k1,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyType::Ed25519 => "ed25519",
+            KeyType::Sr25519 => "sr25519",
+            KeyType::Secp256k1 => "secp256k1",
+        }
+    }
+}
+
+/// A decrypted private key: the 32-byte ed25519 seed, sr25519 mini secret
+/// or secp256k1 scalar. Zeroized on drop and never printed.
+pub struct SecretKey {
+    key_type: KeyType,
+    seed: Zeroizing<[u8; 32]>,
+}
+
+impl SecretKey {
+    /// Fresh key from the operating system's RNG
+    pub fn generate(key_type: KeyType) -> Self {
+        loop {
+            let mut seed = Zeroizing::new([0u8; 32]);
+            OsRng.fill_bytes(seed.as_mut());
+            // Only secp256k1 rejects seeds, about once in 2^128 tries
+            if let Ok(key) = Self::from_seed(key_type, seed.as_ref()) {
+                return key;
+            }
+        }
+    }
+
+    pub fn from_seed(key_type: KeyType, seed: &[u8]) -> Result<Self, KeystoreError> {
+        let seed: [u8; 32] = seed
+            .try_into()
+            .map_err(|_| KeystoreError::InvalidKey(format!("{} secrets are 32 bytes", key_type.as_str())))?;
+        let key = SecretKey { key_type, seed: Zeroizing::new(seed) };
+        if key_type == KeyType::Secp256k1 {
+            key.secp256k1()?;
+        }
+        Ok(key)
+    }
+
+    /// Key in near-cli's `ed25519:<base58 of seed and public key>` form
+    pub fn from_near(text: &str) -> Result<Self, KeystoreError> {
+        let encoded = text
+            .strip_prefix("ed25519:")
+            .ok_or_else(|| KeystoreError::InvalidKey("only ed25519 NEAR keys are supported".to_string()))?;
+        let bytes = Zeroizing::new(
+            bs58::decode(encoded).into_vec().map_err(|_| KeystoreError::InvalidKey("not base58".to_string()))?,
+        );
+        let key = Self::from_seed(KeyType::Ed25519, bytes.get(..32).unwrap_or_default())?;
+        if bytes.len() == 64 && bytes[32..] != key.public_key()[..] {
+            return Err(KeystoreError::InvalidKey("public half does not match the seed".to_string()));
+        }
+        Ok(key)
+    }
+
+    pub fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    /// Raw public key; secp256k1 keys are SEC1-compressed (33 bytes)
+    pub fn public_key(&self) -> Vec<u8> {
+        match self.key_type {
+            KeyType::Ed25519 => self.ed25519().verifying_key().to_bytes().to_vec(),
+            KeyType::Sr25519 => self.sr25519().public.to_bytes().to_vec(),
+            KeyType::Secp256k1 => self.secp256k1().expect("checked on creation").verifying_key().to_sec1_bytes().to_vec(),
+        }
+    }
+
+    /// Signature over `message`: 64 bytes for ed25519 and sr25519 (Substrate
+    /// context), and for secp256k1 `r || s || recovery id` over its Keccak-256
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self.key_type {
+            KeyType::Ed25519 => self.ed25519().sign(message).to_bytes().to_vec(),
+            KeyType::Sr25519 => {
+                let context = schnorrkel::signing_context(SR25519_CONTEXT);
+                self.sr25519().sign(context.bytes(message)).to_bytes().to_vec()
+            }
+            KeyType::Secp256k1 => {
+                let key = self.secp256k1().expect("checked on creation");
+                let (signature, recovery) =
+                    key.sign_prehash_recoverable(&Keccak256::digest(message)).expect("32-byte prehash");
+                let mut out = signature.to_bytes().to_vec();
+                out.push(recovery.to_byte());
+                out
+            }
+        }
+    }
+
+    fn ed25519(&self) -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(&self.seed)
+    }
+
+    fn sr25519(&self) -> schnorrkel::Keypair {
+        schnorrkel::MiniSecretKey::from_bytes(self.seed.as_ref())
+            .expect("32 bytes")
+            .expand_to_keypair(schnorrkel::ExpansionMode::Ed25519)
+    }
+
+    fn secp256k1(&self) -> Result<k256::ecdsa::SigningKey, KeystoreError> {
+        k256::ecdsa::SigningKey::from_slice(self.seed.as_ref())
+            .map_err(|_| KeystoreError::InvalidKey("not a secp256k1 scalar".to_string()))
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretKey({}, <redacted>)", self.key_type.as_str())
+    }
+}
+
+/// How the file key is stretched from the password
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "lowercase")]
+pub enum Kdf {
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    /// Memory cost in KiB
+    Argon2id { m_cost: u32, t_cost: u32, p_cost: u32 },
+}
+
+/// Most memory a stored KDF may ask for, so a crafted file cannot exhaust it
+pub const MAX_KDF_MEMORY_BYTES: u64 = 1 << 30;
+/// Most passes or lanes a stored KDF may ask for
+pub const MAX_KDF_PASSES: u32 = 16;
+
+impl Default for Kdf {
+    /// Argon2id with OWASP's minimum parameters
+    fn default() -> Self {
+        Kdf::Argon2id { m_cost: 19_456, t_cost: 2, p_cost: 1 }
+    }
+}
+
+impl Kdf {
+    /// scrypt with the parameters geth uses for new keys
+    pub fn scrypt() -> Self {
+        Kdf::Scrypt { log_n: 18, r: 8, p: 1 }
+    }
+
+    /// Refuse parameters past [`MAX_KDF_MEMORY_BYTES`] and [`MAX_KDF_PASSES`]
+    fn check_limits(&self) -> Result<(), KeystoreError> {
+        let (memory, passes) = match *self {
+            // scrypt needs 128 * r * 2^log_n bytes, once per p
+            Kdf::Scrypt { log_n, r, p } => {
+                let memory = 1u64.checked_shl(log_n.into()).and_then(|n| n.checked_mul(128 * u64::from(r)));
+                (memory, p)
+            }
+            Kdf::Argon2id { m_cost, t_cost, p_cost } => (Some(u64::from(m_cost) * 1024), t_cost.max(p_cost)),
+        };
+        if memory.is_none_or(|memory| memory > MAX_KDF_MEMORY_BYTES) || passes > MAX_KDF_PASSES {
+            return Err(KeystoreError::Kdf(format!("{:?} exceeds the keystore's KDF limits", self)));
+        }
+        Ok(())
+    }
+
+    fn derive(&self, password: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, KeystoreError> {
+        self.check_limits()?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        let kdf_error = |e: &dyn fmt::Display| KeystoreError::Kdf(e.to_string());
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, 32).map_err(|e| kdf_error(&e))?;
+                scrypt::scrypt(password.as_bytes(), salt, &params, key.as_mut()).map_err(|e| kdf_error(&e))?;
+            }
+            Kdf::Argon2id { m_cost, t_cost, p_cost } => {
+                let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(32)).map_err(|e| kdf_error(&e))?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password.as_bytes(), salt, key.as_mut())
+                    .map_err(|e| kdf_error(&e))?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// Public details of a stored key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+    pub name: String,
+    pub key_type: KeyType,
+    pub public_key: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sealed {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredKey {
+    key_type: KeyType,
+    public_key: String,
+    #[serde(flatten)]
+    sealed: Sealed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u32,
+    kdf: Kdf,
+    salt: String,
+    /// Empty plaintext sealed under the file key, so a wrong password fails
+    /// on unlock even when there are no keys yet
+    check: Sealed,
+    keys: BTreeMap<String, StoredKey>,
+}
+
+/// An unlocked keystore. Holds the stretched file key, never a decrypted secret.
+pub struct Keystore {
+    file: KeystoreFile,
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl Keystore {
+    /// Empty keystore protected by `password`
+    pub fn create(password: &str, kdf: Kdf) -> Result<Self, KeystoreError> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let key = kdf.derive(password, &salt)?;
+        let check = seal(&key, &[], CHECK_AAD)?;
+        let file = KeystoreFile { version: KEYSTORE_VERSION, kdf, salt: hex::encode(salt), check, keys: BTreeMap::new() };
+        Ok(Keystore { file, key })
+    }
+
+    /// Unlock a keystore from its JSON form
+    pub fn unlock(json: &str, password: &str) -> Result<Self, KeystoreError> {
+        let file: KeystoreFile = serde_json::from_str(json)?;
+        if file.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion(file.version));
+        }
+        let salt = hex::decode(&file.salt).map_err(|e| KeystoreError::Format(e.to_string()))?;
+        let key = file.kdf.derive(password, &salt)?;
+        open(&key, &file.check, CHECK_AAD).map_err(|_| KeystoreError::WrongPassword)?;
+        Ok(Keystore { file, key })
+    }
+
+    /// Read and unlock a keystore file
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(path: impl AsRef<std::path::Path>, password: &str) -> Result<Self, KeystoreError> {
+        Self::unlock(&std::fs::read_to_string(path)?, password)
+    }
+
+    /// Encrypted JSON form; safe to store, secrets stay sealed
+    pub fn to_json(&self) -> Result<String, KeystoreError> {
+        Ok(serde_json::to_string_pretty(&self.file)?)
+    }
+
+    /// Write the keystore to `path` through a temporary file, readable only by
+    /// the owner on Unix. A stale temporary file is replaced rather than
+    /// reused, so it cannot keep looser permissions.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), KeystoreError> {
+        use std::io::Write;
+
+        let path = path.as_ref();
+        let temp = path.with_extension("tmp");
+        match std::fs::remove_file(&temp) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(&temp)?.write_all(self.to_json()?.as_bytes())?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    /// Generate and store a new key
+    pub fn generate(&mut self, name: &str, key_type: KeyType) -> Result<KeyInfo, KeystoreError> {
+        self.import(name, &SecretKey::generate(key_type))
+    }
+
+    /// Seal an existing secret under `name`
+    pub fn import(&mut self, name: &str, secret: &SecretKey) -> Result<KeyInfo, KeystoreError> {
+        if self.file.keys.contains_key(name) {
+            return Err(KeystoreError::DuplicateKey(name.to_string()));
+        }
+        let public_key = secret.public_key();
+        let stored = StoredKey {
+            key_type: secret.key_type,
+            public_key: hex::encode(&public_key),
+            sealed: seal(&self.key, secret.seed.as_ref(), &key_aad(name, secret.key_type, &public_key))?,
+        };
+        self.file.keys.insert(name.to_string(), stored);
+        Ok(KeyInfo { name: name.to_string(), key_type: secret.key_type, public_key })
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), KeystoreError> {
+        self.file.keys.remove(name).map(|_| ()).ok_or_else(|| KeystoreError::UnknownKey(name.to_string()))
+    }
+
+    pub fn key(&self, name: &str) -> Option<KeyInfo> {
+        let stored = self.file.keys.get(name)?;
+        Some(KeyInfo {
+            name: name.to_string(),
+            key_type: stored.key_type,
+            public_key: hex::decode(&stored.public_key).ok()?,
+        })
+    }
+
+    /// Stored keys, by name
+    pub fn keys(&self) -> Vec<KeyInfo> {
+        self.file.keys.keys().filter_map(|name| self.key(name)).collect()
+    }
+
+    /// Sign `message` with the named key; the secret is wiped before returning
+    pub fn sign(&self, name: &str, message: &[u8]) -> Result<Vec<u8>, KeystoreError> {
+        Ok(self.secret(name)?.sign(message))
+    }
+
+    /// Re-seal every key under a new password and salt
+    pub fn change_password(&mut self, password: &str) -> Result<(), KeystoreError> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let key = self.file.kdf.derive(password, &salt)?;
+        let mut keys = BTreeMap::new();
+        for (name, stored) in &self.file.keys {
+            let secret = self.secret(name)?;
+            let aad = key_aad(name, stored.key_type, &secret.public_key());
+            keys.insert(name.clone(), StoredKey { sealed: seal(&key, secret.seed.as_ref(), &aad)?, ..stored.clone() });
+        }
+        self.file.check = seal(&key, &[], CHECK_AAD)?;
+        self.file.salt = hex::encode(salt);
+        self.file.keys = keys;
+        self.key = key;
+        Ok(())
+    }
+
+    fn secret(&self, name: &str) -> Result<SecretKey, KeystoreError> {
+        let stored = self.file.keys.get(name).ok_or_else(|| KeystoreError::UnknownKey(name.to_string()))?;
+        let public_key = hex::decode(&stored.public_key).map_err(|e| KeystoreError::Format(e.to_string()))?;
+        let seed = open(&self.key, &stored.sealed, &key_aad(name, stored.key_type, &public_key))?;
+        let secret = SecretKey::from_seed(stored.key_type, &seed)?;
+        if secret.public_key() != public_key {
+            return Err(KeystoreError::Format(format!("public key of '{}' does not match its secret", name)));
+        }
+        Ok(secret)
+    }
+}
+
+impl fmt::Debug for Keystore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keystore").field("kdf", &self.file.kdf).field("keys", &self.file.keys.keys()).finish()
+    }
+}
+
+/// Binds a sealed secret to its name, type and public key
+fn key_aad(name: &str, key_type: KeyType, public_key: &[u8]) -> Vec<u8> {
+    format!("{}:{}:{}", name, key_type.as_str(), hex::encode(public_key)).into_bytes()
+}
+
+fn seal(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Result<Sealed, KeystoreError> {
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|_| KeystoreError::Format("encryption failed".to_string()))?;
+    Ok(Sealed { nonce: hex::encode(nonce), ciphertext: hex::encode(ciphertext) })
+}
+
+fn open(key: &[u8; 32], sealed: &Sealed, aad: &[u8]) -> Result<Zeroizing<Vec<u8>>, KeystoreError> {
+    let format_error = |e: hex::FromHexError| KeystoreError::Format(e.to_string());
+    let nonce = hex::decode(&sealed.nonce).map_err(format_error)?;
+    let ciphertext = hex::decode(&sealed.ciphertext).map_err(format_error)?;
+    if nonce.len() != 12 {
+        return Err(KeystoreError::Format("nonces are 12 bytes".to_string()));
+    }
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad })
+        .map(Zeroizing::new)
+        .map_err(|_| KeystoreError::Format("sealed key failed authentication".to_string()))
+}
+
+/// [`Signer`] for one keystore key on one chain.
+///
+/// Encodes NEAR function calls as Borsh transactions, EVM calls as EIP-155
+/// legacy transactions, Solana calls as legacy messages and Polkadot calls as
+/// signed pallet-nfts extrinsics for Asset Hub.
+pub struct KeystoreSigner {
+    keystore: Arc<Keystore>,
+    key: String,
+    chain: ChainType,
+    public_key: Vec<u8>,
+    account_id: String,
+}
+
+impl KeystoreSigner {
+    /// Signer for `key`, acting for the account its public key names: the
+    /// implicit account on NEAR, the address elsewhere
+    pub fn new(keystore: Arc<Keystore>, key: &str, chain: ChainType) -> Result<Self, KeystoreError> {
+        let info = keystore.key(key).ok_or_else(|| KeystoreError::UnknownKey(key.to_string()))?;
+        if info.key_type != KeyType::for_chain(chain) {
+            return Err(KeystoreError::WrongKeyType { key: info.key_type, chain });
+        }
+        let account = match chain {
+            ChainType::Solana => bs58::encode(&info.public_key).into_string(),
+            ChainType::Ethereum | ChainType::Polygon => format!("0x{}", hex::encode(evm_address(&info.public_key)?)),
+            ChainType::Polkadot => format!("0x{}", hex::encode(&info.public_key)),
+            ChainType::Near => hex::encode(&info.public_key),
+        };
+        let account_id = ChainAddress::parse(chain, &account)
+            .map_err(|e| KeystoreError::InvalidKey(e.to_string()))?
+            .normalized();
+        Ok(KeystoreSigner { keystore, key: key.to_string(), chain, public_key: info.public_key, account_id })
+    }
+
+    /// Act for a named account the key has access to, e.g. `artist.near`
+    pub fn with_account_id(mut self, account_id: &str) -> Self {
+        self.account_id = account_id.to_string();
+        self
+    }
+
+    /// Chain the signer encodes transactions for
+    pub fn chain(&self) -> ChainType {
+        self.chain
+    }
+
+    /// Raw public key, as the chain's transaction encoding expects it
+    pub fn public_key_bytes(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Sign `message` with the key, for callers that encode transactions the
+    /// [`Signer`] trait has no shape for, such as NEAR contract deployments
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, KeystoreError> {
+        self.keystore.sign(&self.key, message)
+    }
+
+    fn near_transaction(&self, tx: &UnsignedTransaction) -> Result<Vec<u8>, ChainError> {
+        let TxContext::Near { nonce, block_hash, gas } = &tx.context else {
+            return Err(ChainError::Signer("expected a NEAR transaction".to_string()));
+        };
+        let nonce = nonce.ok_or_else(|| ChainError::Signer("NEAR access key nonce is missing".to_string()))?;
+        let block_hash = bs58::decode(block_hash)
+            .into_vec()
+            .ok()
+            .filter(|hash| hash.len() == 32)
+            .ok_or_else(|| ChainError::Signer("NEAR block hashes are 32 bytes of base58".to_string()))?;
+        let args = serde_json::to_vec(&tx.args).map_err(|e| ChainError::Signer(e.to_string()))?;
+
+        // Borsh `Transaction` with a single `FunctionCall` action
+        let mut out = Vec::new();
+        borsh_bytes(&mut out, tx.signer_id.as_bytes());
+        out.push(0);
+        out.extend_from_slice(&self.public_key);
+        out.extend_from_slice(&nonce.to_le_bytes());
+        borsh_bytes(&mut out, tx.receiver_id.as_bytes());
+        out.extend_from_slice(&block_hash);
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.push(2);
+        borsh_bytes(&mut out, tx.method.as_bytes());
+        borsh_bytes(&mut out, &args);
+        out.extend_from_slice(&gas.to_le_bytes());
+        out.extend_from_slice(&tx.deposit.to_le_bytes());
+
+        let signature = self.keystore.sign(&self.key, &Sha256::digest(&out))?;
+        out.push(0);
+        out.extend(signature);
+        Ok(out)
+    }
+
+    fn evm_transaction(&self, tx: &UnsignedTransaction) -> Result<Vec<u8>, ChainError> {
+        let TxContext::Evm { chain_id, nonce, gas_price, gas_limit } = tx.context else {
+            return Err(ChainError::Signer("expected an EVM transaction".to_string()));
+        };
+        let to = ChainAddress::parse(self.chain, &tx.receiver_id)?;
+        let data = abi_encode_call(self.chain, &tx.method, &tx.args)?;
+
+        let mut fields = Vec::new();
+        rlp_uint(&mut fields, nonce as u128);
+        rlp_uint(&mut fields, gas_price);
+        rlp_uint(&mut fields, gas_limit as u128);
+        rlp_bytes(&mut fields, to.as_bytes());
+        rlp_uint(&mut fields, tx.deposit);
+        rlp_bytes(&mut fields, &data);
+
+        // EIP-155: sign over the fields plus (chain_id, 0, 0)
+        let mut unsigned = fields.clone();
+        rlp_uint(&mut unsigned, chain_id as u128);
+        rlp_uint(&mut unsigned, 0);
+        rlp_uint(&mut unsigned, 0);
+        let signature = self.keystore.sign(&self.key, &rlp_list(&unsigned))?;
+
+        rlp_uint(&mut fields, signature[64] as u128 + 35 + 2 * chain_id as u128);
+        rlp_bytes(&mut fields, strip_zeros(&signature[..32]));
+        rlp_bytes(&mut fields, strip_zeros(&signature[32..64]));
+        Ok(rlp_list(&fields))
+    }
+
+    /// One-instruction legacy message signed by the fee payer. SPL
+    /// `transfer_checked` moves the token between associated token accounts;
+    /// other calls go to the program with its Anchor discriminator and the
+    /// arguments as a Borsh JSON string, the fee payer as the only account.
+    fn solana_transaction(&self, tx: &UnsignedTransaction) -> Result<Vec<u8>, ChainError> {
+        let TxContext::Solana { recent_blockhash } = &tx.context else {
+            return Err(ChainError::Signer("expected a Solana transaction".to_string()));
+        };
+        let recent_blockhash = solana_key(recent_blockhash, "recent blockhash")?;
+        let payer: [u8; 32] = self.public_key.as_slice().try_into().expect("ed25519 keys are 32 bytes");
+        let program = solana_key(&tx.receiver_id, "program id")?;
+
+        let (accounts, data) = if tx.receiver_id == SPL_TOKEN_PROGRAM_ID && tx.method == "transfer_checked" {
+            let arg = |name: &str| tx.args.get(name).ok_or_else(|| ChainError::Signer(format!("missing '{}'", name)));
+            let mint = solana_key(arg("mint")?.as_str().unwrap_or_default(), "mint")?;
+            let destination = solana_key(arg("destination_owner")?.as_str().unwrap_or_default(), "destination owner")?;
+            let amount = arg("amount")?.as_u64().ok_or_else(|| ChainError::Signer("amount is a u64".to_string()))?;
+            let decimals = arg("decimals")?
+                .as_u64()
+                .and_then(|d| u8::try_from(d).ok())
+                .ok_or_else(|| ChainError::Signer("decimals is a u8".to_string()))?;
+            let mut data = vec![SPL_TRANSFER_CHECKED];
+            data.extend_from_slice(&amount.to_le_bytes());
+            data.push(decimals);
+            let accounts = vec![
+                SolanaAccount { key: associated_token_account(&payer, &mint)?, signer: false, writable: true },
+                SolanaAccount { key: mint, signer: false, writable: false },
+                SolanaAccount { key: associated_token_account(&destination, &mint)?, signer: false, writable: true },
+                SolanaAccount { key: payer, signer: true, writable: false },
+            ];
+            (accounts, data)
+        } else {
+            let mut data = Sha256::digest(format!("global:{}", tx.method).as_bytes())[..8].to_vec();
+            let args = serde_json::to_vec(&tx.args).map_err(|e| ChainError::Signer(e.to_string()))?;
+            borsh_bytes(&mut data, &args);
+            (Vec::new(), data)
+        };
+
+        let message = solana_message(payer, program, &accounts, &data, recent_blockhash);
+        let signature = self.keystore.sign(&self.key, &message)?;
+        let mut out = Vec::new();
+        solana_length(&mut out, 1);
+        out.extend(signature);
+        out.extend(message);
+        Ok(out)
+    }
+
+    /// Immortal signed extrinsic with the Asset Hub transaction extensions:
+    /// era, nonce, tip, fee asset and metadata hash mode
+    fn polkadot_transaction(&self, tx: &UnsignedTransaction) -> Result<Vec<u8>, ChainError> {
+        let TxContext::Polkadot { nonce, genesis_hash, block_hash: _, spec_version, transaction_version } = &tx.context
+        else {
+            return Err(ChainError::Signer("expected a Polkadot transaction".to_string()));
+        };
+        let genesis_hash = hex::decode(genesis_hash.trim_start_matches("0x"))
+            .ok()
+            .filter(|hash| hash.len() == 32)
+            .ok_or_else(|| ChainError::Signer("genesis hashes are 32 bytes of hex".to_string()))?;
+        let call = nfts_call(tx)?;
+
+        let mut extra = vec![0];
+        scale_compact(*nonce, &mut extra);
+        extra.extend_from_slice(&[0, 0, 0]);
+
+        // An immortal era checks the genesis hash in place of a block hash
+        let mut payload = call.clone();
+        payload.extend_from_slice(&extra);
+        payload.extend_from_slice(&spec_version.to_le_bytes());
+        payload.extend_from_slice(&transaction_version.to_le_bytes());
+        payload.extend_from_slice(&genesis_hash);
+        payload.extend_from_slice(&genesis_hash);
+        payload.push(0);
+        let signature = if payload.len() > MAX_UNHASHED_PAYLOAD {
+            self.keystore.sign(&self.key, &Blake2b::<U32>::digest(&payload))?
+        } else {
+            self.keystore.sign(&self.key, &payload)?
+        };
+
+        let mut body = vec![EXTRINSIC_SIGNED_V4, 0];
+        body.extend_from_slice(&self.public_key);
+        body.push(1);
+        body.extend(signature);
+        body.extend(extra);
+        body.extend(call);
+        let mut out = Vec::new();
+        scale_compact(body.len() as u64, &mut out);
+        out.extend(body);
+        Ok(out)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Signer for KeystoreSigner {
+    fn account_id(&self) -> String {
+        self.account_id.clone()
+    }
+
+    fn public_key(&self) -> Option<String> {
+        Some(match self.chain {
+            ChainType::Near => format!("ed25519:{}", bs58::encode(&self.public_key).into_string()),
+            ChainType::Solana => bs58::encode(&self.public_key).into_string(),
+            _ => format!("0x{}", hex::encode(&self.public_key)),
+        })
+    }
+
+    async fn sign_transaction(&self, tx: &UnsignedTransaction) -> Result<Vec<u8>, ChainError> {
+        match self.chain {
+            ChainType::Near => self.near_transaction(tx),
+            ChainType::Ethereum | ChainType::Polygon => self.evm_transaction(tx),
+            ChainType::Solana => self.solana_transaction(tx),
+            ChainType::Polkadot => self.polkadot_transaction(tx),
+        }
+    }
+}
+
+/// Ethereum address of a compressed secp256k1 public key
+fn evm_address(public_key: &[u8]) -> Result<[u8; 20], KeystoreError> {
+    let key = k256::PublicKey::from_sec1_bytes(public_key)
+        .map_err(|_| KeystoreError::InvalidKey("not a secp256k1 public key".to_string()))?;
+    let point = k256::elliptic_curve::sec1::ToEncodedPoint::to_encoded_point(&key, false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    Ok(hash[12..].try_into().expect("20 bytes"))
+}
+
+fn solana_key(text: &str, what: &str) -> Result<[u8; 32], ChainError> {
+    bs58::decode(text)
+        .into_vec()
+        .ok()
+        .and_then(|key| key.try_into().ok())
+        .ok_or_else(|| ChainError::Signer(format!("{} is not a base58 32-byte key", what)))
+}
+
+fn associated_token_account(owner: &[u8; 32], mint: &[u8; 32]) -> Result<[u8; 32], ChainError> {
+    let token_program = solana_key(SPL_TOKEN_PROGRAM_ID, "token program")?;
+    let program = solana_key(SPL_ASSOCIATED_TOKEN_PROGRAM_ID, "associated token program")?;
+    find_program_address(&[owner, &token_program, mint], &program)
+        .ok_or_else(|| ChainError::Signer("no associated token account".to_string()))
+}
+
+struct SolanaAccount {
+    key: [u8; 32],
+    signer: bool,
+    writable: bool,
+}
+
+/// Solana `compact-u16` length
+fn solana_length(out: &mut Vec<u8>, mut len: usize) {
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Legacy message with one instruction. Keys are ordered writable signers,
+/// read-only signers, writable accounts, read-only accounts; the fee payer first.
+fn solana_message(
+    payer: [u8; 32],
+    program: [u8; 32],
+    accounts: &[SolanaAccount],
+    data: &[u8],
+    recent_blockhash: [u8; 32],
+) -> Vec<u8> {
+    let mut keys = vec![SolanaAccount { key: payer, signer: true, writable: true }];
+    for account in accounts.iter().chain([&SolanaAccount { key: program, signer: false, writable: false }]) {
+        match keys.iter_mut().find(|k| k.key == account.key) {
+            Some(known) => {
+                known.signer |= account.signer;
+                known.writable |= account.writable;
+            }
+            None => keys.push(SolanaAccount { key: account.key, ..*account }),
+        }
+    }
+    // Stable sort keeps the fee payer ahead of other writable signers
+    keys.sort_by_key(|k| (!k.signer, !k.writable));
+    let index = |key: &[u8; 32]| keys.iter().position(|k| k.key == *key).expect("collected above") as u8;
+
+    let mut out = vec![
+        keys.iter().filter(|k| k.signer).count() as u8,
+        keys.iter().filter(|k| k.signer && !k.writable).count() as u8,
+        keys.iter().filter(|k| !k.signer && !k.writable).count() as u8,
+    ];
+    solana_length(&mut out, keys.len());
+    for k in &keys {
+        out.extend_from_slice(&k.key);
+    }
+    out.extend_from_slice(&recent_blockhash);
+    solana_length(&mut out, 1);
+    out.push(index(&program));
+    solana_length(&mut out, accounts.len());
+    out.extend(accounts.iter().map(|account| index(&account.key)));
+    solana_length(&mut out, data.len());
+    out.extend_from_slice(data);
+    out
+}
+
+/// SCALE call for a pallet-nfts method. Mints with metadata are batched with
+/// `Nfts.set_metadata` through `Utility.batch_all`.
+fn nfts_call(tx: &UnsignedTransaction) -> Result<Vec<u8>, ChainError> {
+    let u32_arg = |name: &str| {
+        tx.args
+            .get(name)
+            .and_then(Value::as_u64)
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or_else(|| ChainError::Signer(format!("'{}' is a u32", name)))
+    };
+    let account_arg = |name: &str| -> Result<[u8; 32], ChainError> {
+        let text = tx.args.get(name).and_then(Value::as_str).unwrap_or_default();
+        Ok(ChainAddress::parse(ChainType::Polkadot, text)?.account_id32().expect("substrate accounts are 32 bytes"))
+    };
+    let collection = u32_arg("collection")?.to_le_bytes();
+    let item = u32_arg("item")?.to_le_bytes();
+
+    let mut call = vec![NFTS_PALLET];
+    match tx.method.as_str() {
+        "mint" => {
+            call.push(NFTS_MINT);
+            call.extend_from_slice(&collection);
+            call.extend_from_slice(&item);
+            call.push(0);
+            call.extend_from_slice(&account_arg("mint_to")?);
+            // No mint witness
+            call.push(0);
+            let Some(metadata) = tx.args.get("metadata").and_then(Value::as_str) else {
+                return Ok(call);
+            };
+            let mut set_metadata = vec![NFTS_PALLET, NFTS_SET_METADATA];
+            set_metadata.extend_from_slice(&collection);
+            set_metadata.extend_from_slice(&item);
+            scale_compact(metadata.len() as u64, &mut set_metadata);
+            set_metadata.extend_from_slice(metadata.as_bytes());
+
+            let mut batch = vec![UTILITY_PALLET, UTILITY_BATCH_ALL];
+            scale_compact(2, &mut batch);
+            batch.extend(call);
+            batch.extend(set_metadata);
+            Ok(batch)
+        }
+        "transfer" => {
+            call.push(NFTS_TRANSFER);
+            call.extend_from_slice(&collection);
+            call.extend_from_slice(&item);
+            call.push(0);
+            call.extend_from_slice(&account_arg("dest")?);
+            Ok(call)
+        }
+        method => Err(ChainError::Signer(format!("the keystore cannot encode Nfts.{}", method))),
+    }
+}
+
+fn borsh_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+fn strip_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn rlp_length(out: &mut Vec<u8>, len: usize, offset: u8) {
+    if len < 56 {
+        out.push(offset + len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let len_bytes = strip_zeros(&len_bytes);
+        out.push(offset + 55 + len_bytes.len() as u8);
+        out.extend_from_slice(len_bytes);
+    }
+}
+
+fn rlp_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        out.push(bytes[0]);
+    } else {
+        rlp_length(out, bytes.len(), 0x80);
+        out.extend_from_slice(bytes);
+    }
+}
+
+fn rlp_uint(out: &mut Vec<u8>, value: u128) {
+    rlp_bytes(out, strip_zeros(&value.to_be_bytes()));
+}
+
+fn rlp_list(items: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    rlp_length(&mut out, items.len(), 0xc0);
+    out.extend_from_slice(items);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// Cheap parameters so tests stay fast
+    fn test_kdf() -> Kdf {
+        Kdf::Scrypt { log_n: 4, r: 8, p: 1 }
+    }
+
+    #[test]
+    fn test_keys_round_trip_through_the_encrypted_file() {
+        let mut keystore = Keystore::create("correct horse", test_kdf()).unwrap();
+        let seed = [9u8; 32];
+        keystore.import("near", &SecretKey::from_seed(KeyType::Ed25519, &seed).unwrap()).unwrap();
+        keystore.generate("polkadot", KeyType::Sr25519).unwrap();
+        keystore.generate("evm", KeyType::Secp256k1).unwrap();
+        assert_eq!(keystore.generate("evm", KeyType::Secp256k1), Err(KeystoreError::DuplicateKey("evm".to_string())));
+
+        let json = keystore.to_json().unwrap();
+        assert!(!json.contains(&hex::encode(seed)));
+        assert!(matches!(Keystore::unlock(&json, "wrong"), Err(KeystoreError::WrongPassword)));
+
+        let reopened = Keystore::unlock(&json, "correct horse").unwrap();
+        assert_eq!(reopened.keys(), keystore.keys());
+
+        let message = b"interactive nft";
+        let near = reopened.key("near").unwrap();
+        let signature = ed25519_dalek::Signature::from_slice(&reopened.sign("near", message).unwrap()).unwrap();
+        let verifying = ed25519_dalek::VerifyingKey::from_bytes(&near.public_key.try_into().unwrap()).unwrap();
+        assert!(verifying.verify_strict(message, &signature).is_ok());
+
+        let polkadot = schnorrkel::PublicKey::from_bytes(&reopened.key("polkadot").unwrap().public_key).unwrap();
+        let signature = schnorrkel::Signature::from_bytes(&reopened.sign("polkadot", message).unwrap()).unwrap();
+        assert!(polkadot.verify(schnorrkel::signing_context(SR25519_CONTEXT).bytes(message), &signature).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_keys_fail_to_open() {
+        let mut keystore = Keystore::create("pw", Kdf::Argon2id { m_cost: 64, t_cost: 1, p_cost: 1 }).unwrap();
+        keystore.generate("a", KeyType::Ed25519).unwrap();
+        keystore.generate("b", KeyType::Ed25519).unwrap();
+
+        // Swapping sealed secrets between names breaks the associated data
+        let mut file: Value = serde_json::from_str(&keystore.to_json().unwrap()).unwrap();
+        let a = file["keys"]["a"].clone();
+        file["keys"]["a"] = file["keys"]["b"].clone();
+        file["keys"]["b"] = a;
+        let swapped = Keystore::unlock(&file.to_string(), "pw").unwrap();
+        assert!(matches!(swapped.sign("a", b"x"), Err(KeystoreError::Format(_))));
+
+        keystore.change_password("new").unwrap();
+        let json = keystore.to_json().unwrap();
+        assert!(matches!(Keystore::unlock(&json, "pw"), Err(KeystoreError::WrongPassword)));
+        assert!(Keystore::unlock(&json, "new").unwrap().sign("b", b"x").is_ok());
+    }
+
+    #[test]
+    fn test_kdf_parameters_are_capped() {
+        for kdf in [
+            Kdf::Scrypt { log_n: 63, r: 8, p: 1 },
+            Kdf::Scrypt { log_n: 21, r: 8, p: 1 },
+            Kdf::Scrypt { log_n: 4, r: 8, p: 1000 },
+            Kdf::Argon2id { m_cost: u32::MAX, t_cost: 1, p_cost: 1 },
+            Kdf::Argon2id { m_cost: 64, t_cost: 1_000_000, p_cost: 1 },
+        ] {
+            assert!(matches!(Keystore::create("pw", kdf), Err(KeystoreError::Kdf(_))));
+        }
+        assert!(Kdf::scrypt().check_limits().is_ok());
+        assert!(Kdf::default().check_limits().is_ok());
+
+        // A stored file asking for more is refused before any stretching
+        let keystore = Keystore::create("pw", test_kdf()).unwrap();
+        let mut file: Value = serde_json::from_str(&keystore.to_json().unwrap()).unwrap();
+        file["kdf"]["log_n"] = json!(60);
+        assert!(matches!(Keystore::unlock(&file.to_string(), "pw"), Err(KeystoreError::Kdf(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_replaces_a_stale_temp_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("nft-keystore-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("keys.json");
+        std::fs::write(path.with_extension("tmp"), "stale").unwrap();
+        std::fs::set_permissions(path.with_extension("tmp"), std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let keystore = Keystore::create("pw", test_kdf()).unwrap();
+        keystore.save(&path).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(Keystore::open(&path, "pw").is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_near_cli_keys_import() {
+        let secret = SecretKey::from_seed(KeyType::Ed25519, &[7u8; 32]).unwrap();
+        let text = format!("ed25519:{}", bs58::encode([[7u8; 32].to_vec(), secret.public_key()].concat()).into_string());
+        assert_eq!(SecretKey::from_near(&text).unwrap().public_key(), secret.public_key());
+        assert!(SecretKey::from_near("secp256k1:abc").is_err());
+        assert!(format!("{:?}", secret).contains("redacted"));
+    }
+
+    #[tokio::test]
+    async fn test_near_signer_encodes_a_borsh_function_call() {
+        let mut keystore = Keystore::create("pw", test_kdf()).unwrap();
+        let info = keystore.generate("artist", KeyType::Ed25519).unwrap();
+        let keystore = Arc::new(keystore);
+        assert!(matches!(
+            KeystoreSigner::new(keystore.clone(), "artist", ChainType::Polkadot),
+            Err(KeystoreError::WrongKeyType { .. })
+        ));
+
+        let signer = KeystoreSigner::new(keystore, "artist", ChainType::Near).unwrap();
+        assert_eq!(signer.account_id(), hex::encode(&info.public_key));
+        let signer = signer.with_account_id("artist.testnet");
+        assert!(signer.public_key().unwrap().starts_with("ed25519:"));
+
+        let tx = UnsignedTransaction {
+            signer_id: signer.account_id(),
+            receiver_id: "nft.compiling.testnet".to_string(),
+            method: "nft_transfer".to_string(),
+            args: json!({ "receiver_id": "bob.testnet", "token_id": "1" }),
+            deposit: 1,
+            context: TxContext::Near { nonce: Some(5), block_hash: bs58::encode([1u8; 32]).into_string(), gas: 30 },
+        };
+        let signed = signer.sign_transaction(&tx).await.unwrap();
+        let (body, signature) = signed.split_at(signed.len() - 65);
+        assert_eq!(&body[..4], &14u32.to_le_bytes());
+        assert_eq!(&body[4..18], b"artist.testnet");
+        assert_eq!(&body[body.len() - 16..], &1u128.to_le_bytes());
+        assert_eq!(signature[0], 0);
+
+        let verifying = ed25519_dalek::VerifyingKey::from_bytes(&info.public_key.try_into().unwrap()).unwrap();
+        let signature = ed25519_dalek::Signature::from_slice(&signature[1..]).unwrap();
+        assert!(verifying.verify_strict(&Sha256::digest(body), &signature).is_ok());
+
+        let missing_nonce = UnsignedTransaction {
+            context: TxContext::Near { nonce: None, block_hash: bs58::encode([1u8; 32]).into_string(), gas: 30 },
+            ..tx
+        };
+        assert!(signer.sign_transaction(&missing_nonce).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_solana_signer_signs_the_legacy_message() {
+        let mut keystore = Keystore::create("pw", test_kdf()).unwrap();
+        let info = keystore.generate("collector", KeyType::Ed25519).unwrap();
+        let signer = KeystoreSigner::new(Arc::new(keystore), "collector", ChainType::Solana).unwrap();
+        let blockhash = bs58::encode([4u8; 32]).into_string();
+        let mint = bs58::encode([5u8; 32]).into_string();
+
+        let tx = UnsignedTransaction {
+            signer_id: signer.account_id(),
+            receiver_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+            method: "transfer_checked".to_string(),
+            args: json!({ "mint": mint, "destination_owner": bs58::encode([6u8; 32]).into_string(), "amount": 1, "decimals": 0 }),
+            deposit: 0,
+            context: TxContext::Solana { recent_blockhash: blockhash.clone() },
+        };
+        let signed = signer.sign_transaction(&tx).await.unwrap();
+        assert_eq!(signed[0], 1);
+        let (signature, message) = signed[1..].split_at(64);
+
+        // Payer signs; mint and token program are read-only; two token accounts are writable
+        assert_eq!(&message[..3], &[1, 0, 2]);
+        assert_eq!(message[3], 5);
+        assert_eq!(&message[4..36], &info.public_key[..]);
+        assert_eq!(&message[4 + 5 * 32..4 + 6 * 32], &[4u8; 32]);
+        assert!(message.ends_with(&[10, SPL_TRANSFER_CHECKED, 1, 0, 0, 0, 0, 0, 0, 0, 0]));
+
+        let verifying = ed25519_dalek::VerifyingKey::from_bytes(&info.public_key.try_into().unwrap()).unwrap();
+        let signature = ed25519_dalek::Signature::from_slice(signature).unwrap();
+        assert!(verifying.verify_strict(message, &signature).is_ok());
+
+        let wrong_context = UnsignedTransaction { context: TxContext::Evm { chain_id: 1, nonce: 0, gas_price: 0, gas_limit: 0 }, ..tx };
+        assert!(signer.sign_transaction(&wrong_context).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_polkadot_signer_signs_the_extrinsic_payload() {
+        let mut keystore = Keystore::create("pw", test_kdf()).unwrap();
+        let info = keystore.generate("curator", KeyType::Sr25519).unwrap();
+        let signer = KeystoreSigner::new(Arc::new(keystore), "curator", ChainType::Polkadot).unwrap();
+        let public = schnorrkel::PublicKey::from_bytes(&info.public_key).unwrap();
+        let genesis = [7u8; 32];
+        let context = TxContext::Polkadot {
+            nonce: 9,
+            genesis_hash: format!("0x{}", hex::encode(genesis)),
+            block_hash: format!("0x{}", hex::encode([8u8; 32])),
+            spec_version: 1_003_000,
+            transaction_version: 15,
+        };
+
+        // A short transfer is signed as is; a mint with long metadata as its blake2-256 hash
+        let transfer = json!({ "collection": 3, "item": 1, "dest": format!("0x{}", hex::encode([2u8; 32])) });
+        let mint = json!({ "collection": 3, "item": 2, "mint_to": signer.account_id(), "metadata": "ipfs://".repeat(40) });
+        for (method, args) in [("transfer", transfer), ("mint", mint)] {
+            let tx = UnsignedTransaction {
+                signer_id: signer.account_id(),
+                receiver_id: "Nfts".to_string(),
+                method: method.to_string(),
+                args,
+                deposit: 0,
+                context: context.clone(),
+            };
+            let signed = signer.sign_transaction(&tx).await.unwrap();
+            let mut length = Vec::new();
+            scale_compact((signed.len() - 2) as u64, &mut length);
+            assert_eq!(&signed[..2], &length[..]);
+            let body = &signed[2..];
+            assert_eq!(&body[..2], &[EXTRINSIC_SIGNED_V4, 0]);
+            assert_eq!(&body[2..34], &info.public_key[..]);
+            assert_eq!(body[34], 1);
+            let signature = schnorrkel::Signature::from_bytes(&body[35..99]).unwrap();
+            let (extra, call) = body[99..].split_at(5);
+            assert_eq!(extra, &[0, 9 << 2, 0, 0, 0]);
+
+            let mut payload = call.to_vec();
+            payload.extend_from_slice(extra);
+            payload.extend_from_slice(&1_003_000u32.to_le_bytes());
+            payload.extend_from_slice(&15u32.to_le_bytes());
+            payload.extend_from_slice(&genesis);
+            payload.extend_from_slice(&genesis);
+            payload.push(0);
+            let message = if payload.len() > MAX_UNHASHED_PAYLOAD {
+                assert_eq!(method, "mint");
+                assert_eq!(&call[..2], &[UTILITY_PALLET, UTILITY_BATCH_ALL]);
+                Blake2b::<U32>::digest(&payload).to_vec()
+            } else {
+                assert_eq!(&call[..2], &[NFTS_PALLET, NFTS_TRANSFER]);
+                payload
+            };
+            assert!(public.verify(schnorrkel::signing_context(SR25519_CONTEXT).bytes(&message), &signature).is_ok());
+        }
+    }
+
+    /// Byte-string items of a flat RLP list
+    fn rlp_items(data: &[u8]) -> Vec<Vec<u8>> {
+        let header = |data: &[u8], offset: u8| -> (usize, usize) {
+            let short = data[0] - offset;
+            if short < 56 {
+                (1, short as usize)
+            } else {
+                let n = (short - 55) as usize;
+                (1 + n, data[1..=n].iter().fold(0, |len, b| len << 8 | *b as usize))
+            }
+        };
+        let (start, _) = header(data, 0xc0);
+        let mut rest = &data[start..];
+        let mut items = Vec::new();
+        while !rest.is_empty() {
+            if rest[0] < 0x80 {
+                items.push(vec![rest[0]]);
+                rest = &rest[1..];
+            } else {
+                let (skip, len) = header(rest, 0x80);
+                items.push(rest[skip..skip + len].to_vec());
+                rest = &rest[skip + len..];
+            }
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn test_evm_signer_recovers_to_its_address() {
+        let mut keystore = Keystore::create("pw", test_kdf()).unwrap();
+        keystore.generate("minter", KeyType::Secp256k1).unwrap();
+        let signer = KeystoreSigner::new(Arc::new(keystore), "minter", ChainType::Ethereum).unwrap();
+        let address = ChainAddress::parse(ChainType::Ethereum, &signer.account_id()).unwrap();
+
+        let tx = UnsignedTransaction {
+            signer_id: signer.account_id(),
+            receiver_id: "0x1234567890123456789012345678901234567890".to_string(),
+            method: "mint(address,string)".to_string(),
+            args: json!([signer.account_id(), "ipfs://cid"]),
+            deposit: 0,
+            context: TxContext::Evm { chain_id: 11_155_111, nonce: 3, gas_price: 2_000_000_000, gas_limit: 120_000 },
+        };
+        let signed = signer.sign_transaction(&tx).await.unwrap();
+        let items = rlp_items(&signed);
+        assert_eq!(items.len(), 9);
+        assert_eq!(items[3], hex::decode("1234567890123456789012345678901234567890").unwrap());
+        assert_eq!(items[5], abi_encode_call(ChainType::Ethereum, &tx.method, &tx.args).unwrap());
+
+        // Rebuild the EIP-155 payload and recover the sender from (v, r, s)
+        let mut unsigned = Vec::new();
+        for item in &items[..6] {
+            rlp_bytes(&mut unsigned, item);
+        }
+        rlp_uint(&mut unsigned, 11_155_111);
+        rlp_uint(&mut unsigned, 0);
+        rlp_uint(&mut unsigned, 0);
+        let prehash = Keccak256::digest(rlp_list(&unsigned));
+
+        let v = items[6].iter().fold(0u64, |v, b| v << 8 | *b as u64);
+        let recovery = k256::ecdsa::RecoveryId::from_byte((v - 35 - 2 * 11_155_111) as u8).unwrap();
+        let mut rs = [0u8; 64];
+        rs[32 - items[7].len()..32].copy_from_slice(&items[7]);
+        rs[64 - items[8].len()..].copy_from_slice(&items[8]);
+        let signature = k256::ecdsa::Signature::from_slice(&rs).unwrap();
+        let recovered = k256::ecdsa::VerifyingKey::recover_from_prehash(&prehash, &signature, recovery).unwrap();
+        let recovered = evm_address(&recovered.to_sec1_bytes()).unwrap();
+        assert_eq!(&recovered, address.as_bytes());
+    }
+}
@@ -6,7 +6,9 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
+use emotion_core::{EmotionTaxonomy, Forecaster, Holt, PadOctants, Prediction, Vad};
+use reputation_core::{AuditEntry, Reputation, ReputationConfig, ReputationError, ReputationEvent, Source};
 use uuid::Uuid;
 
 // WASM bindings
@@ -18,6 +20,24 @@ pub mod blockchain_integration;
 pub mod enhanced_webgpu_engine;
 pub mod ai_blockchain_integration;
 pub mod enhanced_soulbound;
+mod emotion;
+mod session_export;
+mod timeline;
+mod nft_metadata;
+mod chain_adapter;
+mod chain_rpc;
+mod address;
+mod fees;
+mod bridge;
+mod keystore;
+mod metadata_template;
+mod indexer;
+mod cpu_renderer;
+mod shader_controls;
+mod uniform_animation;
+mod shader_toolchain;
+mod frame_capture;
+mod adaptive_quality;
 
 // Re-export for convenience
 pub use webgpu_engine::*;
@@ -25,6 +45,26 @@ pub use blockchain_integration::*;
 pub use enhanced_webgpu_engine::*;
 pub use ai_blockchain_integration::*;
 pub use enhanced_soulbound::*;
+pub use emotion_core;
+pub use reputation_core;
+pub use session_core;
+pub use session_export::SESSION_SCHEMA_VERSION;
+pub use timeline::*;
+pub use nft_metadata::*;
+pub use chain_adapter::*;
+pub use chain_rpc::*;
+pub use address::*;
+pub use fees::*;
+pub use bridge::*;
+pub use keystore::*;
+pub use metadata_template::*;
+pub use indexer::*;
+pub use cpu_renderer::*;
+pub use shader_controls::*;
+pub use uniform_animation::*;
+pub use shader_toolchain::*;
+pub use frame_capture::*;
+pub use adaptive_quality::*;
 
 // WASM initialization
 #[wasm_bindgen(start)]
@@ -76,6 +116,32 @@ pub enum CreativeData {
     Reputation(ReputationData),
 }
 
+impl CreativeData {
+    /// When the data was captured, as recorded in the data itself
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            CreativeData::Audiovisual(d) => d.timestamp,
+            CreativeData::Emotional(d) => d.timestamp,
+            CreativeData::Shader(d) => d.timestamp,
+            CreativeData::Performance(d) => d.start_time,
+            CreativeData::Bridge(d) => d.bridge_timestamp,
+            CreativeData::Reputation(d) => d.last_updated,
+        }
+    }
+
+    /// Short name of the variant
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CreativeData::Audiovisual(_) => "audiovisual",
+            CreativeData::Emotional(_) => "emotional",
+            CreativeData::Shader(_) => "shader",
+            CreativeData::Performance(_) => "performance",
+            CreativeData::Bridge(_) => "bridge",
+            CreativeData::Reputation(_) => "reputation",
+        }
+    }
+}
+
 /// Audiovisual data from creative sessions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudiovisualData {
@@ -117,6 +183,16 @@ pub struct ShaderData {
     pub parameters: HashMap<String, f32>,
     pub seed: u64,
     pub iteration_count: u32,
+    /// Controls of the program that rendered it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub controls: Option<ControlSchema>,
+}
+
+impl ShaderData {
+    pub fn with_controls(mut self, controls: ControlSchema) -> Self {
+        self.controls = Some(controls);
+        self
+    }
 }
 
 /// Live performance metadata
@@ -131,6 +207,9 @@ pub struct PerformanceData {
     pub emotional_impact: f32, // How emotionally impactful the performance was
     pub creativity_boost: f32, // How much creativity was demonstrated
     pub audience_engagement: f32, // How engaged the audience was
+    /// Uniform choreography the performance played, to reproduce it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation: Option<UniformTimeline>,
 }
 
 /// Individual performance parameter
@@ -169,6 +248,29 @@ pub struct ReputationData {
     pub emotional_consistency: f32, // How consistent the creator's emotional expressions are
     pub creativity_score: f32, // Overall creativity rating
     pub community_rank: f32, // Community standing
+    /// Evidence and audit log behind `reputation_score`
+    #[serde(default)]
+    pub history: Reputation,
+}
+
+impl ReputationData {
+    /// Record a rated event and update the score from it
+    ///
+    /// Without a history yet, the current score seeds one, so scores kept
+    /// before the engine carry over.
+    pub fn record_event(&mut self, config: &ReputationConfig, event: ReputationEvent) -> Result<AuditEntry, ReputationError> {
+        if self.history.updated_at().is_none() {
+            let since = (self.last_updated.timestamp().max(0) as u64).min(event.timestamp);
+            self.history = Reputation::with_score(config, self.reputation_score, since);
+        }
+        let entry = self.history.record(config, event)?;
+        self.reputation_score = entry.score_after;
+        self.total_interactions += 1;
+        if let Some(at) = Utc.timestamp_opt(event.timestamp as i64, 0).single() {
+            self.last_updated = at;
+        }
+        Ok(entry)
+    }
 }
 
 /// Emotional metrics for reputation
@@ -202,7 +304,7 @@ impl EmotionalVector {
     /// Create new emotional vector with enhanced fields
     pub fn new(valence: f32, arousal: f32, dominance: f32) -> Self {
         let timestamp = Utc::now();
-        let category = Self::get_emotional_category(valence, arousal);
+        let category = Self::get_emotional_category(valence, arousal, dominance);
         
         Self {
             valence: valence.clamp(-1.0, 1.0),
@@ -218,13 +320,18 @@ impl EmotionalVector {
     }
     
     /// Get human-readable emotional category
-    pub fn get_emotional_category(valence: f32, arousal: f32) -> String {
-        match (valence, arousal) {
-            (v, a) if v > 0.5 && a > 0.5 => "Excited".to_string(),
-            (v, a) if v > 0.5 && a <= 0.5 => "Happy".to_string(),
-            (v, a) if v <= 0.5 && a > 0.5 => "Anxious".to_string(),
-            _ => "Calm".to_string(),
-        }
+    pub fn get_emotional_category(valence: f32, arousal: f32, dominance: f32) -> String {
+        Self::get_emotional_category_with(valence, arousal, dominance, &PadOctants)
+    }
+    
+    /// Get emotional category under a chosen taxonomy
+    pub fn get_emotional_category_with(
+        valence: f32,
+        arousal: f32,
+        dominance: f32,
+        taxonomy: &dyn EmotionTaxonomy,
+    ) -> String {
+        Vad::clamped(valence, arousal, dominance).classify(taxonomy).label.to_string()
     }
     
     /// Add point to emotional trajectory
@@ -238,46 +345,38 @@ impl EmotionalVector {
     
     /// Calculate emotional complexity based on trajectory
     pub fn calculate_complexity(&mut self) {
-        if self.emotional_trajectory.len() < 2 {
-            self.emotional_complexity = 0.0;
-            return;
-        }
-        
-        let mut total_distance = 0.0;
-        for i in 1..self.emotional_trajectory.len() {
-            let prev = &self.emotional_trajectory[i-1];
-            let curr = &self.emotional_trajectory[i];
-            let distance = ((curr.valence - prev.valence).powi(2) + 
-                           (curr.arousal - prev.arousal).powi(2)).sqrt();
-            total_distance += distance;
+        // Trajectory points carry no dominance, so it adds no spread
+        self.emotional_complexity = emotion_core::complexity(
+            self.emotional_trajectory
+                .iter()
+                .map(|p| Vad::clamped(p.valence, p.arousal, self.dominance)),
+        );
+    }
+    
+    /// Forecast the trajectory `steps_ahead` points past its end
+    ///
+    /// Trajectory points carry no dominance, so the current one stands in.
+    pub fn forecast_emotion(&self, forecaster: &mut dyn Forecaster, steps_ahead: usize) -> Option<Prediction> {
+        forecaster.reset();
+        for point in &self.emotional_trajectory {
+            forecaster.observe(Vad::clamped(point.valence, point.arousal, self.dominance));
         }
-        
-        // Normalize by number of points
-        self.emotional_complexity = (total_distance / self.emotional_trajectory.len() as f32).clamp(0.0, 1.0);
+        forecaster.predict(steps_ahead)
     }
     
     /// Predict next emotional state
     pub fn predict_next_emotion(&self) -> Option<EmotionalVector> {
-        if self.emotional_trajectory.len() < 3 {
-            return None;
-        }
-        
-        let len = self.emotional_trajectory.len();
-        let latest = &self.emotional_trajectory[len - 1];
-        let previous = &self.emotional_trajectory[len - 2];
-        let older = &self.emotional_trajectory[len - 3];
-        
-        // Simple linear extrapolation
-        let valence_delta = (latest.valence - previous.valence) * 0.7 + (previous.valence - older.valence) * 0.3;
-        let arousal_delta = (latest.arousal - previous.arousal) * 0.7 + (previous.arousal - older.arousal) * 0.3;
+        let prediction = self.forecast_emotion(&mut Holt::default(), 1)?;
+        let mean = prediction.mean;
         
         Some(EmotionalVector {
-            valence: (latest.valence + valence_delta).clamp(-1.0, 1.0),
-            arousal: (latest.arousal + arousal_delta).clamp(0.0, 1.0),
-            dominance: self.dominance,
-            confidence: (self.confidence - 0.1).max(0.0), // Confidence decreases with prediction
+            valence: mean.valence,
+            arousal: mean.arousal,
+            dominance: mean.dominance,
+            // Never more certain than the reading the forecast starts from
+            confidence: self.confidence.min(prediction.confidence()),
             timestamp: Utc::now(),
-            emotional_category: EmotionalVector::get_emotional_category(latest.valence + valence_delta, latest.arousal + arousal_delta),
+            emotional_category: mean.category().to_string(),
             emotional_trajectory: self.emotional_trajectory.clone(),
             predicted_emotion: None, // Would need recursive handling in a real implementation
             emotional_complexity: self.emotional_complexity,
@@ -290,12 +389,15 @@ impl EmotionalVector {
 pub struct CreativeSession {
     session_id: Uuid,
     start_time: DateTime<Utc>,
-    data_points: Vec<CreativeData>,
+    data_points: Vec<TimedData>,
     metadata: HashMap<String, serde_json::Value>,
     // Add cross-chain bridge support
     pub cross_chain_info: Option<BridgeData>,
     // Add reputation tracking
     pub creator_reputation: Option<ReputationData>,
+    /// Scoring rules applied to `creator_reputation`
+    #[serde(default)]
+    reputation_config: ReputationConfig,
     // Add emotional computing
     pub emotional_profile: Option<EmotionalVector>,
     // Enhanced fields
@@ -314,6 +416,7 @@ impl CreativeSession {
             metadata: HashMap::new(),
             cross_chain_info: None,
             creator_reputation: None,
+            reputation_config: ReputationConfig::default(),
             emotional_profile: None,
             emotional_complexity: 0.0,
             creativity_index: 0.0,
@@ -321,9 +424,17 @@ impl CreativeSession {
         }
     }
 
-    /// Add creative data to the session
+    /// Add creative data to the session, stamped with the time since start
     pub fn add_data(&mut self, data: CreativeData) {
-        self.data_points.push(data);
+        let offset_ms = self.offset_of(Utc::now());
+        self.add_data_at(offset_ms, data);
+    }
+
+    /// Add creative data at a session-relative offset, after any other
+    /// data at the same offset
+    pub fn add_data_at(&mut self, offset_ms: u64, data: CreativeData) {
+        let at = self.data_points.partition_point(|d| d.offset_ms <= offset_ms);
+        self.data_points.insert(at, TimedData { offset_ms, data });
     }
 
     /// Generate metadata for blockchain tokenization
@@ -333,14 +444,7 @@ impl CreativeSession {
             "start_time": self.start_time.to_rfc3339(),
             "data_points_count": self.data_points.len(),
             "duration_ms": Utc::now().signed_duration_since(self.start_time).num_milliseconds(),
-            "data_types": self.data_points.iter().map(|d| match d {
-                CreativeData::Audiovisual(_) => "audiovisual",
-                CreativeData::Emotional(_) => "emotional",
-                CreativeData::Shader(_) => "shader",
-                CreativeData::Performance(_) => "performance",
-                CreativeData::Bridge(_) => "bridge",
-                CreativeData::Reputation(_) => "reputation",
-            }).collect::<Vec<_>>(),
+            "data_types": self.data_points.iter().map(|d| d.data.kind()).collect::<Vec<_>>(),
             "metadata": self.metadata,
             // Add cross-chain info if available
             "cross_chain_info": self.cross_chain_info,
@@ -363,22 +467,6 @@ impl CreativeSession {
         Ok(metadata)
     }
 
-    /// Export session data for IPFS/Filecoin storage
-    pub fn export_for_storage(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        let export_data = serde_json::json!({
-            "session_id": self.session_id.to_string(),
-            "start_time": self.start_time.to_rfc3339(),
-            "data_points_count": self.data_points.len(),
-            "export_timestamp": Utc::now().to_rfc3339(),
-            "cross_chain_info": self.cross_chain_info,
-            "emotional_profile": self.emotional_profile,
-            "emotional_complexity": self.emotional_complexity,
-            "creativity_index": self.creativity_index,
-        });
-
-        Ok(serde_json::to_vec(&export_data)?)
-    }
-    
     /// Set cross-chain bridge information
     pub fn set_cross_chain_info(&mut self, info: BridgeData) {
         self.cross_chain_info = Some(info);
@@ -389,16 +477,33 @@ impl CreativeSession {
         self.creator_reputation = Some(reputation);
     }
     
-    /// Update reputation based on interaction quality
-    pub fn update_reputation(&mut self, interaction_quality: f32) {
-        if let Some(ref mut reputation) = self.creator_reputation {
-            if interaction_quality > 0.5 {
-                reputation.reputation_score = (reputation.reputation_score + 0.1).min(1.0);
-            } else {
-                reputation.reputation_score = (reputation.reputation_score - 0.05).max(0.0);
-            }
-            reputation.total_interactions += 1;
-            reputation.last_updated = Utc::now();
+    /// Scoring rules for the creator's reputation
+    pub fn reputation_config(&self) -> &ReputationConfig {
+        &self.reputation_config
+    }
+    
+    /// Replace the scoring rules used for later reputation events
+    pub fn set_reputation_config(&mut self, config: ReputationConfig) -> Result<(), ReputationError> {
+        config.validate()?;
+        self.reputation_config = config;
+        Ok(())
+    }
+    
+    /// Record an interaction of the given quality (0.0-1.0) against the
+    /// creator's reputation
+    pub fn update_reputation(&mut self, interaction_quality: f32) -> Result<(), ReputationError> {
+        let now = Utc::now().timestamp().max(0) as u64;
+        let event = ReputationEvent::new(Source::Interaction, interaction_quality, now);
+        self.record_reputation_event(event).map(|_| ())
+    }
+    
+    /// Record a rated event against the creator's reputation under the
+    /// session's scoring rules; `None` when the session has no creator
+    /// reputation
+    pub fn record_reputation_event(&mut self, event: ReputationEvent) -> Result<Option<AuditEntry>, ReputationError> {
+        match &mut self.creator_reputation {
+            Some(reputation) => reputation.record_event(&self.reputation_config, event).map(Some),
+            None => Ok(None),
         }
     }
     
@@ -409,16 +514,14 @@ impl CreativeSession {
     
     /// Get emotional category based on VAD values
     pub fn get_emotional_category(&self) -> Option<String> {
-        if let Some(profile) = &self.emotional_profile {
-            Some(match (profile.valence, profile.arousal) {
-                (v, a) if v > 0.5 && a > 0.5 => "Excited".to_string(),
-                (v, a) if v > 0.5 && a <= 0.5 => "Happy".to_string(),
-                (v, a) if v <= 0.5 && a > 0.5 => "Anxious".to_string(),
-                _ => "Calm".to_string(),
-            })
-        } else {
-            None
-        }
+        self.get_emotional_category_with(&PadOctants)
+    }
+    
+    /// Get emotional category under a chosen taxonomy
+    pub fn get_emotional_category_with(&self, taxonomy: &dyn EmotionTaxonomy) -> Option<String> {
+        self.emotional_profile
+            .as_ref()
+            .map(|p| EmotionalVector::get_emotional_category_with(p.valence, p.arousal, p.dominance, taxonomy))
     }
     
     /// Update emotional complexity based on profile
@@ -434,7 +537,7 @@ impl CreativeSession {
         let mut emotional_count = 0;
         
         for data in &self.data_points {
-            match data {
+            match data.data {
                 CreativeData::Shader(_) => shader_count += 1,
                 CreativeData::Emotional(_) => emotional_count += 1,
                 _ => {}
@@ -460,7 +563,7 @@ pub fn generate_emotional_data(
     raw_vector: Vec<f32>
 ) -> EmotionalData {
     let timestamp = Utc::now();
-    let category = EmotionalData::get_emotional_category(valence, arousal);
+    let category = EmotionalData::get_emotional_category(valence, arousal, dominance);
     
     EmotionalData {
         timestamp,
@@ -478,13 +581,18 @@ pub fn generate_emotional_data(
 
 impl EmotionalData {
     /// Get human-readable emotional category
-    pub fn get_emotional_category(valence: f32, arousal: f32) -> String {
-        match (valence, arousal) {
-            (v, a) if v > 0.5 && a > 0.5 => "Excited".to_string(),
-            (v, a) if v > 0.5 && a <= 0.5 => "Happy".to_string(),
-            (v, a) if v <= 0.5 && a > 0.5 => "Anxious".to_string(),
-            _ => "Calm".to_string(),
-        }
+    pub fn get_emotional_category(valence: f32, arousal: f32, dominance: f32) -> String {
+        Self::get_emotional_category_with(valence, arousal, dominance, &PadOctants)
+    }
+    
+    /// Get emotional category under a chosen taxonomy
+    pub fn get_emotional_category_with(
+        valence: f32,
+        arousal: f32,
+        dominance: f32,
+        taxonomy: &dyn EmotionTaxonomy,
+    ) -> String {
+        Vad::clamped(valence, arousal, dominance).classify(taxonomy).label.to_string()
     }
     
     /// Add point to emotional trajectory
@@ -498,22 +606,12 @@ impl EmotionalData {
     
     /// Calculate emotional complexity based on trajectory
     pub fn calculate_complexity(&mut self) {
-        if self.emotional_trajectory.len() < 2 {
-            self.emotional_complexity = 0.0;
-            return;
-        }
-        
-        let mut total_distance = 0.0;
-        for i in 1..self.emotional_trajectory.len() {
-            let prev = &self.emotional_trajectory[i-1];
-            let curr = &self.emotional_trajectory[i];
-            let distance = ((curr.valence - prev.valence).powi(2) + 
-                           (curr.arousal - prev.arousal).powi(2)).sqrt();
-            total_distance += distance;
-        }
-        
-        // Normalize by number of points
-        self.emotional_complexity = (total_distance / self.emotional_trajectory.len() as f32).clamp(0.0, 1.0);
+        // Trajectory points carry no dominance, so it adds no spread
+        self.emotional_complexity = emotion_core::complexity(
+            self.emotional_trajectory
+                .iter()
+                .map(|p| Vad::clamped(p.valence, p.arousal, self.dominance)),
+        );
     }
 }
 
@@ -530,6 +628,7 @@ pub fn generate_shader_data(
         parameters,
         seed,
         iteration_count,
+        controls: None,
     }
 }
 
@@ -575,6 +674,7 @@ pub fn create_reputation_data(creator_id: &str) -> ReputationData {
         emotional_consistency: 0.0,
         creativity_score: 0.0,
         community_rank: 0.0,
+        history: Reputation::default(),
     }
 }
 
@@ -632,6 +732,33 @@ mod tests {
         assert_eq!(session.creator_reputation.unwrap().creator_id, "creator1");
     }
     
+    #[test]
+    fn test_reputation_events() {
+        let mut session = CreativeSession::new();
+        assert_eq!(session.update_reputation(0.9), Ok(()));
+        
+        let mut reputation = create_reputation_data("creator1");
+        reputation.reputation_score = 0.7;
+        session.set_creator_reputation(reputation);
+        
+        let config = ReputationConfig::elo(32.0);
+        assert!(session.set_reputation_config(ReputationConfig::elo(-1.0)).is_err());
+        session.set_reputation_config(config).unwrap();
+        let now = Utc::now().timestamp() as u64;
+        let entry = session
+            .record_reputation_event(ReputationEvent::new(Source::Sale, 1.0, now))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.score_before, 0.7);
+        assert!(session.update_reputation(1.5).is_err());
+        
+        let reputation = session.creator_reputation.unwrap();
+        assert_eq!(reputation.total_interactions, 1);
+        assert_eq!(reputation.reputation_score, entry.score_after);
+        assert!(matches!(reputation.history.standing(), reputation_core::Standing::Elo { .. }));
+        assert_eq!(reputation.history.verify(&config), Ok(()));
+    }
+    
     #[test]
     fn test_emotional_profile() {
         let mut session = CreativeSession::new();
@@ -640,6 +767,14 @@ mod tests {
         
         assert!(session.emotional_profile.is_some());
         assert_eq!(session.get_emotional_category().unwrap(), "Excited");
+        assert_eq!(
+            session.get_emotional_category_with(&emotion_core::Circumplex).unwrap(),
+            "Excited"
+        );
+        assert_eq!(
+            session.get_emotional_category_with(&emotion_core::Plutchik).unwrap(),
+            "Joy"
+        );
     }
     
     #[test]
@@ -657,5 +792,11 @@ mod tests {
         
         let prediction = emotional_vector.predict_next_emotion();
         assert!(prediction.is_some());
+        
+        let forecast = emotional_vector
+            .forecast_emotion(&mut emotion_core::Kalman::default(), 5)
+            .unwrap();
+        assert_eq!(forecast.mean.dominance, emotional_vector.dominance);
+        assert!(emotional_vector.forecast_emotion(&mut emotion_core::AutoRegressive::new(2), 1).is_none());
     }
 }
\ No newline at end of file
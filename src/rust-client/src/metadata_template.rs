@@ -0,0 +1,365 @@
+//! Metadata templates checked against versioned JSON Schemas.
+//!
+//! A [`MetadataGenerator`] renders token metadata from a Jinja-style template
+//! with variables, `{% if %}` conditionals and `{% for %}` loops. Templates
+//! see the base metadata, the generator's parameters, the current emotion,
+//! its trajectory and the creator's reputation. Every `{{ value }}` is
+//! written as JSON, so `{{ base.name }}` comes out quoted and escaped.
+//!
+//! The rendered document must satisfy the schema registered in
+//! [`MetadataSchemas`] under the generator's version. Projects register their
+//! own layouts there instead of changing code.
+
+use minijinja::{AutoEscape, Environment};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Version of the built-in metadata layout
+pub const METADATA_SCHEMA_V1: &str = "1.0";
+
+/// Template for [`METADATA_SCHEMA_V1`]: the base metadata with the emotion,
+/// trajectory and reputation added when known
+pub const DEFAULT_METADATA_TEMPLATE: &str = r#"{
+{%- for key, value in base|items if key not in ["emotional_context", "emotional_trajectory", "creator_reputation", "metadata_version"] %}
+  {{ key }}: {{ value }},
+{%- endfor %}
+{%- if emotion %}
+  "emotional_context": {
+    "valence": {{ emotion.valence }},
+    "arousal": {{ emotion.arousal }},
+    "dominance": {{ emotion.dominance }},
+    "category": {{ emotion.category }}
+  },
+{%- endif %}
+{%- if trajectory %}
+  "emotional_trajectory": [
+  {%- for point in trajectory %}
+    {"valence": {{ point.valence }}, "arousal": {{ point.arousal }}, "dominance": {{ point.dominance }}, "timestamp": {{ point.timestamp }}}{% if not loop.last %},{% endif %}
+  {%- endfor %}
+  ],
+{%- endif %}
+{%- if reputation %}
+  "creator_reputation": {"score": {{ reputation.score }}, "interactions": {{ reputation.interactions }}},
+{%- endif %}
+  "metadata_version": {{ version }}
+}"#;
+
+/// Errors from rendering or validating metadata
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    /// The template does not parse
+    Syntax(String),
+    Render(String),
+    /// The rendered text is not JSON
+    NotJson(String),
+    /// No schema is registered for the version
+    UnknownVersion(String),
+    InvalidSchema(String),
+    /// The rendered metadata breaks its schema, one message per violation
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Syntax(msg) => write!(f, "template syntax error: {}", msg),
+            TemplateError::Render(msg) => write!(f, "template render error: {}", msg),
+            TemplateError::NotJson(msg) => write!(f, "rendered metadata is not JSON: {}", msg),
+            TemplateError::UnknownVersion(version) => write!(f, "no metadata schema for version {}", version),
+            TemplateError::InvalidSchema(msg) => write!(f, "invalid metadata schema: {}", msg),
+            TemplateError::Invalid(errors) => write!(f, "metadata does not match its schema: {}", errors.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Emotion as templates see it, the current one or a trajectory point
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemplateEmotion {
+    pub valence: f64,
+    pub arousal: f64,
+    pub dominance: f64,
+    pub confidence: f64,
+    pub timestamp: u64,
+    pub category: String,
+}
+
+impl TemplateEmotion {
+    pub fn new(vad: [f32; 3], confidence: f32, timestamp: u64, category: &str) -> Self {
+        TemplateEmotion {
+            valence: short_f64(vad[0]),
+            arousal: short_f64(vad[1]),
+            dominance: short_f64(vad[2]),
+            confidence: short_f64(confidence),
+            timestamp,
+            category: category.to_string(),
+        }
+    }
+}
+
+/// Creator reputation as templates see it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemplateReputation {
+    pub score: f64,
+    pub interactions: u64,
+}
+
+impl TemplateReputation {
+    pub fn new(score: f32, interactions: u64) -> Self {
+        TemplateReputation { score: short_f64(score), interactions }
+    }
+}
+
+/// Mint-time values a template renders from, on top of the generator's
+/// `params` and `version`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetadataContext {
+    /// Metadata the caller started from, usually an object
+    pub base: Value,
+    pub emotion: Option<TemplateEmotion>,
+    /// Earlier emotional states, oldest first
+    pub trajectory: Vec<TemplateEmotion>,
+    pub reputation: Option<TemplateReputation>,
+}
+
+/// f32 widened through its shortest decimal form, so 0.8 stays 0.8 in JSON
+fn short_f64(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or_default()
+}
+
+/// Template and parameters for generated metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataGenerator {
+    pub template: String,
+    pub parameters: HashMap<String, String>,
+    /// Schema version the output is checked against
+    pub version: String,
+}
+
+impl MetadataGenerator {
+    /// Generator for `template`, which must parse; an empty template means
+    /// [`DEFAULT_METADATA_TEMPLATE`]
+    pub fn new(template: &str, version: &str) -> Result<Self, TemplateError> {
+        let template = if template.trim().is_empty() { DEFAULT_METADATA_TEMPLATE } else { template };
+        environment().template_from_str(template).map_err(|e| TemplateError::Syntax(describe(&e)))?;
+        Ok(MetadataGenerator { template: template.to_string(), parameters: HashMap::new(), version: version.to_string() })
+    }
+
+    pub fn with_parameter(mut self, key: &str, value: &str) -> Self {
+        self.parameters.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Rendered template text, before it is parsed or validated
+    pub fn render(&self, context: &MetadataContext) -> Result<String, TemplateError> {
+        let env = environment();
+        let template = env.template_from_str(&self.template).map_err(|e| TemplateError::Syntax(describe(&e)))?;
+        template
+            .render(minijinja::context! {
+                base => &context.base,
+                params => &self.parameters,
+                emotion => &context.emotion,
+                trajectory => &context.trajectory,
+                reputation => &context.reputation,
+                version => &self.version,
+            })
+            .map_err(|e| TemplateError::Render(describe(&e)))
+    }
+
+    /// Render, parse and validate against the schema for this generator's version
+    pub fn generate(&self, context: &MetadataContext, schemas: &MetadataSchemas) -> Result<Value, TemplateError> {
+        let rendered = self.render(context)?;
+        let metadata: Value = serde_json::from_str(&rendered).map_err(|e| TemplateError::NotJson(e.to_string()))?;
+        schemas.validate(&self.version, &metadata)?;
+        Ok(metadata)
+    }
+}
+
+fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_auto_escape_callback(|_| AutoEscape::Json);
+    env
+}
+
+/// minijinja's message with the line it points at
+fn describe(error: &minijinja::Error) -> String {
+    match error.line() {
+        Some(line) => format!("{} (line {})", error, line),
+        None => error.to_string(),
+    }
+}
+
+/// JSON Schemas for generated metadata, by version
+pub struct MetadataSchemas {
+    schemas: BTreeMap<String, (Value, jsonschema::Validator)>,
+}
+
+impl Default for MetadataSchemas {
+    /// The built-in [`METADATA_SCHEMA_V1`] layout
+    fn default() -> Self {
+        let mut schemas = MetadataSchemas::empty();
+        schemas.register(METADATA_SCHEMA_V1, metadata_schema_v1()).expect("built-in schema compiles");
+        schemas
+    }
+}
+
+impl MetadataSchemas {
+    /// No versions at all, not even the built-in one
+    pub fn empty() -> Self {
+        MetadataSchemas { schemas: BTreeMap::new() }
+    }
+
+    /// Add or replace the schema for `version`
+    pub fn register(&mut self, version: &str, schema: Value) -> Result<(), TemplateError> {
+        let validator = jsonschema::validator_for(&schema).map_err(|e| TemplateError::InvalidSchema(e.to_string()))?;
+        self.schemas.insert(version.to_string(), (schema, validator));
+        Ok(())
+    }
+
+    pub fn schema(&self, version: &str) -> Option<&Value> {
+        self.schemas.get(version).map(|(schema, _)| schema)
+    }
+
+    pub fn versions(&self) -> Vec<&str> {
+        self.schemas.keys().map(String::as_str).collect()
+    }
+
+    /// Every violation of the version's schema, each prefixed with where it is
+    pub fn validate(&self, version: &str, metadata: &Value) -> Result<(), TemplateError> {
+        let (_, validator) = self.schemas.get(version).ok_or_else(|| TemplateError::UnknownVersion(version.to_string()))?;
+        let errors: Vec<String> = validator
+            .iter_errors(metadata)
+            .map(|e| match e.instance_path().to_string() {
+                path if path.is_empty() => e.to_string(),
+                path => format!("{}: {}", path, e),
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(TemplateError::Invalid(errors))
+        }
+    }
+}
+
+/// Schema for the output of [`DEFAULT_METADATA_TEMPLATE`]
+fn metadata_schema_v1() -> Value {
+    let unit = |min: f64| json!({ "type": "number", "minimum": min, "maximum": 1.0 });
+    let vad = json!({
+        "type": "object",
+        "required": ["valence", "arousal", "dominance"],
+        "properties": { "valence": unit(-1.0), "arousal": unit(0.0), "dominance": unit(0.0) },
+    });
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "required": ["name", "metadata_version"],
+        "properties": {
+            "name": { "type": "string", "minLength": 1 },
+            "description": { "type": "string" },
+            "image": { "type": "string" },
+            "media": { "type": "string" },
+            "attributes": {
+                "type": "array",
+                "items": { "type": "object", "required": ["trait_type", "value"] },
+            },
+            "emotional_context": {
+                "allOf": [vad, { "required": ["category"], "properties": { "category": { "type": "string" } } }],
+            },
+            "emotional_trajectory": { "type": "array", "items": vad },
+            "creator_reputation": {
+                "type": "object",
+                "required": ["score", "interactions"],
+                "properties": {
+                    "score": unit(0.0),
+                    "interactions": { "type": "integer", "minimum": 0 },
+                },
+            },
+            "metadata_version": { "type": "string" },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> MetadataContext {
+        MetadataContext {
+            base: json!({ "name": "Calm \"Waves\"", "description": "Generative piece", "media": "ipfs://cid" }),
+            emotion: Some(TemplateEmotion::new([0.6, 0.3, 0.5], 0.9, 30, "calm")),
+            trajectory: vec![
+                TemplateEmotion::new([0.1, 0.8, 0.4], 1.0, 10, "tense"),
+                TemplateEmotion::new([0.6, 0.3, 0.5], 0.9, 30, "calm"),
+            ],
+            reputation: Some(TemplateReputation::new(0.75, 12)),
+        }
+    }
+
+    #[test]
+    fn test_default_template_renders_valid_v1_metadata() {
+        let generator = MetadataGenerator::new("", METADATA_SCHEMA_V1).unwrap();
+        let metadata = generator.generate(&context(), &MetadataSchemas::default()).unwrap();
+
+        assert_eq!(metadata["name"], "Calm \"Waves\"");
+        assert_eq!(metadata["emotional_context"]["valence"], 0.6);
+        assert_eq!(metadata["emotional_context"]["category"], "calm");
+        assert_eq!(metadata["emotional_trajectory"].as_array().unwrap().len(), 2);
+        assert_eq!(metadata["emotional_trajectory"][0]["timestamp"], 10);
+        assert_eq!(metadata["creator_reputation"]["interactions"], 12);
+        assert_eq!(metadata["metadata_version"], "1.0");
+
+        // Without an emotion or reputation the optional sections disappear
+        let bare = MetadataContext { base: json!({ "name": "Bare" }), ..MetadataContext::default() };
+        let metadata = generator.generate(&bare, &MetadataSchemas::default()).unwrap();
+        assert_eq!(metadata, json!({ "name": "Bare", "metadata_version": "1.0" }));
+    }
+
+    #[test]
+    fn test_project_layouts_use_params_loops_and_their_own_schema() {
+        let template = r#"{
+            "title": {{ params.prefix ~ " " ~ base.name }},
+            "mood": {% if emotion.valence > 0.5 %}"bright"{% else %}"dark"{% endif %},
+            "path": [{% for p in trajectory %}{{ p.category }}{% if not loop.last %}, {% endif %}{% endfor %}]
+        }"#;
+        let generator = MetadataGenerator::new(template, "gallery-2").unwrap().with_parameter("prefix", "Gallery:");
+
+        let mut schemas = MetadataSchemas::default();
+        assert_eq!(
+            generator.generate(&context(), &schemas),
+            Err(TemplateError::UnknownVersion("gallery-2".to_string()))
+        );
+        schemas
+            .register(
+                "gallery-2",
+                json!({ "type": "object", "required": ["title", "mood"], "properties": { "path": { "maxItems": 1 } } }),
+            )
+            .unwrap();
+        assert_eq!(schemas.versions(), vec![METADATA_SCHEMA_V1, "gallery-2"]);
+
+        let rendered: Value = serde_json::from_str(&generator.render(&context()).unwrap()).unwrap();
+        assert_eq!(rendered, json!({ "title": "Gallery: Calm \"Waves\"", "mood": "bright", "path": ["tense", "calm"] }));
+        match generator.generate(&context(), &schemas) {
+            Err(TemplateError::Invalid(errors)) => assert!(errors[0].starts_with("/path:"), "{:?}", errors),
+            other => panic!("expected a schema violation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_broken_templates_and_schemas_are_reported() {
+        assert!(matches!(MetadataGenerator::new("{% if %}", "1.0"), Err(TemplateError::Syntax(_))));
+
+        let generator = MetadataGenerator::new("{ \"name\": {{ base.name }}", METADATA_SCHEMA_V1).unwrap();
+        assert!(matches!(generator.generate(&context(), &MetadataSchemas::default()), Err(TemplateError::NotJson(_))));
+
+        let missing_name = MetadataGenerator::new("", METADATA_SCHEMA_V1).unwrap();
+        let base = MetadataContext { base: json!({ "description": "no name" }), ..MetadataContext::default() };
+        assert!(matches!(missing_name.generate(&base, &MetadataSchemas::default()), Err(TemplateError::Invalid(_))));
+
+        let mut schemas = MetadataSchemas::empty();
+        assert!(matches!(schemas.register("bad", json!({ "type": 5 })), Err(TemplateError::InvalidSchema(_))));
+    }
+}
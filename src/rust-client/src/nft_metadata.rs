@@ -0,0 +1,608 @@
+//! Marketplace metadata for a `CreativeSession`
+//!
+//! A session maps onto four standards:
+//!
+//! - [`Nep177`]: NEAR `TokenMetadata`
+//! - [`Erc721`]: ERC-721 metadata JSON as read by OpenSea
+//! - [`Metaplex`]: Metaplex Token Metadata JSON on Solana
+//! - [`PalletNfts`]: pallet-nfts item metadata and attributes, which RMRK
+//!   reads as well
+//!
+//! Every exporter builds the same reference JSON from the mint details and
+//! the session's attributes, with keys sorted, and embeds its SHA-256 hash.
+//! Minting one session on several chains therefore points every token at a
+//! single reference document.
+
+use crate::session_export::canonical_json;
+use crate::CreativeSession;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+
+/// Longest Metaplex `name`, in bytes
+pub const METAPLEX_MAX_NAME_LEN: usize = 32;
+/// Longest Metaplex `symbol`, in bytes
+pub const METAPLEX_MAX_SYMBOL_LEN: usize = 10;
+/// Longest Metaplex on-chain `uri`, in bytes
+pub const METAPLEX_MAX_URI_LEN: usize = 200;
+/// Most creators a Metaplex token can list
+pub const METAPLEX_MAX_CREATORS: usize = 5;
+/// pallet-nfts `KeyLimit` on Asset Hub
+pub const PALLET_NFTS_KEY_LIMIT: usize = 64;
+/// pallet-nfts `ValueLimit` on Asset Hub
+pub const PALLET_NFTS_VALUE_LIMIT: usize = 256;
+/// pallet-nfts `StringLimit` on Asset Hub, which bounds item metadata
+pub const PALLET_NFTS_STRING_LIMIT: usize = 256;
+
+/// Creator and their share of royalties, in percent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreatorShare {
+    pub address: String,
+    pub share: u8,
+}
+
+/// What a mint needs beyond the session itself
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MintDetails {
+    pub name: String,
+    pub description: String,
+    /// Media URI, preferably `ipfs://`
+    pub media: String,
+    /// SHA-256 of the media file, when known
+    pub media_hash: Option<[u8; 32]>,
+    /// Where the reference JSON is stored
+    pub reference: Option<String>,
+    pub external_url: Option<String>,
+    /// Collection symbol, used by Metaplex
+    pub symbol: String,
+    /// Royalty in basis points, used by Metaplex
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CreatorShare>,
+}
+
+/// A trait of the session, shared by every standard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionAttribute {
+    pub trait_type: String,
+    pub value: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_type: Option<String>,
+}
+
+impl SessionAttribute {
+    fn new(trait_type: &str, value: Value) -> Self {
+        Self {
+            trait_type: trait_type.to_string(),
+            value,
+            display_type: None,
+        }
+    }
+
+    fn number(trait_type: &str, value: f32) -> Self {
+        // Four decimals, so f32 noise never reaches a marketplace
+        let value = (value as f64 * 10_000.0).round() / 10_000.0;
+        Self {
+            display_type: Some("number".to_string()),
+            ..Self::new(trait_type, json!(value))
+        }
+    }
+
+    fn count(trait_type: &str, value: u64) -> Self {
+        Self {
+            display_type: Some("number".to_string()),
+            ..Self::new(trait_type, json!(value))
+        }
+    }
+}
+
+/// Metadata for one standard together with the reference it points at
+#[derive(Debug, Clone)]
+pub struct MetadataExport<T> {
+    pub metadata: T,
+    /// Canonical reference JSON, to be stored at `MintDetails::reference`
+    pub reference_json: Vec<u8>,
+    /// SHA-256 of `reference_json`
+    pub reference_hash: [u8; 32],
+}
+
+/// Maps a session onto one NFT metadata standard
+pub trait MetadataExporter {
+    type Metadata: Serialize;
+
+    /// Short name of the standard
+    fn standard(&self) -> &'static str;
+
+    /// Check the mint details carry everything the standard requires
+    fn validate(&self, details: &MintDetails) -> Result<(), Box<dyn Error>>;
+
+    /// Build the metadata from validated details
+    fn build(
+        &self,
+        session: &CreativeSession,
+        details: &MintDetails,
+        attributes: &[SessionAttribute],
+        reference_hash: &[u8; 32],
+    ) -> Self::Metadata;
+}
+
+fn require(standard: &str, field: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    if value.trim().is_empty() {
+        return Err(format!("{} metadata requires a {}", standard, field).into());
+    }
+    Ok(())
+}
+
+fn limit(standard: &str, field: &str, value: &str, max: usize) -> Result<(), Box<dyn Error>> {
+    if value.len() > max {
+        return Err(format!("{} {} is {} bytes, limit is {}", standard, field, value.len(), max).into());
+    }
+    Ok(())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+fn hash_hex(hash: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+fn attribute_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl CreativeSession {
+    /// Traits of the session, in a fixed order
+    ///
+    /// Only recorded data is used, never the current time, so the same
+    /// session always yields the same attributes.
+    pub fn attributes(&self) -> Vec<SessionAttribute> {
+        let mut kinds: Vec<&str> = self.data_points.iter().map(|d| d.data.kind()).collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+
+        let mut attributes = vec![
+            SessionAttribute::new("Session", json!(self.session_id.to_string())),
+            SessionAttribute {
+                display_type: Some("date".to_string()),
+                ..SessionAttribute::new("Started", json!(self.start_time.timestamp()))
+            },
+            SessionAttribute::count("Data Points", self.data_points.len() as u64),
+            SessionAttribute::count("Duration (ms)", self.timeline().duration_ms()),
+            SessionAttribute::new("Data Types", json!(kinds.join(", "))),
+        ];
+
+        if let Some(profile) = &self.emotional_profile {
+            attributes.push(SessionAttribute::new("Emotion", json!(profile.emotional_category)));
+            attributes.push(SessionAttribute::number("Valence", profile.valence));
+            attributes.push(SessionAttribute::number("Arousal", profile.arousal));
+            attributes.push(SessionAttribute::number("Dominance", profile.dominance));
+        }
+        attributes.push(SessionAttribute::number("Emotional Complexity", self.emotional_complexity));
+        attributes.push(SessionAttribute::number("Creativity Index", self.creativity_index));
+        attributes.push(SessionAttribute::number("Community Engagement", self.community_engagement));
+
+        if let Some(reputation) = &self.creator_reputation {
+            attributes.push(SessionAttribute::new("Creator", json!(reputation.creator_id)));
+            attributes.push(SessionAttribute::number("Creator Reputation", reputation.reputation_score));
+        }
+        if let Some(bridge) = &self.cross_chain_info {
+            attributes.push(SessionAttribute::new("Source Chain", json!(bridge.source_chain)));
+            attributes.push(SessionAttribute::new("Target Chain", json!(bridge.target_chain)));
+        }
+        attributes
+    }
+
+    /// Canonical reference JSON shared by every standard
+    pub fn reference_json(&self, details: &MintDetails) -> Result<Vec<u8>, Box<dyn Error>> {
+        canonical_json(&json!({
+            "name": details.name,
+            "description": details.description,
+            "media": details.media,
+            "media_hash": details.media_hash.as_ref().map(hash_hex),
+            "external_url": details.external_url,
+            "creators": details.creators,
+            "attributes": self.attributes(),
+        }))
+    }
+
+    /// Validate `details` and export metadata for one standard
+    pub fn export_metadata<E: MetadataExporter>(
+        &self,
+        exporter: &E,
+        details: &MintDetails,
+    ) -> Result<MetadataExport<E::Metadata>, Box<dyn Error>> {
+        exporter.validate(details)?;
+        let reference_json = self.reference_json(details)?;
+        let reference_hash: [u8; 32] = Sha256::digest(&reference_json).into();
+        let metadata = exporter.build(self, details, &self.attributes(), &reference_hash);
+        Ok(MetadataExport {
+            metadata,
+            reference_json,
+            reference_hash,
+        })
+    }
+}
+
+/// NEP-177 `TokenMetadata`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Nep177TokenMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    /// Base64-encoded SHA-256 of the media
+    pub media_hash: Option<String>,
+    pub copies: Option<u64>,
+    pub issued_at: Option<u64>,
+    pub expires_at: Option<u64>,
+    pub starts_at: Option<u64>,
+    pub updated_at: Option<u64>,
+    pub extra: Option<String>,
+    pub reference: Option<String>,
+    /// Base64-encoded SHA-256 of the reference JSON
+    pub reference_hash: Option<String>,
+}
+
+/// NEAR NEP-177 exporter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Nep177;
+
+impl MetadataExporter for Nep177 {
+    type Metadata = Nep177TokenMetadata;
+
+    fn standard(&self) -> &'static str {
+        "nep177"
+    }
+
+    fn validate(&self, details: &MintDetails) -> Result<(), Box<dyn Error>> {
+        require("NEP-177", "name", &details.name)?;
+        require("NEP-177", "media URI", &details.media)?;
+        // A reference_hash is meaningless without the reference it hashes
+        require("NEP-177", "reference URI", details.reference.as_deref().unwrap_or(""))
+    }
+
+    fn build(
+        &self,
+        session: &CreativeSession,
+        details: &MintDetails,
+        _attributes: &[SessionAttribute],
+        reference_hash: &[u8; 32],
+    ) -> Nep177TokenMetadata {
+        Nep177TokenMetadata {
+            title: Some(details.name.clone()),
+            description: Some(details.description.clone()),
+            media: Some(details.media.clone()),
+            media_hash: details.media_hash.map(|h| base64(&h)),
+            copies: Some(1),
+            starts_at: Some(session.start_time.timestamp_millis().max(0) as u64),
+            extra: Some(json!({ "session_id": session.session_id.to_string() }).to_string()),
+            reference: details.reference.clone(),
+            reference_hash: Some(base64(reference_hash)),
+            ..Default::default()
+        }
+    }
+}
+
+/// ERC-721 metadata JSON in the OpenSea layout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Erc721Metadata {
+    pub name: String,
+    pub description: String,
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_url: Option<String>,
+    pub attributes: Vec<SessionAttribute>,
+}
+
+/// ERC-721 / OpenSea exporter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Erc721;
+
+impl MetadataExporter for Erc721 {
+    type Metadata = Erc721Metadata;
+
+    fn standard(&self) -> &'static str {
+        "erc721"
+    }
+
+    fn validate(&self, details: &MintDetails) -> Result<(), Box<dyn Error>> {
+        require("ERC-721", "name", &details.name)?;
+        require("ERC-721", "image URI", &details.media)
+    }
+
+    fn build(
+        &self,
+        _session: &CreativeSession,
+        details: &MintDetails,
+        attributes: &[SessionAttribute],
+        reference_hash: &[u8; 32],
+    ) -> Erc721Metadata {
+        let mut attributes = attributes.to_vec();
+        attributes.push(SessionAttribute::new("Reference Hash", json!(hash_hex(reference_hash))));
+        Erc721Metadata {
+            name: details.name.clone(),
+            description: details.description.clone(),
+            image: details.media.clone(),
+            external_url: details.external_url.clone(),
+            attributes,
+        }
+    }
+}
+
+/// File entry under Metaplex `properties.files`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetaplexFile {
+    pub uri: String,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+}
+
+/// Metaplex `properties`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetaplexProperties {
+    pub category: String,
+    pub files: Vec<MetaplexFile>,
+    pub creators: Vec<CreatorShare>,
+}
+
+/// Metaplex Token Metadata JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetaplexMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    pub seller_fee_basis_points: u16,
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_url: Option<String>,
+    pub attributes: Vec<SessionAttribute>,
+    pub properties: MetaplexProperties,
+}
+
+/// Metaplex category and MIME type for a media URI, by extension
+fn metaplex_media_type(uri: &str) -> (&'static str, &'static str) {
+    let extension = uri.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => ("image", "image/png"),
+        "jpg" | "jpeg" => ("image", "image/jpeg"),
+        "gif" => ("image", "image/gif"),
+        "svg" => ("image", "image/svg+xml"),
+        "mp4" => ("video", "video/mp4"),
+        "webm" => ("video", "video/webm"),
+        "wav" => ("audio", "audio/wav"),
+        "mp3" => ("audio", "audio/mpeg"),
+        "glb" => ("vr", "model/gltf-binary"),
+        "html" => ("html", "text/html"),
+        _ => ("image", "application/octet-stream"),
+    }
+}
+
+/// Solana Metaplex exporter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Metaplex;
+
+impl MetadataExporter for Metaplex {
+    type Metadata = MetaplexMetadata;
+
+    fn standard(&self) -> &'static str {
+        "metaplex"
+    }
+
+    fn validate(&self, details: &MintDetails) -> Result<(), Box<dyn Error>> {
+        require("Metaplex", "name", &details.name)?;
+        require("Metaplex", "symbol", &details.symbol)?;
+        require("Metaplex", "image URI", &details.media)?;
+        limit("Metaplex", "name", &details.name, METAPLEX_MAX_NAME_LEN)?;
+        limit("Metaplex", "symbol", &details.symbol, METAPLEX_MAX_SYMBOL_LEN)?;
+        if let Some(reference) = &details.reference {
+            limit("Metaplex", "uri", reference, METAPLEX_MAX_URI_LEN)?;
+        }
+        if details.seller_fee_basis_points > 10_000 {
+            return Err(format!(
+                "Metaplex seller_fee_basis_points is {}, limit is 10000",
+                details.seller_fee_basis_points
+            )
+            .into());
+        }
+        if details.creators.len() > METAPLEX_MAX_CREATORS {
+            return Err(format!("Metaplex allows at most {} creators", METAPLEX_MAX_CREATORS).into());
+        }
+        let shares: u32 = details.creators.iter().map(|c| c.share as u32).sum();
+        if !details.creators.is_empty() && shares != 100 {
+            return Err(format!("Metaplex creator shares sum to {}, not 100", shares).into());
+        }
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _session: &CreativeSession,
+        details: &MintDetails,
+        attributes: &[SessionAttribute],
+        reference_hash: &[u8; 32],
+    ) -> MetaplexMetadata {
+        let (category, mime_type) = metaplex_media_type(&details.media);
+        let mut attributes: Vec<SessionAttribute> = attributes
+            .iter()
+            .map(|a| SessionAttribute { display_type: None, ..a.clone() })
+            .collect();
+        attributes.push(SessionAttribute::new("Reference Hash", json!(hash_hex(reference_hash))));
+
+        MetaplexMetadata {
+            name: details.name.clone(),
+            symbol: details.symbol.clone(),
+            description: details.description.clone(),
+            seller_fee_basis_points: details.seller_fee_basis_points,
+            image: details.media.clone(),
+            external_url: details.external_url.clone(),
+            attributes,
+            properties: MetaplexProperties {
+                category: category.to_string(),
+                files: vec![MetaplexFile {
+                    uri: details.media.clone(),
+                    mime_type: mime_type.to_string(),
+                }],
+                creators: details.creators.clone(),
+            },
+        }
+    }
+}
+
+/// Item metadata and attributes for pallet-nfts, also read by RMRK
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PalletNftsMetadata {
+    /// Passed to `set_metadata`: the reference URI, or the name without one
+    pub data: String,
+    /// Passed to `set_attribute`, one call per pair
+    pub attributes: Vec<(String, String)>,
+}
+
+/// Polkadot pallet-nfts / RMRK exporter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PalletNfts;
+
+impl MetadataExporter for PalletNfts {
+    type Metadata = PalletNftsMetadata;
+
+    fn standard(&self) -> &'static str {
+        "pallet-nfts"
+    }
+
+    fn validate(&self, details: &MintDetails) -> Result<(), Box<dyn Error>> {
+        require("pallet-nfts", "name", &details.name)?;
+        require("pallet-nfts", "media URI", &details.media)?;
+        limit("pallet-nfts", "name", &details.name, PALLET_NFTS_VALUE_LIMIT)?;
+        limit("pallet-nfts", "media URI", &details.media, PALLET_NFTS_VALUE_LIMIT)?;
+        if let Some(reference) = &details.reference {
+            limit("pallet-nfts", "metadata", reference, PALLET_NFTS_STRING_LIMIT)?;
+        }
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _session: &CreativeSession,
+        details: &MintDetails,
+        attributes: &[SessionAttribute],
+        reference_hash: &[u8; 32],
+    ) -> PalletNftsMetadata {
+        let mut pairs = vec![
+            ("name".to_string(), details.name.clone()),
+            ("media".to_string(), details.media.clone()),
+            ("reference_hash".to_string(), hash_hex(reference_hash)),
+        ];
+        pairs.extend(attributes.iter().map(|a| (a.trait_type.clone(), attribute_text(&a.value))));
+
+        // Session values are short, but clip rather than fail a mint on one
+        for (key, value) in &mut pairs {
+            truncate_utf8(key, PALLET_NFTS_KEY_LIMIT);
+            truncate_utf8(value, PALLET_NFTS_VALUE_LIMIT);
+        }
+
+        PalletNftsMetadata {
+            data: details.reference.clone().unwrap_or_else(|| details.name.clone()),
+            attributes: pairs,
+        }
+    }
+}
+
+fn truncate_utf8(s: &mut String, max: usize) {
+    if s.len() > max {
+        let mut end = max;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn session() -> CreativeSession {
+        let mut session = CreativeSession::new();
+        session.add_data_at(0, CreativeData::Emotional(generate_emotional_data(0.7, 0.8, 0.6, vec![])));
+        session.add_data_at(1200, CreativeData::Emotional(generate_emotional_data(0.2, 0.4, 0.6, vec![])));
+        session.set_emotional_profile(create_emotional_vector(0.7, 0.8, 0.6));
+        session.set_creator_reputation(create_reputation_data("creator.near"));
+        session
+    }
+
+    fn details() -> MintDetails {
+        MintDetails {
+            name: "Fractal Session".to_string(),
+            description: "Live fractal performance".to_string(),
+            media: "ipfs://bafy/frame.png".to_string(),
+            media_hash: Some([7; 32]),
+            reference: Some("ipfs://bafy/reference.json".to_string()),
+            symbol: "FRCT".to_string(),
+            seller_fee_basis_points: 500,
+            creators: vec![CreatorShare { address: "creator".to_string(), share: 100 }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reference_hash_shared_across_standards() {
+        let session = session();
+        let details = details();
+        let near = session.export_metadata(&Nep177, &details).unwrap();
+        let evm = session.export_metadata(&Erc721, &details).unwrap();
+        let solana = session.export_metadata(&Metaplex, &details).unwrap();
+        let polkadot = session.export_metadata(&PalletNfts, &details).unwrap();
+
+        assert_eq!(near.reference_hash, evm.reference_hash);
+        assert_eq!(near.reference_hash, solana.reference_hash);
+        assert_eq!(near.reference_hash, polkadot.reference_hash);
+        assert_eq!(near.reference_hash, <[u8; 32]>::from(Sha256::digest(&near.reference_json)));
+
+        let hex = hash_hex(&near.reference_hash);
+        assert_eq!(near.metadata.reference_hash, Some(base64(&near.reference_hash)));
+        assert!(evm.metadata.attributes.iter().any(|a| a.value == json!(hex)));
+        assert!(solana.metadata.attributes.iter().any(|a| a.value == json!(hex)));
+        assert!(polkadot.metadata.attributes.contains(&("reference_hash".to_string(), hex)));
+    }
+
+    #[test]
+    fn test_exports_are_deterministic() {
+        let session = session();
+        let first = session.export_metadata(&Metaplex, &details()).unwrap();
+        let second = session.export_metadata(&Metaplex, &details()).unwrap();
+        assert_eq!(first.reference_json, second.reference_json);
+        assert_eq!(first.metadata, second.metadata);
+        assert_eq!(first.metadata.properties.files[0].mime_type, "image/png");
+
+        let attributes = session.attributes();
+        let duration = attributes.iter().find(|a| a.trait_type == "Duration (ms)").unwrap();
+        assert_eq!(duration.value, json!(1200));
+        let valence = attributes.iter().find(|a| a.trait_type == "Valence").unwrap();
+        assert_eq!(valence.value, json!(0.7));
+    }
+
+    #[test]
+    fn test_required_fields() {
+        let session = session();
+        let mut details = details();
+        details.reference = None;
+        assert!(session.export_metadata(&Nep177, &details).is_err());
+        assert!(session.export_metadata(&Erc721, &details).is_ok());
+
+        details.name = "A name far longer than thirty-two bytes".to_string();
+        assert!(session.export_metadata(&Metaplex, &details).is_err());
+
+        let mut details = self::details();
+        details.creators.push(CreatorShare { address: "other".to_string(), share: 10 });
+        let err = session.export_metadata(&Metaplex, &details).unwrap_err();
+        assert!(err.to_string().contains("sum to 110"));
+
+        details.media.clear();
+        assert!(session.export_metadata(&PalletNfts, &details).is_err());
+    }
+}
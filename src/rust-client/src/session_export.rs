@@ -0,0 +1,319 @@
+//! Versioned export of a `CreativeSession` for IPFS/Filecoin storage
+//!
+//! Version 1 was a summary without a `schema_version` field: ids, profile
+//! and scores, but no data points, metadata or reputation. It still imports,
+//! as a session holding only what the summary kept. Version 2 carries the
+//! whole session under `"session"`. Version 3 stamps each data point with
+//! its offset from the session start; version 2 data points get theirs from
+//! their own timestamps, kept in their original order. Imported data points
+//! are sorted by offset, ties in file order, since the timeline searches
+//! them by offset.
+
+use crate::{CreativeData, CreativeSession};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::error::Error;
+
+/// Current export schema version
+pub const SESSION_SCHEMA_VERSION: u32 = 3;
+
+impl CreativeSession {
+    /// Export the full session as versioned JSON
+    ///
+    /// Object keys are sorted, so exporting an imported session reproduces
+    /// the original bytes.
+    pub fn export_for_storage(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        canonical_json(&json!({
+            "schema_version": SESSION_SCHEMA_VERSION,
+            "session": serde_json::from_slice::<Value>(&serde_json::to_vec(self)?)?,
+        }))
+    }
+
+    /// Rebuild a session from [`export_for_storage`](Self::export_for_storage)
+    /// output of this or any earlier schema version
+    pub fn import_from_storage(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut export: Value = serde_json::from_slice(bytes)?;
+        let version = match export.get("schema_version") {
+            None => 1,
+            Some(v) => v.as_u64().ok_or("Session schema_version is not a number")?,
+        };
+        if version == 0 || version > u64::from(SESSION_SCHEMA_VERSION) {
+            return Err(format!(
+                "Unsupported session schema version {} (expected at most {})",
+                version, SESSION_SCHEMA_VERSION
+            )
+            .into());
+        }
+
+        let mut version = u32::try_from(version)?;
+        while version < SESSION_SCHEMA_VERSION {
+            export = migrate(version, export)?;
+            version += 1;
+        }
+
+        let session = export.get_mut("session").ok_or("Session export has no session")?.take();
+        let mut session: CreativeSession = serde_json::from_value(session)?;
+        session.data_points.sort_by_key(|d| d.offset_ms);
+        Ok(session)
+    }
+}
+
+/// Upgrade an export from `version` to `version + 1`
+fn migrate(version: u32, export: Value) -> Result<Value, Box<dyn Error>> {
+    match version {
+        1 => Ok(migrate_v1(export)),
+        2 => migrate_v2(export),
+        _ => Err(format!("No migration from session schema version {}", version).into()),
+    }
+}
+
+/// Wrap a v1 summary as a v2 session; what v1 dropped stays empty
+fn migrate_v1(summary: Value) -> Value {
+    let field = |name: &str| summary.get(name).cloned().unwrap_or(Value::Null);
+    let score = |name: &str| summary.get(name).cloned().unwrap_or(json!(0.0));
+
+    json!({
+        "schema_version": 2,
+        "session": {
+            "session_id": field("session_id"),
+            "start_time": field("start_time"),
+            "data_points": [],
+            "metadata": {},
+            "cross_chain_info": field("cross_chain_info"),
+            "creator_reputation": null,
+            "emotional_profile": field("emotional_profile"),
+            "emotional_complexity": score("emotional_complexity"),
+            "creativity_index": score("creativity_index"),
+            "community_engagement": score("community_engagement"),
+        },
+    })
+}
+
+/// Stamp each v2 data point with its offset from the session start
+fn migrate_v2(mut export: Value) -> Result<Value, Box<dyn Error>> {
+    let session = export.get_mut("session").ok_or("Session export has no session")?;
+    let start_time: DateTime<Utc> = serde_json::from_value(session["start_time"].clone())?;
+    let data_points = match session["data_points"].take() {
+        Value::Null => Vec::new(),
+        points => serde_json::from_value::<Vec<Value>>(points)?,
+    };
+
+    let mut previous = 0;
+    let mut timed = Vec::with_capacity(data_points.len());
+    for point in data_points {
+        let data: CreativeData = serde_json::from_value(point.clone())?;
+        // Never earlier than the point before, so the order survives
+        let offset_ms = ((data.timestamp() - start_time).num_milliseconds().max(0) as u64).max(previous);
+        previous = offset_ms;
+        timed.push(json!({ "offset_ms": offset_ms, "data": point }));
+    }
+
+    session["data_points"] = Value::Array(timed);
+    export["schema_version"] = json!(3);
+    Ok(export)
+}
+
+/// Serialize with every object's keys sorted, so equal values always give
+/// equal bytes
+pub(crate) fn canonical_json<T: Serialize>(value: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+    // Through text first so floats keep their shortest f32 spelling
+    let value: Value = serde_json::from_slice(&serde_json::to_vec(value)?)?;
+    Ok(serde_json::to_vec(&sorted(value))?)
+}
+
+/// Rebuild every object with its keys in order, whatever map type
+/// serde_json was built with
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(k, v)| (k, sorted(v))).collect::<Map<_, _>>())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sorted).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use chrono::Utc;
+
+    fn full_session() -> CreativeSession {
+        let now = Utc::now();
+        let mut session = CreativeSession::new();
+        for i in 0..8 {
+            session.metadata.insert(format!("key{}", i), json!({ "value": i, "label": "x" }));
+        }
+
+        session.add_data(CreativeData::Audiovisual(AudiovisualData {
+            timestamp: now,
+            format: "wav".to_string(),
+            data: vec![1, 2, 3],
+            metadata: [("bpm".to_string(), json!(120)), ("key".to_string(), json!("C"))].into_iter().collect(),
+        }));
+        session.add_data(CreativeData::Emotional(generate_emotional_data(0.1, 0.7, 0.3, vec![0.1, 0.2])));
+        session.add_data(CreativeData::Shader(ShaderData {
+            timestamp: now,
+            shader_type: "fractal".to_string(),
+            parameters: (0..6).map(|i| (format!("p{}", i), i as f32 * 0.1)).collect(),
+            seed: 42,
+            iteration_count: 7,
+            controls: None,
+        }));
+        session.add_data(CreativeData::Performance(PerformanceData {
+            session_id: uuid::Uuid::new_v4(),
+            performer_id: "vj".to_string(),
+            start_time: now,
+            duration_ms: 1000,
+            parameters: vec![PerformanceParameter { name: "zoom".to_string(), value: 1.5, timestamp: now }],
+            emotional_impact: 0.3,
+            creativity_boost: 0.4,
+            audience_engagement: 0.5,
+            animation: None,
+        }));
+        let bridge = BridgeData {
+            source_chain: "near".to_string(),
+            target_chain: "solana".to_string(),
+            source_contract: "a".to_string(),
+            target_contract: "b".to_string(),
+            bridge_status: "pending".to_string(),
+            bridge_timestamp: now,
+            emotional_metadata: Some(create_emotional_vector(0.2, 0.3, 0.4)),
+            emotional_preservation: 0.9,
+            bridge_complexity: 0.1,
+            cross_chain_emotional_sync: true,
+        };
+        session.add_data(CreativeData::Bridge(bridge.clone()));
+        let mut reputation = ReputationData {
+            creator_id: "creator".to_string(),
+            reputation_score: 0.7,
+            total_interactions: 3,
+            last_updated: now,
+            emotional_metrics: Some(EmotionalMetrics {
+                avg_valence: 0.1,
+                avg_arousal: 0.2,
+                emotional_consistency: 0.3,
+                emotional_range: 0.4,
+                emotional_maturity: 0.5,
+                emotional_volatility: 0.6,
+            }),
+            emotional_consistency: 0.3,
+            creativity_score: 0.8,
+            community_rank: 0.9,
+            history: Default::default(),
+        };
+        let event = reputation_core::ReputationEvent::new(reputation_core::Source::Sale, 0.9, now.timestamp() as u64);
+        reputation.record_event(&Default::default(), event).unwrap();
+        session.add_data(CreativeData::Reputation(reputation.clone()));
+
+        session.set_cross_chain_info(bridge);
+        session.set_creator_reputation(reputation);
+        session.set_emotional_profile(create_emotional_vector(0.8, 0.9, 0.7));
+        session
+    }
+
+    #[test]
+    fn test_export_import_export_is_byte_identical() {
+        let session = full_session();
+        let exported = session.export_for_storage().unwrap();
+        let imported = CreativeSession::import_from_storage(&exported).unwrap();
+
+        assert_eq!(imported.session_id, session.session_id);
+        assert_eq!(imported.data_points.len(), 6);
+        assert_eq!(imported.metadata, session.metadata);
+        assert!(imported.creator_reputation.is_some());
+        assert_eq!(imported.export_for_storage().unwrap(), exported);
+    }
+
+    #[test]
+    fn test_import_v1_summary() {
+        let summary = json!({
+            "session_id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "start_time": "2024-01-01T00:00:00+00:00",
+            "data_points_count": 3,
+            "export_timestamp": "2024-01-01T01:00:00+00:00",
+            "cross_chain_info": null,
+            "emotional_profile": null,
+            "emotional_complexity": 0.25,
+            "creativity_index": 0.5,
+        });
+
+        let session = CreativeSession::import_from_storage(&serde_json::to_vec(&summary).unwrap()).unwrap();
+        assert_eq!(session.session_id.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+        assert!(session.data_points.is_empty());
+        assert_eq!(session.creativity_index, 0.5);
+        assert_eq!(session.community_engagement, 0.0);
+
+        // Once migrated, it exports as the current version
+        let reexported: Value = serde_json::from_slice(&session.export_for_storage().unwrap()).unwrap();
+        assert_eq!(reexported["schema_version"], json!(SESSION_SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_import_v2_stamps_offsets() {
+        let start: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+        let at = |ms: i64| {
+            let mut data = generate_emotional_data(0.0, 0.5, 0.5, vec![]);
+            data.timestamp = start + chrono::Duration::milliseconds(ms);
+            serde_json::to_value(CreativeData::Emotional(data)).unwrap()
+        };
+        let export = json!({
+            "schema_version": 2,
+            "session": {
+                "session_id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "start_time": start,
+                "data_points": [at(1500), at(400), at(-10)],
+                "metadata": {},
+                "cross_chain_info": null,
+                "creator_reputation": null,
+                "emotional_profile": null,
+                "emotional_complexity": 0.0,
+                "creativity_index": 0.0,
+                "community_engagement": 0.0,
+            },
+        });
+
+        let session = CreativeSession::import_from_storage(&serde_json::to_vec(&export).unwrap()).unwrap();
+        let offsets: Vec<u64> = session.data_points.iter().map(|d| d.offset_ms).collect();
+        assert_eq!(offsets, vec![1500, 1500, 1500]);
+        assert_eq!(session.timeline().duration_ms(), 1500);
+    }
+
+    #[test]
+    fn test_import_v3_sorts_by_offset() {
+        let point = |offset_ms: u64, valence: f32| {
+            let data = CreativeData::Emotional(generate_emotional_data(valence, 0.5, 0.5, vec![]));
+            json!({ "offset_ms": offset_ms, "data": data })
+        };
+        let mut export: Value = serde_json::from_slice(&CreativeSession::new().export_for_storage().unwrap()).unwrap();
+        export["session"]["data_points"] = json!([point(300, 0.3), point(100, 0.1), point(0, 0.0), point(100, 0.2)]);
+
+        let session = CreativeSession::import_from_storage(&serde_json::to_vec(&export).unwrap()).unwrap();
+        let order: Vec<(u64, f32)> = session
+            .data_points
+            .iter()
+            .map(|d| match &d.data {
+                CreativeData::Emotional(e) => (d.offset_ms, e.valence),
+                _ => panic!("expected emotional data"),
+            })
+            .collect();
+        assert_eq!(order, vec![(0, 0.0), (100, 0.1), (100, 0.2), (300, 0.3)]);
+        assert_eq!(session.timeline().seek(100).len(), 3);
+    }
+
+    #[test]
+    fn test_rejects_future_version() {
+        let export = json!({ "schema_version": SESSION_SCHEMA_VERSION + 1, "session": {} });
+        let err = CreativeSession::import_from_storage(&serde_json::to_vec(&export).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Unsupported session schema version"));
+
+        // 2^32 + 1 would read as version 1 if truncated
+        let export = json!({ "schema_version": 4_294_967_297u64, "session": {} });
+        let err = CreativeSession::import_from_storage(&serde_json::to_vec(&export).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Unsupported session schema version 4294967297"));
+    }
+}
@@ -0,0 +1,612 @@
+//! Uniform reflection and control schemas for shader programs.
+//!
+//! A [`ControlSchema`] lists the uniforms a linked program actually uses, as
+//! reported by `getActiveUniform`, with typed ranges and defaults taken from
+//! annotations in the GLSL source:
+//!
+//! ```glsl
+//! // @label Zoom @range 0.1 10
+//! uniform float u_zoom; // @default 2.5 @step 0.1
+//! ```
+//!
+//! Annotations go in a line comment on the declaration or on the comment line
+//! right above it. Ones that do not parse are listed as warnings rather than
+//! failing the program, since the shader itself compiled. Uniforms the engine
+//! drives itself, like `u_time`, are marked so UIs and OSC/MIDI mappers can
+//! leave them alone.
+
+use crate::webgpu_engine::UniformValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Uniforms `ShaderEngine::render` sets on every frame
+pub const DRIVEN_UNIFORMS: [&str; 7] = [
+    "u_time",
+    "u_resolution",
+    "u_emotion_valence",
+    "u_emotion_arousal",
+    "u_emotion_dominance",
+    "u_emotion_confidence",
+    "u_emotion_complexity",
+];
+
+/// An annotation or declaration that does not parse, and was skipped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlWarning {
+    /// 1-based source line
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ControlWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// GLSL ES 1.0 uniform types
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UniformType {
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+    Int,
+    IVec2,
+    IVec3,
+    IVec4,
+    Bool,
+    BVec2,
+    BVec3,
+    BVec4,
+    Mat2,
+    Mat3,
+    Mat4,
+    #[serde(rename = "sampler2D")]
+    Sampler2D,
+    #[serde(rename = "samplerCube")]
+    SamplerCube,
+}
+
+impl UniformType {
+    const ALL: [UniformType; 17] = [
+        UniformType::Float,
+        UniformType::Vec2,
+        UniformType::Vec3,
+        UniformType::Vec4,
+        UniformType::Int,
+        UniformType::IVec2,
+        UniformType::IVec3,
+        UniformType::IVec4,
+        UniformType::Bool,
+        UniformType::BVec2,
+        UniformType::BVec3,
+        UniformType::BVec4,
+        UniformType::Mat2,
+        UniformType::Mat3,
+        UniformType::Mat4,
+        UniformType::Sampler2D,
+        UniformType::SamplerCube,
+    ];
+
+    /// Keyword in GLSL source
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UniformType::Float => "float",
+            UniformType::Vec2 => "vec2",
+            UniformType::Vec3 => "vec3",
+            UniformType::Vec4 => "vec4",
+            UniformType::Int => "int",
+            UniformType::IVec2 => "ivec2",
+            UniformType::IVec3 => "ivec3",
+            UniformType::IVec4 => "ivec4",
+            UniformType::Bool => "bool",
+            UniformType::BVec2 => "bvec2",
+            UniformType::BVec3 => "bvec3",
+            UniformType::BVec4 => "bvec4",
+            UniformType::Mat2 => "mat2",
+            UniformType::Mat3 => "mat3",
+            UniformType::Mat4 => "mat4",
+            UniformType::Sampler2D => "sampler2D",
+            UniformType::SamplerCube => "samplerCube",
+        }
+    }
+
+    pub fn from_glsl(keyword: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == keyword)
+    }
+
+    /// Type from the `type` of a `WebGLActiveInfo`
+    pub fn from_gl(gl_type: u32) -> Option<Self> {
+        Some(match gl_type {
+            0x1406 => UniformType::Float,
+            0x8B50 => UniformType::Vec2,
+            0x8B51 => UniformType::Vec3,
+            0x8B52 => UniformType::Vec4,
+            0x1404 => UniformType::Int,
+            0x8B53 => UniformType::IVec2,
+            0x8B54 => UniformType::IVec3,
+            0x8B55 => UniformType::IVec4,
+            0x8B56 => UniformType::Bool,
+            0x8B57 => UniformType::BVec2,
+            0x8B58 => UniformType::BVec3,
+            0x8B59 => UniformType::BVec4,
+            0x8B5A => UniformType::Mat2,
+            0x8B5B => UniformType::Mat3,
+            0x8B5C => UniformType::Mat4,
+            0x8B5E => UniformType::Sampler2D,
+            0x8B60 => UniformType::SamplerCube,
+            _ => return None,
+        })
+    }
+
+    /// Scalars per element
+    pub fn components(&self) -> usize {
+        match self {
+            UniformType::Float | UniformType::Int | UniformType::Bool => 1,
+            UniformType::Sampler2D | UniformType::SamplerCube => 1,
+            UniformType::Vec2 | UniformType::IVec2 | UniformType::BVec2 => 2,
+            UniformType::Vec3 | UniformType::IVec3 | UniformType::BVec3 => 3,
+            UniformType::Vec4 | UniformType::IVec4 | UniformType::BVec4 | UniformType::Mat2 => 4,
+            UniformType::Mat3 => 9,
+            UniformType::Mat4 => 16,
+        }
+    }
+
+    /// Whether a UI can offer a control for it; matrices and samplers need
+    /// more than sliders
+    pub fn is_controllable(&self) -> bool {
+        !matches!(
+            self,
+            UniformType::Mat2
+                | UniformType::Mat3
+                | UniformType::Mat4
+                | UniformType::Sampler2D
+                | UniformType::SamplerCube
+        )
+    }
+}
+
+/// One uniform of a program and how to control it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniformControl {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub uniform_type: UniformType,
+    /// Array length, 1 for plain uniforms
+    pub size: u32,
+    /// Inclusive bounds of every component
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<[f32; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<f32>,
+    /// One value per component
+    pub default: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Set by the engine each frame rather than by users
+    #[serde(default)]
+    pub driven: bool,
+}
+
+impl UniformControl {
+    fn new(name: &str, uniform_type: UniformType, size: u32) -> Self {
+        UniformControl {
+            name: name.to_string(),
+            uniform_type,
+            size,
+            range: None,
+            step: None,
+            default: vec![0.0; uniform_type.components()],
+            label: None,
+            driven: DRIVEN_UNIFORMS.contains(&name),
+        }
+    }
+
+    /// Value for a normalized controller position (0 to 1, e.g. MIDI CC / 127)
+    /// within the range, snapped to the step and to integers for int and bool
+    /// types. `None` without a range.
+    pub fn from_unit(&self, position: f32) -> Option<f32> {
+        let [min, max] = self.range?;
+        let mut value = min + (max - min) * position.clamp(0.0, 1.0);
+        if let Some(step) = self.step.filter(|s| *s > 0.0) {
+            value = (min + ((value - min) / step).round() * step).clamp(min.min(max), min.max(max));
+        }
+        Some(if self.is_integer() { value.round() } else { value })
+    }
+
+    /// Default as the value `ShaderEngine` records for it, if it is controllable
+    pub fn default_value(&self) -> Option<UniformValue> {
+        let d = &self.default;
+        if d.len() < self.uniform_type.components() {
+            return None;
+        }
+        Some(match self.uniform_type {
+            UniformType::Float => UniformValue::Float(d[0]),
+            UniformType::Vec2 => UniformValue::Vec2([d[0], d[1]]),
+            UniformType::Vec3 => UniformValue::Vec3([d[0], d[1], d[2]]),
+            UniformType::Vec4 => UniformValue::Vec4([d[0], d[1], d[2], d[3]]),
+            UniformType::Int => UniformValue::Int(d[0] as i32),
+            UniformType::Bool => UniformValue::Bool(d[0] != 0.0),
+            _ => return None,
+        })
+    }
+
+    fn is_integer(&self) -> bool {
+        matches!(
+            self.uniform_type,
+            UniformType::Int
+                | UniformType::IVec2
+                | UniformType::IVec3
+                | UniformType::IVec4
+                | UniformType::Bool
+                | UniformType::BVec2
+                | UniformType::BVec3
+                | UniformType::BVec4
+        )
+    }
+
+    fn annotate(&mut self, annotations: &Annotations) {
+        if let Some(default) = &annotations.default {
+            self.default = match default.len() {
+                1 => vec![default[0]; self.uniform_type.components()],
+                _ => default.clone(),
+            };
+        }
+        self.range = annotations.range.or(self.range);
+        if let Some([min, max]) = self.range {
+            for value in &mut self.default {
+                *value = value.clamp(min.min(max), min.max(max));
+            }
+        }
+        self.step = annotations.step.or(self.step);
+        self.label = annotations.label.clone().or(self.label.take());
+    }
+}
+
+/// Controls of one program, in declaration order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ControlSchema {
+    pub uniforms: Vec<UniformControl>,
+    /// Annotations and declarations that were skipped
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ControlWarning>,
+}
+
+impl ControlSchema {
+    /// Every uniform declared in `sources`, annotated
+    pub fn from_source(sources: &[&str]) -> Self {
+        let mut uniforms: Vec<UniformControl> = Vec::new();
+        let mut warnings = Vec::new();
+        for source in sources {
+            for declared in parse_source(source, &mut warnings) {
+                // Uniforms shared by vertex and fragment stages are declared twice
+                match uniforms.iter_mut().find(|u| u.name == declared.name) {
+                    Some(existing) => existing.annotate(&declared.annotations),
+                    None => {
+                        let mut control = UniformControl::new(&declared.name, declared.uniform_type, declared.size);
+                        control.annotate(&declared.annotations);
+                        uniforms.push(control);
+                    }
+                }
+            }
+        }
+        ControlSchema { uniforms, warnings }
+    }
+
+    /// The program's active uniforms as `(name, GL type, size)` from
+    /// `getActiveUniform`, annotated from `sources`.
+    ///
+    /// Compilers drop uniforms a program never reads, so only active ones are
+    /// listed; their types and sizes come from GL, and declaration order from
+    /// the source where it has them.
+    pub fn from_active(active: &[(String, u32, u32)], sources: &[&str]) -> Self {
+        let declared = Self::from_source(sources);
+        let mut uniforms: Vec<UniformControl> = active
+            .iter()
+            .filter_map(|(name, gl_type, size)| {
+                // Arrays are reported as `name[0]`
+                let name = name.strip_suffix("[0]").unwrap_or(name);
+                let uniform_type = UniformType::from_gl(*gl_type)?;
+                let mut control = UniformControl::new(name, uniform_type, *size);
+                if let Some(source) = declared.get(name).filter(|d| d.uniform_type == uniform_type) {
+                    control = UniformControl { size: *size, ..source.clone() };
+                }
+                Some(control)
+            })
+            .collect();
+        let position = |name: &str| declared.uniforms.iter().position(|u| u.name == name).unwrap_or(usize::MAX);
+        uniforms.sort_by_key(|u| position(&u.name));
+        ControlSchema { uniforms, warnings: declared.warnings }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&UniformControl> {
+        self.uniforms.iter().find(|u| u.name == name)
+    }
+
+    /// Uniforms a user or controller should drive
+    pub fn controls(&self) -> impl Iterator<Item = &UniformControl> {
+        self.uniforms.iter().filter(|u| !u.driven && u.uniform_type.is_controllable())
+    }
+
+    /// Defaults of every control, by name
+    pub fn defaults(&self) -> HashMap<String, UniformValue> {
+        self.controls().filter_map(|u| Some((u.name.clone(), u.default_value()?))).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Annotations {
+    range: Option<[f32; 2]>,
+    step: Option<f32>,
+    default: Option<Vec<f32>>,
+    label: Option<String>,
+}
+
+struct Declared {
+    name: String,
+    uniform_type: UniformType,
+    size: u32,
+    annotations: Annotations,
+}
+
+fn parse_source(source: &str, warnings: &mut Vec<ControlWarning>) -> Vec<Declared> {
+    let mut declared = Vec::new();
+    // Annotations of the line above, when it held nothing but a comment
+    let mut above = Annotations::default();
+    let mut in_block_comment = false;
+    for (index, line) in source.lines().enumerate() {
+        let line_no = index + 1;
+        let (code, comment) = split_comments(line, &mut in_block_comment);
+        let mut warn = |message: String| warnings.push(ControlWarning { line: line_no, message });
+        if code.trim().is_empty() {
+            above = parse_annotations(comment, Annotations::default(), &mut warn);
+            continue;
+        }
+        let annotations = parse_annotations(comment, std::mem::take(&mut above), &mut warn);
+        for statement in code.split(';') {
+            let Some(declaration) = statement.trim().strip_prefix("uniform ") else { continue };
+            let names = match parse_declaration(declaration) {
+                Ok(names) => names,
+                Err(message) => {
+                    warn(message);
+                    continue;
+                }
+            };
+            for (name, uniform_type, size) in names {
+                let components = uniform_type.components();
+                let mut annotations = annotations.clone();
+                if annotations.default.as_ref().is_some_and(|d| d.len() != 1 && d.len() != components) {
+                    warn(format!("@default of '{}' needs 1 or {} numbers", name, components));
+                    annotations.default = None;
+                }
+                declared.push(Declared { name, uniform_type, size, annotations });
+            }
+        }
+    }
+    declared
+}
+
+/// Code of `line` with block comments blanked out, and the text of its line
+/// comment. `in_block` carries an unclosed `/*` over to the next line.
+fn split_comments<'a>(line: &'a str, in_block: &mut bool) -> (String, &'a str) {
+    let mut code = String::new();
+    let mut rest = line;
+    loop {
+        if *in_block {
+            match rest.find("*/") {
+                Some(end) => {
+                    rest = &rest[end + 2..];
+                    *in_block = false;
+                    code.push(' ');
+                }
+                None => return (code, ""),
+            }
+        }
+        match (rest.find("//"), rest.find("/*")) {
+            (Some(line_at), block_at) if block_at.is_none_or(|block_at| line_at < block_at) => {
+                code.push_str(&rest[..line_at]);
+                return (code, &rest[line_at + 2..]);
+            }
+            (_, Some(block_at)) => {
+                code.push_str(&rest[..block_at]);
+                rest = &rest[block_at + 2..];
+                *in_block = true;
+            }
+            _ => {
+                code.push_str(rest);
+                return (code, "");
+            }
+        }
+    }
+}
+
+/// Names, types and array sizes of `[precision] type name[N], ...`
+fn parse_declaration(declaration: &str) -> Result<Vec<(String, UniformType, u32)>, String> {
+    let mut words = declaration.split_whitespace().peekable();
+    if matches!(words.peek(), Some(&("lowp" | "mediump" | "highp"))) {
+        words.next();
+    }
+    let keyword = words.next().ok_or_else(|| "uniform without a type".to_string())?;
+    let uniform_type = UniformType::from_glsl(keyword).ok_or_else(|| format!("unsupported uniform type '{}'", keyword))?;
+    let names: String = words.collect();
+    names
+        .split(',')
+        .map(|name| match name.split_once('[') {
+            Some((name, size)) => {
+                let size = size.trim_end_matches(']').parse();
+                let size = size.map_err(|_| format!("bad array size in '{}'", name))?;
+                Ok((name.to_string(), uniform_type, size))
+            }
+            None => Ok((name.to_string(), uniform_type, 1)),
+        })
+        .collect()
+}
+
+/// Read `@key values` pairs from a comment over `annotations`, warning about
+/// and skipping the ones that do not parse
+fn parse_annotations(comment: &str, mut annotations: Annotations, warn: &mut impl FnMut(String)) -> Annotations {
+    for part in comment.split('@').skip(1) {
+        let (key, rest) = part.split_once(char::is_whitespace).unwrap_or((part, ""));
+        let rest = rest.trim();
+        let numbers = || -> Result<Vec<f32>, String> {
+            rest.split_whitespace()
+                .map(|n| n.parse::<f32>().map_err(|_| format!("@{} expects numbers, got '{}'", key, n)))
+                .collect()
+        };
+        let mut apply = || -> Result<(), String> {
+            match key {
+                "range" => match numbers()?[..] {
+                    [min, max] => annotations.range = Some([min, max]),
+                    _ => return Err("@range expects a minimum and a maximum".to_string()),
+                },
+                "step" => match numbers()?[..] {
+                    [step] if step > 0.0 => annotations.step = Some(step),
+                    _ => return Err("@step expects one positive number".to_string()),
+                },
+                "default" => {
+                    let values = numbers()?;
+                    if values.is_empty() {
+                        return Err("@default expects at least one number".to_string());
+                    }
+                    annotations.default = Some(values);
+                }
+                "label" if !rest.is_empty() => annotations.label = Some(rest.to_string()),
+                "label" => return Err("@label expects text".to_string()),
+                other => return Err(format!("unknown annotation @{}", other)),
+            }
+            Ok(())
+        };
+        if let Err(message) = apply() {
+            warn(message);
+        }
+    }
+    annotations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+precision highp float;
+
+uniform float u_time;
+uniform vec2 u_resolution;
+// @label Zoom @range 0.1 10
+uniform float u_zoom; // @default 2.5 @step 0.1
+uniform vec3 u_tint; // @range 0 1 @default 1
+uniform int u_max_iter; // @range 1 1000 @default 200
+uniform float u_unused, u_weights[4]; // @range -1 1
+uniform sampler2D u_palette;
+
+// A plain comment
+void main() {}
+"#;
+
+    #[test]
+    fn test_source_annotations_become_controls() {
+        let schema = ControlSchema::from_source(&[SOURCE]);
+        let names: Vec<&str> = schema.uniforms.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["u_time", "u_resolution", "u_zoom", "u_tint", "u_max_iter", "u_unused", "u_weights", "u_palette"]
+        );
+
+        let zoom = schema.get("u_zoom").unwrap();
+        assert_eq!((zoom.range, zoom.step, zoom.default.clone()), (Some([0.1, 10.0]), Some(0.1), vec![2.5]));
+        assert_eq!(zoom.label.as_deref(), Some("Zoom"));
+        assert_eq!(schema.get("u_tint").unwrap().default, [1.0, 1.0, 1.0]);
+        assert_eq!(schema.get("u_weights").unwrap().size, 4);
+        assert_eq!(schema.get("u_weights").unwrap().range, Some([-1.0, 1.0]));
+        assert!(schema.get("u_time").unwrap().driven);
+        assert!(schema.warnings.is_empty());
+
+        let controls: Vec<&str> = schema.controls().map(|u| u.name.as_str()).collect();
+        assert_eq!(controls, ["u_zoom", "u_tint", "u_max_iter", "u_unused", "u_weights"]);
+        assert!(matches!(schema.defaults()["u_max_iter"], UniformValue::Int(200)));
+
+        let json = serde_json::to_value(&schema).unwrap();
+        assert_eq!(json["uniforms"][7]["type"], "sampler2D");
+        assert_eq!(serde_json::from_value::<ControlSchema>(json).unwrap(), schema);
+    }
+
+    #[test]
+    fn test_active_uniforms_decide_what_is_listed() {
+        let active = [
+            ("u_max_iter".to_string(), 0x1404, 1),
+            ("u_weights[0]".to_string(), 0x1406, 4),
+            ("u_zoom".to_string(), 0x1406, 1),
+            ("u_extra".to_string(), 0x8B52, 1),
+        ];
+        let schema = ControlSchema::from_active(&active, &["void main() {}", SOURCE]);
+        let names: Vec<&str> = schema.uniforms.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, ["u_zoom", "u_max_iter", "u_weights", "u_extra"]);
+        assert_eq!(schema.get("u_zoom").unwrap().step, Some(0.1));
+        assert_eq!(schema.get("u_extra").unwrap().default, [0.0; 4]);
+    }
+
+    #[test]
+    fn test_controller_positions_map_into_ranges() {
+        let schema = ControlSchema::from_source(&[SOURCE]);
+        let zoom = schema.get("u_zoom").unwrap();
+        assert_eq!(zoom.from_unit(0.0), Some(0.1));
+        assert!((zoom.from_unit(0.6).unwrap() - 6.0).abs() < 1e-4);
+        assert_eq!(schema.get("u_max_iter").unwrap().from_unit(0.3337), Some(334.0));
+        assert_eq!(schema.get("u_time").unwrap().from_unit(0.5), None);
+    }
+
+    #[test]
+    fn test_bad_annotations_are_skipped_with_warnings() {
+        let source = "uniform float a; // @range 1 @step 0.5\n\
+                      uniform vec3 b; // @default 1 2 @colour red\n\
+                      uniform struct c;\n\
+                      uniform float d; // @step x";
+        let schema = ControlSchema::from_source(&[source]);
+        let warnings: Vec<(usize, &str)> = schema.warnings.iter().map(|w| (w.line, w.message.as_str())).collect();
+        assert_eq!(
+            warnings,
+            [
+                (1, "@range expects a minimum and a maximum"),
+                (2, "unknown annotation @colour"),
+                (2, "@default of 'b' needs 1 or 3 numbers"),
+                (3, "unsupported uniform type 'struct'"),
+                (4, "@step expects numbers, got 'x'"),
+            ]
+        );
+        let names: Vec<&str> = schema.uniforms.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "d"]);
+        assert_eq!((schema.get("a").unwrap().range, schema.get("a").unwrap().step), (None, Some(0.5)));
+        assert_eq!(schema.get("b").unwrap().default, [0.0; 3]);
+    }
+
+    #[test]
+    fn test_annotations_come_from_the_line_or_the_one_above() {
+        let source = r#"
+// @range 0 10
+// @label Far
+uniform float u_far;
+// @range 0 1
+
+uniform float u_gap;
+uniform float u_a; uniform vec2 u_b; // @range -1 1 @default 5
+/* uniform float u_hidden; // @range 0 2
+   @label Hidden */ uniform float u_after; /* @range 3 4 */ // @default -2
+"#;
+        let schema = ControlSchema::from_source(&[source]);
+        let names: Vec<&str> = schema.uniforms.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, ["u_far", "u_gap", "u_a", "u_b", "u_after"]);
+        let far = schema.get("u_far").unwrap();
+        assert_eq!((far.label.as_deref(), far.range), (Some("Far"), None));
+        assert_eq!(schema.get("u_gap").unwrap().range, None);
+        // Both declarations take the line's annotations; defaults are clamped
+        // into the range whichever comes first
+        assert_eq!(schema.get("u_a").unwrap().default, [1.0]);
+        assert_eq!(schema.get("u_b").unwrap().default, [1.0, 1.0]);
+        let after = schema.get("u_after").unwrap();
+        assert_eq!((after.range, after.default.clone(), after.label.clone()), (None, vec![-2.0], None));
+        assert!(schema.warnings.is_empty());
+    }
+}
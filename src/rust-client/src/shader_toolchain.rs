@@ -0,0 +1,1108 @@
+//! GLSL and WGSL validation and translation for studio shaders.
+//!
+//! `ShaderEngine` runs GLSL ES on WebGL while studio shaders are written in
+//! WGSL for WebGPU. Both languages go through naga here: sources are parsed and
+//! validated into a [`ShaderModule`] with line-accurate [`Diagnostic`]s, and a
+//! module can be written out as WGSL or as GLSL ES 1.00 / 3.00.
+//!
+//! naga reads Vulkan-flavoured GLSL 450, so WebGL sources are rewritten first:
+//! a `#version 450` header, bindings for loose uniforms, locations for
+//! attributes and varyings and a declared output for `gl_FragColor`. The
+//! rewrite keeps a map back to the source, so diagnostics point at what was
+//! written.
+//!
+//! `gl_FragCoord` starts at the bottom left and WGSL `@builtin(position)` at
+//! the top left. A fragment shader written in the other language reads its
+//! position as `resolution.y - y`, through a `u_resolution` or `resolution`
+//! uniform that is declared when the source has none.
+
+use naga::back::{glsl, wgsl};
+use naga::valid::{Capabilities, ModuleInfo, ValidationFlags, Validator};
+use naga::{
+    AddressSpace, BinaryOperator, Binding, BuiltIn, Expression, Function, GlobalVariable, Handle, Module,
+    ResourceBinding, Scalar, SourceLocation, Span, Statement, Type, TypeInner, VectorSize,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+/// Iteration cap of the `for` loops that stand in for `while` in GLSL ES 1.00
+pub const ES100_LOOP_LIMIT: u32 = 65536;
+
+/// Identifiers and built-ins GLSL ES 1.00 lacks but naga's GLSL ES 3.00 output may use
+const ES300_ONLY: [&str; 26] = [
+    "uint", "uvec2", "uvec3", "uvec4", "switch", "round", "roundEven", "trunc", "isnan", "isinf", "sinh", "cosh",
+    "tanh", "asinh", "acosh", "atanh", "modf", "inverse", "transpose", "determinant", "outerProduct",
+    "floatBitsToInt", "floatBitsToUint", "intBitsToFloat", "gl_VertexID", "gl_InstanceID",
+];
+
+/// Words WGSL allows as names but GLSL reserves
+const GLSL_RESERVED: [&str; 20] = [
+    "attribute", "varying", "uniform", "in", "out", "inout", "input", "output", "sample", "filter", "common",
+    "partition", "active", "highp", "mediump", "lowp", "precision", "invariant", "texture", "buffer",
+];
+
+/// Source language of a shader
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShaderLanguage {
+    Glsl,
+    Wgsl,
+}
+
+impl ShaderLanguage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShaderLanguage::Glsl => "glsl",
+            ShaderLanguage::Wgsl => "wgsl",
+        }
+    }
+}
+
+impl FromStr for ShaderLanguage {
+    type Err = ToolchainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "glsl" => Ok(ShaderLanguage::Glsl),
+            "wgsl" => Ok(ShaderLanguage::Wgsl),
+            _ => Err(ToolchainError::UnknownName(s.to_string())),
+        }
+    }
+}
+
+/// Pipeline stage a shader's entry point runs in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+impl ShaderStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShaderStage::Vertex => "vertex",
+            ShaderStage::Fragment => "fragment",
+            ShaderStage::Compute => "compute",
+        }
+    }
+
+    fn to_naga(self) -> naga::ShaderStage {
+        match self {
+            ShaderStage::Vertex => naga::ShaderStage::Vertex,
+            ShaderStage::Fragment => naga::ShaderStage::Fragment,
+            ShaderStage::Compute => naga::ShaderStage::Compute,
+        }
+    }
+}
+
+impl FromStr for ShaderStage {
+    type Err = ToolchainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "vertex" => Ok(ShaderStage::Vertex),
+            "fragment" => Ok(ShaderStage::Fragment),
+            "compute" => Ok(ShaderStage::Compute),
+            _ => Err(ToolchainError::UnknownName(s.to_string())),
+        }
+    }
+}
+
+/// GLSL dialect to write
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GlslVersion {
+    /// GLSL ES 1.00, for WebGL 1 and `ShaderEngine`
+    Es100,
+    /// GLSL ES 3.00, for WebGL 2
+    Es300,
+}
+
+/// A problem in a shader source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// 1-based source line, 0 when naga gives no position
+    pub line: usize,
+    /// 1-based byte column in the line
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "{}", self.message),
+            line => write!(f, "{}:{}: {}", line, self.column, self.message),
+        }
+    }
+}
+
+/// Errors from validating and translating shaders
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolchainError {
+    /// A language or stage name that is not known
+    UnknownName(String),
+    /// Source that does not parse or validate
+    Invalid(Vec<Diagnostic>),
+    MissingEntryPoint(ShaderStage),
+    /// A valid shader the target language cannot express
+    Unsupported(String),
+}
+
+impl fmt::Display for ToolchainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolchainError::UnknownName(name) => write!(f, "unknown shader language or stage '{}'", name),
+            ToolchainError::Invalid(diagnostics) => {
+                write!(f, "invalid shader")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{}", diagnostic)?;
+                }
+                Ok(())
+            }
+            ToolchainError::MissingEntryPoint(stage) => write!(f, "no {} entry point", stage.as_str()),
+            ToolchainError::Unsupported(msg) => write!(f, "cannot translate shader: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ToolchainError {}
+
+/// A parsed and validated shader with one entry point for its stage
+#[derive(Debug)]
+pub struct ShaderModule {
+    language: ShaderLanguage,
+    stage: ShaderStage,
+    entry_point: String,
+    module: Module,
+    info: ModuleInfo,
+}
+
+impl ShaderModule {
+    /// Parse and validate `source`; GLSL may be ES 1.00, ES 3.00 or 450
+    pub fn parse(source: &str, language: ShaderLanguage, stage: ShaderStage) -> Result<Self, ToolchainError> {
+        let shim = match language {
+            ShaderLanguage::Glsl => Some(GlslShim::rewrite(source, stage)?),
+            ShaderLanguage::Wgsl => None,
+        };
+        let text = shim.as_ref().map_or(source, |shim| shim.source.as_str());
+        let locate = |message: String, location: Option<SourceLocation>| match &shim {
+            Some(shim) => shim.diagnostic(message, location),
+            None => plain_diagnostic(message, location),
+        };
+
+        let module = match language {
+            ShaderLanguage::Glsl => {
+                let options = naga::front::glsl::Options::from(stage.to_naga());
+                let mut frontend = naga::front::glsl::Frontend::default();
+                frontend.parse(&options, text).map_err(|errors| {
+                    let diagnostics =
+                        errors.errors.iter().map(|e| locate(e.kind.to_string(), e.location(text))).collect();
+                    ToolchainError::Invalid(diagnostics)
+                })?
+            }
+            ShaderLanguage::Wgsl => naga::front::wgsl::parse_str(text)
+                .map_err(|e| ToolchainError::Invalid(vec![locate(e.message().to_string(), e.location(text))]))?,
+        };
+        let info = Validator::new(ValidationFlags::all(), Capabilities::default())
+            .validate(&module)
+            .map_err(|e| ToolchainError::Invalid(vec![locate(error_chain(e.as_inner()), e.location(text))]))?;
+
+        let entry_point = module
+            .entry_points
+            .iter()
+            .find(|ep| ep.stage == stage.to_naga())
+            .ok_or(ToolchainError::MissingEntryPoint(stage))?
+            .name
+            .clone();
+        Ok(ShaderModule { language, stage, entry_point, module, info })
+    }
+
+    pub fn stage(&self) -> ShaderStage {
+        self.stage
+    }
+
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+
+    /// Names of the uniforms, by group and binding
+    pub fn uniforms(&self) -> Vec<String> {
+        let mut uniforms: Vec<_> = self
+            .module
+            .global_variables
+            .iter()
+            .filter(|(_, var)| var.space == AddressSpace::Uniform)
+            .map(|(_, var)| (var.binding.as_ref().map(|b| (b.group, b.binding)), var.name.clone().unwrap_or_default()))
+            .collect();
+        uniforms.sort();
+        uniforms.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Prefix the uniforms whose names lack `prefix`, e.g. a studio `time`
+    /// becomes the `u_time` `ShaderEngine` drives
+    pub fn prefix_uniforms(&mut self, prefix: &str) {
+        for (_, var) in self.module.global_variables.iter_mut() {
+            if var.space != AddressSpace::Uniform {
+                continue;
+            }
+            if let Some(name) = var.name.as_mut().filter(|name| !name.starts_with(prefix)) {
+                name.insert_str(0, prefix);
+            }
+        }
+    }
+
+    pub fn to_wgsl(&self) -> Result<String, ToolchainError> {
+        let flipped = self.flipped_for(ShaderLanguage::Wgsl)?;
+        let (module, info) = flipped.as_ref().map_or((&self.module, &self.info), |(module, info)| (module, info));
+        let mut text = wgsl::write_string(module, info, wgsl::WriterFlags::empty())
+            .map_err(|e| ToolchainError::Unsupported(e.to_string()))?;
+        // naga appends `_` to names ending in a digit; uniforms keep theirs
+        for name in self.uniforms() {
+            let written = format!("{}_", name);
+            let is_used = |id: &str| text.lines().any(|line| identifiers(line).any(|(_, i)| i == id));
+            if name.ends_with(|c: char| c.is_ascii_digit()) && is_used(&written) && !is_used(&name) {
+                text = replace_identifier(&text, &written, &name);
+            }
+        }
+        Ok(text)
+    }
+
+    /// The entry point as GLSL ES with plain `uniform` declarations under
+    /// their source names, so they can be set with `uniform*` calls
+    pub fn to_glsl(&self, version: GlslVersion) -> Result<String, ToolchainError> {
+        let unsupported = |e: glsl::Error| ToolchainError::Unsupported(e.to_string());
+        let options = glsl::Options {
+            version: glsl::Version::Embedded { version: 300, is_webgl: true },
+            writer_flags: glsl::WriterFlags::empty(),
+            ..Default::default()
+        };
+        let pipeline = glsl::PipelineOptions {
+            shader_stage: self.stage.to_naga(),
+            entry_point: self.entry_point.clone(),
+            multiview: None,
+        };
+        let flipped = self.flipped_for(ShaderLanguage::Glsl)?;
+        let (module, info) = flipped.as_ref().map_or((&self.module, &self.info), |(module, info)| (module, info));
+        let mut text = String::new();
+        let reflection = {
+            let policies = naga::proc::BoundsCheckPolicies::default();
+            let mut writer =
+                glsl::Writer::new(&mut text, module, info, &options, &pipeline, policies).map_err(unsupported)?;
+            writer.write().map_err(unsupported)?
+        };
+
+        let text = loose_uniforms(module, &text, &reflection);
+        match version {
+            GlslVersion::Es300 => Ok(text),
+            GlslVersion::Es100 => downlevel_es100(&text, self.stage),
+        }
+    }
+
+    /// The module with its fragment position flipped for `target`, `None`
+    /// when the shader stays in its language or reads no position
+    fn flipped_for(&self, target: ShaderLanguage) -> Result<Option<(Module, ModuleInfo)>, ToolchainError> {
+        if target == self.language || self.stage != ShaderStage::Fragment {
+            return Ok(None);
+        }
+        let Some(module) = flip_frag_coord(&self.module, &self.entry_point) else {
+            return Ok(None);
+        };
+        let info = Validator::new(ValidationFlags::all(), Capabilities::default())
+            .validate(&module)
+            .map_err(|e| ToolchainError::Unsupported(error_chain(e.as_inner())))?;
+        Ok(Some((module, info)))
+    }
+}
+
+/// Turn the one-member uniform blocks naga writes into plain uniforms
+fn loose_uniforms(module: &Module, text: &str, reflection: &glsl::ReflectionInfo) -> String {
+    let mut renames = Vec::new();
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let block = line.strip_prefix("layout(std140) uniform ").and_then(|rest| {
+            let (block, member) = rest.split_once(" { ")?;
+            let member = member.strip_suffix("; };")?;
+            let (handle, _) = reflection.uniforms.iter().find(|(_, name)| name.as_str() == block)?;
+            Some((*handle, member))
+        });
+        let Some((handle, member)) = block else {
+            lines.push(line.to_string());
+            continue;
+        };
+        let (ty, declarator) = member.rsplit_once(' ').unwrap_or(("", member));
+        let written = declarator.split('[').next().unwrap_or(declarator);
+        let name = module.global_variables[handle].name.as_deref().unwrap_or(written);
+        let name = if name != written && is_free_name(name, text) {
+            renames.push((written.to_string(), name.to_string()));
+            name
+        } else {
+            written
+        };
+        lines.push(format!("uniform {} {}{};", ty, name, &declarator[written.len()..]));
+    }
+    let mut text = lines.join("\n") + "\n";
+    for (from, to) in renames {
+        text = replace_identifier(&text, &from, &to);
+    }
+    text
+}
+
+/// `module` with the fragment entry point moved into a plain function and
+/// called with its position input mirrored vertically, `None` when the entry
+/// point reads no position
+fn flip_frag_coord(module: &Module, entry_point: &str) -> Option<Module> {
+    let is_position = |binding: &Option<Binding>| matches!(binding, Some(Binding::BuiltIn(BuiltIn::Position { .. })));
+    let index =
+        module.entry_points.iter().position(|ep| ep.stage == naga::ShaderStage::Fragment && ep.name == entry_point)?;
+    let source = &module.entry_points[index].function;
+    // Per argument: `Some(None)` when it is the position, `Some(Some(member))` when a struct member is
+    let positions: Vec<Option<Option<u32>>> = source
+        .arguments
+        .iter()
+        .map(|arg| match &module.types[arg.ty].inner {
+            _ if is_position(&arg.binding) => Some(None),
+            TypeInner::Struct { members, .. } => {
+                members.iter().position(|member| is_position(&member.binding)).map(|member| Some(member as u32))
+            }
+            _ => None,
+        })
+        .collect();
+    if positions.iter().all(Option::is_none) {
+        return None;
+    }
+
+    let mut module = module.clone();
+    let span = Span::UNDEFINED;
+    let resolution = resolution_uniform(&mut module);
+    let mut outer = Function {
+        name: source.name.clone(),
+        arguments: source.arguments.clone(),
+        result: source.result.clone(),
+        ..Default::default()
+    };
+    let mut inner = std::mem::take(&mut module.entry_points[index].function);
+    inner.name = Some(format!("{}_unflipped", entry_point));
+    for arg in &mut inner.arguments {
+        arg.binding = None;
+    }
+    if let Some(result) = &mut inner.result {
+        result.binding = None;
+    }
+    let inner = module.functions.append(inner, span);
+
+    let expressions = &mut outer.expressions;
+    let args: Vec<_> =
+        (0..positions.len()).map(|i| expressions.append(Expression::FunctionArgument(i as u32), span)).collect();
+    let pointer = expressions.append(Expression::GlobalVariable(resolution), span);
+    let emitted = expressions.len();
+    let size = expressions.append(Expression::Load { pointer }, span);
+    let height = expressions.append(Expression::AccessIndex { base: size, index: 1 }, span);
+    let flip = |expressions: &mut naga::Arena<Expression>, position: Handle<Expression>, ty: Handle<Type>| {
+        let components = (0..4)
+            .map(|index| {
+                let component = expressions.append(Expression::AccessIndex { base: position, index }, span);
+                match index {
+                    1 => expressions.append(
+                        Expression::Binary { op: BinaryOperator::Subtract, left: height, right: component },
+                        span,
+                    ),
+                    _ => component,
+                }
+            })
+            .collect();
+        expressions.append(Expression::Compose { ty, components }, span)
+    };
+    let mut arguments = Vec::new();
+    for (i, position) in positions.iter().enumerate() {
+        let ty = outer.arguments[i].ty;
+        arguments.push(match position {
+            None => args[i],
+            Some(None) => flip(expressions, args[i], ty),
+            Some(Some(member)) => {
+                let TypeInner::Struct { members, .. } = &module.types[ty].inner else { unreachable!() };
+                let components = members
+                    .iter()
+                    .enumerate()
+                    .map(|(index, field)| {
+                        let base = args[i];
+                        let value = expressions.append(Expression::AccessIndex { base, index: index as u32 }, span);
+                        match index as u32 == *member {
+                            true => flip(expressions, value, field.ty),
+                            false => value,
+                        }
+                    })
+                    .collect();
+                expressions.append(Expression::Compose { ty, components }, span)
+            }
+        });
+    }
+    let emitted = expressions.range_from(emitted);
+    let result = outer.result.as_ref().map(|_| expressions.append(Expression::CallResult(inner), span));
+    outer.body.push(Statement::Emit(emitted), span);
+    outer.body.push(Statement::Call { function: inner, arguments, result }, span);
+    outer.body.push(Statement::Return { value: result }, span);
+    module.entry_points[index].function = outer;
+    Some(module)
+}
+
+/// The `vec2<f32>` resolution uniform of `module`, declared as `u_resolution`
+/// after the other group 0 bindings when there is none
+fn resolution_uniform(module: &mut Module) -> Handle<GlobalVariable> {
+    let vec2 = TypeInner::Vector { size: VectorSize::Bi, scalar: Scalar::F32 };
+    let found = module.global_variables.iter().find(|(_, var)| {
+        var.space == AddressSpace::Uniform
+            && matches!(var.name.as_deref(), Some("u_resolution" | "resolution"))
+            && module.types[var.ty].inner == vec2
+    });
+    if let Some((handle, _)) = found {
+        return handle;
+    }
+    let ty = module.types.insert(Type { name: None, inner: vec2 }, Span::UNDEFINED);
+    let binding = module
+        .global_variables
+        .iter()
+        .filter_map(|(_, var)| var.binding.as_ref().filter(|binding| binding.group == 0))
+        .map(|binding| binding.binding + 1)
+        .max()
+        .unwrap_or(0);
+    let uniform = GlobalVariable {
+        name: Some("u_resolution".to_string()),
+        space: AddressSpace::Uniform,
+        binding: Some(ResourceBinding { group: 0, binding }),
+        ty,
+        init: None,
+        memory_decorations: Default::default(),
+    };
+    module.global_variables.append(uniform, Span::UNDEFINED)
+}
+
+/// Diagnostics of `source`, empty when it is valid
+pub fn validate(source: &str, language: ShaderLanguage, stage: ShaderStage) -> Vec<Diagnostic> {
+    match ShaderModule::parse(source, language, stage) {
+        Ok(_) => vec![],
+        Err(ToolchainError::Invalid(diagnostics)) => diagnostics,
+        Err(e) => vec![Diagnostic { line: 0, column: 0, message: e.to_string() }],
+    }
+}
+
+/// `source` in the language `to`; GLSL is written as GLSL ES 1.00 for `ShaderEngine`
+pub fn translate(
+    source: &str,
+    from: ShaderLanguage,
+    to: ShaderLanguage,
+    stage: ShaderStage,
+) -> Result<String, ToolchainError> {
+    let module = ShaderModule::parse(source, from, stage)?;
+    match to {
+        ShaderLanguage::Wgsl => module.to_wgsl(),
+        ShaderLanguage::Glsl => module.to_glsl(GlslVersion::Es100),
+    }
+}
+
+/// Diagnostics of a shader as `[{ line, column, message }]`, empty when it is valid
+#[wasm_bindgen]
+pub fn validate_shader(source: &str, language: &str, stage: &str) -> Result<JsValue, JsValue> {
+    let js_error = |e: ToolchainError| JsValue::from_str(&e.to_string());
+    let diagnostics = validate(source, language.parse().map_err(js_error)?, stage.parse().map_err(js_error)?);
+    let json = serde_json::to_string(&diagnostics).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    js_sys::JSON::parse(&json)
+}
+
+/// Translate a shader between "glsl" and "wgsl"; GLSL comes out as GLSL ES 1.00
+#[wasm_bindgen]
+pub fn translate_shader(source: &str, from: &str, to: &str, stage: &str) -> Result<String, JsValue> {
+    let js_error = |e: ToolchainError| JsValue::from_str(&e.to_string());
+    let (from, to) = (from.parse().map_err(js_error)?, to.parse().map_err(js_error)?);
+    translate(source, from, to, stage.parse().map_err(js_error)?).map_err(js_error)
+}
+
+fn plain_diagnostic(message: String, location: Option<SourceLocation>) -> Diagnostic {
+    let (line, column) = location.map_or((0, 0), |l| (l.line_number as usize, l.line_position as usize));
+    Diagnostic { line, column, message }
+}
+
+/// An error and its causes, as validation errors nest the specific problem
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Dialect {
+    Es100,
+    Es300,
+    /// GLSL 440 and later, which naga reads as is
+    Desktop,
+}
+
+/// Where a line of rewritten GLSL came from
+struct LineMap {
+    /// 0-based source line
+    line: usize,
+    /// Replacements as (rewritten byte range, source column, source length)
+    edits: Vec<(Range<usize>, usize, usize)>,
+}
+
+impl LineMap {
+    /// 0-based source column of a rewritten one
+    fn column(&self, column: usize) -> usize {
+        let mut shift = 0isize;
+        for (range, original, original_len) in &self.edits {
+            if column < range.start {
+                break;
+            }
+            if column < range.end {
+                return *original;
+            }
+            shift += range.len() as isize - *original_len as isize;
+        }
+        (column as isize - shift).max(0) as usize
+    }
+}
+
+/// A replacement of `len` bytes at `at` in a source line
+struct Edit {
+    at: usize,
+    len: usize,
+    with: String,
+}
+
+/// GLSL rewritten into the GLSL 450 naga reads
+struct GlslShim {
+    source: String,
+    /// Origin of each rewritten line, `None` for inserted ones
+    lines: Vec<Option<LineMap>>,
+}
+
+/// Running counts of the declarations that get bindings and locations
+#[derive(Default)]
+struct Slots {
+    binding: u32,
+    inputs: u32,
+    varyings: u32,
+    outputs: u32,
+    depth: i32,
+}
+
+impl GlslShim {
+    fn rewrite(source: &str, stage: ShaderStage) -> Result<Self, ToolchainError> {
+        let masked = mask_comments(source);
+        let version_at = masked.iter().position(|line| line.trim_start().starts_with("#version"));
+        let dialect = match version_at.map(|at| masked[at].split_whitespace().skip(1).collect::<Vec<_>>()) {
+            None => Dialect::Es100,
+            Some(words) => match words[..] {
+                ["100", ..] => Dialect::Es100,
+                [_, "es", ..] => Dialect::Es300,
+                _ => Dialect::Desktop,
+            },
+        };
+
+        let mut shim = GlslShim { source: String::new(), lines: vec![] };
+        if dialect == Dialect::Desktop {
+            for (index, line) in source.lines().enumerate() {
+                shim.push(line.to_string(), Some(LineMap { line: index, edits: vec![] }));
+            }
+            return Ok(shim);
+        }
+
+        let uses_frag_color = masked.iter().any(|line| identifiers(line).any(|(_, id)| id == "gl_FragColor"));
+        let frag_color = (stage == ShaderStage::Fragment && dialect == Dialect::Es100 && uses_frag_color)
+            .then(|| unused_name(source, "fragColor"));
+        let header = |shim: &mut GlslShim| {
+            if let Some(name) = &frag_color {
+                shim.push(format!("layout(location = 0) out vec4 {};", name), None);
+            }
+        };
+        if version_at.is_none() {
+            shim.push("#version 450".to_string(), None);
+            header(&mut shim);
+        }
+
+        let mut slots = Slots::default();
+        for (index, line) in source.lines().enumerate() {
+            if Some(index) == version_at {
+                let edits = vec![(0..12, 0, line.len())];
+                shim.push("#version 450".to_string(), Some(LineMap { line: index, edits }));
+                header(&mut shim);
+                continue;
+            }
+            let mut edits = declaration_edits(&masked[index], index, dialect, stage, &mut slots)?;
+            if let Some(name) = &frag_color {
+                for (at, id) in identifiers(&masked[index]) {
+                    if id == "gl_FragColor" {
+                        edits.push(Edit { at, len: id.len(), with: name.clone() });
+                    }
+                }
+            }
+            edits.sort_by_key(|edit| edit.at);
+            shim.push_edited(index, line, edits);
+        }
+        Ok(shim)
+    }
+
+    fn push(&mut self, line: String, origin: Option<LineMap>) {
+        self.source.push_str(&line);
+        self.source.push('\n');
+        self.lines.push(origin);
+    }
+
+    fn push_edited(&mut self, index: usize, line: &str, edits: Vec<Edit>) {
+        let mut text = String::with_capacity(line.len());
+        let mut map = LineMap { line: index, edits: vec![] };
+        let mut copied = 0;
+        for edit in edits {
+            text.push_str(&line[copied..edit.at]);
+            let start = text.len();
+            text.push_str(&edit.with);
+            map.edits.push((start..text.len(), edit.at, edit.len));
+            copied = edit.at + edit.len;
+        }
+        text.push_str(&line[copied..]);
+        self.push(text, Some(map));
+    }
+
+    /// A diagnostic at a position in the rewritten source, moved to the source
+    fn diagnostic(&self, message: String, location: Option<SourceLocation>) -> Diagnostic {
+        let origin = location.and_then(|l| {
+            let map = self.lines.get((l.line_number as usize).checked_sub(1)?)?.as_ref()?;
+            Some((map.line + 1, map.column((l.line_position as usize).saturating_sub(1)) + 1))
+        });
+        let (line, column) = origin.unwrap_or((0, 0));
+        Diagnostic { line, column, message }
+    }
+}
+
+/// Edits giving the global declaration on a line (comments masked) the
+/// bindings and locations naga needs
+fn declaration_edits(
+    line: &str,
+    index: usize,
+    dialect: Dialect,
+    stage: ShaderStage,
+    slots: &mut Slots,
+) -> Result<Vec<Edit>, ToolchainError> {
+    let depth = slots.depth;
+    slots.depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+    let start = line.len() - line.trim_start().len();
+    let code = line.trim();
+    if code.starts_with("#extension") {
+        return Ok(vec![Edit { at: start, len: code.len(), with: String::new() }]);
+    }
+    if depth != 0 {
+        return Ok(vec![]);
+    }
+
+    let words: Vec<(usize, &str)> = identifiers(line).take(3).collect();
+    let word = |n: usize| words.get(n).map_or("", |(_, w)| *w);
+    let location = |count: &mut u32| {
+        *count += 1;
+        format!("layout(location = {}) ", *count - 1)
+    };
+    let binding = |slots: &mut Slots| {
+        slots.binding += 1;
+        format!("set = 0, binding = {}", slots.binding - 1)
+    };
+
+    if code.starts_with("layout(") && code.contains(" uniform ") && !code.contains("binding") {
+        let with = format!("{}, ", binding(slots));
+        return Ok(vec![Edit { at: start + "layout(".len(), len: 0, with }]);
+    }
+    if word(0) == "uniform" {
+        let (type_at, type_name) = match word(1) {
+            "lowp" | "mediump" | "highp" => words.get(2).copied().unwrap_or_default(),
+            _ => words.get(1).copied().unwrap_or_default(),
+        };
+        if type_name.starts_with("sampler") {
+            let message = "sampler uniforms are not supported".to_string();
+            return Err(ToolchainError::Invalid(vec![Diagnostic { line: index + 1, column: type_at + 1, message }]));
+        }
+        let type_end = type_at + type_name.len();
+        let declarators = match line.find(';') {
+            Some(end) if end < type_end => {
+                let message = "expected a type before ';'".to_string();
+                return Err(ToolchainError::Invalid(vec![Diagnostic { line: index + 1, column: end + 1, message }]));
+            }
+            end => end.map(|end| (end, &line[type_end..end])),
+        };
+        return Ok(match declarators {
+            // `uniform float a, b;` becomes one declaration per name
+            Some((end, names)) if !code.contains('{') && names.contains(',') => {
+                let declared = &line[start..type_end];
+                let with = names
+                    .split(',')
+                    .map(|name| format!("layout({}) {} {};", binding(slots), declared, name.trim()))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                vec![Edit { at: start, len: end + 1 - start, with }]
+            }
+            _ => vec![Edit { at: start, len: 0, with: format!("layout({}) ", binding(slots)) }],
+        });
+    }
+
+    let is_io = |w: &str| w == "in" || w == "out";
+    let qualified = matches!(word(0), "flat" | "smooth" | "centroid" | "noperspective") && is_io(word(1));
+    let (keyword_at, keyword) = match (dialect, word(0)) {
+        (Dialect::Es100, "attribute" | "varying") => words[0],
+        (Dialect::Es300, w) if is_io(w) => words[0],
+        (Dialect::Es300, _) if qualified => words[1],
+        _ => return Ok(vec![]),
+    };
+    let counter = match (stage, keyword) {
+        (ShaderStage::Vertex, "attribute" | "in") => &mut slots.inputs,
+        (ShaderStage::Fragment, "out") => &mut slots.outputs,
+        _ => &mut slots.varyings,
+    };
+    let layout = location(counter);
+    Ok(match keyword {
+        "attribute" => vec![Edit { at: keyword_at, len: keyword.len(), with: format!("{}in", layout) }],
+        "varying" => {
+            let direction = if stage == ShaderStage::Vertex { "out" } else { "in" };
+            vec![Edit { at: keyword_at, len: keyword.len(), with: format!("{}{}", layout, direction) }]
+        }
+        _ => vec![Edit { at: start, len: 0, with: layout }],
+    })
+}
+
+/// GLSL ES 3.00 from naga brought down to GLSL ES 1.00
+fn downlevel_es100(text: &str, stage: ShaderStage) -> Result<String, ToolchainError> {
+    let mut lines = Vec::new();
+    let mut outputs = Vec::new();
+    let mut loops = 0;
+    for line in text.lines() {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let code = line.trim_start();
+        if code.starts_with("#version") {
+            continue;
+        }
+        if code == "while(true) {" {
+            lines.push(format!(
+                "{}for (int loop_guard_{n} = 0; loop_guard_{n} < {}; loop_guard_{n}++) {{",
+                indent,
+                ES100_LOOP_LIMIT,
+                n = loops
+            ));
+            loops += 1;
+            continue;
+        }
+        let declaration = code.strip_prefix("layout(").and_then(|rest| rest.split_once(") ")).map(|(_, d)| d);
+        let declaration = declaration.or_else(|| code.strip_prefix("smooth ")).unwrap_or(code);
+        if code.starts_with("flat ") {
+            return Err(ToolchainError::Unsupported("flat varyings need GLSL ES 3.00".to_string()));
+        }
+        let rewritten = match (stage, declaration.split_once(' ')) {
+            (ShaderStage::Fragment, Some(("out", rest))) => {
+                outputs.push(rest.trim_end_matches(';').rsplit(' ').next().unwrap_or_default().to_string());
+                continue;
+            }
+            (ShaderStage::Vertex, Some(("in", rest))) => format!("attribute {}", rest),
+            (_, Some(("in" | "out", rest))) => format!("varying {}", rest),
+            _ => line.to_string(),
+        };
+        lines.push(rewritten);
+    }
+
+    let mut text = lines.join("\n") + "\n";
+    match &outputs[..] {
+        [] => {}
+        [output] => text = replace_identifier(&text, output, "gl_FragColor"),
+        _ => return Err(ToolchainError::Unsupported("several fragment outputs need GLSL ES 3.00".to_string())),
+    }
+    for line in text.lines().filter(|line| !line.trim_start().starts_with('#')) {
+        if let Some((_, id)) = identifiers(line).find(|(_, id)| ES300_ONLY.contains(id)) {
+            return Err(ToolchainError::Unsupported(format!("'{}' needs GLSL ES 3.00", id)));
+        }
+        let operators = line.replace("&&", "").replace("||", "");
+        if let Some(op) = ["%", "<<", ">>", "&", "|", "^", "~"].into_iter().find(|op| operators.contains(op)) {
+            return Err(ToolchainError::Unsupported(format!("operator '{}' needs GLSL ES 3.00", op)));
+        }
+    }
+    Ok(text)
+}
+
+/// Lines of `source` with comments blanked out, byte lengths unchanged
+fn mask_comments(source: &str) -> Vec<String> {
+    let mut in_block = false;
+    let blank = |c: &str| " ".repeat(c.len());
+    source
+        .lines()
+        .map(|line| {
+            let mut masked = String::with_capacity(line.len());
+            let mut rest = line;
+            while !rest.is_empty() {
+                if in_block {
+                    match rest.find("*/") {
+                        Some(end) => {
+                            masked.push_str(&blank(&rest[..end + 2]));
+                            rest = &rest[end + 2..];
+                            in_block = false;
+                        }
+                        None => {
+                            masked.push_str(&blank(rest));
+                            rest = "";
+                        }
+                    }
+                    continue;
+                }
+                let comment = [rest.find("//"), rest.find("/*")].into_iter().flatten().min();
+                match comment {
+                    Some(at) => {
+                        masked.push_str(&rest[..at]);
+                        rest = &rest[at..];
+                        if rest.starts_with("//") {
+                            masked.push_str(&blank(rest));
+                            rest = "";
+                        } else {
+                            masked.push_str("  ");
+                            rest = &rest[2..];
+                            in_block = true;
+                        }
+                    }
+                    None => {
+                        masked.push_str(rest);
+                        rest = "";
+                    }
+                }
+            }
+            masked
+        })
+        .collect()
+}
+
+/// Identifiers in `text` with their byte offsets, leaving out numbers and
+/// fields after a `.`
+fn identifiers(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let bytes = text.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut at = 0;
+    std::iter::from_fn(move || {
+        while at < bytes.len() {
+            let start = at;
+            if !is_ident(bytes[at]) {
+                at += 1;
+                continue;
+            }
+            while at < bytes.len() && is_ident(bytes[at]) {
+                at += 1;
+            }
+            let after_dot = start > 0 && bytes[start - 1] == b'.';
+            if !bytes[start].is_ascii_digit() && !after_dot {
+                return Some((start, &text[start..at]));
+            }
+        }
+        None
+    })
+}
+
+fn replace_identifier(text: &str, from: &str, to: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (at, id) in identifiers(text) {
+        if id == from {
+            out.push_str(&text[copied..at]);
+            out.push_str(to);
+            copied = at + id.len();
+        }
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+/// Whether a source name can replace naga's in GLSL output
+fn is_free_name(name: &str, text: &str) -> bool {
+    !name.starts_with("gl_")
+        && !name.contains("__")
+        && !GLSL_RESERVED.contains(&name)
+        && !text.lines().any(|line| identifiers(line).any(|(_, id)| id == name))
+}
+
+/// `base`, or `base` with a number appended, not already used in `source`
+fn unused_name(source: &str, base: &str) -> String {
+    let used = |name: &str| source.lines().any(|line| identifiers(line).any(|(_, id)| id == name));
+    (0..).map(|n| if n == 0 { base.to_string() } else { format!("{}{}", base, n) }).find(|n| !used(n)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu_renderer::FractalPreset;
+    use crate::webgpu_engine::fractal_fragment;
+
+    /// `WGSLShader::fractal_template` from the NEAR contract
+    const STUDIO_FRACTAL: &str = r#"
+@group(0) @binding(0) var<uniform> time: f32;
+@group(0) @binding(1) var<uniform> resolution: vec2<f32>;
+@group(0) @binding(2) var<uniform> zoom: f32;
+@group(0) @binding(3) var<uniform> center: vec2<f32>;
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    var uv = (pos.xy / resolution - 0.5) * zoom + center;
+    var z = vec2<f32>(0.0, 0.0);
+    var iterations = 0;
+
+    for (var i = 0; i < 100; i = i + 1) {
+        if (length(z) > 2.0) {
+            break;
+        }
+        z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + uv;
+        iterations = i;
+    }
+
+    let color = f32(iterations) / 100.0;
+    return vec4<f32>(vec3<f32>(color), 1.0);
+}
+"#;
+
+    /// `WGSLShader::default_vertex_shader` from the NEAR contract
+    const STUDIO_VERTEX: &str = r#"
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    var positions = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(-1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0)
+    );
+
+    let pos = positions[vertex_index];
+    return vec4<f32>(pos, 0.0, 1.0);
+}
+"#;
+
+    /// `FractalParams::generate_shader_code` for a Mandelbrot set with 100 iterations
+    const STUDIO_MANDELBROT_GLSL: &str = r#"
+            precision highp float;
+            uniform vec2 u_resolution;
+            uniform float u_zoom;
+            uniform vec2 u_center;
+            uniform int u_max_iter;
+
+            void main() {
+                vec2 c = (gl_FragCoord.xy / u_resolution - 0.5) * u_zoom + u_center;
+                vec2 z = vec2(0.0);
+                int iter = 0;
+
+                for (int i = 0; i < 100; i++) {
+                    if (length(z) > 2.0) break;
+                    z = vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+                    iter = i;
+                }
+
+                float color = float(iter) / float(u_max_iter);
+                gl_FragColor = vec4(vec3(color), 1.0);
+            }
+            "#;
+
+    #[test]
+    fn test_presets_round_trip_through_wgsl() {
+        for preset in FractalPreset::ALL {
+            let source = fractal_fragment(preset);
+            let module = ShaderModule::parse(source, ShaderLanguage::Glsl, ShaderStage::Fragment)
+                .unwrap_or_else(|e| panic!("{}: {}", preset.as_str(), e));
+            assert!(module.uniforms().contains(&"u_zoom".to_string()));
+
+            let wgsl = module.to_wgsl().unwrap();
+            assert!(wgsl.contains("var<uniform> u_zoom: f32;"), "{}", wgsl);
+            let back = ShaderModule::parse(&wgsl, ShaderLanguage::Wgsl, ShaderStage::Fragment).unwrap();
+            assert_eq!(back.uniforms(), module.uniforms());
+            let glsl = back.to_glsl(GlslVersion::Es100).unwrap();
+            assert!(!glsl.contains("#version") && glsl.contains("gl_FragColor = "), "{}", glsl);
+            assert!(glsl.contains("uniform vec3 u_color1;") && glsl.contains("for (int loop_guard_0 = 0;"));
+            assert!(validate(&glsl, ShaderLanguage::Glsl, ShaderStage::Fragment).is_empty(), "{}", glsl);
+        }
+
+        let (glsl, wgsl) = (ShaderLanguage::Glsl, ShaderLanguage::Wgsl);
+        let fragment = translate(STUDIO_MANDELBROT_GLSL, glsl, wgsl, ShaderStage::Fragment).unwrap();
+        assert!(fragment.contains("@fragment"));
+    }
+
+    #[test]
+    fn test_studio_wgsl_runs_as_glsl() {
+        let mut module = ShaderModule::parse(STUDIO_FRACTAL, ShaderLanguage::Wgsl, ShaderStage::Fragment).unwrap();
+        assert_eq!(module.entry_point(), "fs_main");
+        assert_eq!(module.uniforms(), ["time", "resolution", "zoom", "center"]);
+        module.prefix_uniforms("u_");
+
+        let es100 = module.to_glsl(GlslVersion::Es100).unwrap();
+        assert!(es100.contains("uniform vec2 u_resolution;") && es100.contains("gl_FragCoord"), "{}", es100);
+        let reparsed = ShaderModule::parse(&es100, ShaderLanguage::Glsl, ShaderStage::Fragment).unwrap();
+        assert_eq!(reparsed.uniforms(), ["u_resolution", "u_zoom", "u_center"]);
+
+        let es300 = module.to_glsl(GlslVersion::Es300).unwrap();
+        assert!(es300.starts_with("#version 300 es") && es300.contains("out vec4 "));
+        assert!(ShaderModule::parse(&es300, ShaderLanguage::Glsl, ShaderStage::Fragment).is_ok(), "{}", es300);
+
+        // WebGL 1 has no vertex index
+        let vertex = ShaderModule::parse(STUDIO_VERTEX, ShaderLanguage::Wgsl, ShaderStage::Vertex).unwrap();
+        assert!(vertex.to_glsl(GlslVersion::Es300).is_ok());
+        assert!(matches!(vertex.to_glsl(GlslVersion::Es100), Err(ToolchainError::Unsupported(_))));
+        assert_eq!(
+            ShaderModule::parse(STUDIO_VERTEX, ShaderLanguage::Wgsl, ShaderStage::Fragment).unwrap_err(),
+            ToolchainError::MissingEntryPoint(ShaderStage::Fragment)
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_point_at_the_source() {
+        let glsl = "precision highp float;\nuniform float u_zoom; // @range 0 1\n\nvoid main() {\n    \
+                    gl_FragColor = vec4(u_missing);\n}\n";
+        let diagnostics = validate(glsl, ShaderLanguage::Glsl, ShaderStage::Fragment);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (5, 25), "{:?}", diagnostics);
+        assert!(diagnostics[0].message.contains("u_missing"));
+
+        let wgsl = "@fragment\nfn fs_main() -> @location(0) vec4<f32> {\n    let x = 1.0\n    \
+                    return vec4<f32>(x);\n}\n";
+        let diagnostics = validate(wgsl, ShaderLanguage::Wgsl, ShaderStage::Fragment);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 4);
+
+        let glsl = "#version 300 es\nprecision highp float;\nout vec4 color;\nuniform sampler2D u_palette;\n";
+        let diagnostics = validate(glsl, ShaderLanguage::Glsl, ShaderStage::Fragment);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (4, 9));
+        assert_eq!("hlsl".parse::<ShaderLanguage>(), Err(ToolchainError::UnknownName("hlsl".to_string())));
+    }
+
+    #[test]
+    fn test_malformed_uniforms_are_diagnostics() {
+        for source in ["uniform; float x;", "uniform highp; float x;", "uniform"] {
+            let diagnostics = validate(source, ShaderLanguage::Glsl, ShaderStage::Fragment);
+            assert_eq!(diagnostics.len(), 1, "{}: {:?}", source, diagnostics);
+        }
+        let diagnostics = validate("uniform; float x;", ShaderLanguage::Glsl, ShaderStage::Fragment);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (1, 8));
+    }
+
+    #[test]
+    fn test_frag_coord_is_flipped_between_languages() {
+        let glsl = "precision highp float;\nvoid main() { gl_FragColor = vec4(gl_FragCoord.y); }\n";
+        let module = ShaderModule::parse(glsl, ShaderLanguage::Glsl, ShaderStage::Fragment).unwrap();
+        let wgsl = module.to_wgsl().unwrap();
+        assert!(wgsl.contains("var<uniform> u_resolution: vec2<f32>;"), "{}", wgsl);
+        assert!(wgsl.contains(".y - gl_FragCoord.y)"), "{}", wgsl);
+        assert!(module.to_glsl(GlslVersion::Es300).is_ok_and(|text| !text.contains("u_resolution")));
+
+        // The studio shader reuses its own resolution uniform
+        let studio = ShaderModule::parse(STUDIO_FRACTAL, ShaderLanguage::Wgsl, ShaderStage::Fragment).unwrap();
+        let es100 = studio.to_glsl(GlslVersion::Es100).unwrap();
+        assert!(es100.contains("(_e2.y - pos.y)") && !es100.contains("u_resolution"), "{}", es100);
+        assert!(!studio.to_wgsl().unwrap().contains(".y - "));
+
+        let back = ShaderModule::parse(&wgsl, ShaderLanguage::Wgsl, ShaderStage::Fragment).unwrap();
+        assert_eq!(back.uniforms(), ["u_resolution"]);
+        let glsl = back.to_glsl(GlslVersion::Es100).unwrap();
+        assert!(validate(&glsl, ShaderLanguage::Glsl, ShaderStage::Fragment).is_empty(), "{}", glsl);
+    }
+}
@@ -0,0 +1,346 @@
+//! Session-relative timeline of `CreativeData` and deterministic replay
+//!
+//! Every data point in a `CreativeSession` carries its offset in
+//! milliseconds from the session start. A [`SessionTimeline`] keeps those
+//! points ordered by offset, with ties in insertion order, so seeking,
+//! windowing and replay always visit them in the same order.
+//!
+//! Replay maps each offset to a playback time with integer-rounded
+//! arithmetic and hands the waiting to a [`ReplayClock`]. With a
+//! [`VirtualClock`] a replay never touches the wall clock, so rendering one
+//! frame per [`SessionTimeline::windows`] step yields the same frames every
+//! time.
+
+use crate::{CreativeData, CreativeSession};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// A data point stamped with its offset from the session start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedData {
+    pub offset_ms: u64,
+    pub data: CreativeData,
+}
+
+/// Data points falling in `[start_ms, start_ms + window_ms)`
+#[derive(Debug, Clone, Copy)]
+pub struct TimelineWindow<'a> {
+    pub index: usize,
+    pub start_ms: u64,
+    pub events: &'a [TimedData],
+}
+
+/// Waits for playback time to reach a point during a replay
+pub trait ReplayClock {
+    /// Block until `playback_ms` after the replay started
+    fn wait_until(&mut self, playback_ms: u64);
+}
+
+/// Clock that never waits, only records how far playback has got
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VirtualClock {
+    pub now_ms: u64,
+}
+
+impl ReplayClock for VirtualClock {
+    fn wait_until(&mut self, playback_ms: u64) {
+        self.now_ms = self.now_ms.max(playback_ms);
+    }
+}
+
+/// Clock that sleeps the current thread until each event is due
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    started: std::time::Instant,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SystemClock {
+    pub fn start() -> Self {
+        Self { started: std::time::Instant::now() }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ReplayClock for SystemClock {
+    fn wait_until(&mut self, playback_ms: u64) {
+        let due = self.started + std::time::Duration::from_millis(playback_ms);
+        let now = std::time::Instant::now();
+        if due > now {
+            std::thread::sleep(due - now);
+        }
+    }
+}
+
+/// Ordered data points of one or more sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTimeline {
+    start_time: DateTime<Utc>,
+    events: Vec<TimedData>,
+}
+
+impl SessionTimeline {
+    /// Empty timeline starting at `start_time`
+    pub fn new(start_time: DateTime<Utc>) -> Self {
+        Self { start_time, events: Vec::new() }
+    }
+
+    pub fn start_time(&self) -> DateTime<Utc> {
+        self.start_time
+    }
+
+    pub fn events(&self) -> &[TimedData] {
+        &self.events
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Offset of the last data point
+    pub fn duration_ms(&self) -> u64 {
+        self.events.last().map_or(0, |e| e.offset_ms)
+    }
+
+    /// Insert a data point after any others at the same offset
+    pub fn insert(&mut self, offset_ms: u64, data: CreativeData) {
+        let at = self.events.partition_point(|e| e.offset_ms <= offset_ms);
+        self.events.insert(at, TimedData { offset_ms, data });
+    }
+
+    /// Index of the first data point at or after `offset_ms`
+    pub fn position(&self, offset_ms: u64) -> usize {
+        self.events.partition_point(|e| e.offset_ms < offset_ms)
+    }
+
+    /// Data points from `offset_ms` onwards
+    pub fn seek(&self, offset_ms: u64) -> &[TimedData] {
+        &self.events[self.position(offset_ms)..]
+    }
+
+    /// Data points in `[start_ms, end_ms)`
+    pub fn range(&self, start_ms: u64, end_ms: u64) -> &[TimedData] {
+        let start = self.position(start_ms);
+        let end = self.position(end_ms).max(start);
+        &self.events[start..end]
+    }
+
+    /// Consecutive windows of `window_ms` (at least 1) covering the whole
+    /// timeline, empty ones included, so window `n` always starts at
+    /// `n * window_ms`
+    ///
+    /// A window that would end past `u64::MAX` runs to the end of the
+    /// timeline.
+    pub fn windows(&self, window_ms: u64) -> impl Iterator<Item = TimelineWindow<'_>> + '_ {
+        let window_ms = window_ms.max(1);
+        let last = if self.events.is_empty() { None } else { Some(self.duration_ms() / window_ms) };
+        last.into_iter().flat_map(|last| 0..=last).map(move |index| {
+            let start_ms = index * window_ms;
+            let events = match start_ms.checked_add(window_ms) {
+                Some(end_ms) => self.range(start_ms, end_ms),
+                None => self.seek(start_ms),
+            };
+            TimelineWindow { index: index as usize, start_ms, events }
+        })
+    }
+
+    /// Combine two timelines on the wall clock
+    ///
+    /// The result starts at the earlier of the two start times. At equal
+    /// offsets, data points from `self` come before those from `other`.
+    /// Fails if a shifted offset no longer fits in a `u64`.
+    pub fn merge(&self, other: &SessionTimeline) -> Result<SessionTimeline, Box<dyn Error>> {
+        let start_time = self.start_time.min(other.start_time);
+        let shifted = |timeline: &SessionTimeline| -> Result<Vec<TimedData>, Box<dyn Error>> {
+            let shift = (timeline.start_time - start_time).num_milliseconds().max(0) as u64;
+            timeline
+                .events
+                .iter()
+                .map(|event| {
+                    let offset_ms = event.offset_ms.checked_add(shift).ok_or_else(|| {
+                        format!("Offset {} ms shifted by {} ms overflows the merged timeline", event.offset_ms, shift)
+                    })?;
+                    Ok(TimedData { offset_ms, data: event.data.clone() })
+                })
+                .collect()
+        };
+        let (ours, theirs) = (shifted(self)?, shifted(other)?);
+
+        let mut events = Vec::with_capacity(ours.len() + theirs.len());
+        let (mut a, mut b) = (ours.into_iter().peekable(), theirs.into_iter().peekable());
+        loop {
+            let take_ours = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) => x.offset_ms <= y.offset_ms,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let event = if take_ours { a.next() } else { b.next() };
+            events.push(event.expect("peeked"));
+        }
+
+        Ok(SessionTimeline { start_time, events })
+    }
+
+    /// Replay data points from `from_ms` onwards through `on_event`
+    ///
+    /// `speed` scales playback: 1.0 is the original pace, 2.0 twice as fast.
+    /// `on_event` receives each data point with its playback time relative
+    /// to `from_ms`, after `clock` has waited for it.
+    pub fn replay<F>(
+        &self,
+        from_ms: u64,
+        speed: f32,
+        clock: &mut dyn ReplayClock,
+        mut on_event: F,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: FnMut(u64, &TimedData),
+    {
+        if !speed.is_finite() || speed <= 0.0 {
+            return Err(format!("Replay speed must be positive, got {}", speed).into());
+        }
+
+        for event in self.seek(from_ms) {
+            let playback_ms = ((event.offset_ms - from_ms) as f64 / speed as f64).round() as u64;
+            clock.wait_until(playback_ms);
+            on_event(playback_ms, event);
+        }
+        Ok(())
+    }
+}
+
+impl CreativeSession {
+    /// Milliseconds between the session start and `at`, zero if earlier
+    pub fn offset_of(&self, at: DateTime<Utc>) -> u64 {
+        (at - self.start_time).num_milliseconds().max(0) as u64
+    }
+
+    /// Wall-clock time of a session-relative offset
+    ///
+    /// Fails if the time falls outside what `DateTime` can represent.
+    pub fn time_at(&self, offset_ms: u64) -> Result<DateTime<Utc>, Box<dyn Error>> {
+        let out_of_range = || format!("Offset {} ms is past the latest representable time", offset_ms);
+        let offset = i64::try_from(offset_ms).ok().and_then(Duration::try_milliseconds).ok_or_else(out_of_range)?;
+        Ok(self.start_time.checked_add_signed(offset).ok_or_else(out_of_range)?)
+    }
+
+    /// Snapshot of the session's data points as a timeline
+    pub fn timeline(&self) -> SessionTimeline {
+        SessionTimeline {
+            start_time: self.start_time,
+            events: self.data_points.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_emotional_data;
+
+    fn emotional(valence: f32) -> CreativeData {
+        CreativeData::Emotional(generate_emotional_data(valence, 0.5, 0.5, vec![]))
+    }
+
+    fn valence(event: &TimedData) -> f32 {
+        match &event.data {
+            CreativeData::Emotional(e) => e.valence,
+            _ => panic!("expected emotional data"),
+        }
+    }
+
+    fn timeline() -> SessionTimeline {
+        let mut session = CreativeSession::new();
+        session.add_data_at(250, emotional(0.3));
+        session.add_data_at(0, emotional(0.0));
+        session.add_data_at(100, emotional(0.1));
+        session.add_data_at(100, emotional(0.2));
+        session.timeline()
+    }
+
+    #[test]
+    fn test_insertion_keeps_order() {
+        let timeline = timeline();
+        let order: Vec<f32> = timeline.events().iter().map(valence).collect();
+        assert_eq!(order, vec![0.0, 0.1, 0.2, 0.3]);
+        assert_eq!(timeline.duration_ms(), 250);
+    }
+
+    #[test]
+    fn test_seek_and_windows() {
+        let timeline = timeline();
+        assert_eq!(timeline.seek(100).len(), 3);
+        assert_eq!(timeline.seek(101).len(), 1);
+        assert!(timeline.seek(251).is_empty());
+        assert_eq!(timeline.range(0, 100).len(), 1);
+
+        let sizes: Vec<usize> = timeline.windows(100).map(|w| w.events.len()).collect();
+        assert_eq!(sizes, vec![1, 2, 1]);
+        let starts: Vec<u64> = timeline.windows(100).map(|w| w.start_ms).collect();
+        assert_eq!(starts, vec![0, 100, 200]);
+    }
+
+    #[test]
+    fn test_merge_aligns_start_times() {
+        let first = timeline();
+        let mut second = SessionTimeline::new(first.start_time() + Duration::milliseconds(50));
+        second.insert(50, emotional(-0.5));
+
+        let merged = first.merge(&second).unwrap();
+        assert_eq!(merged.start_time(), first.start_time());
+        assert_eq!(merged.len(), 5);
+        let order: Vec<(u64, f32)> = merged.events().iter().map(|e| (e.offset_ms, valence(e))).collect();
+        // Second session's point lands at 50 + 50 = 100, after first's ties
+        assert_eq!(order[3], (100, -0.5));
+    }
+
+    #[test]
+    fn test_replay_is_deterministic() {
+        let timeline = timeline();
+        let run = |speed: f32| {
+            let mut clock = VirtualClock::default();
+            let mut frames = Vec::new();
+            timeline
+                .replay(100, speed, &mut clock, |at, event| frames.push((at, valence(event))))
+                .unwrap();
+            (frames, clock.now_ms)
+        };
+
+        let (frames, end) = run(2.0);
+        assert_eq!(frames, vec![(0, 0.1), (0, 0.2), (75, 0.3)]);
+        assert_eq!(end, 75);
+        assert_eq!(run(2.0), (frames, end));
+        assert_eq!(run(1.0).1, 150);
+
+        assert!(timeline.replay(0, 0.0, &mut VirtualClock::default(), |_, _| {}).is_err());
+    }
+
+    #[test]
+    fn test_offsets_near_u64_max() {
+        let mut session = CreativeSession::new();
+        session.add_data_at(0, emotional(0.0));
+        session.add_data_at(u64::MAX, emotional(0.9));
+        let timeline = session.timeline();
+
+        let sizes: Vec<usize> = timeline.windows(u64::MAX).map(|w| w.events.len()).collect();
+        assert_eq!(sizes, vec![1, 1]);
+        assert_eq!(timeline.windows(1 << 63).last().unwrap().events.len(), 1);
+
+        let mut later = SessionTimeline::new(timeline.start_time() + Duration::milliseconds(1));
+        later.insert(0, emotional(0.5));
+        assert!(timeline.merge(&later).is_ok());
+        later.insert(u64::MAX, emotional(0.5));
+        assert!(timeline.merge(&later).is_err());
+
+        assert_eq!(session.time_at(1000).unwrap(), session.start_time + Duration::seconds(1));
+        assert!(session.time_at(u64::MAX).is_err());
+        assert!(session.time_at(i64::MAX as u64).is_err());
+    }
+}
@@ -0,0 +1,493 @@
+//! Keyframe animation of shader uniforms
+//!
+//! A [`UniformTimeline`] holds one keyframed track per uniform, named
+//! markers and an optional loop region. `ShaderEngine::render` advances it
+//! by each frame's delta and applies the sampled values.
+//!
+//! The playhead counts whole microseconds, so the same frame deltas always
+//! land on the same positions and a stored performance replays exactly.
+//! Timelines export as versioned JSON; the playhead is not part of it.
+
+use crate::webgpu_engine::UniformValue;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Layout version of exported timelines
+pub const ANIMATION_VERSION: u32 = 1;
+
+/// Latest keyframe, marker or loop time, the last millisecond the
+/// microsecond playhead can count to
+pub const MAX_TIME_MS: u64 = u64::MAX / 1000;
+
+/// Errors from building and importing timelines
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimationError {
+    UnsupportedVersion(u32),
+    /// Loop regions must end after they start
+    InvalidLoop { start_ms: u64, end_ms: u64 },
+    /// Bezier control points with x outside 0..=1
+    InvalidEasing,
+    UnknownMarker(String),
+    /// A time past [`MAX_TIME_MS`]
+    TimeOutOfRange(u64),
+    Format(String),
+}
+
+impl fmt::Display for AnimationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimationError::UnsupportedVersion(v) => write!(f, "unsupported animation version {}", v),
+            AnimationError::InvalidLoop { start_ms, end_ms } => {
+                write!(f, "loop region {}..{} ms is empty", start_ms, end_ms)
+            }
+            AnimationError::InvalidEasing => write!(f, "cubic-bezier x coordinates must be within 0 and 1"),
+            AnimationError::UnknownMarker(name) => write!(f, "no marker named '{}'", name),
+            AnimationError::TimeOutOfRange(time_ms) => {
+                write!(f, "time {} ms is past the last playable {} ms", time_ms, MAX_TIME_MS)
+            }
+            AnimationError::Format(msg) => write!(f, "malformed animation: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AnimationError {}
+
+impl From<serde_json::Error> for AnimationError {
+    fn from(e: serde_json::Error) -> Self {
+        AnimationError::Format(e.to_string())
+    }
+}
+
+/// How a track moves from a keyframe to the next one
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Hold the value until the next keyframe
+    Step,
+    /// CSS `cubic-bezier(x1, y1, x2, y2)`
+    CubicBezier([f32; 4]),
+}
+
+impl Easing {
+    pub const EASE: Easing = Easing::CubicBezier([0.25, 0.1, 0.25, 1.0]);
+    pub const EASE_IN: Easing = Easing::CubicBezier([0.42, 0.0, 1.0, 1.0]);
+    pub const EASE_OUT: Easing = Easing::CubicBezier([0.0, 0.0, 0.58, 1.0]);
+    pub const EASE_IN_OUT: Easing = Easing::CubicBezier([0.42, 0.0, 0.58, 1.0]);
+
+    /// Eased progress for linear progress `x` in 0..=1
+    pub fn apply(&self, x: f64) -> f64 {
+        match self {
+            Easing::Linear => x,
+            Easing::Step => 0.0,
+            Easing::CubicBezier([x1, y1, x2, y2]) => {
+                let (x1, y1, x2, y2) = (*x1 as f64, *y1 as f64, *x2 as f64, *y2 as f64);
+                bezier(y1, y2, bezier_t(x1, x2, x))
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), AnimationError> {
+        match self {
+            Easing::CubicBezier([x1, _, x2, _]) if !(0.0..=1.0).contains(x1) || !(0.0..=1.0).contains(x2) => {
+                Err(AnimationError::InvalidEasing)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// One coordinate of a cubic Bezier from (0,0) to (1,1) at `t`
+fn bezier(p1: f64, p2: f64, t: f64) -> f64 {
+    let u = 1.0 - t;
+    3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+}
+
+/// Curve parameter whose x is `x`, by bisection; x grows with t while the
+/// control points' x stay within 0..=1
+fn bezier_t(x1: f64, x2: f64, x: f64) -> f64 {
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..48 {
+        let mid = (low + high) / 2.0;
+        if bezier(x1, x2, mid) < x {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// A uniform's value at a point in time, and the easing towards the next keyframe
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    pub time_ms: u64,
+    pub value: UniformValue,
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+/// Keyframes of one uniform, in time order
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UniformTrack {
+    pub uniform: String,
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl UniformTrack {
+    pub fn new(uniform: &str) -> Self {
+        UniformTrack { uniform: uniform.to_string(), keyframes: Vec::new() }
+    }
+
+    /// Add a keyframe, replacing one at the same time
+    pub fn insert(&mut self, keyframe: Keyframe) {
+        match self.keyframes.binary_search_by_key(&keyframe.time_ms, |k| k.time_ms) {
+            Ok(i) => self.keyframes[i] = keyframe,
+            Err(i) => self.keyframes.insert(i, keyframe),
+        }
+    }
+
+    /// Value at `time_ms`; before the first and after the last keyframe the
+    /// track holds their values
+    pub fn sample(&self, time_ms: f64) -> Option<UniformValue> {
+        let next = self.keyframes.partition_point(|k| (k.time_ms as f64) <= time_ms);
+        let (from, to) = match next {
+            0 => return self.keyframes.first().map(|k| k.value.clone()),
+            n if n == self.keyframes.len() => return Some(self.keyframes[n - 1].value.clone()),
+            n => (&self.keyframes[n - 1], &self.keyframes[n]),
+        };
+        let progress = (time_ms - from.time_ms as f64) / (to.time_ms - from.time_ms) as f64;
+        Some(interpolate(&from.value, &to.value, from.easing.apply(progress)))
+    }
+}
+
+/// `from` moved `t` of the way to `to`. Ints round, bools and values of
+/// different types switch at the next keyframe.
+fn interpolate(from: &UniformValue, to: &UniformValue, t: f64) -> UniformValue {
+    let lerp = |a: f32, b: f32| (a as f64 + (b as f64 - a as f64) * t) as f32;
+    let lerp_n = |a: &[f32], b: &[f32]| -> Vec<f32> { a.iter().zip(b).map(|(a, b)| lerp(*a, *b)).collect() };
+    match (from, to) {
+        (UniformValue::Float(a), UniformValue::Float(b)) => UniformValue::Float(lerp(*a, *b)),
+        (UniformValue::Vec2(a), UniformValue::Vec2(b)) => UniformValue::Vec2(lerp_n(a, b).try_into().expect("2")),
+        (UniformValue::Vec3(a), UniformValue::Vec3(b)) => UniformValue::Vec3(lerp_n(a, b).try_into().expect("3")),
+        (UniformValue::Vec4(a), UniformValue::Vec4(b)) => UniformValue::Vec4(lerp_n(a, b).try_into().expect("4")),
+        (UniformValue::Int(a), UniformValue::Int(b)) => {
+            UniformValue::Int((*a as f64 + (*b as f64 - *a as f64) * t).round() as i32)
+        }
+        _ => from.clone(),
+    }
+}
+
+/// Named cue point, e.g. the drop of a track
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub name: String,
+    pub time_ms: u64,
+}
+
+/// Region the playhead wraps around in, `[start_ms, end_ms)`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoopRegion {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Keyframed uniform tracks with markers and looping.
+///
+/// Serializes with its layout version, wherever it is embedded; the
+/// playhead is left out and starts at zero when read back.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "VersionedTimeline", into = "VersionedTimeline")]
+pub struct UniformTimeline {
+    pub tracks: Vec<UniformTrack>,
+    pub markers: Vec<Marker>,
+    pub loop_region: Option<LoopRegion>,
+    /// Playhead in microseconds
+    position_us: u64,
+}
+
+/// Serialized form of [`UniformTimeline`]
+#[derive(Serialize, Deserialize)]
+struct VersionedTimeline {
+    version: u32,
+    tracks: Vec<UniformTrack>,
+    #[serde(default)]
+    markers: Vec<Marker>,
+    #[serde(default, rename = "loop", skip_serializing_if = "Option::is_none")]
+    loop_region: Option<LoopRegion>,
+}
+
+impl From<UniformTimeline> for VersionedTimeline {
+    fn from(timeline: UniformTimeline) -> Self {
+        VersionedTimeline {
+            version: ANIMATION_VERSION,
+            tracks: timeline.tracks,
+            markers: timeline.markers,
+            loop_region: timeline.loop_region,
+        }
+    }
+}
+
+impl TryFrom<VersionedTimeline> for UniformTimeline {
+    type Error = AnimationError;
+
+    fn try_from(versioned: VersionedTimeline) -> Result<Self, Self::Error> {
+        if versioned.version != ANIMATION_VERSION {
+            return Err(AnimationError::UnsupportedVersion(versioned.version));
+        }
+        let mut timeline = UniformTimeline { tracks: versioned.tracks, markers: versioned.markers, ..Self::default() };
+        for track in &mut timeline.tracks {
+            for keyframe in &track.keyframes {
+                check_time(keyframe.time_ms)?;
+                keyframe.easing.validate()?;
+            }
+            track.keyframes.sort_by_key(|k| k.time_ms);
+        }
+        for marker in &timeline.markers {
+            check_time(marker.time_ms)?;
+        }
+        timeline.markers.sort_by_key(|m| m.time_ms);
+        timeline.set_loop(versioned.loop_region)?;
+        Ok(timeline)
+    }
+}
+
+fn check_time(time_ms: u64) -> Result<(), AnimationError> {
+    match time_ms {
+        0..=MAX_TIME_MS => Ok(()),
+        _ => Err(AnimationError::TimeOutOfRange(time_ms)),
+    }
+}
+
+fn marker_us(marker: &Marker) -> u64 {
+    marker.time_ms.saturating_mul(1000)
+}
+
+impl UniformTimeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a keyframe to `uniform`'s track, creating it if needed
+    pub fn add_keyframe(
+        &mut self,
+        uniform: &str,
+        time_ms: u64,
+        value: UniformValue,
+        easing: Easing,
+    ) -> Result<(), AnimationError> {
+        check_time(time_ms)?;
+        easing.validate()?;
+        let keyframe = Keyframe { time_ms, value, easing };
+        match self.tracks.iter_mut().find(|t| t.uniform == uniform) {
+            Some(track) => track.insert(keyframe),
+            None => {
+                let mut track = UniformTrack::new(uniform);
+                track.insert(keyframe);
+                self.tracks.push(track);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_marker(&mut self, name: &str, time_ms: u64) {
+        let at = self.markers.partition_point(|m| m.time_ms <= time_ms);
+        self.markers.insert(at, Marker { name: name.to_string(), time_ms });
+    }
+
+    /// Loop over `[start_ms, end_ms)`, or play through with `None`
+    pub fn set_loop(&mut self, region: Option<LoopRegion>) -> Result<(), AnimationError> {
+        if let Some(LoopRegion { start_ms, end_ms }) = region {
+            if end_ms <= start_ms {
+                return Err(AnimationError::InvalidLoop { start_ms, end_ms });
+            }
+            check_time(end_ms)?;
+        }
+        self.loop_region = region;
+        Ok(())
+    }
+
+    /// Time of the last keyframe or marker
+    pub fn duration_ms(&self) -> u64 {
+        let keyframes = self.tracks.iter().filter_map(|t| t.keyframes.last()).map(|k| k.time_ms);
+        keyframes.chain(self.markers.iter().map(|m| m.time_ms)).max().unwrap_or(0)
+    }
+
+    pub fn position_ms(&self) -> f64 {
+        self.position_us as f64 / 1000.0
+    }
+
+    pub fn seek(&mut self, time_ms: f64) {
+        self.position_us = (time_ms.max(0.0) * 1000.0).round() as u64;
+    }
+
+    /// Jump to the first marker named `name`
+    pub fn seek_marker(&mut self, name: &str) -> Result<(), AnimationError> {
+        let marker = self.markers.iter().find(|m| m.name == name);
+        let time_ms = marker.ok_or_else(|| AnimationError::UnknownMarker(name.to_string()))?.time_ms;
+        self.position_us = time_ms.saturating_mul(1000);
+        Ok(())
+    }
+
+    /// Move the playhead by `delta_seconds`, wrapping inside the loop region,
+    /// and return the markers passed on the way, in playback order. Markers
+    /// passed on whole laps skipped in one step are reported once, not per lap.
+    pub fn advance(&mut self, delta_seconds: f32) -> Vec<Marker> {
+        let mut remaining = (delta_seconds.max(0.0) as f64 * 1_000_000.0).round() as u64;
+        let mut at = self.position_us;
+        let mut passed = Vec::new();
+        if let Some(LoopRegion { start_ms, end_ms }) = self.loop_region {
+            let (start, end) = (start_ms.saturating_mul(1000), end_ms.saturating_mul(1000));
+            if at < end && remaining >= end - at {
+                // Each wrap lands on the loop start, passing the markers there
+                let at_start = self.markers.iter().filter(|m| marker_us(m) == start);
+                passed.extend(self.markers_between(at, end - 1).chain(at_start).cloned());
+                remaining -= end - at;
+                at = start;
+
+                let lap = end - start;
+                let at_start = self.markers.iter().filter(|m| marker_us(m) == start);
+                if remaining >= lap {
+                    passed.extend(self.markers_between(start, end - 1).chain(at_start).cloned());
+                }
+                remaining %= lap;
+            }
+        }
+        let to = at.saturating_add(remaining);
+        passed.extend(self.markers_between(at, to).cloned());
+        self.position_us = to;
+        passed
+    }
+
+    /// Markers with `from_us < time <= to_us`
+    fn markers_between(&self, from_us: u64, to_us: u64) -> impl Iterator<Item = &Marker> {
+        self.markers.iter().filter(move |m| marker_us(m) > from_us && marker_us(m) <= to_us)
+    }
+
+    /// Every track's value at the playhead
+    pub fn values(&self) -> Vec<(&str, UniformValue)> {
+        let at = self.position_ms();
+        self.tracks.iter().filter_map(|t| Some((t.uniform.as_str(), t.sample(at)?))).collect()
+    }
+
+    pub fn to_json(&self) -> Result<String, AnimationError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Import a timeline exported by [`to_json`](Self::to_json); the playhead starts at zero
+    pub fn from_json(json: &str) -> Result<Self, AnimationError> {
+        Self::try_from(serde_json::from_str::<VersionedTimeline>(json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn float(value: Option<UniformValue>) -> f32 {
+        match value {
+            Some(UniformValue::Float(f)) => f,
+            other => panic!("not a float: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_easings_shape_the_segment_between_keyframes() {
+        let mut timeline = UniformTimeline::new();
+        timeline.add_keyframe("u_zoom", 0, UniformValue::Float(1.0), Easing::Linear).unwrap();
+        timeline.add_keyframe("u_zoom", 1000, UniformValue::Float(3.0), Easing::EASE_IN_OUT).unwrap();
+        timeline.add_keyframe("u_zoom", 2000, UniformValue::Float(5.0), Easing::Step).unwrap();
+        timeline.add_keyframe("u_zoom", 3000, UniformValue::Float(0.0), Easing::Linear).unwrap();
+        let zoom = &timeline.tracks[0];
+
+        assert_eq!(float(zoom.sample(250.0)), 1.5);
+        let eased = float(zoom.sample(1250.0));
+        assert!(eased > 3.0 && eased < 3.5, "{}", eased);
+        assert!((float(zoom.sample(1500.0)) - 4.0).abs() < 1e-4);
+        assert_eq!(float(zoom.sample(2999.0)), 5.0);
+        assert_eq!(float(zoom.sample(5000.0)), 0.0);
+
+        timeline.add_keyframe("u_max_iter", 0, UniformValue::Int(10), Easing::Linear).unwrap();
+        timeline.add_keyframe("u_max_iter", 1000, UniformValue::Int(20), Easing::Linear).unwrap();
+        timeline.seek(450.0);
+        let values = timeline.values();
+        assert!(matches!(values[1], ("u_max_iter", UniformValue::Int(15))));
+        let overshoot = Easing::CubicBezier([1.5, 0.0, 0.5, 1.0]);
+        assert!(timeline.add_keyframe("u_zoom", 0, UniformValue::Float(0.0), overshoot).is_err());
+    }
+
+    #[test]
+    fn test_loops_wrap_and_report_markers() {
+        let mut timeline = UniformTimeline::new();
+        timeline.add_keyframe("u_zoom", 0, UniformValue::Float(0.0), Easing::Linear).unwrap();
+        timeline.add_keyframe("u_zoom", 4000, UniformValue::Float(4.0), Easing::Linear).unwrap();
+        timeline.add_marker("drop", 3000);
+        timeline.add_marker("intro", 500);
+        timeline.set_loop(Some(LoopRegion { start_ms: 1000, end_ms: 4000 })).unwrap();
+        assert_eq!(timeline.duration_ms(), 4000);
+
+        let names = |markers: Vec<Marker>| markers.into_iter().map(|m| m.name).collect::<Vec<_>>();
+        assert_eq!(names(timeline.advance(0.6)), ["intro"]);
+        assert_eq!(names(timeline.advance(2.0)), Vec::<String>::new());
+        assert_eq!(names(timeline.advance(0.5)), ["drop"]);
+        // 3100 ms + 1500 ms wraps to 1600 ms
+        assert!(timeline.advance(1.5).is_empty());
+        assert_eq!(timeline.position_ms(), 1600.0);
+        assert_eq!(float(timeline.values().pop().map(|(_, v)| v)), 1.6);
+        // Two and a half laps pass the drop three times
+        assert_eq!(names(timeline.advance(7.5)), ["drop", "drop", "drop"]);
+        assert_eq!(timeline.position_ms(), 3100.0);
+
+        // Laps are counted, not stepped through, and a long step reports
+        // each lap's markers once
+        assert_eq!(names(timeline.advance(3000.0)), ["drop", "drop"]);
+        assert_eq!(timeline.position_ms(), 3100.0);
+        timeline.set_loop(Some(LoopRegion { start_ms: 1000, end_ms: 1001 })).unwrap();
+        timeline.seek(1000.0);
+        assert!(timeline.advance(1.0e7).is_empty());
+        assert_eq!(timeline.position_ms(), 1000.0);
+        timeline.set_loop(Some(LoopRegion { start_ms: 1000, end_ms: 4000 })).unwrap();
+
+        timeline.seek_marker("intro").unwrap();
+        assert_eq!(timeline.position_ms(), 500.0);
+        assert!(timeline.seek_marker("outro").is_err());
+        assert!(timeline.set_loop(Some(LoopRegion { start_ms: 10, end_ms: 10 })).is_err());
+    }
+
+    #[test]
+    fn test_times_past_the_playhead_range_are_rejected() {
+        let late = MAX_TIME_MS + 1;
+        let json = |markers: &str| format!(r#"{{"version":1,"tracks":[],"markers":{}}}"#, markers);
+        assert!(UniformTimeline::from_json(&json(&format!(r#"[{{"name":"end","time_ms":{}}}]"#, MAX_TIME_MS))).is_ok());
+        let result = UniformTimeline::from_json(&json(&format!(r#"[{{"name":"end","time_ms":{}}}]"#, late)));
+        assert_eq!(result, Err(AnimationError::TimeOutOfRange(late)));
+
+        let mut timeline = UniformTimeline::new();
+        let keyframe = timeline.add_keyframe("u_zoom", late, UniformValue::Float(1.0), Easing::Linear);
+        assert_eq!(keyframe, Err(AnimationError::TimeOutOfRange(late)));
+        assert!(timeline.set_loop(Some(LoopRegion { start_ms: 0, end_ms: u64::MAX })).is_err());
+    }
+
+    #[test]
+    fn test_exported_timelines_replay_exactly() {
+        let mut timeline = UniformTimeline::new();
+        timeline.add_keyframe("u_offset", 0, UniformValue::Vec2([0.0, 0.0]), Easing::EASE).unwrap();
+        timeline.add_keyframe("u_offset", 2000, UniformValue::Vec2([-0.7, 0.3]), Easing::Linear).unwrap();
+        timeline.add_marker("start", 0);
+        timeline.set_loop(Some(LoopRegion { start_ms: 0, end_ms: 2000 })).unwrap();
+
+        let json = timeline.to_json().unwrap();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&json).unwrap()["version"], ANIMATION_VERSION);
+        let mut replay = UniformTimeline::from_json(&json).unwrap();
+        assert_eq!(replay, timeline);
+        for _ in 0..500 {
+            timeline.advance(1.0 / 60.0);
+            replay.advance(1.0 / 60.0);
+            assert_eq!(timeline.values(), replay.values());
+        }
+
+        let future = json.replace("\"version\":1", "\"version\":9");
+        assert_eq!(UniformTimeline::from_json(&future), Err(AnimationError::UnsupportedVersion(9)));
+    }
+}
@@ -3,10 +3,18 @@
 //! Enhanced with emotional computing integration and advanced rendering capabilities.
 
 use wasm_bindgen::prelude::*;
-use web_sys::{WebGlRenderingContext, WebGlShader, WebGlProgram};
+use web_sys::{ExtDisjointTimerQuery, WebGlQuery, WebGlRenderingContext, WebGlShader, WebGlProgram};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use chrono::{DateTime, Utc};
+use emotion_core::{EmotionError, EmotionTaxonomy, Forecaster, Holt, PadOctants, Prediction, TaxonomyModel, Vad};
+use crate::adaptive_quality::{AdaptiveQuality, FrameStats, QualityLevel};
+use crate::cpu_renderer::{FractalPreset, RgbaImage};
+use crate::frame_capture::{CaptureError, CapturePolicy, FrameFormat, FrameRecorder};
+use crate::shader_controls::ControlSchema;
+use crate::shader_toolchain::{GlslVersion, ShaderLanguage, ShaderModule, ShaderStage, ToolchainError};
+use crate::uniform_animation::UniformTimeline;
+use crate::ShaderData;
 
 /// WebGPU/WebGL shader engine for real-time creative rendering
 #[wasm_bindgen]
@@ -16,6 +24,19 @@ pub struct ShaderEngine {
     programs: HashMap<String, WebGlProgram>,
     current_program: Option<WebGlProgram>,
     uniforms: HashMap<String, UniformValue>,
+    // Reflected controls per program, and those of the current one
+    controls: HashMap<String, ControlSchema>,
+    current_controls: Option<ControlSchema>,
+    // Keyframed uniforms played by `render`, and the markers its last frame passed
+    animation: Option<UniformTimeline>,
+    passed_markers: Vec<String>,
+    // Frames kept for the session's asset bundle
+    recorder: Option<FrameRecorder>,
+    // Frame times fed to `render` with the cost of drawing each, and the
+    // controller trading quality for them
+    frame_stats: FrameStats,
+    gpu_timer: Option<GpuTimer>,
+    quality: Option<AdaptiveQuality>,
     time: f32,
     resolution: [f32; 2],
     // Add emotional computing integration
@@ -54,7 +75,7 @@ impl EmotionalVector {
     /// Create new emotional vector with enhanced fields
     pub fn new(valence: f32, arousal: f32, dominance: f32) -> Self {
         let timestamp = Utc::now();
-        let category = Self::get_emotional_category(valence, arousal);
+        let category = Self::get_emotional_category(valence, arousal, dominance);
         
         Self {
             valence: valence.clamp(-1.0, 1.0),
@@ -70,13 +91,18 @@ impl EmotionalVector {
     }
     
     /// Get human-readable emotional category
-    pub fn get_emotional_category(valence: f32, arousal: f32) -> String {
-        match (valence, arousal) {
-            (v, a) if v > 0.5 && a > 0.5 => "Excited".to_string(),
-            (v, a) if v > 0.5 && a <= 0.5 => "Happy".to_string(),
-            (v, a) if v <= 0.5 && a > 0.5 => "Anxious".to_string(),
-            _ => "Calm".to_string(),
-        }
+    pub fn get_emotional_category(valence: f32, arousal: f32, dominance: f32) -> String {
+        Self::get_emotional_category_with(valence, arousal, dominance, &PadOctants)
+    }
+    
+    /// Get emotional category under a chosen taxonomy
+    pub fn get_emotional_category_with(
+        valence: f32,
+        arousal: f32,
+        dominance: f32,
+        taxonomy: &dyn EmotionTaxonomy,
+    ) -> String {
+        Vad::clamped(valence, arousal, dominance).classify(taxonomy).label.to_string()
     }
     
     /// Add point to emotional trajectory
@@ -90,46 +116,38 @@ impl EmotionalVector {
     
     /// Calculate emotional complexity based on trajectory
     pub fn calculate_complexity(&mut self) {
-        if self.emotional_trajectory.len() < 2 {
-            self.emotional_complexity = 0.0;
-            return;
-        }
-        
-        let mut total_distance = 0.0;
-        for i in 1..self.emotional_trajectory.len() {
-            let prev = &self.emotional_trajectory[i-1];
-            let curr = &self.emotional_trajectory[i];
-            let distance = ((curr.valence - prev.valence).powi(2) + 
-                           (curr.arousal - prev.arousal).powi(2)).sqrt();
-            total_distance += distance;
+        // Trajectory points carry no dominance, so it adds no spread
+        self.emotional_complexity = emotion_core::complexity(
+            self.emotional_trajectory
+                .iter()
+                .map(|p| Vad::clamped(p.valence, p.arousal, self.dominance)),
+        );
+    }
+    
+    /// Forecast the trajectory `steps_ahead` points past its end
+    ///
+    /// Trajectory points carry no dominance, so the current one stands in.
+    pub fn forecast_emotion(&self, forecaster: &mut dyn Forecaster, steps_ahead: usize) -> Option<Prediction> {
+        forecaster.reset();
+        for point in &self.emotional_trajectory {
+            forecaster.observe(Vad::clamped(point.valence, point.arousal, self.dominance));
         }
-        
-        // Normalize by number of points
-        self.emotional_complexity = (total_distance / self.emotional_trajectory.len() as f32).clamp(0.0, 1.0);
+        forecaster.predict(steps_ahead)
     }
     
     /// Predict next emotional state
     pub fn predict_next_emotion(&self) -> Option<EmotionalVector> {
-        if self.emotional_trajectory.len() < 3 {
-            return None;
-        }
-        
-        let len = self.emotional_trajectory.len();
-        let latest = &self.emotional_trajectory[len - 1];
-        let previous = &self.emotional_trajectory[len - 2];
-        let older = &self.emotional_trajectory[len - 3];
-        
-        // Simple linear extrapolation
-        let valence_delta = (latest.valence - previous.valence) * 0.7 + (previous.valence - older.valence) * 0.3;
-        let arousal_delta = (latest.arousal - previous.arousal) * 0.7 + (previous.arousal - older.arousal) * 0.3;
+        let prediction = self.forecast_emotion(&mut Holt::default(), 1)?;
+        let mean = prediction.mean;
         
         Some(EmotionalVector {
-            valence: (latest.valence + valence_delta).clamp(-1.0, 1.0),
-            arousal: (latest.arousal + arousal_delta).clamp(0.0, 1.0),
-            dominance: self.dominance,
-            confidence: (self.confidence - 0.1).max(0.0), // Confidence decreases with prediction
+            valence: mean.valence,
+            arousal: mean.arousal,
+            dominance: mean.dominance,
+            // Never more certain than the reading the forecast starts from
+            confidence: self.confidence.min(prediction.confidence()),
             timestamp: Utc::now(),
-            emotional_category: EmotionalVector::get_emotional_category(latest.valence + valence_delta, latest.arousal + arousal_delta),
+            emotional_category: mean.category().to_string(),
             emotional_trajectory: self.emotional_trajectory.clone(),
             predicted_emotion: None, // Would need recursive handling in a real implementation
             emotional_complexity: self.emotional_complexity,
@@ -137,7 +155,7 @@ impl EmotionalVector {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum UniformValue {
     Float(f32),
     Vec2([f32; 2]),
@@ -171,6 +189,11 @@ impl ShaderEngine {
         let _ = gl.get_extension("OES_texture_float");
         let _ = gl.get_extension("OES_standard_derivatives");
         let _ = gl.get_extension("EXT_shader_texture_lod");
+        let gpu_timer = gl
+            .get_extension("EXT_disjoint_timer_query")
+            .ok()
+            .flatten()
+            .map(|ext| GpuTimer { ext: ext.unchecked_into(), pending: VecDeque::new(), active: false });
 
         Ok(ShaderEngine {
             canvas,
@@ -178,6 +201,14 @@ impl ShaderEngine {
             programs: HashMap::new(),
             current_program: None,
             uniforms: HashMap::new(),
+            controls: HashMap::new(),
+            current_controls: None,
+            animation: None,
+            passed_markers: vec![],
+            recorder: None,
+            frame_stats: FrameStats::default(),
+            gpu_timer,
+            quality: None,
             time: 0.0,
             resolution: [800.0, 600.0],
             emotional_state: None,
@@ -204,16 +235,31 @@ impl ShaderEngine {
             return Err(JsValue::from_str(&format!("Shader link error: {}", log)));
         }
 
+        let controls = self.reflect_controls(&program, &[vertex_src, fragment_src]);
+        self.controls.insert(name.to_string(), controls);
         self.programs.insert(name.to_string(), program);
         Ok(())
     }
 
+    /// Compile the fragment entry point of a WGSL shader, such as a studio
+    /// template, for the fullscreen quad. Its uniforms get the engine's `u_`
+    /// prefix, so `time` and `resolution` are driven like `u_time` and `u_resolution`.
+    #[wasm_bindgen]
+    pub fn create_program_from_wgsl(&mut self, name: &str, wgsl_src: &str) -> Result<(), JsValue> {
+        let js_error = |e: ToolchainError| JsValue::from_str(&e.to_string());
+        let mut module = ShaderModule::parse(wgsl_src, ShaderLanguage::Wgsl, ShaderStage::Fragment).map_err(js_error)?;
+        module.prefix_uniforms("u_");
+        let fragment_src = module.to_glsl(GlslVersion::Es100).map_err(js_error)?;
+        self.create_program(name, VERTEX_SHADER, &fragment_src)
+    }
+
     /// Use shader program
     #[wasm_bindgen]
     pub fn use_program(&mut self, name: &str) -> Result<(), JsValue> {
         if let Some(program) = self.programs.get(name) {
             self.gl.use_program(Some(program));
             self.current_program = Some(program.clone());
+            self.current_controls = self.controls.get(name).cloned();
             Ok(())
         } else {
             Err(JsValue::from_str("Program not found"))
@@ -265,15 +311,27 @@ impl ShaderEngine {
     /// Render frame
     #[wasm_bindgen]
     pub fn render(&mut self, delta_time: f32) -> Result<(), JsValue> {
+        let started_ms = now_ms();
         self.time += delta_time;
 
         // Update time uniform
         self.set_uniform("u_time", JsValue::from(self.time))?;
 
+        // Track frame times, stepping quality when they leave the budget.
+        // `delta_time` follows the display's refresh, so it shows dropped
+        // frames; headroom is judged on the render costs recorded below.
+        self.frame_stats.record(delta_time * 1000.0);
+        if let Some(quality) = self.quality.as_mut() {
+            if quality.update(&mut self.frame_stats).is_some() {
+                self.apply_render_scale();
+            }
+        }
+
         // Update resolution uniform
+        let [width, height] = self.buffer_size();
         self.set_uniform("u_resolution", JsValue::from(js_sys::Array::of2(
-            &JsValue::from(self.resolution[0]),
-            &JsValue::from(self.resolution[1])
+            &JsValue::from(width),
+            &JsValue::from(height)
         )))?;
 
         // Update emotional uniforms if enabled
@@ -286,6 +344,17 @@ impl ShaderEngine {
             None
         };
 
+        // Play keyframed uniforms
+        self.passed_markers.clear();
+        if let Some(animation) = self.animation.as_mut() {
+            self.passed_markers = animation.advance(delta_time).into_iter().map(|m| m.name).collect();
+            let values: Vec<(String, UniformValue)> =
+                animation.values().into_iter().map(|(name, value)| (name.to_string(), value)).collect();
+            for (name, value) in values {
+                self.apply_uniform(&name, &value);
+            }
+        }
+
         if let Some((valence, arousal, dominance, confidence, complexity)) = emotional_values {
             self.set_uniform("u_emotion_valence", JsValue::from(valence))?;
             self.set_uniform("u_emotion_arousal", JsValue::from(arousal))?;
@@ -294,30 +363,40 @@ impl ShaderEngine {
             self.set_uniform("u_emotion_complexity", JsValue::from(complexity))?;
         }
 
+        // Render fewer iterations than requested at reduced quality
+        self.upload_max_iter();
+
         // Clear and draw
+        if let Some(timer) = self.gpu_timer.as_mut() {
+            timer.begin();
+        }
         self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
         self.gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
 
         // Draw fullscreen quad
         self.gl.draw_arrays(WebGlRenderingContext::TRIANGLE_STRIP, 0, 4);
 
-        Ok(())
+        // GPU time of earlier frames once their queries resolve; without the
+        // extension, the CPU time spent in `render`
+        match self.gpu_timer.as_mut() {
+            Some(timer) => {
+                timer.end();
+                for render_ms in timer.finished(&self.gl) {
+                    self.frame_stats.record_render(render_ms);
+                }
+            }
+            None => self.frame_stats.record_render((now_ms() - started_ms) as f32),
+        }
+
+        self.capture_rendered_frame()
     }
 
     /// Load fractal shader preset
     #[wasm_bindgen]
     pub fn load_fractal_shader(&mut self, preset: &str) -> Result<(), JsValue> {
-        let (vertex_src, fragment_src) = match preset {
-            "mandelbrot" => (VERTEX_SHADER, MANDELBROT_FRAGMENT),
-            "julia" => (VERTEX_SHADER, JULIA_FRAGMENT),
-            "burning_ship" => (VERTEX_SHADER, BURNING_SHIP_FRAGMENT),
-            "newton" => (VERTEX_SHADER, NEWTON_FRAGMENT),
-            "phoenix" => (VERTEX_SHADER, PHOENIX_FRAGMENT),
-            "emotional_mandelbrot" => (VERTEX_SHADER, EMOTIONAL_MANDELBROT_FRAGMENT),
-            _ => return Err(JsValue::from_str("Unknown preset"))
-        };
+        let fragment_src = fractal_fragment(preset.parse().map_err(|_| JsValue::from_str("Unknown preset"))?);
 
-        self.create_program(preset, vertex_src, fragment_src)?;
+        self.create_program(preset, VERTEX_SHADER, fragment_src)?;
         self.use_program(preset)?;
 
         // Set up vertex attributes for fullscreen quad
@@ -330,9 +409,40 @@ impl ShaderEngine {
     #[wasm_bindgen]
     pub fn resize(&mut self, width: f32, height: f32) {
         self.resolution = [width, height];
-        self.canvas.set_width(width as u32);
-        self.canvas.set_height(height as u32);
-        self.gl.viewport(0, 0, width as i32, height as i32);
+        self.apply_render_scale();
+    }
+
+    /// Drop and restore `u_max_iter`, resolution and supersampling to keep
+    /// frames within the budget of `target_fps`
+    #[wasm_bindgen]
+    pub fn enable_adaptive_quality(&mut self, target_fps: f32) -> Result<(), JsValue> {
+        self.frame_stats = FrameStats::for_fps(target_fps).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.quality = Some(AdaptiveQuality::default());
+        self.apply_render_scale();
+        Ok(())
+    }
+
+    /// Go back to full quality
+    #[wasm_bindgen]
+    pub fn disable_adaptive_quality(&mut self) {
+        self.quality = None;
+        self.apply_render_scale();
+        self.upload_max_iter();
+    }
+
+    /// Current quality as `{ iteration_scale, resolution_scale, supersampling }`
+    #[wasm_bindgen]
+    pub fn get_quality(&self) -> Result<JsValue, JsValue> {
+        let json = serde_json::to_string(&self.quality_level()).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        js_sys::JSON::parse(&json)
+    }
+
+    /// Frame times and render costs of `render` as `nuwe_storage::PerformanceMetrics`
+    #[wasm_bindgen]
+    pub fn get_performance_metrics(&self) -> Result<JsValue, JsValue> {
+        let metrics = serde_json::to_string(&self.frame_stats.performance_metrics())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        js_sys::JSON::parse(&metrics)
     }
 
     /// Get current uniform values (for UI sync)
@@ -356,6 +466,128 @@ impl ShaderEngine {
         Ok(JsValue::from(obj))
     }
 
+    /// Control schema of the current program as `{ uniforms: [{ name, type, size, range?,
+    /// step?, default, label?, driven }], warnings?: [{ line, message }] }`, or `null`
+    /// before a program is in use
+    #[wasm_bindgen]
+    pub fn get_control_schema(&self) -> Result<JsValue, JsValue> {
+        let json = serde_json::to_string(&self.current_controls).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        js_sys::JSON::parse(&json)
+    }
+
+    /// Play a uniform timeline exported by `export_animation`, from its start
+    #[wasm_bindgen]
+    pub fn load_animation(&mut self, json: &str) -> Result<(), JsValue> {
+        let timeline = UniformTimeline::from_json(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.animation = Some(timeline);
+        Ok(())
+    }
+
+    /// The loaded timeline as JSON
+    #[wasm_bindgen]
+    pub fn export_animation(&self) -> Result<String, JsValue> {
+        let animation = self.animation.as_ref().ok_or("No animation loaded")?;
+        animation.to_json().map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Stop playing keyframed uniforms
+    #[wasm_bindgen]
+    pub fn clear_animation(&mut self) {
+        self.animation = None;
+    }
+
+    /// Move the animation playhead to `time_ms`
+    #[wasm_bindgen]
+    pub fn seek_animation(&mut self, time_ms: f64) {
+        if let Some(animation) = self.animation.as_mut() {
+            animation.seek(time_ms);
+        }
+    }
+
+    /// Move the animation playhead to a marker
+    #[wasm_bindgen]
+    pub fn seek_animation_marker(&mut self, name: &str) -> Result<(), JsValue> {
+        let animation = self.animation.as_mut().ok_or("No animation loaded")?;
+        animation.seek_marker(name).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Animation playhead in milliseconds
+    #[wasm_bindgen]
+    pub fn get_animation_position(&self) -> f64 {
+        self.animation.as_ref().map_or(0.0, |a| a.position_ms())
+    }
+
+    /// Names of the markers the last `render` passed
+    #[wasm_bindgen]
+    pub fn get_passed_markers(&self) -> JsValue {
+        let arr = js_sys::Array::new();
+        for name in &self.passed_markers {
+            arr.push(&JsValue::from(name.as_str()));
+        }
+        JsValue::from(arr)
+    }
+
+    /// Start keeping frames drawn by `render`: one every `interval_ms` of
+    /// render time and one each time the emotional intensity rises through
+    /// `peak_threshold`, encoded as "png" or "webp"
+    #[wasm_bindgen]
+    pub fn start_capture(
+        &mut self,
+        interval_ms: Option<f64>,
+        peak_threshold: Option<f32>,
+        format: &str,
+    ) -> Result<(), JsValue> {
+        let format: FrameFormat = format.parse().map_err(|e: CaptureError| JsValue::from_str(&e.to_string()))?;
+        let mut policy = CapturePolicy { interval_ms, ..Default::default() }.with_format(format);
+        if let Some(threshold) = peak_threshold {
+            let cooldown_ms = policy.peak_cooldown_ms;
+            policy = policy.with_peaks(threshold, cooldown_ms);
+        }
+        let started_at_ms = (Utc::now().timestamp_millis() - (self.time as f64 * 1000.0) as i64).max(0) as u64;
+        let recorder = FrameRecorder::new(policy, started_at_ms).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.recorder = Some(recorder);
+        Ok(())
+    }
+
+    /// Keep the frame last drawn, whatever the capture schedule
+    #[wasm_bindgen]
+    pub fn capture_frame(&mut self) -> Result<(), JsValue> {
+        let image = self.read_pixels()?;
+        let time_ms = self.time as f64 * 1000.0;
+        let recorder = self.recorder.as_mut().ok_or("Capture not started")?;
+        recorder.capture(&image, time_ms).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(())
+    }
+
+    /// Frames kept since the last call, as `[{ frame_number, timestamp, cid: "", format,
+    /// resolution, reason, session_time_ms, intensity?, bytes }]` with `bytes` a `Uint8Array`
+    #[wasm_bindgen]
+    pub fn take_captured_frames(&mut self) -> Result<JsValue, JsValue> {
+        let frames = self.recorder.as_mut().map(FrameRecorder::take_frames).unwrap_or_default();
+        let arr = js_sys::Array::new();
+        for frame in frames {
+            let to_js_error = |e: serde_json::Error| JsValue::from_str(&e.to_string());
+            let mut entry = serde_json::to_value(frame.frame_reference("")).map_err(to_js_error)?;
+            entry["reason"] = serde_json::to_value(frame.reason).map_err(to_js_error)?;
+            entry["session_time_ms"] = frame.session_time_ms.into();
+            if let Some(intensity) = frame.intensity {
+                entry["intensity"] = intensity.into();
+            }
+            let obj = js_sys::JSON::parse(&entry.to_string())?;
+            js_sys::Reflect::set(&obj, &"bytes".into(), &js_sys::Uint8Array::from(frame.bytes.as_slice()))?;
+            arr.push(&obj);
+        }
+        Ok(JsValue::from(arr))
+    }
+
+    /// Stop capturing; frames not yet taken are returned like `take_captured_frames`
+    #[wasm_bindgen]
+    pub fn stop_capture(&mut self) -> Result<JsValue, JsValue> {
+        let frames = self.take_captured_frames();
+        self.recorder = None;
+        frames
+    }
+
     /// Set emotional state for modulation
     #[wasm_bindgen]
     pub fn set_emotional_state(&mut self, valence: f32, arousal: f32, dominance: f32) {
@@ -451,6 +683,96 @@ impl ShaderEngine {
     }
 
     // Private methods
+    /// Upload `value` with the GL call for its type and record it
+    fn apply_uniform(&mut self, name: &str, value: &UniformValue) {
+        self.upload_uniform(name, value);
+        self.uniforms.insert(name.to_string(), value.clone());
+    }
+
+    /// Upload `value` without recording it, for values derived from the recorded ones
+    fn upload_uniform(&self, name: &str, value: &UniformValue) {
+        let Some(program) = &self.current_program else { return };
+        let Some(loc) = self.gl.get_uniform_location(program, name) else { return };
+        match *value {
+            UniformValue::Float(f) => self.gl.uniform1f(Some(&loc), f),
+            UniformValue::Vec2([x, y]) => self.gl.uniform2f(Some(&loc), x, y),
+            UniformValue::Vec3([x, y, z]) => self.gl.uniform3f(Some(&loc), x, y, z),
+            UniformValue::Vec4([x, y, z, w]) => self.gl.uniform4f(Some(&loc), x, y, z, w),
+            UniformValue::Int(i) => self.gl.uniform1i(Some(&loc), i),
+            UniformValue::Bool(b) => self.gl.uniform1i(Some(&loc), b as i32),
+        }
+    }
+
+    fn quality_level(&self) -> QualityLevel {
+        self.quality.as_ref().map_or(QualityLevel::FULL, AdaptiveQuality::level)
+    }
+
+    /// Size of the drawing buffer, the canvas size times the render scale
+    fn buffer_size(&self) -> [f32; 2] {
+        let scale = self.quality_level().render_scale();
+        [(self.resolution[0] * scale).round().max(1.0), (self.resolution[1] * scale).round().max(1.0)]
+    }
+
+    fn apply_render_scale(&mut self) {
+        let [width, height] = self.buffer_size();
+        self.canvas.set_width(width as u32);
+        self.canvas.set_height(height as u32);
+        self.gl.viewport(0, 0, width as i32, height as i32);
+    }
+
+    /// Upload the recorded `u_max_iter` scaled by the quality level; numbers
+    /// set from JS are recorded as floats
+    fn upload_max_iter(&self) {
+        let requested = match self.uniforms.get("u_max_iter") {
+            Some(UniformValue::Int(i)) => *i,
+            Some(UniformValue::Float(f)) => f.round() as i32,
+            _ => return,
+        };
+        self.upload_uniform("u_max_iter", &UniformValue::Int(self.quality_level().max_iter(requested)));
+    }
+
+    /// Offer the frame just drawn to the recorder
+    fn capture_rendered_frame(&mut self) -> Result<(), JsValue> {
+        let time_ms = self.time as f64 * 1000.0;
+        let emotion = self.emotional_state.as_ref().map(|e| Vad::clamped(e.valence, e.arousal, e.dominance));
+        let Some(recorder) = self.recorder.as_mut() else { return Ok(()) };
+        let Some(reason) = recorder.should_capture(time_ms, emotion.as_ref()) else { return Ok(()) };
+        let image = self.read_pixels()?;
+        let recorder = self.recorder.as_mut().ok_or("Capture not started")?;
+        recorder.record(&image, time_ms, reason, emotion.as_ref()).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(())
+    }
+
+    /// The drawing buffer as an image with rows from the top
+    fn read_pixels(&self) -> Result<RgbaImage, JsValue> {
+        let (width, height) = (self.gl.drawing_buffer_width(), self.gl.drawing_buffer_height());
+        let mut pixels = vec![0u8; width.max(0) as usize * height.max(0) as usize * 4];
+        self.gl.read_pixels_with_opt_u8_array(
+            0,
+            0,
+            width,
+            height,
+            WebGlRenderingContext::RGBA,
+            WebGlRenderingContext::UNSIGNED_BYTE,
+            Some(&mut pixels),
+        )?;
+        // GL rows start at the bottom
+        let row = width.max(1) as usize * 4;
+        let pixels = pixels.chunks_exact(row).rev().flatten().copied().collect();
+        RgbaImage::new(width as u32, height as u32, pixels).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Active uniforms of a linked program, annotated from its sources
+    fn reflect_controls(&self, program: &WebGlProgram, sources: &[&str]) -> ControlSchema {
+        let count = self.gl.get_program_parameter(program, WebGlRenderingContext::ACTIVE_UNIFORMS);
+        let count = count.as_f64().unwrap_or(0.0);
+        let active: Vec<(String, u32, u32)> = (0..count as u32)
+            .filter_map(|i| self.gl.get_active_uniform(program, i))
+            .map(|info| (info.name(), info.type_(), info.size().max(1) as u32))
+            .collect();
+        ControlSchema::from_active(&active, sources)
+    }
+
     fn compile_shader(&self, shader_type: u32, source: &str) -> Result<WebGlShader, JsValue> {
         let shader = self.gl.create_shader(shader_type).ok_or("Failed to create shader")?;
         self.gl.shader_source(&shader, source);
@@ -497,6 +819,69 @@ impl ShaderEngine {
     }
 }
 
+impl ShaderEngine {
+    /// Uniforms set so far, e.g. for `FractalUniforms::from_uniforms` to render the
+    /// same frame on the CPU
+    pub fn uniform_values(&self) -> &HashMap<String, UniformValue> {
+        &self.uniforms
+    }
+
+    /// Play `animation` on every `render`, or stop with `None`
+    pub fn set_animation(&mut self, animation: Option<UniformTimeline>) {
+        self.animation = animation;
+    }
+
+    pub fn animation(&self) -> Option<&UniformTimeline> {
+        self.animation.as_ref()
+    }
+
+    /// Frames kept since `start_capture`
+    pub fn frame_recorder(&self) -> Option<&FrameRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// Frame times fed to `render`
+    pub fn frame_stats(&self) -> &FrameStats {
+        &self.frame_stats
+    }
+
+    /// Controls of the current program
+    pub fn control_schema(&self) -> Option<&ControlSchema> {
+        self.current_controls.as_ref()
+    }
+
+    /// Scalar uniforms set so far and the current program's controls, for minting
+    pub fn shader_data(&self, shader_type: &str, seed: u64) -> ShaderData {
+        let parameters: HashMap<String, f32> = self
+            .uniforms
+            .iter()
+            .filter_map(|(name, value)| match value {
+                UniformValue::Float(f) => Some((name.clone(), *f)),
+                UniformValue::Int(i) => Some((name.clone(), *i as f32)),
+                _ => None,
+            })
+            .collect();
+        let iteration_count = parameters.get("u_max_iter").map_or(0, |n| *n as u32);
+        let data = crate::generate_shader_data(shader_type, parameters, seed, iteration_count);
+        match &self.current_controls {
+            Some(controls) => data.with_controls(controls.clone()),
+            None => data,
+        }
+    }
+}
+
+/// Fragment shader source of a fractal preset
+pub(crate) fn fractal_fragment(preset: FractalPreset) -> &'static str {
+    match preset {
+        FractalPreset::Mandelbrot => MANDELBROT_FRAGMENT,
+        FractalPreset::Julia => JULIA_FRAGMENT,
+        FractalPreset::BurningShip => BURNING_SHIP_FRAGMENT,
+        FractalPreset::Newton => NEWTON_FRAGMENT,
+        FractalPreset::Phoenix => PHOENIX_FRAGMENT,
+        FractalPreset::EmotionalMandelbrot => EMOTIONAL_MANDELBROT_FRAGMENT,
+    }
+}
+
 // Shader source code
 const VERTEX_SHADER: &str = r#"
 attribute vec2 a_position;
@@ -764,25 +1149,41 @@ pub struct PerformanceMonitor {
     frame_count: u32,
     last_time: f64,
     fps: f32,
+    // Time of the previous `update`, and the frame times between them
+    last_frame: f64,
+    stats: FrameStats,
 }
 
 #[wasm_bindgen]
 impl PerformanceMonitor {
     #[wasm_bindgen(constructor)]
     pub fn new() -> PerformanceMonitor {
+        let now = js_sys::Date::now();
         PerformanceMonitor {
             frame_count: 0,
-            last_time: js_sys::Date::now(),
+            last_time: now,
             fps: 0.0,
+            last_frame: now,
+            stats: FrameStats::default(),
         }
     }
 
+    /// Count frames against the budget of `target_fps` instead of 60, starting over
+    #[wasm_bindgen]
+    pub fn set_target_fps(&mut self, target_fps: f32) -> Result<(), JsValue> {
+        self.stats = FrameStats::for_fps(target_fps).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(())
+    }
+
     #[wasm_bindgen]
     pub fn update(&mut self) -> f32 {
         self.frame_count += 1;
         let current_time = js_sys::Date::now();
         let delta = current_time - self.last_time;
 
+        self.stats.record((current_time - self.last_frame) as f32);
+        self.last_frame = current_time;
+
         if delta >= 1000.0 {
             self.fps = (self.frame_count as f64 / delta * 1000.0) as f32;
             self.frame_count = 0;
@@ -796,6 +1197,98 @@ impl PerformanceMonitor {
     pub fn get_fps(&self) -> f32 {
         self.fps
     }
+
+    /// Add the time the last frame took to draw, reported as `avg_render_time_ms`
+    #[wasm_bindgen]
+    pub fn record_render_time(&mut self, render_ms: f32) {
+        self.stats.record_render(render_ms);
+    }
+
+    /// Refreshes missed by slow frames so far
+    #[wasm_bindgen]
+    pub fn get_dropped_frames(&self) -> f64 {
+        self.stats.dropped_frames() as f64
+    }
+
+    /// `{ p50_ms, p95_ms, p99_ms }` of recent frames, or null before the first one
+    #[wasm_bindgen]
+    pub fn get_percentiles(&self) -> Result<JsValue, JsValue> {
+        let json = serde_json::to_string(&self.stats.percentiles()).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        js_sys::JSON::parse(&json)
+    }
+
+    /// `{ bucket_ms, counts }`, the last count for frames past the last bucket
+    #[wasm_bindgen]
+    pub fn get_histogram(&self) -> Result<JsValue, JsValue> {
+        let json = serde_json::to_string(self.stats.histogram()).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        js_sys::JSON::parse(&json)
+    }
+
+    /// The stats as `nuwe_storage::PerformanceMetrics`
+    #[wasm_bindgen]
+    pub fn get_performance_metrics(&self) -> Result<JsValue, JsValue> {
+        let metrics =
+            serde_json::to_string(&self.stats.performance_metrics()).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        js_sys::JSON::parse(&metrics)
+    }
+}
+
+/// Queries still unresolved before the oldest is given up on
+const MAX_PENDING_QUERIES: usize = 8;
+
+/// Times each frame's draw on the GPU with `EXT_disjoint_timer_query`;
+/// results arrive a few frames later
+struct GpuTimer {
+    ext: ExtDisjointTimerQuery,
+    pending: VecDeque<WebGlQuery>,
+    /// Whether `begin` started a query that `end` has not ended
+    active: bool,
+}
+
+impl GpuTimer {
+    fn begin(&mut self) {
+        if self.pending.len() == MAX_PENDING_QUERIES {
+            self.ext.delete_query_ext(self.pending.pop_front().as_ref());
+        }
+        if let Some(query) = self.ext.create_query_ext() {
+            self.ext.begin_query_ext(ExtDisjointTimerQuery::TIME_ELAPSED_EXT, &query);
+            self.pending.push_back(query);
+            self.active = true;
+        }
+    }
+
+    fn end(&mut self) {
+        if std::mem::take(&mut self.active) {
+            self.ext.end_query_ext(ExtDisjointTimerQuery::TIME_ELAPSED_EXT);
+        }
+    }
+
+    /// Milliseconds of each query resolved since the last call, oldest first.
+    /// Results are dropped when the GPU reports a disjoint, e.g. a clock change.
+    fn finished(&mut self, gl: &WebGlRenderingContext) -> Vec<f32> {
+        let disjoint = gl
+            .get_parameter(ExtDisjointTimerQuery::GPU_DISJOINT_EXT)
+            .ok()
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        let mut times = Vec::new();
+        while let Some(query) = self.pending.front() {
+            if self.ext.get_query_object_ext(query, ExtDisjointTimerQuery::QUERY_RESULT_AVAILABLE_EXT).as_bool() != Some(true) {
+                break;
+            }
+            let elapsed_ns = self.ext.get_query_object_ext(query, ExtDisjointTimerQuery::QUERY_RESULT_EXT).as_f64();
+            self.ext.delete_query_ext(self.pending.pop_front().as_ref());
+            if let (false, Some(elapsed_ns)) = (disjoint, elapsed_ns) {
+                times.push((elapsed_ns / 1_000_000.0) as f32);
+            }
+        }
+        times
+    }
+}
+
+/// High-resolution page time in milliseconds
+fn now_ms() -> f64 {
+    web_sys::window().and_then(|window| window.performance()).map_or_else(js_sys::Date::now, |p| p.now())
 }
 
 /// Utility function to create emotional vector
@@ -821,5 +1314,13 @@ pub fn create_emotional_vector(valence: f32, arousal: f32, dominance: f32) -> Js
 /// Utility function to get emotional category
 #[wasm_bindgen]
 pub fn get_emotional_category(valence: f32, arousal: f32, dominance: f32) -> String {
-    EmotionalVector::get_emotional_category(valence, arousal)
+    EmotionalVector::get_emotional_category(valence, arousal, dominance)
+}
+
+/// Utility function to get emotional category under a named taxonomy
+/// ("pad", "circumplex" or "plutchik")
+#[wasm_bindgen]
+pub fn get_emotional_category_with(valence: f32, arousal: f32, dominance: f32, model: &str) -> Result<String, JsValue> {
+    let model: TaxonomyModel = model.parse().map_err(|e: EmotionError| JsValue::from_str(&e.to_string()))?;
+    Ok(EmotionalVector::get_emotional_category_with(valence, arousal, dominance, &model))
 }
\ No newline at end of file
@@ -0,0 +1,60 @@
+//! # Session Core
+//!
+//! Records of a creative session that the Rust client produces and NUWE
+//! storage bundles: a [`FrameReference`] per captured frame and the
+//! session's [`PerformanceMetrics`]. Both crates use these types, so what the
+//! renderer writes is what a `NuweAssetBundle` reads.
+
+use serde::{Deserialize, Serialize};
+
+/// Performance metrics for session
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PerformanceMetrics {
+    pub avg_fps: f32,
+    pub peak_fps: f32,
+    pub total_frames: u64,
+    pub dropped_frames: u32,
+    pub avg_render_time_ms: f32,
+    /// Frame time percentiles, absent in sessions recorded before they were tracked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frame_times: Option<FrameTimePercentiles>,
+}
+
+/// Frame times at the 50th, 95th and 99th percentiles
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct FrameTimePercentiles {
+    pub p50_ms: f32,
+    pub p95_ms: f32,
+    pub p99_ms: f32,
+}
+
+/// Reference to a rendered frame
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FrameReference {
+    pub frame_number: u64,
+    pub timestamp: u64,
+    pub cid: String,
+    pub format: String, // "png", "jpg", "webp"
+    pub resolution: (u32, u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_reference_json() {
+        let json = r#"{"frame_number":5,"timestamp":1700000000420,"cid":"bafyframe","format":"png","resolution":[16,12]}"#;
+        let frame: FrameReference = serde_json::from_str(json).unwrap();
+        assert_eq!(frame.resolution, (16, 12));
+        assert_eq!(serde_json::to_string(&frame).unwrap(), json);
+    }
+
+    #[test]
+    fn test_metrics_without_frame_times() {
+        let json = r#"{"avg_fps":60.0,"peak_fps":62.5,"total_frames":10,"dropped_frames":1,"avg_render_time_ms":4.0}"#;
+        let metrics: PerformanceMetrics = serde_json::from_str(json).unwrap();
+        assert_eq!(metrics.frame_times, None);
+        assert_eq!(serde_json::to_string(&metrics).unwrap(), json);
+    }
+}
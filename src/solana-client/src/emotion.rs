@@ -0,0 +1,63 @@
+//! Conversions between `EmotionalVector` and `emotion_core`
+//!
+//! Timestamps on chain are whole seconds from the Solana clock, so readings
+//! with a sub-second part are rejected rather than truncated.
+
+use crate::EmotionalVector;
+use emotion_core::{EmotionError, EmotionReading, Vad};
+
+impl TryFrom<&EmotionalVector> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(v: &EmotionalVector) -> Result<Self, Self::Error> {
+        EmotionReading::new(Vad::new(v.valence, v.arousal, v.dominance)?)
+            .with_confidence(v.confidence)?
+            .with_timestamp_secs(v.timestamp)
+    }
+}
+
+impl TryFrom<EmotionReading> for EmotionalVector {
+    type Error = EmotionError;
+
+    fn try_from(reading: EmotionReading) -> Result<Self, Self::Error> {
+        reading.validate()?;
+        Ok(Self {
+            valence: reading.vad.valence,
+            arousal: reading.vad.arousal,
+            dominance: reading.vad.dominance,
+            confidence: reading.require_confidence()?,
+            timestamp: reading.require_timestamp_secs()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emotional_vector_round_trip() {
+        let vector = EmotionalVector {
+            valence: -0.4,
+            arousal: 0.9,
+            dominance: 0.2,
+            confidence: 0.75,
+            timestamp: 1_700_000_000,
+        };
+        let reading = EmotionReading::try_from(&vector).unwrap();
+        assert_eq!(reading.category().as_str(), "Anxious");
+
+        let restored = EmotionalVector::try_from(reading).unwrap();
+        assert_eq!(restored.timestamp, vector.timestamp);
+        assert_eq!(restored.confidence, vector.confidence);
+    }
+
+    #[test]
+    fn test_missing_confidence_rejected() {
+        let reading = EmotionReading::new(Vad::NEUTRAL).with_timestamp_ns(0);
+        assert_eq!(
+            EmotionalVector::try_from(reading).err(),
+            Some(EmotionError::Missing { field: "confidence" })
+        );
+    }
+}
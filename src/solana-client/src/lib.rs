@@ -4,6 +4,9 @@
 //! Enhanced with cross-chain bridge capabilities and advanced compression.
 
 use anchor_lang::prelude::*;
+use emotion_core::Vad;
+
+mod emotion;
 
 declare_id!("CreativeMetadata111111111111111111111111111");
 
@@ -104,27 +107,7 @@ fn hash_data(data: &[u8]) -> [u8; 32] {
 
 // Helper function to calculate emotional complexity
 fn calculate_emotional_complexity(history: &[EmotionalVector]) -> f32 {
-    if history.len() < 2 {
-        return 0.0;
-    }
-    
-    let mut variance = 0.0;
-    let len = history.len() as f32;
-    
-    // Calculate mean
-    let mean_valence: f32 = history.iter().map(|e| e.valence).sum::<f32>() / len;
-    let mean_arousal: f32 = history.iter().map(|e| e.arousal).sum::<f32>() / len;
-    let mean_dominance: f32 = history.iter().map(|e| e.dominance).sum::<f32>() / len;
-    
-    // Calculate variance
-    for emotion in history {
-        variance += (emotion.valence - mean_valence).powi(2);
-        variance += (emotion.arousal - mean_arousal).powi(2);
-        variance += (emotion.dominance - mean_dominance).powi(2);
-    }
-    
-    variance /= len * 3.0; // Normalize by number of dimensions
-    variance.min(1.0) // Clamp to 0.0-1.0 range
+    emotion_core::complexity(history.iter().map(|e| Vad::clamped(e.valence, e.arousal, e.dominance)))
 }
 
 #[program]
@@ -5,6 +5,7 @@
 
 use anchor_lang::prelude::*;
 use std::collections::HashMap;
+use emotion_core::{EmotionError, EmotionReading, EmotionTaxonomy, Forecaster, Holt, PadOctants, Prediction, Vad};
 
 /// Emotional state vector (Valence-Arousal-Dominance model)
 #[account]
@@ -131,10 +132,10 @@ pub struct AdvancedEmotionalPattern {
     pub creative_potential: f32,
 }
 
-/// Emotional trajectory predictor with advanced ML models
+/// Emotional trajectory predictor backed by an `emotion_core` forecaster
 pub struct EmotionalTrajectoryPredictor {
     historical_data: Vec<EmotionalVector>,
-    model_parameters: Vec<f32>,
+    model: Option<Holt>,
     // Add advanced features
     confidence_history: Vec<f32>,
     prediction_accuracy: f32,
@@ -144,7 +145,7 @@ impl EmotionalTrajectoryPredictor {
     pub fn new() -> Self {
         Self {
             historical_data: Vec::new(),
-            model_parameters: Vec::new(),
+            model: None,
             confidence_history: Vec::new(),
             prediction_accuracy: 0.0,
         }
@@ -165,100 +166,31 @@ impl EmotionalTrajectoryPredictor {
             return;
         }
         
-        // Simple linear regression for each dimension
-        let n = self.historical_data.len() as f32;
-        let mut sum_x = 0.0;
-        let mut sum_y_valence = 0.0;
-        let mut sum_y_arousal = 0.0;
-        let mut sum_y_dominance = 0.0;
-        let mut sum_xy_valence = 0.0;
-        let mut sum_xy_arousal = 0.0;
-        let mut sum_xy_dominance = 0.0;
-        let mut sum_xx = 0.0;
-        
-        for (i, emotion) in self.historical_data.iter().enumerate() {
-            let x = i as f32;
-            sum_x += x;
-            sum_y_valence += emotion.valence;
-            sum_y_arousal += emotion.arousal;
-            sum_y_dominance += emotion.dominance;
-            sum_xy_valence += x * emotion.valence;
-            sum_xy_arousal += x * emotion.arousal;
-            sum_xy_dominance += x * emotion.dominance;
-            sum_xx += x * x;
+        let mut model = Holt::default();
+        for emotion in &self.historical_data {
+            model.observe(Vad::clamped(emotion.valence, emotion.arousal, emotion.dominance));
         }
-        
-        let slope_valence = (n * sum_xy_valence - sum_x * sum_y_valence) / (n * sum_xx - sum_x * sum_x);
-        let slope_arousal = (n * sum_xy_arousal - sum_x * sum_y_arousal) / (n * sum_xx - sum_x * sum_x);
-        let slope_dominance = (n * sum_xy_dominance - sum_x * sum_y_dominance) / (n * sum_xx - sum_x * sum_x);
-        
-        let intercept_valence = (sum_y_valence - slope_valence * sum_x) / n;
-        let intercept_arousal = (sum_y_arousal - slope_arousal * sum_x) / n;
-        let intercept_dominance = (sum_y_dominance - slope_dominance * sum_x) / n;
-        
-        self.model_parameters = vec![slope_valence, intercept_valence, slope_arousal, intercept_arousal, slope_dominance, intercept_dominance];
+        self.model = Some(model);
+    }
+    
+    /// Forecast `steps_ahead` states with per-dimension uncertainty
+    pub fn forecast(&self, steps_ahead: u32) -> Option<Prediction> {
+        self.model.as_ref()?.predict(steps_ahead as usize)
     }
     
     /// Predict next emotional state
     pub fn predict_next(&self, steps_ahead: u32) -> Option<EmotionalVector> {
-        if self.model_parameters.len() != 6 || self.historical_data.is_empty() {
-            return None;
-        }
-        
-        let slope_valence = self.model_parameters[0];
-        let intercept_valence = self.model_parameters[1];
-        let slope_arousal = self.model_parameters[2];
-        let intercept_arousal = self.model_parameters[3];
-        let slope_dominance = self.model_parameters[4];
-        let intercept_dominance = self.model_parameters[5];
-        
-        let next_x = (self.historical_data.len() as f32) + (steps_ahead as f32);
-        
+        let mean = self.forecast(steps_ahead)?.mean;
         Some(EmotionalVector {
-            valence: (intercept_valence + slope_valence * next_x).clamp(-1.0, 1.0),
-            arousal: (intercept_arousal + slope_arousal * next_x).clamp(0.0, 1.0),
-            dominance: (intercept_dominance + slope_dominance * next_x).clamp(0.0, 1.0),
+            valence: mean.valence,
+            arousal: mean.arousal,
+            dominance: mean.dominance,
         })
     }
     
-    /// Calculate prediction confidence based on model fit
+    /// Prediction confidence from the width of the one-step forecast interval
     pub fn prediction_confidence(&self) -> f32 {
-        if self.model_parameters.len() != 6 || self.historical_data.len() < 3 {
-            return 0.0;
-        }
-        
-        // Calculate R-squared as confidence measure
-        let mean_valence: f32 = self.historical_data.iter().map(|e| e.valence).sum::<f32>() / self.historical_data.len() as f32;
-        let mean_arousal: f32 = self.historical_data.iter().map(|e| e.arousal).sum::<f32>() / self.historical_data.len() as f32;
-        let mean_dominance: f32 = self.historical_data.iter().map(|e| e.dominance).sum::<f32>() / self.historical_data.len() as f32;
-        
-        let mut ss_res_valence = 0.0;
-        let mut ss_tot_valence = 0.0;
-        let mut ss_res_arousal = 0.0;
-        let mut ss_tot_arousal = 0.0;
-        let mut ss_res_dominance = 0.0;
-        let mut ss_tot_dominance = 0.0;
-        
-        for (i, emotion) in self.historical_data.iter().enumerate() {
-            let x = i as f32;
-            let pred_valence = self.model_parameters[1] + self.model_parameters[0] * x;
-            let pred_arousal = self.model_parameters[3] + self.model_parameters[2] * x;
-            let pred_dominance = self.model_parameters[5] + self.model_parameters[4] * x;
-            
-            ss_res_valence += (emotion.valence - pred_valence).powi(2);
-            ss_tot_valence += (emotion.valence - mean_valence).powi(2);
-            ss_res_arousal += (emotion.arousal - pred_arousal).powi(2);
-            ss_tot_arousal += (emotion.arousal - mean_arousal).powi(2);
-            ss_res_dominance += (emotion.dominance - pred_dominance).powi(2);
-            ss_tot_dominance += (emotion.dominance - mean_dominance).powi(2);
-        }
-        
-        let r2_valence = 1.0 - (ss_res_valence / ss_tot_valence);
-        let r2_arousal = 1.0 - (ss_res_arousal / ss_tot_arousal);
-        let r2_dominance = 1.0 - (ss_res_dominance / ss_tot_dominance);
-        
-        // Average R-squared as confidence (0-1 scale)
-        ((r2_valence + r2_arousal + r2_dominance) / 3.0).max(0.0).min(1.0)
+        self.forecast(1).map_or(0.0, |prediction| prediction.confidence())
     }
     
     /// Update prediction accuracy based on actual vs predicted values
@@ -338,16 +270,45 @@ impl EmotionalState {
     
     /// Get emotional category based on VAD values
     pub fn get_emotional_category(&self) -> String {
-        match (self.valence, self.arousal, self.dominance) {
-            (v, a, d) if v > 0.5 && a > 0.5 && d > 0.5 => "Excited".to_string(),
-            (v, a, d) if v > 0.5 && a > 0.5 && d <= 0.5 => "Happy".to_string(),
-            (v, a, d) if v > 0.5 && a <= 0.5 && d > 0.5 => "Proud".to_string(),
-            (v, a, d) if v > 0.5 && a <= 0.5 && d <= 0.5 => "Calm".to_string(),
-            (v, a, d) if v <= 0.5 && a > 0.5 && d > 0.5 => "Angry".to_string(),
-            (v, a, d) if v <= 0.5 && a > 0.5 && d <= 0.5 => "Anxious".to_string(),
-            (v, a, d) if v <= 0.5 && a <= 0.5 && d > 0.5 => "Bored".to_string(),
-            _ => "Sad".to_string(),
-        }
+        self.get_emotional_category_with(&PadOctants)
+    }
+    
+    /// Get emotional category under a chosen taxonomy
+    pub fn get_emotional_category_with(&self, taxonomy: &dyn EmotionTaxonomy) -> String {
+        Vad::clamped(self.valence, self.arousal, self.dominance).classify(taxonomy).label.to_string()
+    }
+}
+
+impl TryFrom<&EmotionalState> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(state: &EmotionalState) -> std::result::Result<Self, Self::Error> {
+        EmotionReading::new(Vad::new(state.valence, state.arousal, state.dominance)?)
+            .with_confidence(state.confidence)?
+            .with_timestamp_secs(state.timestamp)
+    }
+}
+
+impl TryFrom<&EmotionalVector> for EmotionReading {
+    type Error = EmotionError;
+
+    fn try_from(v: &EmotionalVector) -> std::result::Result<Self, Self::Error> {
+        Ok(EmotionReading::new(Vad::new(v.valence, v.arousal, v.dominance)?))
+    }
+}
+
+/// Fails if the reading has a confidence or timestamp, which this type cannot hold
+impl TryFrom<EmotionReading> for EmotionalVector {
+    type Error = EmotionError;
+
+    fn try_from(reading: EmotionReading) -> std::result::Result<Self, Self::Error> {
+        let vad = reading.into_vad_only()?;
+        vad.validate()?;
+        Ok(Self {
+            valence: vad.valence,
+            arousal: vad.arousal,
+            dominance: vad.dominance,
+        })
     }
 }
 
@@ -361,6 +322,11 @@ impl CompressedEmotionalState {
             (self.d as f32) / 100.0,
         )
     }
+
+    /// VAD point of this state, clamped into range
+    pub fn vad(&self) -> Vad {
+        Vad::clamped((self.v as f32) / 100.0, (self.a as f32) / 100.0, (self.d as f32) / 100.0)
+    }
 }
 
 impl DiffusionGeneration {
@@ -466,31 +432,13 @@ impl NeuroemotiveSession {
         variance_sum / self.emotional_states.len() as f32
     }
     
-    /// Calculate emotional complexity based on variance and trajectory
+    /// Calculate emotional complexity as the normalised spread of the session
     pub fn calculate_emotional_complexity(&self) -> f32 {
-        if self.emotional_states.len() < 2 {
-            return 0.0;
-        }
-        
-        // Use variance as the base complexity measure
-        let variance = self.emotional_variance();
-        
-        // Add trajectory complexity (direction changes)
-        let mut direction_changes = 0;
-        for i in 1..self.emotional_states.len() {
-            let prev = &self.emotional_states[i-1];
-            let curr = &self.emotional_states[i];
-            
-            // Check for significant direction changes in valence
-            if (curr.valence - prev.valence).abs() > 0.3 {
-                direction_changes += 1;
-            }
-        }
-        
-        let trajectory_complexity = direction_changes as f32 / self.emotional_states.len() as f32;
-        
-        // Combine variance and trajectory complexity (0-1 scale)
-        (variance + trajectory_complexity).min(1.0)
+        emotion_core::complexity(
+            self.emotional_states
+                .iter()
+                .map(|s| Vad::clamped(s.valence, s.arousal, s.dominance)),
+        )
     }
     
     /// Calculate creativity index based on emotional dynamics
@@ -760,32 +708,28 @@ impl EmotionalTrajectory {
         self.compressed_states.push(state);
     }
     
+    /// Forecast the trajectory `steps_ahead` states past its end
+    pub fn forecast(&self, forecaster: &mut dyn Forecaster, steps_ahead: usize) -> Option<Prediction> {
+        forecaster.reset();
+        for state in &self.compressed_states {
+            forecaster.observe(Vad::clamped(
+                state.v as f32 / 100.0,
+                state.a as f32 / 100.0,
+                state.d as f32 / 100.0,
+            ));
+        }
+        forecaster.predict(steps_ahead)
+    }
+    
     /// Predict next emotional state in the trajectory
     pub fn predict_next_state(&mut self) -> Option<EmotionalVector> {
-        if self.compressed_states.len() < 3 {
-            return None;
-        }
-        
-        // Simple prediction based on last few states
-        let len = self.compressed_states.len();
-        let last_state = &self.compressed_states[len-1];
-        let prev_state = &self.compressed_states[len-2];
-        
-        let delta_v = last_state.v - prev_state.v;
-        let delta_a = last_state.a as i16 - prev_state.a as i16;
-        let delta_d = last_state.d as i16 - prev_state.d as i16;
-        
-        let predicted_v = (last_state.v as i16 + delta_v) as i8;
-        let predicted_a = (last_state.a as i16 + delta_a) as u8;
-        let predicted_d = (last_state.d as i16 + delta_d) as u8;
-        
-        // Confidence decreases with prediction distance
-        self.prediction_confidence = 0.8 - (len as f32 * 0.05).min(0.7);
-        
+        let prediction = self.forecast(&mut Holt::default(), 1)?;
+        self.prediction_confidence = prediction.confidence();
+        let mean = prediction.mean;
         Some(EmotionalVector {
-            valence: (predicted_v as f32) / 100.0,
-            arousal: (predicted_a as f32) / 100.0,
-            dominance: (predicted_d as f32) / 100.0,
+            valence: mean.valence,
+            arousal: mean.arousal,
+            dominance: mean.dominance,
         })
     }
     
@@ -815,18 +759,7 @@ impl EmotionalTrajectory {
         // Identify dominant emotions
         let mut emotion_counts = HashMap::new();
         for state in &self.compressed_states {
-            // Decompress to get emotional category
-            let valence = (state.v as f32) / 100.0;
-            let arousal = (state.a as f32) / 100.0;
-            let dominance = (state.d as f32) / 100.0;
-            
-            let category = match (valence, arousal, dominance) {
-                (v, a, d) if v > 0.5 && a > 0.5 => "Excited".to_string(),
-                (v, a, d) if v > 0.5 && a <= 0.5 => "Happy".to_string(),
-                (v, a, d) if v <= 0.5 && a > 0.5 => "Anxious".to_string(),
-                _ => "Calm".to_string(),
-            };
-            
+            let category = state.vad().classify(&PadOctants).label.to_string();
             *emotion_counts.entry(category).or_insert(0) += 1;
         }
         
@@ -1053,6 +986,27 @@ mod tests {
         assert_eq!(category, "Excited");
     }
     
+    #[test]
+    fn test_trajectory_labels_match_the_state() {
+        for (v, a, d) in [(0.7, 0.8, 0.3), (0.6, 0.9, 0.8), (-0.4, 0.2, 0.7), (0.3, 0.4, 0.2)] {
+            let state = EmotionalState { valence: v, arousal: a, dominance: d, ..Default::default() };
+            let mut trajectory = EmotionalTrajectory {
+                trajectory_id: [0u8; 32],
+                creator: Pubkey::default(),
+                start_time: 0,
+                compressed_states: vec![state.compress()],
+                metadata: TrajectoryMetadata::default(),
+                predicted_next_state: None,
+                prediction_confidence: 0.0,
+            };
+            trajectory.calculate_metrics();
+
+            let label = state.get_emotional_category();
+            assert_eq!(label, Vad::clamped(v, a, d).category().as_str());
+            assert_eq!(trajectory.metadata.dominant_emotions, vec![label]);
+        }
+    }
+
     #[test]
     fn test_prediction() {
         let mut session = NeuroemotiveSession {
@@ -5,6 +5,7 @@
 //! Enhanced with machine learning-based compression and cross-chain data synchronization.
 
 use anchor_lang::prelude::*;
+use emotion_core::Vad;
 use std::collections::HashMap;
 
 /// Advanced storage account for biometric and creative data
@@ -439,22 +440,8 @@ impl CompressedBiometricSession {
                 let curr = &self.emotional_states[i];
                 
                 // Check if emotional categories are consistent
-                let prev_category = match (prev.valence, prev.arousal) {
-                    (v, a) if v > 50 && a > 50 => "Excited",
-                    (v, a) if v > 50 && a <= 50 => "Happy",
-                    (v, a) if v <= 50 && a > 50 => "Anxious",
-                    _ => "Calm",
-                };
-                
-                let curr_category = match (curr.valence, curr.arousal) {
-                    (v, a) if v > 50 && a > 50 => "Excited",
-                    (v, a) if v > 50 && a <= 50 => "Happy",
-                    (v, a) if v <= 50 && a > 50 => "Anxious",
-                    _ => "Calm",
-                };
-                
                 total_comparisons += 1;
-                if prev_category == curr_category {
+                if prev.vad().category() == curr.vad().category() {
                     consistent_patterns += 1;
                 }
             }
@@ -855,6 +842,15 @@ impl CompressedEmotionalState {
         }
     }
 
+    /// VAD point of this state, clamped into range
+    pub fn vad(&self) -> Vad {
+        Vad::clamped(
+            (self.valence as f32) / 100.0,
+            (self.arousal as f32) / 100.0,
+            (self.dominance as f32) / 100.0,
+        )
+    }
+
     /// Compress from full emotional state
     pub fn compress(state: &DetailedEmotionalState, base_timestamp: i64) -> Self {
         Self {
@@ -941,6 +937,23 @@ mod tests {
         assert!((state.arousal - decompressed.arousal).abs() < 0.02);
     }
 
+    #[test]
+    fn test_compressed_state_category() {
+        for (v, a, d) in [(0.7, 0.8, 0.3), (0.6, 0.9, 0.8), (-0.4, 0.2, 0.7), (0.3, 0.4, 0.2)] {
+            let state = DetailedEmotionalState {
+                timestamp_offset: 0,
+                valence: v,
+                arousal: a,
+                dominance: d,
+                confidence: 1.0,
+                intensity: 0.5,
+                engagement: 0.5,
+            };
+            let compressed = CompressedEmotionalState::compress(&state, 0);
+            assert_eq!(compressed.vad().category(), Vad::clamped(v, a, d).category());
+        }
+    }
+
     #[test]
     fn test_compression_ratio() {
         let original = 10000u64;
@@ -0,0 +1,13 @@
+//! Fixtures shared by the crate's tests
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Fresh, empty directory under the system temp dir, unique to `name` and
+/// this process
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("nftbi-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}